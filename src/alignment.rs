@@ -0,0 +1,373 @@
+// Copyright (c) 2020 Tianyi Shi
+//
+// This software is released under the MIT License.
+// https://opensource.org/licenses/MIT
+
+//! Pairwise alignment of one-letter sequences.
+//!
+//! Global alignments use the algorithm of Needleman & Wunsch (1970) and local alignments that of
+//! Smith & Waterman (1981), both with affine gap penalties (Gotoh, 1982): a gap of length `L` costs
+//! `gap_open + L * gap_extend`. Peptides are usually scored with BLOSUM62 and nucleic acids with
+//! a simple match/mismatch scheme.
+
+use crate::entry::Entry;
+use crate::sequence::SequenceSource;
+use std::ops::Range;
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlignmentMode {
+    /// End to end
+    Global,
+    /// The best scoring pair of subsequences
+    Local,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scoring {
+    /// BLOSUM62, with `X` for unknown letters
+    Blosum62,
+    /// A fixed score for identical and for different letters
+    Identity { matching: i32, mismatch: i32 },
+}
+
+const BLOSUM62_ORDER: &[u8] = b"ARNDCQEGHILKMFPSTWYVBZX*";
+
+#[rustfmt::skip]
+const BLOSUM62: [[i8; 24]; 24] = [
+    [ 4, -1, -2, -2,  0, -1, -1,  0, -2, -1, -1, -1, -1, -2, -1,  1,  0, -3, -2,  0, -2, -1,  0, -4],
+    [-1,  5,  0, -2, -3,  1,  0, -2,  0, -3, -2,  2, -1, -3, -2, -1, -1, -3, -2, -3, -1,  0, -1, -4],
+    [-2,  0,  6,  1, -3,  0,  0,  0,  1, -3, -3,  0, -2, -3, -2,  1,  0, -4, -2, -3,  3,  0, -1, -4],
+    [-2, -2,  1,  6, -3,  0,  2, -1, -1, -3, -4, -1, -3, -3, -1,  0, -1, -4, -3, -3,  4,  1, -1, -4],
+    [ 0, -3, -3, -3,  9, -3, -4, -3, -3, -1, -1, -3, -1, -2, -3, -1, -1, -2, -2, -1, -3, -3, -2, -4],
+    [-1,  1,  0,  0, -3,  5,  2, -2,  0, -3, -2,  1,  0, -3, -1,  0, -1, -2, -1, -2,  0,  3, -1, -4],
+    [-1,  0,  0,  2, -4,  2,  5, -2,  0, -3, -3,  1, -2, -3, -1,  0, -1, -3, -2, -2,  1,  4, -1, -4],
+    [ 0, -2,  0, -1, -3, -2, -2,  6, -2, -4, -4, -2, -3, -3, -2,  0, -2, -2, -3, -3, -1, -2, -1, -4],
+    [-2,  0,  1, -1, -3,  0,  0, -2,  8, -3, -3, -1, -2, -1, -2, -1, -2, -2,  2, -3,  0,  0, -1, -4],
+    [-1, -3, -3, -3, -1, -3, -3, -4, -3,  4,  2, -3,  1,  0, -3, -2, -1, -3, -1,  3, -3, -3, -1, -4],
+    [-1, -2, -3, -4, -1, -2, -3, -4, -3,  2,  4, -2,  2,  0, -3, -2, -1, -2, -1,  1, -4, -3, -1, -4],
+    [-1,  2,  0, -1, -3,  1,  1, -2, -1, -3, -2,  5, -1, -3, -1,  0, -1, -3, -2, -2,  0,  1, -1, -4],
+    [-1, -1, -2, -3, -1,  0, -2, -3, -2,  1,  2, -1,  5,  0, -2, -1, -1, -1, -1,  1, -3, -1, -1, -4],
+    [-2, -3, -3, -3, -2, -3, -3, -3, -1,  0,  0, -3,  0,  6, -4, -2, -2,  1,  3, -1, -3, -3, -1, -4],
+    [-1, -2, -2, -1, -3, -1, -1, -2, -2, -3, -3, -1, -2, -4,  7, -1, -1, -4, -3, -2, -2, -1, -2, -4],
+    [ 1, -1,  1,  0, -1,  0,  0,  0, -1, -2, -2,  0, -1, -2, -1,  4,  1, -3, -2, -2,  0,  0,  0, -4],
+    [ 0, -1,  0, -1, -1, -1, -1, -2, -2, -1, -1, -1, -1, -2, -1,  1,  5, -2, -2,  0, -1, -1,  0, -4],
+    [-3, -3, -4, -4, -2, -2, -3, -2, -2, -3, -2, -3, -1,  1, -4, -3, -2, 11,  2, -3, -4, -3, -2, -4],
+    [-2, -2, -2, -3, -2, -1, -2, -3,  2, -1, -1, -2, -1,  3, -3, -2, -2,  2,  7, -1, -3, -2, -1, -4],
+    [ 0, -3, -3, -3, -1, -2, -2, -3, -3,  3,  1, -2,  1, -1, -2, -2,  0, -3, -1,  4, -3, -2, -1, -4],
+    [-2, -1,  3,  4, -3,  0,  1, -1,  0, -3, -4,  0, -3, -3, -2,  0, -1, -4, -3, -3,  4,  1, -1, -4],
+    [-1,  0,  0,  1, -3,  3,  4, -2,  0, -3, -3,  1, -1, -3, -1,  0, -1, -3, -2, -2,  1,  4, -1, -4],
+    [ 0, -1, -1, -1, -2, -1, -1, -1, -1, -1, -1, -1, -1, -1, -2,  0,  0, -2, -1, -1, -1, -1, -1, -4],
+    [-4, -4, -4, -4, -4, -4, -4, -4, -4, -4, -4, -4, -4, -4, -4, -4, -4, -4, -4, -4, -4, -4, -4,  1],
+];
+
+fn blosum62_index(c: u8) -> usize {
+    // * selenocysteine and pyrrolysine are scored as their closest standard amino acids
+    let c = match c.to_ascii_uppercase() {
+        b'U' => b'C',
+        b'O' => b'K',
+        c => c,
+    };
+    BLOSUM62_ORDER.iter().position(|&x| x == c).unwrap_or(22)
+}
+
+impl Scoring {
+    pub fn score(&self, a: u8, b: u8) -> i32 {
+        match self {
+            Self::Blosum62 => BLOSUM62[blosum62_index(a)][blosum62_index(b)] as i32,
+            Self::Identity { matching, mismatch } => {
+                if a.eq_ignore_ascii_case(&b) {
+                    *matching
+                } else {
+                    *mismatch
+                }
+            }
+        }
+    }
+}
+
+/// Parameters of an alignment. Gap penalties are positive numbers.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Aligner {
+    pub mode: AlignmentMode,
+    pub scoring: Scoring,
+    pub gap_open: i32,
+    pub gap_extend: i32,
+}
+
+impl Default for Aligner {
+    /// Global alignment of peptides with BLOSUM62 and the gap penalties of BLASTP (11 and 1).
+    fn default() -> Self {
+        Self {
+            mode: AlignmentMode::Global,
+            scoring: Scoring::Blosum62,
+            gap_open: 11,
+            gap_extend: 1,
+        }
+    }
+}
+
+impl Aligner {
+    /// Global alignment of nucleic acids, scoring 2 for a match and -3 for a mismatch, with the gap
+    /// penalties of BLASTN (5 and 2).
+    pub fn nucleotide() -> Self {
+        Self {
+            mode: AlignmentMode::Global,
+            scoring: Scoring::Identity {
+                matching: 2,
+                mismatch: -3,
+            },
+            gap_open: 5,
+            gap_extend: 2,
+        }
+    }
+
+    pub fn local(self) -> Self {
+        Self {
+            mode: AlignmentMode::Local,
+            ..self
+        }
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alignment {
+    pub score: i32,
+    /// The aligned part of the first sequence, with `-` for gaps
+    pub a: String,
+    /// The aligned part of the second sequence, with `-` for gaps
+    pub b: String,
+    /// The positions of the aligned part in the first sequence; all of it for global alignments
+    pub a_range: Range<usize>,
+    /// The positions of the aligned part in the second sequence
+    pub b_range: Range<usize>,
+}
+
+impl Alignment {
+    /// The number of columns with the same letter in both sequences.
+    pub fn identities(&self) -> usize {
+        self.a
+            .bytes()
+            .zip(self.b.bytes())
+            .filter(|&(x, y)| x != b'-' && x.eq_ignore_ascii_case(&y))
+            .count()
+    }
+
+    /// The fraction of columns, including gaps, with the same letter in both sequences.
+    pub fn identity(&self) -> f32 {
+        match self.a.len() {
+            0 => 0.0,
+            len => self.identities() as f32 / len as f32,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Match,
+    /// A letter of `a` against a gap
+    GapB,
+    /// A letter of `b` against a gap
+    GapA,
+}
+
+const NEG: i32 = i32::MIN / 4;
+
+impl Aligner {
+    pub fn align(&self, a: &str, b: &str) -> Alignment {
+        let (a, b) = (a.as_bytes(), b.as_bytes());
+        let (n, m) = (a.len(), b.len());
+        let local = self.mode == AlignmentMode::Local;
+        let (open, extend) = (self.gap_open + self.gap_extend, self.gap_extend);
+        // * the best score of an alignment of a[..i] and b[..j] ending in each state
+        let mut mat = vec![vec![NEG; m + 1]; n + 1];
+        let mut gap_b = vec![vec![NEG; m + 1]; n + 1];
+        let mut gap_a = vec![vec![NEG; m + 1]; n + 1];
+        mat[0][0] = 0;
+        for i in 1..=n {
+            if local {
+                mat[i][0] = 0;
+            } else {
+                gap_b[i][0] = -open - (i as i32 - 1) * extend;
+            }
+        }
+        for j in 1..=m {
+            if local {
+                mat[0][j] = 0;
+            } else {
+                gap_a[0][j] = -open - (j as i32 - 1) * extend;
+            }
+        }
+        for i in 1..=n {
+            for j in 1..=m {
+                let diagonal = mat[i - 1][j - 1]
+                    .max(gap_b[i - 1][j - 1])
+                    .max(gap_a[i - 1][j - 1]);
+                let diagonal = if local { diagonal.max(0) } else { diagonal };
+                mat[i][j] = diagonal + self.scoring.score(a[i - 1], b[j - 1]);
+                gap_b[i][j] = (mat[i - 1][j] - open)
+                    .max(gap_b[i - 1][j] - extend)
+                    .max(gap_a[i - 1][j] - open);
+                gap_a[i][j] = (mat[i][j - 1] - open)
+                    .max(gap_a[i][j - 1] - extend)
+                    .max(gap_b[i][j - 1] - open);
+            }
+        }
+
+        let (mut i, mut j, mut state, score) = if local {
+            let mut best = (0, 0, State::Match, 0);
+            for (i, row) in mat.iter().enumerate() {
+                for (j, &s) in row.iter().enumerate() {
+                    if s > best.3 {
+                        best = (i, j, State::Match, s);
+                    }
+                }
+            }
+            best
+        } else {
+            let ends = [
+                (State::Match, mat[n][m]),
+                (State::GapB, gap_b[n][m]),
+                (State::GapA, gap_a[n][m]),
+            ];
+            let (state, score) = ends.iter().copied().max_by_key(|e| e.1).unwrap();
+            (n, m, state, if n == 0 && m == 0 { 0 } else { score })
+        };
+        let (a_end, b_end) = (i, j);
+
+        let mut aligned_a = Vec::new();
+        let mut aligned_b = Vec::new();
+        let previous = |i: usize, j: usize, value: i32, cost: [i32; 3]| {
+            if mat[i][j] - cost[0] == value {
+                State::Match
+            } else if gap_b[i][j] - cost[1] == value {
+                State::GapB
+            } else {
+                debug_assert_eq!(gap_a[i][j] - cost[2], value);
+                State::GapA
+            }
+        };
+        while i > 0 || j > 0 {
+            match state {
+                State::Match => {
+                    if i == 0 || j == 0 {
+                        break;
+                    }
+                    let value = mat[i][j] - self.scoring.score(a[i - 1], b[j - 1]);
+                    aligned_a.push(a[i - 1]);
+                    aligned_b.push(b[j - 1]);
+                    i -= 1;
+                    j -= 1;
+                    if local && value == 0 {
+                        break;
+                    }
+                    state = previous(i, j, value, [0, 0, 0]);
+                }
+                State::GapB => {
+                    let value = gap_b[i][j];
+                    aligned_a.push(a[i - 1]);
+                    aligned_b.push(b'-');
+                    i -= 1;
+                    state = previous(i, j, value, [open, extend, open]);
+                }
+                State::GapA => {
+                    let value = gap_a[i][j];
+                    aligned_a.push(b'-');
+                    aligned_b.push(b[j - 1]);
+                    j -= 1;
+                    state = previous(i, j, value, [open, open, extend]);
+                }
+            }
+        }
+        aligned_a.reverse();
+        aligned_b.reverse();
+        Alignment {
+            score,
+            a: String::from_utf8_lossy(&aligned_a).into_owned(),
+            b: String::from_utf8_lossy(&aligned_b).into_owned(),
+            a_range: i..a_end,
+            b_range: j..b_end,
+        }
+    }
+}
+
+impl Entry {
+    /// Aligns the sequences of two chains, which may belong to different entries. `None` if
+    /// either chain has no sequence.
+    pub fn align_chains(
+        &self,
+        a: char,
+        other: &Entry,
+        b: char,
+        source: SequenceSource,
+        aligner: &Aligner,
+    ) -> Option<Alignment> {
+        let a = self.sequence(a, source)?;
+        let b = other.sequence(b, source)?;
+        Some(aligner.align(&a, &b))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_blosum62() {
+        for (i, row) in BLOSUM62.iter().enumerate() {
+            for (j, &x) in row.iter().enumerate() {
+                assert_eq!(x, BLOSUM62[j][i]);
+            }
+        }
+        assert_eq!(Scoring::Blosum62.score(b'W', b'W'), 11);
+        assert_eq!(Scoring::Blosum62.score(b'U', b'C'), 9);
+    }
+
+    /// Scores an alignment from its gapped sequences.
+    fn rescore(aligner: &Aligner, alignment: &Alignment) -> i32 {
+        let (a, b) = (alignment.a.as_bytes(), alignment.b.as_bytes());
+        let gap = |k: usize| (a[k] == b'-', b[k] == b'-');
+        let mut score = 0;
+        for k in 0..a.len() {
+            if gap(k) == (false, false) {
+                score += aligner.scoring.score(a[k], b[k]);
+            } else {
+                let opens = k == 0 || gap(k - 1) != gap(k);
+                score -= aligner.gap_extend + if opens { aligner.gap_open } else { 0 };
+            }
+        }
+        score
+    }
+
+    #[test]
+    fn test_align() {
+        let aligner = Aligner::default();
+        let alignment = aligner.align("HEAGAWGHEE", "PAWHEAE");
+        assert_eq!(alignment.a.len(), alignment.b.len());
+        assert_eq!(alignment.a.replace('-', ""), "HEAGAWGHEE");
+        assert_eq!(alignment.b.replace('-', ""), "PAWHEAE");
+        assert_eq!(rescore(&aligner, &alignment), alignment.score);
+        assert_eq!((alignment.a_range, alignment.b_range), (0..10, 0..7));
+
+        let same = aligner.align("MKTAYIAK", "MKTAYIAK");
+        assert_eq!(same.identity(), 1.0);
+        assert_eq!(same.score, 5 + 5 + 5 + 4 + 7 + 4 + 4 + 5);
+
+        // * a single gap in the middle
+        let gapped = aligner.align("MKTAYIAKQRQISFVKSHFSRQ", "MKTAYIAKQRISFVKSHFSRQ");
+        assert_eq!(gapped.a.len(), 22);
+        assert_eq!(gapped.b.matches('-').count(), 1);
+        assert_eq!(rescore(&aligner, &gapped), gapped.score);
+
+        let local = aligner.local().align("GGGGMKTAYIAKGGGG", "PPMKTAYIAKPP");
+        assert_eq!(local.a, "MKTAYIAK");
+        assert_eq!(local.a_range, 4..12);
+        assert_eq!(local.b_range, 2..10);
+
+        let empty = aligner.align("", "MK");
+        assert_eq!((empty.a.as_str(), empty.b.as_str()), ("--", "MK"));
+        assert_eq!(empty.score, -13);
+    }
+}
@@ -0,0 +1,497 @@
+// Copyright (c) 2020 Tianyi Shi
+//
+// This software is released under the MIT License.
+// https://opensource.org/licenses/MIT
+
+//! The `nom-pdb` command-line tool. Run `nom-pdb help` for usage.
+
+use nom_pdb::complete::{ParseOptions, Parser};
+use nom_pdb::diff::DiffOptions;
+use nom_pdb::edit::Renumbering;
+use nom_pdb::hydrogen::{HistidineState, Protonation};
+use nom_pdb::mmcif::{MmcifParser, MmcifWriter};
+use nom_pdb::selection::Selection;
+use nom_pdb::sequence::{FastaOptions, SequenceSource};
+use nom_pdb::{Entry, Writer};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::process;
+
+const USAGE: &str = "\
+Usage: nom-pdb <command> [options] [input]
+
+Reads a PDB or mmCIF file from `input`, or from standard input if it is omitted or `-`, and
+writes the result to standard output unless `-o` is given. PDB output has the title section,
+SEQRES, MODRES, HELIX, SHEET, SSBOND, LINK and CRYST1 records besides the coordinates, but no
+REMARK records. mmCIF output has the coordinates, the unit cell and space group, and the ID,
+classification, deposition date, title, keywords, authors and methods of the entry; the other
+records are lost in conversion.
+
+Commands:
+    info                  Summarise the entry
+    json [--pretty]       Write the entry as JSON (needs the `serde` feature)
+    fasta                 Write the chain sequences in FASTA format
+        --source seqres|atom    Take the sequences from SEQRES (default) or the coordinates
+        --width N               Letters per line, 0 for one line (default 80)
+        --no-id, --no-chain, --no-entity, --no-description
+                                Leave fields out of the header lines
+    select <expression>   Keep only the atoms matching a selection, e.g. 'chain A and protein'
+    validate              Check bookkeeping, secondary structure, disulfides and residues
+                          against their templates; exits with status 1 if anything is reported
+    convert               Convert between PDB and mmCIF (by default, to the other format)
+    split-models          Write each model to its own file
+        --prefix PREFIX         File name prefix (default `model`), followed by the serial
+    renumber              Renumber the residues of each chain
+        --start N               Number the residues of each chain consecutively from N
+        --offset N              Add N to every residue number
+        --seqres                Number the polymer residues by their position in SEQRES
+        --chain ID              Renumber only chain ID
+        --atoms                 Also number the atoms consecutively from 1
+    stats                 Count chains, residues and atoms by kind and element
+    add-hydrogens         Add idealized hydrogens to the standard amino acids
+        --his hid|hie|hip       Protonation of His on ND1, NE2 or both (default hie)
+        --ash, --glh            Protonate Asp and Glu
+        --lyn                   Leave Lys neutral
+    diff <first> [input]  Report what differs from `first` to the input; exits with status 1
+                          if anything does
+        --tolerance D           Smallest atom displacement reported, in Å (default 0.1)
+        --model N               Index of the model to compare, from 0 (default 0)
+
+Options:
+    -o, --output PATH     Write to PATH instead of standard output
+    -t, --to pdb|cif      Output format of select, convert, split-models, renumber and
+                          add-hydrogens (default: the input format, or the other one for
+                          convert)
+        --no-hydrogens    Skip hydrogen and deuterium atoms when reading
+    -h, --help            Print this message
+";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Pdb,
+    Cif,
+}
+
+impl Format {
+    fn parse(s: &str) -> Result<Self, String> {
+        match s.to_ascii_lowercase().as_str() {
+            "pdb" => Ok(Format::Pdb),
+            "cif" | "mmcif" => Ok(Format::Cif),
+            _ => Err(format!("unknown format `{}`", s)),
+        }
+    }
+
+    fn other(self) -> Self {
+        match self {
+            Format::Pdb => Format::Cif,
+            Format::Cif => Format::Pdb,
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Format::Pdb => "pdb",
+            Format::Cif => "cif",
+        }
+    }
+}
+
+/// Options that take no value.
+const FLAGS: &[&str] = &[
+    "--pretty",
+    "--no-id",
+    "--no-chain",
+    "--no-entity",
+    "--no-description",
+    "--seqres",
+    "--atoms",
+    "--no-hydrogens",
+    "--ash",
+    "--glh",
+    "--lyn",
+];
+
+struct Args {
+    command: String,
+    positional: Vec<String>,
+    options: BTreeMap<String, String>,
+}
+
+impl Args {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let command = match args.next().ok_or("no command given")?.as_str() {
+            "-h" | "--help" => "help".to_owned(),
+            command => command.to_owned(),
+        };
+        let mut positional = Vec::new();
+        let mut options = BTreeMap::new();
+        while let Some(arg) = args.next() {
+            let name = match arg.as_str() {
+                "-o" => "--output".to_owned(),
+                "-t" => "--to".to_owned(),
+                "-h" => "--help".to_owned(),
+                _ => arg.clone(),
+            };
+            if name == "--help" || FLAGS.contains(&name.as_str()) {
+                options.insert(name, String::new());
+            } else if name.starts_with("--") {
+                let value = args
+                    .next()
+                    .ok_or_else(|| format!("`{}` requires a value", name))?;
+                options.insert(name, value);
+            } else {
+                positional.push(arg);
+            }
+        }
+        Ok(Args {
+            command,
+            positional,
+            options,
+        })
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.options.contains_key(name)
+    }
+
+    fn value(&self, name: &str) -> Option<&str> {
+        self.options.get(name).map(String::as_str)
+    }
+
+    fn number<T: std::str::FromStr>(&self, name: &str) -> Result<Option<T>, String> {
+        self.value(name)
+            .map(|v| {
+                v.parse()
+                    .map_err(|_| format!("`{}` expects a number, not `{}`", name, v))
+            })
+            .transpose()
+    }
+
+    /// The input path, the only positional argument apart from those of the command.
+    fn input(&self, skip: usize) -> Result<Option<&str>, String> {
+        match &self.positional[skip.min(self.positional.len())..] {
+            [] => Ok(None),
+            [path] if path == "-" => Ok(None),
+            [path] => Ok(Some(path)),
+            _ => Err("too many arguments".to_owned()),
+        }
+    }
+}
+
+fn read_entry(path: Option<&str>, options: &ParseOptions) -> Result<(Entry, Format), String> {
+    let data = match path {
+        Some(path) => fs::read(path).map_err(|e| format!("cannot read `{}`: {}", path, e))?,
+        None => {
+            let mut data = Vec::new();
+            io::stdin()
+                .read_to_end(&mut data)
+                .map_err(|e| format!("cannot read standard input: {}", e))?;
+            data
+        }
+    };
+    let start = data
+        .iter()
+        .position(|b| !b.is_ascii_whitespace())
+        .unwrap_or(0);
+    if data[start..].starts_with(b"data_") {
+        let entry = MmcifParser::parse_entry_with(&data, options).map_err(|e| e.to_string())?;
+        Ok((entry, Format::Cif))
+    } else {
+        let entry =
+            Parser::parse_entry_with(&data, options).map_err(|e| format!("invalid PDB: {}", e))?;
+        Ok((entry, Format::Pdb))
+    }
+}
+
+fn format_entry(entry: &Entry, format: Format) -> String {
+    match format {
+        Format::Pdb => Writer::write_to_string(entry),
+        Format::Cif => MmcifWriter::write_to_string(entry),
+    }
+}
+
+fn write_output(path: Option<&str>, text: &str) -> Result<(), String> {
+    match path {
+        Some(path) => fs::write(path, text).map_err(|e| format!("cannot write `{}`: {}", path, e)),
+        None => match io::stdout().write_all(text.as_bytes()) {
+            // * e.g. piped into `head`
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+            result => result.map_err(|e| format!("cannot write to standard output: {}", e)),
+        },
+    }
+}
+
+fn info(entry: &Entry) -> String {
+    let mut out = String::new();
+    let metadata = entry.structure.metadata.as_ref();
+    if let Some(header) = metadata.and_then(|m| m.header.as_ref()) {
+        out += &format!("ID: {}\n", header.id_code);
+        out += &format!("Classification: {}\n", header.classification);
+        out += &format!("Deposited: {}\n", header.deposition_date);
+    }
+    if let Some(title) = metadata.and_then(|m| m.title.as_ref()) {
+        out += &format!("Title: {}\n", title);
+    }
+    if let Some(techniques) = metadata.and_then(|m| m.experimental_techniques.as_ref()) {
+        let techniques: Vec<String> = techniques.iter().map(|t| format!("{:?}", t)).collect();
+        out += &format!("Method: {}\n", techniques.join(", "));
+    }
+    if let Some(c) = metadata.and_then(|m| m.cryst1.as_ref()) {
+        out += &format!(
+            "Cell: {:.2} {:.2} {:.2} {:.2} {:.2} {:.2}\n",
+            c.a, c.b, c.c, c.alpha, c.beta, c.gamma
+        );
+    }
+    for compound in &entry.title_section.compounds {
+        let chains: String = compound.chains.iter().collect();
+        out += &format!(
+            "Entity {}: {} (chains {})\n",
+            compound.mol_id,
+            compound.molecule.as_deref().unwrap_or("?"),
+            chains
+        );
+    }
+    let hierarchy = entry.hierarchy();
+    out += &format!("Models: {}\n", hierarchy.len());
+    if let Some(model) = hierarchy.first() {
+        let chains: String = model.chains.iter().map(|c| c.id).collect();
+        out += &format!("Chains: {}\n", chains);
+        out += &format!("Atoms: {}\n", model.atoms.len());
+    }
+    out
+}
+
+fn stats(entry: &Entry) -> String {
+    let mut out = String::new();
+    for model in entry.hierarchy() {
+        let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+        let mut elements: BTreeMap<&str, usize> = BTreeMap::new();
+        for chain in &model.chains {
+            *counts.entry("chains").or_default() += 1;
+            *counts.entry("polymer residues").or_default() += chain.polymer.len();
+            for residue in &chain.non_polymer {
+                let kind = match residue.name().as_str() {
+                    "HOH" | "DOD" => "waters",
+                    _ => "ligands",
+                };
+                *counts.entry(kind).or_default() += 1;
+            }
+        }
+        for k in 0..model.atoms.len() {
+            let symbol = entry.element(model.index, k).map_or("?", |e| e.symbol());
+            *elements.entry(symbol).or_default() += 1;
+        }
+        let b_factor = model
+            .atoms
+            .iter()
+            .map(|a| a.temperature_factor as f64)
+            .sum::<f64>()
+            / model.atoms.len().max(1) as f64;
+        out += &format!("Model {}\n", model.serial);
+        for (name, count) in counts {
+            out += &format!("  {}: {}\n", name, count);
+        }
+        out += &format!("  atoms: {}\n", model.atoms.len());
+        let elements: Vec<String> = elements
+            .iter()
+            .map(|(e, n)| format!("{} {}", e, n))
+            .collect();
+        out += &format!("  elements: {}\n", elements.join(", "));
+        out += &format!("  mean B-factor: {:.2}\n", b_factor);
+    }
+    out
+}
+
+fn validate(entry: &Entry) -> Vec<String> {
+    let mut issues: Vec<String> = entry
+        .check_integrity()
+        .iter()
+        .map(|i| i.to_string())
+        .collect();
+    issues.extend(
+        entry
+            .check_secondary_structure()
+            .iter()
+            .map(|i| i.to_string()),
+    );
+    if !entry.structure.models.is_empty() {
+        issues.extend(entry.check_disulfides(0).iter().map(|i| i.to_string()));
+        issues.extend(entry.check_residues(0).iter().map(|i| i.to_string()));
+    }
+    issues
+}
+
+/// A copy of `entry` with only the model at `index`.
+fn single_model(entry: &Entry, index: usize) -> Entry {
+    let mut single = entry.clone();
+    single.structure.models = vec![entry.structure.models[index].clone()];
+    let mut info = entry.model_info[index].clone();
+    info.serial = None;
+    info.ended = false;
+    single.model_info = vec![info];
+    single.title_section.model_count = None;
+    single
+}
+
+fn run(args: Args) -> Result<i32, String> {
+    if args.flag("--help") || args.command == "help" {
+        print!("{}", USAGE);
+        return Ok(0);
+    }
+    let output = args.value("--output");
+    let to = args.value("--to").map(Format::parse).transpose()?;
+    let parse_options = ParseOptions {
+        skip_hydrogens: args.flag("--no-hydrogens"),
+    };
+    let read_entry = |path: Option<&str>| read_entry(path, &parse_options);
+    match args.command.as_str() {
+        "info" => {
+            let (entry, _) = read_entry(args.input(0)?)?;
+            write_output(output, &info(&entry))?;
+        }
+        #[cfg(feature = "serde")]
+        "json" => {
+            let (entry, _) = read_entry(args.input(0)?)?;
+            let json = if args.flag("--pretty") {
+                nom_pdb::json::to_string_pretty(&entry)
+            } else {
+                nom_pdb::json::to_string(&entry)
+            };
+            write_output(output, &(json + "\n"))?;
+        }
+        #[cfg(not(feature = "serde"))]
+        "json" => return Err("the `json` command needs the `serde` feature".to_owned()),
+        "fasta" => {
+            let (entry, _) = read_entry(args.input(0)?)?;
+            let source = match args.value("--source").unwrap_or("seqres") {
+                "seqres" => SequenceSource::Seqres,
+                "atom" | "coordinates" => SequenceSource::Coordinates,
+                s => return Err(format!("unknown sequence source `{}`", s)),
+            };
+            let options = FastaOptions {
+                id: !args.flag("--no-id"),
+                chain: !args.flag("--no-chain"),
+                entity: !args.flag("--no-entity"),
+                description: !args.flag("--no-description"),
+                line_width: args.number("--width")?.unwrap_or(80),
+            };
+            write_output(output, &entry.fasta(source, &options))?;
+        }
+        "select" => {
+            let expression = args
+                .positional
+                .first()
+                .ok_or("select requires a selection expression")?;
+            let selection: Selection = expression.parse().map_err(|e| format!("{}", e))?;
+            let (entry, format) = read_entry(args.input(1)?)?;
+            let selected = entry.select(&selection);
+            write_output(output, &format_entry(&selected, to.unwrap_or(format)))?;
+        }
+        "validate" => {
+            let (entry, _) = read_entry(args.input(0)?)?;
+            let issues = validate(&entry);
+            let mut report: String = issues.iter().map(|i| format!("{}\n", i)).collect();
+            if issues.is_empty() {
+                report = "No issues found\n".to_owned();
+            }
+            write_output(output, &report)?;
+            return Ok(if issues.is_empty() { 0 } else { 1 });
+        }
+        "convert" => {
+            let (entry, format) = read_entry(args.input(0)?)?;
+            write_output(output, &format_entry(&entry, to.unwrap_or(format.other())))?;
+        }
+        "split-models" => {
+            let (entry, format) = read_entry(args.input(0)?)?;
+            let format = to.unwrap_or(format);
+            let prefix = args.value("--prefix").unwrap_or("model");
+            for index in 0..entry.structure.models.len() {
+                let path = format!(
+                    "{}_{}.{}",
+                    prefix,
+                    entry.model_serial(index),
+                    format.extension()
+                );
+                let text = format_entry(&single_model(&entry, index), format);
+                write_output(Some(&path), &text)?;
+            }
+        }
+        "renumber" => {
+            let (mut entry, format) = read_entry(args.input(0)?)?;
+            let renumbering = match (
+                args.number("--start")?,
+                args.number("--offset")?,
+                args.flag("--seqres"),
+            ) {
+                (Some(start), None, false) => Renumbering::Sequential(start),
+                (None, Some(offset), false) => Renumbering::Offset(offset),
+                (None, None, true) => Renumbering::FromSeqres,
+                _ => {
+                    return Err(
+                        "renumber requires one of `--start`, `--offset` and `--seqres`".to_owned(),
+                    )
+                }
+            };
+            let chain = args.value("--chain").and_then(|c| c.chars().next());
+            entry
+                .renumber_residues(chain, renumbering)
+                .map_err(|e| e.to_string())?;
+            if args.flag("--atoms") {
+                entry.renumber_atoms(1);
+            }
+            write_output(output, &format_entry(&entry, to.unwrap_or(format)))?;
+        }
+        "diff" => {
+            let first = args
+                .positional
+                .first()
+                .ok_or("diff requires the file to compare with")?;
+            let (first, _) = read_entry(Some(first))?;
+            let (second, _) = read_entry(args.input(1)?)?;
+            let mut options = DiffOptions::default();
+            if let Some(tolerance) = args.number("--tolerance")? {
+                options.tolerance = tolerance;
+            }
+            if let Some(model) = args.number("--model")? {
+                options.model = model;
+            }
+            let diff = first.diff(&second, &options);
+            write_output(output, &diff.to_string())?;
+            return Ok(if diff.is_empty() { 0 } else { 1 });
+        }
+        "stats" => {
+            let (entry, _) = read_entry(args.input(0)?)?;
+            write_output(output, &stats(&entry))?;
+        }
+        "add-hydrogens" => {
+            let (mut entry, format) = read_entry(args.input(0)?)?;
+            let histidine = match args.value("--his").unwrap_or("hie") {
+                "hid" => HistidineState::Delta,
+                "hie" => HistidineState::Epsilon,
+                "hip" => HistidineState::Protonated,
+                s => return Err(format!("unknown histidine state `{}`", s)),
+            };
+            entry.add_hydrogens(&Protonation {
+                histidine,
+                protonated_asp: args.flag("--ash"),
+                protonated_glu: args.flag("--glh"),
+                protonated_lys: !args.flag("--lyn"),
+            });
+            write_output(output, &format_entry(&entry, to.unwrap_or(format)))?;
+        }
+        command => return Err(format!("unknown command `{}`\n\n{}", command, USAGE)),
+    }
+    Ok(0)
+}
+
+fn main() {
+    let code = match Args::parse(std::env::args().skip(1)).and_then(run) {
+        Ok(code) => code,
+        Err(message) => {
+            eprintln!("nom-pdb: {}", message);
+            2
+        }
+    };
+    process::exit(code);
+}
@@ -0,0 +1,315 @@
+// Copyright (c) 2020 Tianyi Shi
+//
+// This software is released under the MIT License.
+// https://opensource.org/licenses/MIT
+
+//! The bookkeeping section (MASTER and END) and file integrity checking.
+//!
+//! # MASTER
+//!
+//! The MASTER record is a control record for bookkeeping. It lists the number of lines in the
+//! coordinate entry or file for selected record types.
+//!
+//! ## Record Format
+//!
+//! | COLUMNS | DATA TYPE   | FIELD     | DEFINITION                                        |
+//! |---------|-------------|-----------|---------------------------------------------------|
+//! | 1 -  6  | Record name | "MASTER"  |                                                   |
+//! | 11 - 15 | Integer     | numRemark | Number of REMARK records                          |
+//! | 16 - 20 | Integer     | "0"       |                                                   |
+//! | 21 - 25 | Integer     | numHet    | Number of HET records                             |
+//! | 26 - 30 | Integer     | numHelix  | Number of HELIX records                           |
+//! | 31 - 35 | Integer     | numSheet  | Number of SHEET records                           |
+//! | 36 - 40 | Integer     | numTurn   | deprecated                                        |
+//! | 41 - 45 | Integer     | numSite   | Number of SITE records                            |
+//! | 46 - 50 | Integer     | numXform  | Number of coordinate transformation records       |
+//! |         |             |           | (ORIGX+SCALE+MTRIX)                               |
+//! | 51 - 55 | Integer     | numCoord  | Number of atomic coordinate records records       |
+//! |         |             |           | (ATOM+HETATM)                                     |
+//! | 56 - 60 | Integer     | numTer    | Number of TER records                             |
+//! | 61 - 65 | Integer     | numConect | Number of CONECT records                          |
+//! | 66 - 70 | Integer     | numSeq    | Number of SEQRES records                          |
+//!
+//! # END
+//!
+//! The END record marks the end of the PDB file. An entry that does not end with it has most
+//! likely been truncated.
+
+use crate::common::parser::{columns, take_record_lines, FieldParser};
+use crate::entry::Entry;
+use crate::types::{AtomSerial, ResidueSerial};
+use nom::IResult;
+
+/// Numbers of lines of the record types listed in the MASTER record.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RecordCounts {
+    pub remark: u32,
+    pub het: u32,
+    pub helix: u32,
+    pub sheet: u32,
+    pub turn: u32,
+    pub site: u32,
+    /// ORIGXn, SCALEn and MTRIXn
+    pub transform: u32,
+    /// ATOM and HETATM. As in the MASTER records written by the wwPDB, only the first alternate
+    /// location (blank or `A`) of each atom is counted.
+    pub coordinate: u32,
+    pub ter: u32,
+    pub conect: u32,
+    pub seqres: u32,
+}
+
+impl RecordCounts {
+    /// Adds the lines in `consumed` to the count of the record type `record_name`, if it is one of
+    /// the types listed in MASTER.
+    pub(crate) fn count(&mut self, record_name: &[u8], consumed: &[u8]) {
+        let count = match record_name {
+            b"REMARK" => &mut self.remark,
+            b"HET   " => &mut self.het,
+            b"HELIX " => &mut self.helix,
+            b"SHEET " => &mut self.sheet,
+            b"TURN  " => &mut self.turn,
+            b"SITE  " => &mut self.site,
+            b"ORIGX1" | b"ORIGX2" | b"ORIGX3" | b"SCALE1" | b"SCALE2" | b"SCALE3" | b"MTRIX1"
+            | b"MTRIX2" | b"MTRIX3" => &mut self.transform,
+            b"ATOM  " | b"HETATM" => match consumed.get(16) {
+                Some(b' ') | Some(b'A') => &mut self.coordinate,
+                _ => return,
+            },
+            b"TER   " => &mut self.ter,
+            b"CONECT" => &mut self.conect,
+            b"SEQRES" => &mut self.seqres,
+            _ => return,
+        };
+        *count += consumed.iter().filter(|&&c| c == b'\n').count() as u32;
+    }
+
+    fn fields(&self) -> [(&'static str, u32); 11] {
+        [
+            ("REMARK", self.remark),
+            ("HET", self.het),
+            ("HELIX", self.helix),
+            ("SHEET", self.sheet),
+            ("TURN", self.turn),
+            ("SITE", self.site),
+            ("ORIGX/SCALE/MTRIX", self.transform),
+            ("ATOM/HETATM", self.coordinate),
+            ("TER", self.ter),
+            ("CONECT", self.conect),
+            ("SEQRES", self.seqres),
+        ]
+    }
+}
+
+pub struct MasterParser;
+
+impl FieldParser for MasterParser {
+    type Output = RecordCounts;
+    fn parse(inp: &[u8]) -> IResult<&[u8], RecordCounts> {
+        let (inp, lines) = take_record_lines(inp, b"MASTER")?;
+        let field = |start: usize| columns(lines[0], start, start + 4).parse().unwrap_or(0);
+        Ok((
+            inp,
+            RecordCounts {
+                remark: field(11),
+                het: field(21),
+                helix: field(26),
+                sheet: field(31),
+                turn: field(36),
+                site: field(41),
+                transform: field(46),
+                coordinate: field(51),
+                ter: field(56),
+                conect: field(61),
+                seqres: field(66),
+            },
+        ))
+    }
+}
+
+/// The bookkeeping records of an entry, together with what was actually found while parsing it.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Default)]
+pub struct Bookkeeping {
+    /// The counts declared in the MASTER record.
+    pub master: Option<RecordCounts>,
+    /// The counts of the records actually present.
+    pub record_counts: RecordCounts,
+    /// Whether the END record was reached.
+    pub end: bool,
+    /// The last line of the input, if it was cut off before its line ending and thus could not
+    /// be parsed.
+    pub unterminated_line: Option<String>,
+    /// The number of ENDMDL records that do not close a model opened by MODEL.
+    pub unmatched_endmdl: u32,
+}
+
+/// A problem that suggests the file is incomplete or has been altered.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IntegrityIssue {
+    /// The mandatory MASTER record is absent.
+    MissingMaster,
+    /// The input ended before the END record.
+    MissingEnd,
+    /// The last line was cut off before its line ending.
+    UnterminatedLine(String),
+    /// The number of records of a type differs from that declared in MASTER.
+    CountMismatch {
+        record: String,
+        declared: u32,
+        found: u32,
+    },
+    /// A model opened by MODEL is not closed by ENDMDL.
+    MissingEndmdl(u32),
+    /// ENDMDL records that do not close a model opened by MODEL.
+    UnmatchedEndmdl(u32),
+    /// More than one model has this serial number.
+    DuplicateModelSerial(u32),
+    /// The number of models differs from that declared in NUMMDL.
+    ModelCountMismatch { declared: u32, found: u32 },
+    /// The serial number of a TER record is not one greater than that of the atom preceding it.
+    /// `model` is the serial number of the model.
+    TerSerial {
+        model: u32,
+        expected: AtomSerial,
+        found: AtomSerial,
+    },
+    /// A TER record does not follow an atom of the residue it names.
+    MisplacedTer {
+        model: u32,
+        chain: char,
+        sequence_number: Option<ResidueSerial>,
+    },
+}
+
+impl std::fmt::Display for IntegrityIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::MissingMaster => write!(f, "MASTER record is missing"),
+            Self::MissingEnd => write!(f, "END record is missing; the file may be truncated"),
+            Self::UnterminatedLine(line) => write!(f, "last line is incomplete: {:?}", line),
+            Self::CountMismatch {
+                record,
+                declared,
+                found,
+            } => write!(
+                f,
+                "MASTER declares {} {} records but {} were found",
+                declared, record, found
+            ),
+            Self::MissingEndmdl(model) => write!(f, "model {} is not closed by ENDMDL", model),
+            Self::UnmatchedEndmdl(n) => write!(f, "{} ENDMDL records without a matching MODEL", n),
+            Self::DuplicateModelSerial(model) => {
+                write!(f, "more than one model has serial number {}", model)
+            }
+            Self::ModelCountMismatch { declared, found } => write!(
+                f,
+                "NUMMDL declares {} models but {} were found",
+                declared, found
+            ),
+            Self::TerSerial {
+                model,
+                expected,
+                found,
+            } => write!(
+                f,
+                "TER in model {} has serial {} but {} was expected",
+                model, found, expected
+            ),
+            Self::MisplacedTer {
+                model,
+                chain,
+                sequence_number,
+            } => {
+                write!(f, "TER in model {} for chain '{}'", model, chain)?;
+                if let Some(n) = sequence_number {
+                    write!(f, " residue {}", n)?;
+                }
+                write!(f, " does not follow an atom of that residue")
+            }
+        }
+    }
+}
+
+impl Entry {
+    /// Compares the counts declared in the MASTER record against the records actually parsed,
+    /// checks that the file is not truncated, that MODEL and ENDMDL records pair up and that each
+    /// TER record follows the last atom of its chain. An empty result means no problem was found.
+    pub fn check_integrity(&self) -> Vec<IntegrityIssue> {
+        let bookkeeping = &self.bookkeeping;
+        let mut issues = Vec::new();
+        if let Some(line) = &bookkeeping.unterminated_line {
+            issues.push(IntegrityIssue::UnterminatedLine(line.clone()));
+        }
+        if !bookkeeping.end {
+            issues.push(IntegrityIssue::MissingEnd);
+        }
+        match &bookkeeping.master {
+            None => issues.push(IntegrityIssue::MissingMaster),
+            Some(master) => {
+                let found = bookkeeping.record_counts.fields();
+                for (&(record, declared), &(_, found)) in master.fields().iter().zip(found.iter()) {
+                    if declared != found {
+                        issues.push(IntegrityIssue::CountMismatch {
+                            record: record.to_owned(),
+                            declared,
+                            found,
+                        });
+                    }
+                }
+            }
+        }
+        if bookkeeping.unmatched_endmdl > 0 {
+            issues.push(IntegrityIssue::UnmatchedEndmdl(
+                bookkeeping.unmatched_endmdl,
+            ));
+        }
+        if let Some(declared) = self.title_section.model_count {
+            let found = self.structure.models.len() as u32;
+            if declared != found {
+                issues.push(IntegrityIssue::ModelCountMismatch { declared, found });
+            }
+        }
+        let mut serials = Vec::new();
+        for (index, (info, atoms)) in self
+            .model_info
+            .iter()
+            .zip(self.structure.models.iter().map(|m| &m.atoms))
+            .enumerate()
+        {
+            let model = self.model_serial(index);
+            if serials.contains(&model) {
+                issues.push(IntegrityIssue::DuplicateModelSerial(model));
+            }
+            serials.push(model);
+            if info.serial.is_some() && !info.ended {
+                issues.push(IntegrityIssue::MissingEndmdl(model));
+            }
+            for (preceding, ter) in &info.ters {
+                let last = match preceding.checked_sub(1).map(|i| &atoms[i]) {
+                    Some(last) if ter.terminates(last) => last,
+                    _ => {
+                        issues.push(IntegrityIssue::MisplacedTer {
+                            model,
+                            chain: ter.chain,
+                            sequence_number: ter.sequence_number,
+                        });
+                        continue;
+                    }
+                };
+                if let Some(found) = ter.id {
+                    if found != last.id + 1 {
+                        issues.push(IntegrityIssue::TerSerial {
+                            model,
+                            expected: last.id + 1,
+                            found,
+                        });
+                    }
+                }
+            }
+        }
+        issues
+    }
+}
@@ -0,0 +1,88 @@
+// Copyright (c) 2020 Tianyi Shi
+//
+// This software is released under the MIT License.
+// https://opensource.org/licenses/MIT
+
+//! Net charges of residues and ligands, from the formal charges in columns 79 - 80 of the
+//! ATOM/HETATM records.
+//!
+//! Formal charges are often left out, in particular on standard residues, so a net charge of zero
+//! only means that no charged atom is recorded.
+
+use crate::hierarchy::{ModelView, ResidueView};
+use crate::types::ResidueSerial;
+
+/// The net charge of a residue or ligand.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResidueCharge {
+    pub chain: char,
+    pub sequence_number: ResidueSerial,
+    pub insertion_code: char,
+    pub residue_name: String,
+    pub net_charge: i32,
+}
+
+/// The charged residues and ligands of a model.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ChargeSummary {
+    /// Polymer residues with a non-zero net charge
+    pub residues: Vec<ResidueCharge>,
+    /// Non-polymer residues (waters included) with a non-zero net charge
+    pub ligands: Vec<ResidueCharge>,
+    /// The net charge of the model
+    pub total: i32,
+}
+
+impl<'a> ResidueView<'a> {
+    /// The sum of the formal charges of the atoms. Atoms in alternate locations other than the
+    /// first are not counted.
+    pub fn net_charge(&self) -> i32 {
+        let first_alt_loc = self
+            .atoms
+            .iter()
+            .map(|a| a.id1)
+            .find(|&c| c != ' ')
+            .unwrap_or(' ');
+        self.atoms
+            .iter()
+            .filter(|a| a.id1 == ' ' || a.id1 == first_alt_loc)
+            .map(|a| a.charge as i32)
+            .sum()
+    }
+
+    fn charge(&self) -> ResidueCharge {
+        ResidueCharge {
+            chain: self.chain(),
+            sequence_number: self.sequence_number(),
+            insertion_code: self.insertion_code(),
+            residue_name: self.name(),
+            net_charge: self.net_charge(),
+        }
+    }
+}
+
+impl<'a> ModelView<'a> {
+    pub fn charge_summary(&self) -> ChargeSummary {
+        let charged = |residues: &[ResidueView]| -> Vec<ResidueCharge> {
+            residues
+                .iter()
+                .map(ResidueView::charge)
+                .filter(|c| c.net_charge != 0)
+                .collect()
+        };
+        let mut summary = ChargeSummary::default();
+        for chain in &self.chains {
+            summary.residues.extend(charged(&chain.polymer));
+            summary.ligands.extend(charged(&chain.non_polymer));
+        }
+        summary.total = summary
+            .residues
+            .iter()
+            .chain(summary.ligands.iter())
+            .map(|c| c.net_charge)
+            .sum();
+        summary
+    }
+}
@@ -4,5 +4,6 @@
 // https://opensource.org/licenses/MIT
 
 pub mod error;
+pub mod hybrid36;
 pub mod parser;
 pub mod types;
@@ -0,0 +1,91 @@
+// Copyright (c) 2020 Tianyi Shi
+//
+// This software is released under the MIT License.
+// https://opensource.org/licenses/MIT
+
+//! The [hybrid-36](http://cci.lbl.gov/hybrid_36/) encoding of numbers that do not fit in their
+//! fixed-width field, as written by Phenix/cctbx and many molecular dynamics tools.
+//!
+//! Numbers below `10^width` are written in decimal as usual. Beyond that, the field counts on in
+//! base 36 with an upper-case letter as the first digit (`A0000`, `A0001`, ..., `ZZZZZ`), then
+//! with a lower-case one (`a0000`, ..., `zzzzz`). A 5-column atom serial thus reaches
+//! 87,440,031 and a 4-column residue number 2,436,111.
+
+/// Decodes a right-justified hybrid-36 field of `width` columns. Returns `None` if the field is
+/// blank or not a valid hybrid-36 number.
+pub fn decode(field: &str, width: u32) -> Option<u32> {
+    let s = field.trim_start();
+    let first = *s.as_bytes().first()?;
+    if first.is_ascii_digit() {
+        return s.trim_end().parse().ok();
+    }
+    if s.len() != width as usize {
+        return None;
+    }
+    let (offset, digits_valid): (u32, fn(&u8) -> bool) = if first.is_ascii_uppercase() {
+        (0, |c| c.is_ascii_digit() || c.is_ascii_uppercase())
+    } else if first.is_ascii_lowercase() {
+        (26, |c| c.is_ascii_digit() || c.is_ascii_lowercase())
+    } else {
+        return None;
+    };
+    if !s.as_bytes().iter().all(digits_valid) {
+        return None;
+    }
+    let value = u32::from_str_radix(s, 36).ok()?;
+    // * the leading letter is at least 10 (`A`) in base 36
+    Some(value - 10 * 36u32.pow(width - 1) + offset * 36u32.pow(width - 1) + 10u32.pow(width))
+}
+
+/// Encodes `value` as a right-justified hybrid-36 field of `width` columns. Returns `None` if the
+/// value is too large for the field.
+pub fn encode(value: u32, width: u32) -> Option<String> {
+    let w = width as usize;
+    if value < 10u32.pow(width) {
+        return Some(format!("{:>w$}", value, w = w));
+    }
+    let block = 26 * 36u32.pow(width - 1);
+    let value = value - 10u32.pow(width);
+    let (value, upper) = if value < block {
+        (value, true)
+    } else if value - block < block {
+        (value - block, false)
+    } else {
+        return None;
+    };
+    let mut value = value + 10 * 36u32.pow(width - 1);
+    let mut digits = vec![b'0'; w];
+    for digit in digits.iter_mut().rev() {
+        let d = (value % 36) as u8;
+        *digit = match d {
+            0..=9 => b'0' + d,
+            _ if upper => b'A' + d - 10,
+            _ => b'a' + d - 10,
+        };
+        value /= 36;
+    }
+    Some(String::from_utf8(digits).unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_hybrid36() {
+        assert_eq!(decode("99999", 5), Some(99999));
+        assert_eq!(decode("  123", 5), Some(123));
+        assert_eq!(decode("A0000", 5), Some(100000));
+        assert_eq!(decode("ZZZZZ", 5), Some(43770015));
+        assert_eq!(decode("a0000", 5), Some(43770016));
+        assert_eq!(decode("zzzzz", 5), Some(87440031));
+        assert_eq!(decode("A000", 4), Some(10000));
+        assert_eq!(decode("aZ00", 4), None);
+        assert_eq!(decode("     ", 5), None);
+        for &(value, width) in &[(0, 5), (99999, 5), (100000, 5), (87440031, 5), (2436111, 4)] {
+            assert_eq!(decode(&encode(value, width).unwrap(), width), Some(value));
+        }
+        assert_eq!(encode(100000, 5).unwrap(), "A0000");
+        assert_eq!(encode(10000, 4).unwrap(), "A000");
+        assert_eq!(encode(87440032, 5), None);
+    }
+}
@@ -3,6 +3,7 @@
 // This software is released under the MIT License.
 // https://opensource.org/licenses/MIT
 
+use super::hybrid36;
 use crate::types::*;
 use atoi::atoi;
 use chrono::{
@@ -78,35 +79,46 @@ pub(crate) fn jump_newline(inp: &[u8]) -> IResult<&[u8], ()> {
     Ok((inp, ()))
 }
 
+/// Takes the record name (columns 1 - 6). Lines shorter than 6 columns, such as a bare `END`, are
+/// padded with blanks.
+pub(crate) fn take_record_name(inp: &[u8]) -> IResult<&[u8], [u8; 6]> {
+    let (_, line) = not_line_ending(inp)?;
+    let n = line.len().min(6);
+    let mut name = *b"      ";
+    name[..n].copy_from_slice(&line[..n]);
+    Ok((&inp[n..], name))
+}
+
 fn char_is_space(c: u8) -> bool {
     c == b' '
 }
 
-pub(crate) fn parse_date(i: &[u8]) -> IResult<&[u8], NaiveDate> {
-    let (i, day) = take(2usize)(i)?;
+pub(crate) fn parse_date(inp: &[u8]) -> IResult<&[u8], NaiveDate> {
+    let (i, day) = take(2usize)(inp)?;
     let (i, _) = take(1usize)(i)?;
     let (i, month) = parse_month(i)?;
     let (i, _) = take(1usize)(i)?;
     let (i, year) = take(2usize)(i)?;
-    let mut year = atoi::<i32>(year).unwrap();
-    if year < 50i32 {
-        year += 2000
-    } else {
-        year += 1900
+    let number = |s: &[u8]| std::str::from_utf8(s).ok()?.parse::<u32>().ok();
+    let date = match (number(year), number(day)) {
+        (Some(year), Some(day)) => {
+            let year = if year < 50 { year + 2000 } else { year + 1900 };
+            NaiveDate::from_ymd_opt(year as i32, month, day)
+        }
+        _ => None,
+    };
+    match date {
+        Some(date) => Ok((i, date)),
+        None => Err(nom::Err::Error((inp, nom::error::ErrorKind::Digit))),
     }
-    Ok((
-        i,
-        NaiveDate::from_ymd(year, month, atoi::<u32>(day).unwrap()),
-    ))
 }
 
 fn parse_month(i: &[u8]) -> IResult<&[u8], u32> {
     map_res(take(3usize), |s: &[u8]| -> Result<u32, ()> {
-        let s = unsafe { std::str::from_utf8_unchecked(s) };
+        let s = std::str::from_utf8(s).map_err(|_| ())?;
         let mut parsed = Parsed::new();
-        chrono::format::parse(&mut parsed, s, StrftimeItems::new("%b"))
-            .expect("Failed to parse month");
-        Ok(parsed.month.unwrap())
+        chrono::format::parse(&mut parsed, s, StrftimeItems::new("%b")).map_err(|_| ())?;
+        parsed.month.ok_or(())
     })(i)
 }
 
@@ -127,57 +139,44 @@ where
     }
 }
 
+/// Parses a right-justified integer field that may be in the hybrid-36 encoding, such as atom
+/// serial numbers and residue numbers.
+pub(crate) fn parse_hybrid36(i: &[u8], width: usize) -> IResult<&[u8], u32> {
+    let (rest, field) = take(width)(i)?;
+    let field = std::str::from_utf8(field).unwrap_or("");
+    match hybrid36::decode(field, width as u32) {
+        Some(x) => Ok((rest, x)),
+        None => Err(nom::Err::Error((i, nom::error::ErrorKind::Digit))),
+    }
+}
+
 // * MULTILINE PARSERS ---------------------------------------------------------
 
-pub(crate) fn parse_multiline_list(inp: &[u8]) -> IResult<&[u8], Vec<String>> {
-    // ! need improvement
-    let (mut inp, _) = take(4usize)(inp)?; // 7 - 10
-    let mut v: Vec<String> = Vec::new();
-    loop {
-        let (i, item) = take_while(|c| c != b',' && c != b'\n' && c != b'\r')(inp)?;
-        let item = unsafe { std::str::from_utf8_unchecked(item) };
-        v.push(item.trim().to_owned());
-        let (i, comma_or_newline) = anychar(i)?; // consume \r or \n if newline
-        if comma_or_newline == ',' {
-            let (i, char_after_comma) = peek(anychar)(i)?;
-            if char_after_comma == ' ' {
-                let (i, second_char_after_comma) = peek(anychar)(anychar(i)?.0)?;
-                if !second_char_after_comma.is_alphanumeric() {
-                    // newline
-                    inp = multispace1(i)?.0;
-                    inp = take(10usize)(inp)?.0; // 1 - 10
-                } else {
-                    inp = i;
-                }
-            } else {
-                inp = i;
-            }
-        } else {
-            // end
-            inp = take_while(|x| x == b'\n')(i)?.0;
-            return Ok((inp, v));
-        }
-    }
+/// Parses a list that may be continued over several lines, as in AUTHOR and EXPDTA records.
+pub(crate) fn parse_multiline_list<'a>(
+    inp: &'a [u8],
+    record_identifier: &[u8],
+    delimiter: char,
+) -> IResult<&'a [u8], Vec<String>> {
+    let (inp, lines) = take_record_lines(inp, record_identifier)?;
+    let list = join_continued(lines.iter().map(|line| columns(line, 11, 80)));
+    Ok((inp, split_list(&list, delimiter)))
 }
 
+/// Parses a string that may be continued over several lines, as in TITLE records. Continuation
+/// lines are appended from column 11, which holds the blank that separates them from the text
+/// of the line before, if any.
 pub(crate) fn parse_multiline_string<'a>(
     inp: &'a [u8],
     record_identifier: &[u8],
 ) -> IResult<&'a [u8], String> {
-    // ! need improvement
-    let (mut inp, _) = take(4usize)(inp)?; // 7 - 10
+    let (inp, lines) = take_record_lines(inp, record_identifier)?;
     let mut s = String::new();
-    loop {
-        let (i, item) = not_line_ending(inp)?;
-        let item = std::str::from_utf8(item).unwrap();
-        s.push_str(item.trim_end());
-        let (i, _) = line_ending(i)?;
-        if peek(take(6usize))(i)?.1 != record_identifier {
-            return Ok((i, s));
-        }
-        let (i, _) = take(10usize)(i)?;
-        inp = i;
+    for (k, line) in lines.iter().enumerate() {
+        let text = String::from_utf8_lossy(line.get(4..).unwrap_or_default());
+        s.push_str(if k == 0 { text.trim() } else { text.trim_end() });
     }
+    Ok((inp, s))
 }
 
 pub(crate) fn parse_multiline<'a, T, F>(
@@ -204,6 +203,101 @@ where
     }
 }
 
+/// Takes the current line and every following line that carries the same record name. Each
+/// returned line starts at column 7 and excludes the line ending, which the last line of the
+/// input may lack.
+pub(crate) fn take_record_lines<'a>(
+    inp: &'a [u8],
+    record_identifier: &[u8],
+) -> IResult<&'a [u8], Vec<&'a [u8]>> {
+    let mut lines = Vec::new();
+    let mut inp = inp;
+    loop {
+        let (i, line) = not_line_ending(inp)?;
+        let (i, _) = if i.is_empty() {
+            (i, i)
+        } else {
+            line_ending(i)?
+        };
+        lines.push(line);
+        if i.get(..6) != Some(record_identifier) {
+            return Ok((i, lines));
+        }
+        inp = &i[6..];
+    }
+}
+
+/// Extracts the trimmed content of a fixed-width field from a line returned by
+/// [`take_record_lines`]. `start` and `end` are 1-based and inclusive, as in the format
+/// specification; columns missing from a short line are treated as blank.
+pub(crate) fn columns(line: &[u8], start: usize, end: usize) -> &str {
+    let start = (start - 7).min(line.len());
+    let end = (end - 6).min(line.len());
+    std::str::from_utf8(&line[start..end]).unwrap_or("").trim()
+}
+
+/// Concatenates the pieces of a continued field. Pieces are joined by a single blank, except
+/// after a hyphen or an asterisk, which in practice mark a word or a sequence broken across lines
+/// (e.g. `DNA (5'-` / `D(*TP*CP*...`).
+pub(crate) fn join_continued<'a>(pieces: impl IntoIterator<Item = &'a str>) -> String {
+    let mut s = String::new();
+    for piece in pieces {
+        let piece = piece.trim();
+        if piece.is_empty() {
+            continue;
+        }
+        if !s.is_empty() && !s.ends_with('-') && !s.ends_with('*') {
+            s.push(' ');
+        }
+        s.push_str(piece);
+    }
+    s
+}
+
+/// Splits a List (`,`-separated) or an SList (`;`-separated). A delimiter preceded by a backslash
+/// is kept as part of the item.
+pub(crate) fn split_list(s: &str, delimiter: char) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut item = String::new();
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some(escaped) if escaped == delimiter => item.push(escaped),
+                Some(other) => {
+                    item.push(c);
+                    item.push(other);
+                }
+                None => item.push(c),
+            }
+        } else if c == delimiter {
+            items.push(item.trim().to_owned());
+            item.clear();
+        } else {
+            item.push(c);
+        }
+    }
+    if !item.trim().is_empty() {
+        items.push(item.trim().to_owned());
+    }
+    items
+}
+
+/// Splits a Specification List into `(token, value)` pairs. Specifications without a colon are
+/// skipped.
+pub(crate) fn split_specification_list(s: &str) -> Vec<(String, String)> {
+    split_list(s, ';')
+        .into_iter()
+        .filter_map(|spec| {
+            let colon = spec.find(':')?;
+            Some((
+                spec[..colon].trim().to_owned(),
+                spec[colon + 1..].trim().to_owned(),
+            ))
+        })
+        .collect()
+}
+
 use std::collections::HashMap;
 
 pub(crate) fn parse_residue<'a, 'b>(
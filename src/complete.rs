@@ -5,68 +5,250 @@
 
 /// A simple single-thread parser.
 use crate::{
-    coordinate::*, crystallography::*, primary_structure::*, secondary_structure::*,
+    bookkeeping::*,
+    coordinate::*,
+    crystallography::*,
+    entry::{Entry, ModelInfo},
+    primary_structure::*,
+    secondary_structure::*,
     title_section::*,
 };
 
 // use crate::common::error::PdbParseError;
-use crate::common::parser::FieldParser;
-use nom::bytes::complete::take;
+use crate::common::parser::{take_record_name, FieldParser};
 use nom::character::complete::{line_ending, not_line_ending};
 
 use crate::types::{
-    Connect, Helix, Model, ModifiedAminoAcidTable, ModifiedNucleotideTable, Sheet, Ssbond,
-    Structure,
+    AtomSerial, Connect, Helix, Model, ModifiedAminoAcidTable, ModifiedNucleotideTable, Sheet,
+    Ssbond, Structure,
 };
+use std::collections::HashSet;
 
 use protein_core::metadata::*;
 
+/// Options that control what is kept while parsing.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Skip the atoms of hydrogen and deuterium, together with their ANISOU, SIGATM and SIGUIJ
+    /// records and the CONECT records that refer to them. Record counts are those of the file,
+    /// as is checked against MASTER.
+    pub skip_hydrogens: bool,
+}
+
 pub struct Parser {}
 
 impl Parser {
     pub fn parse(input: &[u8]) -> Result<Structure, Box<dyn std::error::Error + '_>> {
-        let (_, result) = Self::_parse(input)?;
+        let (_, result) = Self::_parse(input, &ParseOptions::default())?;
+        Ok(result.structure)
+    }
+
+    /// Parses the structure together with the PDB-specific records kept in [`Entry`].
+    pub fn parse_entry(input: &[u8]) -> Result<Entry, Box<dyn std::error::Error + '_>> {
+        Self::parse_entry_with(input, &ParseOptions::default())
+    }
+
+    /// Like [`parse_entry`](#method.parse_entry), with the records kept controlled by `options`.
+    pub fn parse_entry_with<'a>(
+        input: &'a [u8],
+        options: &ParseOptions,
+    ) -> Result<Entry, Box<dyn std::error::Error + 'a>> {
+        let (_, result) = Self::_parse(input, options)?;
         Ok(result)
     }
 
-    fn _parse(mut inp: &[u8]) -> nom::IResult<&[u8], Structure> {
+    fn _parse<'a>(mut inp: &'a [u8], options: &ParseOptions) -> nom::IResult<&'a [u8], Entry> {
         let mut metadata = Metadata::default();
+        let mut title_section = TitleSection::default();
+        let mut bookkeeping = Bookkeeping::default();
 
         let mut seqres_buffer: Vec<u8> = Default::default();
         let mut ssbonds: Vec<Ssbond> = Default::default();
+        let mut links: Vec<Link> = Vec::new();
 
         let mut helices: Vec<Helix> = Vec::new();
         let mut sheets: Vec<Sheet> = Vec::new();
+        let mut helix_details: Vec<HelixDetails> = Vec::new();
+        let mut sheet_details: Vec<Vec<StrandDetails>> = Vec::new();
 
         let mut connect: Vec<Connect> = Vec::new();
 
         let mut models: Vec<Model> = vec![Model::default()];
+        let mut model_info: Vec<ModelInfo> = vec![ModelInfo::default()];
 
         let mut modified_aa: ModifiedAminoAcidTable = Default::default();
         let mut modified_nuc: ModifiedNucleotideTable = Default::default();
 
         let mut model_idx = 0;
+        let mut model_started = false;
+
+        // * serial numbers of the atoms skipped in the current model
+        let mut skipped: HashSet<AtomSerial> = HashSet::new();
+        let mut skipped_any = false;
+        let mut last_skipped = false;
+
+        // * a last line without line ending is set aside, so that a file cut off in the middle of
+        // * a record that spans several lines is read up to its last complete line
+        let (body, last_line) = inp.split_at(match inp.iter().rposition(|&c| c == b'\n') {
+            Some(last_newline) => last_newline + 1,
+            None => 0,
+        });
+        inp = body;
 
         loop {
-            let (i, tag) = take(6usize)(inp)?;
-            inp = match tag {
-                b"HEADER" => HeaderParser::parse_into_option(&i, &mut metadata.header),
-                b"TITLE " => TitleParser::parse_into_option(&i, &mut metadata.title),
-                b"AUTHOR" => AuthorsParser::parse_into_option(&i, &mut metadata.authors),
-                b"CRYST1" => Cryst1Parser::parse_into_option(&i, &mut metadata.cryst1),
-                b"SEQRES" => SeqResParser::buffer_seqres(&i, &mut seqres_buffer)?.0,
-                b"MODRES" => ModresParser::parse_into(&i, &mut modified_aa, &mut modified_nuc)?.0,
-                b"SSBOND" => SsbondParser::parse_into_vec(&i, &mut ssbonds),
-                b"EXPDTA" => ExperimentalTechniquesParser::parse_into_option(
-                    &i,
-                    &mut metadata.experimental_techniques,
-                ),
+            if inp.is_empty() {
+                let (_, tag) = take_record_name(last_line)?;
+                if &tag == b"END   " {
+                    bookkeeping.end = true;
+                } else if !last_line.iter().all(u8::is_ascii_whitespace) {
+                    bookkeeping.unterminated_line =
+                        Some(String::from_utf8_lossy(last_line).into_owned());
+                }
+                break;
+            }
+            let (i, tag) = take_record_name(inp)?;
+            let next = match &tag {
+                b"HEADER" => {
+                    let (i, header) = HeaderParser::parse(i)?;
+                    metadata.header = Some(header);
+                    i
+                }
+                b"TITLE " => {
+                    let (i, title) = TitleParser::parse(i)?;
+                    metadata.title = Some(title);
+                    i
+                }
+                b"AUTHOR" => {
+                    let (i, authors) = AuthorsParser::parse(i)?;
+                    metadata.authors = Some(authors);
+                    i
+                }
+                b"OBSLTE" => {
+                    let (i, obsolete) = ObslteParser::parse(i)?;
+                    title_section.obsolete = Some(obsolete);
+                    i
+                }
+                b"SPLIT " => {
+                    let (i, split) = SplitParser::parse(i)?;
+                    title_section.split = Some(split);
+                    i
+                }
+                b"CAVEAT" => {
+                    let (i, caveat) = CaveatParser::parse(i)?;
+                    title_section.caveat = Some(caveat);
+                    i
+                }
+                b"COMPND" => {
+                    let (i, compounds) = CompndParser::parse(i)?;
+                    title_section.compounds = compounds;
+                    i
+                }
+                b"SOURCE" => {
+                    let (i, sources) = SourceParser::parse(i)?;
+                    title_section.sources = sources;
+                    i
+                }
+                b"KEYWDS" => {
+                    let (i, keywords) = KeywordsParser::parse(i)?;
+                    title_section.keywords = Some(keywords);
+                    i
+                }
+                b"NUMMDL" => {
+                    let (i, model_count) = NummdlParser::parse(i)?;
+                    title_section.model_count = Some(model_count);
+                    i
+                }
+                b"MDLTYP" => {
+                    let (i, model_type) = MdltypParser::parse(i)?;
+                    title_section.model_type = Some(model_type);
+                    i
+                }
+                b"REVDAT" => {
+                    let (i, revisions) = RevdatParser::parse(i)?;
+                    title_section.revisions = revisions;
+                    i
+                }
+                b"SPRSDE" => {
+                    let (i, superseded) = SprsdeParser::parse(i)?;
+                    title_section.superseded = Some(superseded);
+                    i
+                }
+                b"JRNL  " => {
+                    let (i, journal) = JournalParser::parse(i)?;
+                    title_section.journal = Some(journal);
+                    i
+                }
+                b"CRYST1" => {
+                    let (i, cryst1) = Cryst1Parser::parse(i)?;
+                    metadata.cryst1 = Some(cryst1);
+                    i
+                }
+                b"SEQRES" => SeqResParser::buffer_seqres(i, &mut seqres_buffer)?.0,
+                b"MODRES" => ModresParser::parse_into(i, &mut modified_aa, &mut modified_nuc)?.0,
+                b"SSBOND" => {
+                    let (i, ssbond) = SsbondParser::parse(i)?;
+                    ssbonds.push(ssbond);
+                    i
+                }
+                b"LINK  " => {
+                    let (i, link) = LinkParser::parse(i)?;
+                    links.push(link);
+                    i
+                }
+                b"EXPDTA" => {
+                    let (i, techniques) = ExperimentalTechniquesParser::parse(i)?;
+                    metadata.experimental_techniques = Some(techniques);
+                    i
+                }
                 b"ATOM  " | b"HETATM" => {
-                    let (i, atom) = GenericAtomParser::parse(&i, &modified_aa, &modified_nuc)?;
-                    models[model_idx].atoms.push(atom);
+                    let (i, (atom, element)) =
+                        GenericAtomParser::parse_with_element(&i, &modified_aa, &modified_nuc)?;
+                    last_skipped =
+                        options.skip_hydrogens && matches!(element, Some(e) if e.is_hydrogen());
+                    if last_skipped {
+                        skipped.insert(atom.id);
+                        skipped_any = true;
+                    } else {
+                        models[model_idx].atoms.push(atom);
+                        model_info[model_idx].elements.push(element);
+                    }
+                    i
+                }
+                b"ANISOU" => {
+                    let (i, record) = AnisouParser::parse(i)?;
+                    if !skipped.contains(&record.id) {
+                        models[model_idx].anisou.push(record);
+                    }
+                    i
+                }
+                b"SIGATM" => {
+                    let (i, record) = SigatmParser::parse(i)?;
+                    if !skipped.contains(&record.id) {
+                        model_info[model_idx].sigatm.push(record);
+                    }
+                    i
+                }
+                b"SIGUIJ" => {
+                    let (i, record) = SiguijParser::parse(i)?;
+                    if !skipped.contains(&record.id) {
+                        model_info[model_idx].siguij.push(record);
+                    }
+                    i
+                }
+                b"TER   " => {
+                    let (i, mut ter) = TerParser::parse(&i)?;
+                    // * the TER follows the last atom kept, not the hydrogen skipped before it
+                    if let (true, Some(_), Some(last)) =
+                        (last_skipped, ter.id, models[model_idx].atoms.last())
+                    {
+                        ter.id = Some(last.id + 1);
+                    }
+                    last_skipped = false;
+                    let preceding = models[model_idx].atoms.len();
+                    model_info[model_idx].ters.push((preceding, ter));
                     i
                 }
-                b"ANISOU" => AnisouParser::parse_into_vec(&i, &mut models[model_idx].anisou),
                 b"CONECT" => {
                     let (i, cnct) = ConectParser::parse(&i)?;
                     for c in cnct {
@@ -77,18 +259,47 @@ impl Parser {
                     i
                 }
                 b"MODEL " => {
-                    if models.len() != 1 {
-                        // * if there's one model, there would be no "MODEL"
+                    let (i, serial) = ModelParser::parse(&i)?;
+                    // * atoms that precede the first MODEL, if any, are kept as a model of their own
+                    if model_started || !models[model_idx].atoms.is_empty() {
                         models.push(Model::default());
+                        model_info.push(ModelInfo::default());
                         model_idx += 1;
                     }
+                    model_started = true;
+                    model_info[model_idx].serial = serial;
+                    skipped.clear();
+                    i
+                }
+                b"ENDMDL" => {
+                    if model_started && !model_info[model_idx].ended {
+                        model_info[model_idx].ended = true;
+                    } else {
+                        bookkeeping.unmatched_endmdl += 1;
+                    }
                     let (i, _) = not_line_ending(i)?;
                     let (i, _) = line_ending(i)?;
                     i
                 }
-                b"SHEET " => SheetParser::parse_into_vec(&i, &mut sheets),
-                b"HELIX " => HelixParser::parse_into_vec(&i, &mut helices),
+                b"SHEET " => {
+                    let (i, (sheet, details)) = SheetParser::parse_with_details(&i)?;
+                    sheets.push(sheet);
+                    sheet_details.push(details);
+                    i
+                }
+                b"HELIX " => {
+                    let (i, (helix, details)) = HelixParser::parse_with_details(&i)?;
+                    helices.push(helix);
+                    helix_details.push(details);
+                    i
+                }
+                b"MASTER" => {
+                    let (i, master) = MasterParser::parse(i)?;
+                    bookkeeping.master = Some(master);
+                    i
+                }
                 b"END   " => {
+                    bookkeeping.end = true;
                     inp = b"";
                     break;
                 }
@@ -98,23 +309,45 @@ impl Parser {
                     let (i, _) = line_ending(i)?;
                     i
                 } //panic!("Unkown field"),
-            }
+            };
+            bookkeeping
+                .record_counts
+                .count(&tag, &inp[..inp.len() - next.len()]);
+            inp = next;
+        }
+        if skipped_any {
+            let serials: HashSet<AtomSerial> = models
+                .iter()
+                .flat_map(|m| m.atoms.iter().map(|a| a.id))
+                .collect();
+            connect.retain(|[a, b]| serials.contains(a) && serials.contains(b));
         }
-        let (_, (chains_aa, chains_nuc)) =
-            SeqResParser::parse(&seqres_buffer, &modified_aa, &modified_nuc).unwrap();
+        let (chains_aa, chains_nuc) =
+            match SeqResParser::parse(&seqres_buffer, &modified_aa, &modified_nuc) {
+                Ok((_, chains)) => chains,
+                Err(e) => return Err(e.map_input(|()| body)),
+            };
         Ok((
             inp,
-            Structure {
-                chains_aa,
-                chains_nuc,
-                helices,
-                sheets,
-                ssbonds,
-                modified_aa,
-                modified_nuc,
-                connect,
-                models,
-                metadata: Some(metadata),
+            Entry {
+                structure: Structure {
+                    chains_aa,
+                    chains_nuc,
+                    helices,
+                    sheets,
+                    ssbonds,
+                    modified_aa,
+                    modified_nuc,
+                    connect,
+                    models,
+                    metadata: Some(metadata),
+                },
+                title_section,
+                bookkeeping,
+                model_info,
+                helix_details,
+                sheet_details,
+                links,
             },
         ))
     }
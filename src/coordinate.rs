@@ -3,14 +3,21 @@
 // This software is released under the MIT License.
 // https://opensource.org/licenses/MIT
 
-use crate::common::parser::{jump_newline, parse_residue, parse_right, FieldParser};
+use crate::common::hybrid36;
+use crate::common::parser::{
+    columns, jump_newline, parse_hybrid36, parse_residue, parse_right, FieldParser,
+};
+use crate::element::ChemicalElement;
 
 use crate::types::{
     Anisou, Atom, AtomName, AtomSerial, Connect, Element, ModifiedAminoAcidTable,
-    ModifiedNucleotideTable, ParseFw2, ParseFw4, Residue,
+    ModifiedNucleotideTable, ParseFw4, Residue, ResidueSerial,
+};
+use nom::{
+    bytes::complete::take,
+    character::complete::{anychar, line_ending, not_line_ending},
+    IResult,
 };
-use nom::{bytes::complete::take, character::complete::anychar, combinator::map, IResult};
-use std::str::from_utf8_unchecked;
 
 /// # ATOM
 ///
@@ -123,16 +130,30 @@ impl GenericAtomParser {
         modified_aa: &'b ModifiedAminoAcidTable,
         modified_nuc: &'b ModifiedNucleotideTable,
     ) -> IResult<&'a [u8], Atom> {
-        let (inp, id) = parse_right::<AtomSerial>(inp, 5)?;
+        let (inp, (atom, _)) = Self::parse_with_element(inp, modified_aa, modified_nuc)?;
+        Ok((inp, atom))
+    }
+
+    /// Also returns the element as a [`ChemicalElement`], which covers the whole periodic table.
+    /// If the element columns are blank or missing, the element is inferred from the atom name.
+    ///
+    /// [`ChemicalElement`]: ../element/struct.ChemicalElement.html
+    pub fn parse_with_element<'a, 'b>(
+        inp: &'a [u8],
+        modified_aa: &'b ModifiedAminoAcidTable,
+        modified_nuc: &'b ModifiedNucleotideTable,
+    ) -> IResult<&'a [u8], (Atom, Option<ChemicalElement>)> {
+        let (inp, id) = parse_hybrid36(inp, 5)?;
         let inp = &inp[1..];
-        let (inp, name) = map(take(4usize), AtomName::parse_fw4)(inp)?;
+        let (inp, raw_name) = take(4usize)(inp)?;
+        let name = AtomName::parse_fw4(raw_name);
         let (inp, id1) = anychar(inp)?;
 
         let (inp, residue) = parse_residue(inp, modified_aa, modified_nuc)?;
 
         let inp = &inp[1..];
         let (inp, chain) = anychar(inp)?;
-        let (inp, sequence_number) = parse_right::<u32>(inp, 4)?;
+        let (inp, sequence_number) = parse_hybrid36(inp, 4)?;
         let (inp, insertion_code) = anychar(inp)?;
         let inp = &inp[3..];
         let (inp, x) = parse_right::<f32>(inp, 8)?;
@@ -140,36 +161,63 @@ impl GenericAtomParser {
         let (inp, z) = parse_right::<f32>(inp, 8)?;
         let (inp, occupancy) = parse_right::<f32>(inp, 6)?;
         let (inp, temperature_factor) = parse_right::<f32>(inp, 6)?;
-        let inp = &inp[10..];
-        let (inp, element) = map(take(2usize), Element::parse_fw2)(inp)?;
-        let (inp, charge) = map(take(2usize), |x: &[u8]| match x {
-            b"  " => 0,
-            _ => {
-                let x = unsafe { from_utf8_unchecked(x) };
-                x.parse::<i8>().unwrap()
-            }
-        })(inp)?;
-        let (inp, _) = nom::character::complete::line_ending(inp)?;
+        // * columns 67 - 80, which older files may leave out
+        let (inp, rest) = not_line_ending(inp)?;
+        let (inp, _) = line_ending(inp)?;
+        let chemical_element = rest
+            .get(10..12)
+            .and_then(|x| std::str::from_utf8(x).ok())
+            .and_then(ChemicalElement::from_symbol)
+            .or_else(|| ChemicalElement::from_atom_name(raw_name, &residue));
+        let element = chemical_element.map_or(Element::Unknown, Element::from);
+        let charge = rest
+            .get(12..)
+            .map_or(0, |x| parse_charge(&x[..x.len().min(2)]));
         Ok((
             inp,
-            Atom {
-                id,
-                id1,
-                name,
-                residue,
-                chain,
-                sequence_number,
-                insertion_code,
-                coord: [x, y, z],
-                occupancy,
-                temperature_factor,
-                element,
-                charge,
-            },
+            (
+                Atom {
+                    id,
+                    id1,
+                    name,
+                    residue,
+                    chain,
+                    sequence_number,
+                    insertion_code,
+                    coord: [x, y, z],
+                    occupancy,
+                    temperature_factor,
+                    element,
+                    charge,
+                },
+                chemical_element,
+            ),
         ))
     }
 }
 
+/// Parses the charge in columns 79 - 80. The format specifies a digit followed by the sign
+/// (`2+`, `1-`), but the reverse order (`+2`) is also accepted, as is a sign alone. Anything else,
+/// including blanks, is read as no charge.
+pub(crate) fn parse_charge(field: &[u8]) -> i8 {
+    let field = std::str::from_utf8(field).unwrap_or("").trim();
+    let (magnitude, sign) = match field.as_bytes() {
+        [] => return 0,
+        [sign @ b'+'] | [sign @ b'-'] => (1, *sign),
+        [d, sign @ b'+'] | [d, sign @ b'-'] | [sign @ b'+', d] | [sign @ b'-', d]
+            if d.is_ascii_digit() =>
+        {
+            ((d - b'0') as i8, *sign)
+        }
+        _ => return 0,
+    };
+    if sign == b'-' {
+        -magnitude
+    } else {
+        magnitude
+    }
+}
+
 /// # ANISOU
 ///
 /// The [ANISOU](http://www.wwpdb.org/documentation/file-format-content/format33/sect9.html#ANISOU) records present the anisotropic temperature factors.
@@ -199,7 +247,7 @@ pub struct AnisouParser;
 impl FieldParser for AnisouParser {
     type Output = Anisou;
     fn parse(inp: &[u8]) -> IResult<&[u8], Anisou> {
-        let (inp, id) = parse_right::<AtomSerial>(inp, 5)?;
+        let (inp, id) = parse_hybrid36(inp, 5)?;
         let inp = &inp[17..]; // 12 - 28
 
         let (inp, u11) = parse_right::<i32>(inp, 7)?;
@@ -208,8 +256,8 @@ impl FieldParser for AnisouParser {
         let (inp, u12) = parse_right::<i32>(inp, 7)?;
         let (inp, u13) = parse_right::<i32>(inp, 7)?;
         let (inp, u23) = parse_right::<i32>(inp, 7)?;
-        let inp = &inp[10..];
-        let (inp, _) = nom::character::complete::line_ending(inp)?;
+        // * columns 71 - 80 may be left out
+        let (inp, _) = jump_newline(inp)?;
         Ok((
             inp,
             Anisou {
@@ -225,6 +273,187 @@ impl FieldParser for AnisouParser {
     }
 }
 
+/// # SIGATM
+///
+/// The [SIGATM](https://www.wwpdb.org/documentation/file-format-content/format23/sect9.html#SIGATM)
+/// records present the standard deviations of atomic parameters as they appear in ATOM or HETATM
+/// records. They were dropped from the format in version 3, but are still written by some
+/// refinement programs.
+///
+/// ## Record Format
+///
+/// | COLUMNS | DATA  TYPE   | FIELD    | DEFINITION                                      |
+/// | ------- | ------------ | -------- | ----------------------------------------------- |
+/// | 1 - 6   | Record name  | "SIGATM" |                                                 |
+/// | 7 - 11  | Integer      | serial   | Atom serial number.                             |
+/// | 13 - 16 | Atom         | name     | Atom name.                                      |
+/// | 17      | Character    | altLoc   | Alternate location indicator.                   |
+/// | 18 - 20 | Residue name | resName  | Residue name.                                   |
+/// | 22      | Character    | chainID  | Chain identifier.                               |
+/// | 23 - 26 | Integer      | resSeq   | Residue sequence number.                        |
+/// | 27      | AChar        | iCode    | Insertion code.                                 |
+/// | 31 - 38 | Real(8.3)    | sigX     | Standard deviation of orthogonal coordinates    |
+/// |         |              |          | for X in Angstroms.                             |
+/// | 39 - 46 | Real(8.3)    | sigY     | Standard deviation of orthogonal coordinates    |
+/// |         |              |          | for Y in Angstroms.                             |
+/// | 47 - 54 | Real(8.3)    | sigZ     | Standard deviation of orthogonal coordinates    |
+/// |         |              |          | for Z in Angstroms.                             |
+/// | 55 - 60 | Real(6.2)    | sigOcc   | Standard deviation of occupancy.                |
+/// | 61 - 66 | Real(6.2)    | sigTemp  | Standard deviation of temperature factor.       |
+/// | 77 - 78 | LString(2)   | element  | Element symbol, right-justified.                |
+/// | 79 - 80 | LString(2)   | charge   | Charge on the atom.                             |
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sigatm {
+    pub id: AtomSerial,
+    pub coord: [f32; 3],
+    pub occupancy: f32,
+    pub temperature_factor: f32,
+}
+
+pub struct SigatmParser;
+
+impl FieldParser for SigatmParser {
+    type Output = Sigatm;
+    fn parse(inp: &[u8]) -> IResult<&[u8], Sigatm> {
+        let (inp, id) = parse_hybrid36(inp, 5)?;
+        let inp = &inp[19..]; // 12 - 30
+        let (inp, x) = parse_right::<f32>(inp, 8)?;
+        let (inp, y) = parse_right::<f32>(inp, 8)?;
+        let (inp, z) = parse_right::<f32>(inp, 8)?;
+        let (inp, occupancy) = parse_right::<f32>(inp, 6)?;
+        let (inp, temperature_factor) = parse_right::<f32>(inp, 6)?;
+        let (inp, _) = jump_newline(inp)?;
+        Ok((
+            inp,
+            Sigatm {
+                id,
+                coord: [x, y, z],
+                occupancy,
+                temperature_factor,
+            },
+        ))
+    }
+}
+
+/// # SIGUIJ
+///
+/// The [SIGUIJ](https://www.wwpdb.org/documentation/file-format-content/format23/sect9.html#SIGUIJ)
+/// records present the standard deviations of anisotropic temperature factors scaled by a factor
+/// of 10**4 (Angstroms**2). Like SIGATM, they were dropped from the format in version 3.
+///
+/// The columns are those of ANISOU, with the standard deviations `sig11` - `sig23` in place of
+/// `u[0][0]` - `u[1][2]`, so the records are parsed into [`Anisou`].
+///
+/// [`Anisou`]: ../../protein_core/structure/anisou/struct.Anisou.html
+pub type SiguijParser = AnisouParser;
+
+/// # MODEL
+///
+/// The [MODEL](http://www.wwpdb.org/documentation/file-format-content/format33/sect9.html#MODEL)
+/// record specifies the model serial number when multiple models of the same structure are
+/// presented in a single coordinate entry, as is often the case with structures determined by
+/// NMR.
+///
+/// ## Record Format
+///
+/// | COLUMNS | DATA  TYPE  | FIELD    | DEFINITION            |
+/// | ------- | ----------- | -------- | --------------------- |
+/// | 1 - 6   | Record name | "MODEL " |                       |
+/// | 11 - 14 | Integer     | serial   | Model serial number.  |
+///
+/// ## Details
+///
+/// This record is used only when more than one model appears in an entry. Generally, it is
+/// employed mainly for NMR structures. The chemical connectivity should be the same for each
+/// model. ATOM, HETATM, ANISOU, and TER records for each model structure and are interspersed as
+/// needed. Each MODEL must have a corresponding ENDMDL record. Models are numbered sequentially,
+/// beginning with 1.
+///
+/// # ENDMDL
+///
+/// The ENDMDL records are paired with MODEL records to group individual structures found in a
+/// coordinate entry.
+pub struct ModelParser;
+
+impl FieldParser for ModelParser {
+    type Output = Option<u32>;
+    fn parse(inp: &[u8]) -> IResult<&[u8], Option<u32>> {
+        let (inp, line) = not_line_ending(inp)?;
+        let (inp, _) = line_ending(inp)?;
+        Ok((inp, columns(line, 11, 14).parse().ok()))
+    }
+}
+
+/// # TER
+///
+/// The [TER](http://www.wwpdb.org/documentation/file-format-content/format33/sect9.html#TER)
+/// record indicates the end of a list of ATOM/HETATM records for a chain.
+///
+/// ## Record Format
+///
+/// | COLUMNS | DATA  TYPE   | FIELD    | DEFINITION                 |
+/// | ------- | ------------ | -------- | -------------------------- |
+/// | 1 - 6   | Record name  | "TER   " |                            |
+/// | 7 - 11  | Integer      | serial   | Serial number.             |
+/// | 18 - 20 | Residue name | resName  | Residue name.              |
+/// | 22      | Character    | chainID  | Chain identifier.          |
+/// | 23 - 26 | Integer      | resSeq   | Residue sequence number.   |
+/// | 27      | AChar        | iCode    | Insertion code.            |
+///
+/// ## Details
+///
+/// Every chain of ATOM/HETATM records presented on SEQRES records is terminated with a TER
+/// record. The TER records occur in the coordinate section of the entry, and indicate the last
+/// residue presented for each polypeptide and/or nucleic acid chain for which there are determined
+/// coordinates. For proteins, the residue defined on the TER record is the carboxy-terminal
+/// residue; for nucleic acids it is the 3'-terminal residue. The TER record has the same residue
+/// name, chain identifier, sequence number and insertion code as the terminal residue. The serial
+/// number of the TER record is one number greater than the serial number of the ATOM/HETATM
+/// preceding the TER.
+///
+/// Some programs write a bare `TER`, so every field is optional.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ter {
+    pub id: Option<AtomSerial>,
+    pub residue_name: String,
+    pub chain: char,
+    pub sequence_number: Option<ResidueSerial>,
+    pub insertion_code: char,
+}
+
+impl Ter {
+    /// Whether `atom` belongs to the residue named on this record. Fields left blank are not
+    /// compared.
+    pub fn terminates(&self, atom: &Atom) -> bool {
+        (self.chain == ' ' || self.chain == atom.chain)
+            && self.sequence_number.unwrap_or(atom.sequence_number) == atom.sequence_number
+            && (self.insertion_code == ' ' || self.insertion_code == atom.insertion_code)
+    }
+}
+
+pub struct TerParser;
+
+impl FieldParser for TerParser {
+    type Output = Ter;
+    fn parse(inp: &[u8]) -> IResult<&[u8], Ter> {
+        let (inp, line) = not_line_ending(inp)?;
+        let (inp, _) = line_ending(inp)?;
+        let character = |column: usize| line.get(column - 7).map_or(' ', |&c| c as char);
+        Ok((
+            inp,
+            Ter {
+                id: hybrid36::decode(columns(line, 7, 11), 5),
+                residue_name: columns(line, 18, 20).to_owned(),
+                chain: character(22),
+                sequence_number: hybrid36::decode(columns(line, 23, 26), 4),
+                insertion_code: character(27),
+            },
+        ))
+    }
+}
+
 /// # Overview
 ///
 /// The CONECT records specify connectivity between atoms for which coordinates are supplied. The connectivity is described using the atom serial number as shown in the entry. CONECT records are mandatory for HET groups (excluding water) and for other Connect not specified in the standard residue connectivity table. These records are generated automatically.
@@ -261,22 +490,26 @@ pub struct ConectParser;
 impl FieldParser for ConectParser {
     type Output = Vec<Connect>;
     fn parse(inp: &[u8]) -> IResult<&[u8], Self::Output> {
+        let (inp, line) = not_line_ending(inp)?;
+        let (inp, _) = line_ending(inp)?;
+        let (bonded, x) = parse_hybrid36(line, 5)?;
         let mut res = Vec::new();
-        let (inp, x) = parse_right::<AtomSerial>(inp, 5)?;
-        let mut last_inp = inp;
-        loop {
-            let (inp, y) = parse_right::<AtomSerial>(last_inp, 5)?;
+        // * columns 12 - 31; the line may end early if fewer than four atoms are bonded
+        for field in bonded.chunks(5).take(4) {
+            let field = std::str::from_utf8(field).unwrap_or("");
+            if field.trim().is_empty() {
+                break;
+            }
+            let y = match hybrid36::decode(field, 5) {
+                Some(y) => y,
+                None => return Err(nom::Err::Error((inp, nom::error::ErrorKind::Digit))),
+            };
             if y > x {
                 res.push([x, y]);
             } else {
                 res.push([y, x]);
             }
-            if inp[..5] == b"     "[..] {
-                break;
-            }
-            last_inp = inp
         }
-        let (inp, _) = jump_newline(last_inp)?;
         Ok((inp, res))
     }
 }
@@ -28,13 +28,9 @@
 //! - https://enacademic.com/dic.nsf/enwiki/1879109
 
 use crate::common::parser::parse_right;
-use crate::common::parser::FieldParser;
+use crate::common::parser::{jump_newline, FieldParser};
 use crate::types::*;
-use nom::{
-    bytes::complete::take,
-    character::complete::{anychar, line_ending},
-    IResult,
-};
+use nom::{bytes::complete::take, error::ErrorKind, IResult};
 
 pub struct Cryst1Parser;
 impl FieldParser for Cryst1Parser {
@@ -46,11 +42,13 @@ impl FieldParser for Cryst1Parser {
         let (i, alpha) = parse_right::<f32>(i, 7)?; // 34 - 40
         let (i, beta) = parse_right::<f32>(i, 7)?; // 41 - 47
         let (i, gamma) = parse_right::<f32>(i, 7)?; // 48 - 54
-        let (i, lattice_type) = parse_lattice_type(i)?; // 55 - 57
-        let (i, space_group) = parse_space_group(i)?; // 58 - 66
+        let (i, s_group) = take(12usize)(i)?; // 55 - 66
+        let (lattice_type, space_group) = std::str::from_utf8(s_group)
+            .ok()
+            .and_then(parse_space_group)
+            .ok_or(nom::Err::Error((s_group, ErrorKind::Verify)))?;
         let (i, z) = parse_right::<u8>(i, 4)?; // 67 - 70
-        let (i, _) = take(10usize)(i)?; // 71 - 80
-        let (i, _) = line_ending(i)?;
+        let (i, _) = jump_newline(i)?; // 71 - 80
         Ok((
             i,
             Cryst1 {
@@ -68,51 +66,61 @@ impl FieldParser for Cryst1Parser {
     }
 }
 
-fn parse_lattice_type(i: &[u8]) -> IResult<&[u8], LatticeType> {
-    let (i, _) = take(1usize)(i)?; // 55
-    let (i, c) = anychar(i)?;
-    let lattice_type = match c {
-        'P' => LatticeType::Primitive,
-        'C' => LatticeType::SideCentered,
-        'I' => LatticeType::BodyCentered,
-        'F' => LatticeType::FaceCentered,
+/// Splits the space group symbol, such as `P 1 21 1`, into its lattice letter and up to three
+/// axes. An axis `21` is read as `GroupAxis(2, 1)` and a plain axis `2` as `GroupAxis(2, 0)`;
+/// whatever follows the digits of an axis (as in `41/a`) is ignored.
+pub(crate) fn parse_space_group(s_group: &str) -> Option<(LatticeType, SpaceGroup)> {
+    let mut tokens = s_group.split_whitespace();
+    let lattice_type = match tokens.next()? {
+        "P" => LatticeType::Primitive,
+        "C" => LatticeType::SideCentered,
+        "I" => LatticeType::BodyCentered,
+        "F" => LatticeType::FaceCentered,
         _ => LatticeType::Unknown,
     };
-    let (i, _) = take(1usize)(i)?; // 57
-    Ok((i, lattice_type))
+    let axes = tokens.map(parse_group_axis).collect::<Option<Vec<_>>>()?;
+    let mut axes = axes.into_iter();
+    let space_group = SpaceGroup(axes.next()?, axes.next(), axes.next());
+    match axes.next() {
+        Some(_) => None,
+        None => Some((lattice_type, space_group)),
+    }
 }
 
-fn parse_space_group(i: &[u8]) -> IResult<&[u8], SpaceGroup> {
-    let (i, a) = parse_group_axis(i)?; // 58 - 60
-    let (i, b) = parse_group_axis(i)?; // 61 - 63
-    let (i, c) = parse_group_axis(i)?; // 64 - 66
-    Ok((i, SpaceGroup(a.unwrap(), b, c)))
+fn parse_group_axis(token: &str) -> Option<GroupAxis> {
+    let mut digits = token.chars().map(|c| c.to_digit(10));
+    let rotation = digits.next()??;
+    let screw = digits.next().flatten().unwrap_or(0);
+    Some(GroupAxis(rotation, screw))
 }
 
-fn parse_group_axis(i: &[u8]) -> IResult<&[u8], Option<GroupAxis>> {
-    let (i, a) = anychar(i)?;
-    let (i, b) = anychar(i)?;
-    let (i, _) = anychar(i)?;
-    let r: Option<GroupAxis> = match (a, b) {
-        (' ', _) => None,
-        (_, ' ') => Some(GroupAxis(a.to_digit(10).unwrap(), 1u32)),
-        _ => Some(GroupAxis(a.to_digit(10).unwrap(), b.to_digit(10).unwrap())),
-    };
-    Ok((i, r))
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_parse_cryst1() {
+        let inp = b"   73.945   65.031   75.995  90.00 107.08  90.00 P 1 21 1      8          \n\
+ORIGX1";
+        let (i, r) = Cryst1Parser::parse(inp).unwrap();
+        assert_eq!(i, b"ORIGX1");
+        assert_eq!(r.a, 73.945);
+        assert_eq!(r.beta, 107.08);
+        assert_eq!(r.lattice_type, LatticeType::Primitive);
+        assert_eq!(
+            r.space_group,
+            SpaceGroup(
+                GroupAxis(1, 0),
+                Some(GroupAxis(2, 1)),
+                Some(GroupAxis(1, 0))
+            )
+        );
+        assert_eq!(r.z, 8);
 
-// #[cfg(test)]
-// mod tests {
-//     use super::*;
-//     #[test]
-//     fn test_parse_cryst1() {
-//         let i = "   41.980   41.980   88.920  90.00  90.00  90.00 P 43 21 2     8
-// ORIGX1      1.000000  0.000000  0.000000        0.00000                         ";
-//         let (i, r) = Cryst1Parser::parse(i).unwrap();
-//         assert_eq!(
-//             i.to_owned(),
-//             "ORIGX1      1.000000  0.000000  0.000000        0.00000                         "
-//                 .to_owned()
-//         );
-//     }
-// }
+        let inp = b"   49.250   49.250  265.480  90.00  90.00  90.00 P 41          8\n";
+        let (_, r) = Cryst1Parser::parse(inp).unwrap();
+        assert_eq!(r.space_group, SpaceGroup(GroupAxis(4, 1), None, None));
+
+        let inp = b"   49.250   49.250  265.480  90.00  90.00  90.00 P             8\n";
+        assert!(Cryst1Parser::parse(inp).is_err());
+    }
+}
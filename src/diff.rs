@@ -0,0 +1,511 @@
+// Copyright (c) 2020 Tianyi Shi
+//
+// This software is released under the MIT License.
+// https://opensource.org/licenses/MIT
+
+//! Differences between two entries, such as two releases of the same structure, as a typed
+//! report that also prints as text.
+//!
+//! Chains present in only one entry are reported as added or removed, except that a removed and
+//! an added chain with the same numbering of polymer residues count as renamed. Residues are matched by sequence
+//! number and insertion code within matched chains, and atoms by name and alternate location
+//! within matched residues. Only one model of each entry is compared; identifiers in the report
+//! are those of the first entry, except for what was added.
+
+use crate::dssp::SecondaryStructure;
+use crate::entry::Entry;
+use crate::geometry::distance;
+use crate::hierarchy::{ChainView, ModelView, ResidueView};
+use crate::sequence::SequenceSource;
+use crate::types::ResidueSerial;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DiffOptions {
+    /// The index of the model to compare in `structure.models`
+    pub model: usize,
+    /// The smallest displacement of an atom that is reported, in Å
+    pub tolerance: f32,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        Self {
+            model: 0,
+            tolerance: 0.1,
+        }
+    }
+}
+
+/// A field of the metadata, or of the records describing the molecules, that differs. `None`
+/// stands for a field missing from one entry.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetadataChange {
+    pub field: String,
+    pub a: Option<String>,
+    pub b: Option<String>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChainChange {
+    Added(char),
+    Removed(char),
+    Renamed { from: char, to: char },
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResidueChange {
+    /// A residue of the given name is only in the second entry
+    Added(String),
+    /// A residue of the given name is only in the first entry
+    Removed(String),
+    Mutated {
+        from: String,
+        to: String,
+    },
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResidueDifference {
+    pub chain: char,
+    pub sequence_number: ResidueSerial,
+    pub insertion_code: char,
+    pub change: ResidueChange,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AtomChange {
+    Added,
+    Removed,
+    /// Moved by the given distance, in Å
+    Moved(f32),
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct AtomDifference {
+    pub chain: char,
+    pub sequence_number: ResidueSerial,
+    pub insertion_code: char,
+    pub residue_name: String,
+    pub atom: String,
+    pub alt_loc: char,
+    pub change: AtomChange,
+}
+
+/// A residue to which the HELIX and SHEET records assign different secondary structures.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SecondaryStructureDifference {
+    pub chain: char,
+    pub sequence_number: ResidueSerial,
+    pub insertion_code: char,
+    pub a: SecondaryStructure,
+    pub b: SecondaryStructure,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StructureDiff {
+    /// The numbers of models, if they differ
+    pub models: Option<(usize, usize)>,
+    pub metadata: Vec<MetadataChange>,
+    pub chains: Vec<ChainChange>,
+    pub residues: Vec<ResidueDifference>,
+    pub atoms: Vec<AtomDifference>,
+    pub secondary_structure: Vec<SecondaryStructureDifference>,
+}
+
+impl StructureDiff {
+    pub fn is_empty(&self) -> bool {
+        self.models.is_none()
+            && self.metadata.is_empty()
+            && self.chains.is_empty()
+            && self.residues.is_empty()
+            && self.atoms.is_empty()
+            && self.secondary_structure.is_empty()
+    }
+}
+
+fn residue_id(chain: char, sequence_number: ResidueSerial, insertion_code: char) -> String {
+    format!("{} {}{}", chain, sequence_number, insertion_code)
+        .trim_end()
+        .to_owned()
+}
+
+impl fmt::Display for StructureDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "No differences");
+        }
+        if let Some((a, b)) = self.models {
+            writeln!(f, "Models: {} -> {}", a, b)?;
+        }
+        let missing = |value: &Option<String>| match value {
+            Some(value) => format!("{:?}", value),
+            None => "(none)".to_owned(),
+        };
+        if !self.metadata.is_empty() {
+            writeln!(f, "Metadata:")?;
+            for change in &self.metadata {
+                writeln!(
+                    f,
+                    "  {}: {} -> {}",
+                    change.field,
+                    missing(&change.a),
+                    missing(&change.b)
+                )?;
+            }
+        }
+        if !self.chains.is_empty() {
+            writeln!(f, "Chains:")?;
+            for change in &self.chains {
+                match change {
+                    ChainChange::Added(c) => writeln!(f, "  {} added", c)?,
+                    ChainChange::Removed(c) => writeln!(f, "  {} removed", c)?,
+                    ChainChange::Renamed { from, to } => {
+                        writeln!(f, "  {} renamed to {}", from, to)?
+                    }
+                }
+            }
+        }
+        if !self.residues.is_empty() {
+            writeln!(f, "Residues:")?;
+            for r in &self.residues {
+                let id = residue_id(r.chain, r.sequence_number, r.insertion_code);
+                match &r.change {
+                    ResidueChange::Added(name) => writeln!(f, "  {} {} added", id, name)?,
+                    ResidueChange::Removed(name) => writeln!(f, "  {} {} removed", id, name)?,
+                    ResidueChange::Mutated { from, to } => {
+                        writeln!(f, "  {} {} -> {}", id, from, to)?
+                    }
+                }
+            }
+        }
+        if !self.atoms.is_empty() {
+            writeln!(f, "Atoms:")?;
+            for a in &self.atoms {
+                let mut atom = a.atom.clone();
+                if a.alt_loc != ' ' {
+                    atom = format!("{} ({})", atom, a.alt_loc);
+                }
+                let id = residue_id(a.chain, a.sequence_number, a.insertion_code);
+                match a.change {
+                    AtomChange::Added => writeln!(f, "  {} {} {} added", id, a.residue_name, atom)?,
+                    AtomChange::Removed => {
+                        writeln!(f, "  {} {} {} removed", id, a.residue_name, atom)?
+                    }
+                    AtomChange::Moved(d) => writeln!(
+                        f,
+                        "  {} {} {} moved by {:.3} Å",
+                        id, a.residue_name, atom, d
+                    )?,
+                }
+            }
+        }
+        if !self.secondary_structure.is_empty() {
+            writeln!(f, "Secondary structure:")?;
+            let code = |s: SecondaryStructure| match s.code() {
+                ' ' => '-',
+                c => c,
+            };
+            for s in &self.secondary_structure {
+                writeln!(
+                    f,
+                    "  {}: {} -> {}",
+                    residue_id(s.chain, s.sequence_number, s.insertion_code),
+                    code(s.a),
+                    code(s.b)
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The fields compared as metadata, each as text, with chain identifiers passed through `chain`.
+fn metadata_fields(entry: &Entry, chain: impl Fn(char) -> char) -> Vec<(String, String)> {
+    let mut fields = Vec::new();
+    if let Some(metadata) = &entry.structure.metadata {
+        if let Some(header) = &metadata.header {
+            fields.push(("ID code".to_owned(), header.id_code.clone()));
+            fields.push(("classification".to_owned(), header.classification.clone()));
+            fields.push((
+                "deposition date".to_owned(),
+                header.deposition_date.to_string(),
+            ));
+        }
+        if let Some(title) = &metadata.title {
+            fields.push(("title".to_owned(), title.clone()));
+        }
+        if let Some(authors) = &metadata.authors {
+            fields.push(("authors".to_owned(), authors.join(", ")));
+        }
+        if let Some(techniques) = &metadata.experimental_techniques {
+            let techniques: Vec<String> = techniques.iter().map(|t| format!("{:?}", t)).collect();
+            fields.push(("experimental techniques".to_owned(), techniques.join(", ")));
+        }
+        if let Some(c) = &metadata.cryst1 {
+            fields.push((
+                "unit cell".to_owned(),
+                format!(
+                    "{:.3} {:.3} {:.3} {:.2} {:.2} {:.2} Z={}",
+                    c.a, c.b, c.c, c.alpha, c.beta, c.gamma, c.z
+                ),
+            ));
+        }
+    }
+    for compound in &entry.title_section.compounds {
+        let chains: String = compound.chains.iter().map(|&c| chain(c)).collect();
+        fields.push((
+            format!("entity {}", compound.mol_id),
+            format!(
+                "{} (chains {})",
+                compound.molecule.as_deref().unwrap_or("?"),
+                chains
+            ),
+        ));
+    }
+    for sequence in entry.sequences(SequenceSource::Seqres) {
+        fields.push((
+            format!("SEQRES {}", chain(sequence.chain)),
+            sequence.sequence,
+        ));
+    }
+    fields
+}
+
+/// The numbers of the polymer residues of a chain, or of all its residues if it has no polymer,
+/// by which renamed chains are recognised.
+fn signature(chain: &ChainView) -> Vec<(ResidueSerial, char)> {
+    let residues = if chain.polymer.is_empty() {
+        &chain.non_polymer
+    } else {
+        &chain.polymer
+    };
+    residues
+        .iter()
+        .map(|r| (r.sequence_number(), r.insertion_code()))
+        .collect()
+}
+
+/// The view of the model at `index`, empty if there is none.
+fn model_view(entry: &Entry, index: usize) -> ModelView<'_> {
+    match (
+        entry.structure.models.get(index),
+        entry.model_info.get(index),
+    ) {
+        (Some(model), Some(info)) => ModelView::new(index, model, info),
+        _ => ModelView {
+            index,
+            serial: 0,
+            atoms: &[],
+            chains: Vec::new(),
+        },
+    }
+}
+
+type ResidueMap<'a, 'b> = HashMap<(ResidueSerial, char), &'b ResidueView<'a>>;
+
+impl Entry {
+    /// The differences from this entry to `other`.
+    pub fn diff(&self, other: &Entry, options: &DiffOptions) -> StructureDiff {
+        let mut diff = StructureDiff::default();
+        let (n, m) = (self.structure.models.len(), other.structure.models.len());
+        if n != m {
+            diff.models = Some((n, m));
+        }
+
+        let (va, vb) = (
+            model_view(self, options.model),
+            model_view(other, options.model),
+        );
+
+        // * chains paired by identifier, then removed and added chains with equal residues
+        let mut pairs: Vec<(&ChainView, &ChainView)> = Vec::new();
+        let mut removed: Vec<&ChainView> = Vec::new();
+        for chain in &va.chains {
+            match vb.chain(chain.id) {
+                Some(other) => pairs.push((chain, other)),
+                None => removed.push(chain),
+            }
+        }
+        let mut added: Vec<&ChainView> = vb
+            .chains
+            .iter()
+            .filter(|c| va.chain(c.id).is_none())
+            .collect();
+        for chain in removed {
+            match added.iter().position(|c| signature(c) == signature(chain)) {
+                Some(k) => {
+                    let to = added.remove(k);
+                    diff.chains.push(ChainChange::Renamed {
+                        from: chain.id,
+                        to: to.id,
+                    });
+                    pairs.push((chain, to));
+                }
+                None => diff.chains.push(ChainChange::Removed(chain.id)),
+            }
+        }
+        diff.chains
+            .extend(added.iter().map(|c| ChainChange::Added(c.id)));
+
+        // * chains of `other` are named as in this entry where they were renamed
+        let renamed = |chain: char| {
+            diff.chains
+                .iter()
+                .find_map(|c| match c {
+                    ChainChange::Renamed { from, to } if *to == chain => Some(*from),
+                    _ => None,
+                })
+                .unwrap_or(chain)
+        };
+        let (a, b) = (
+            metadata_fields(self, |c| c),
+            metadata_fields(other, renamed),
+        );
+        let mut metadata = Vec::new();
+        let mut seen = BTreeSet::new();
+        for (name, _) in a.iter().chain(&b) {
+            if !seen.insert(name) {
+                continue;
+            }
+            let get = |fields: &[(String, String)]| {
+                fields
+                    .iter()
+                    .find(|(n, _)| n == name)
+                    .map(|(_, v)| v.clone())
+            };
+            let (a, b) = (get(&a), get(&b));
+            if a != b {
+                metadata.push(MetadataChange {
+                    field: name.clone(),
+                    a,
+                    b,
+                });
+            }
+        }
+        diff.metadata = metadata;
+
+        for (ca, cb) in &pairs {
+            let residues = |c: &'_ ChainView<'_>| -> Vec<(ResidueSerial, char)> {
+                c.residues()
+                    .map(|r| (r.sequence_number(), r.insertion_code()))
+                    .collect()
+            };
+            let ra: ResidueMap = ca
+                .residues()
+                .map(|r| ((r.sequence_number(), r.insertion_code()), r))
+                .collect();
+            let rb: ResidueMap = cb
+                .residues()
+                .map(|r| ((r.sequence_number(), r.insertion_code()), r))
+                .collect();
+            let mut keys = residues(ca);
+            keys.extend(residues(cb).into_iter().filter(|k| !ra.contains_key(k)));
+            for key in keys {
+                let chain = if ra.contains_key(&key) { ca.id } else { cb.id };
+                let change = match (ra.get(&key), rb.get(&key)) {
+                    (Some(x), Some(y)) => {
+                        let (from, to) = (x.name(), y.name());
+                        if from == to {
+                            diff_atoms(x, y, options.tolerance, &mut diff.atoms);
+                            continue;
+                        }
+                        ResidueChange::Mutated { from, to }
+                    }
+                    (Some(x), None) => ResidueChange::Removed(x.name()),
+                    (None, Some(y)) => ResidueChange::Added(y.name()),
+                    (None, None) => continue,
+                };
+                diff.residues.push(ResidueDifference {
+                    chain,
+                    sequence_number: key.0,
+                    insertion_code: key.1,
+                    change,
+                });
+            }
+        }
+
+        if options.model < n && options.model < m {
+            let (sa, sb) = (
+                self.secondary_structure(options.model),
+                other.secondary_structure(options.model),
+            );
+            for (ca, cb) in &pairs {
+                let (x, y) = match (
+                    sa.iter().find(|s| s.chain == ca.id),
+                    sb.iter().find(|s| s.chain == cb.id),
+                ) {
+                    (Some(x), Some(y)) => (x, y),
+                    _ => continue,
+                };
+                let states: HashMap<_, _> = y.residues.iter().zip(&y.states).collect();
+                for (residue, &a) in x.residues.iter().zip(&x.states) {
+                    match states.get(residue) {
+                        Some(&&b) if b != a => {
+                            diff.secondary_structure.push(SecondaryStructureDifference {
+                                chain: ca.id,
+                                sequence_number: residue.0,
+                                insertion_code: residue.1,
+                                a,
+                                b,
+                            })
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+        diff
+    }
+}
+
+fn diff_atoms(
+    a: &ResidueView,
+    b: &ResidueView,
+    tolerance: f32,
+    differences: &mut Vec<AtomDifference>,
+) {
+    let name = |atom: &crate::types::Atom| {
+        std::str::from_utf8(&atom.name.0)
+            .unwrap_or("")
+            .trim()
+            .to_owned()
+    };
+    let mut report = |atom: &crate::types::Atom, change: AtomChange| {
+        differences.push(AtomDifference {
+            chain: a.chain(),
+            sequence_number: a.sequence_number(),
+            insertion_code: a.insertion_code(),
+            residue_name: a.name(),
+            atom: name(atom),
+            alt_loc: atom.id1,
+            change,
+        })
+    };
+    for x in a.atoms {
+        match b.atoms.iter().find(|y| y.id1 == x.id1 && y.name == x.name) {
+            Some(y) => {
+                let d = distance(&x.coord, &y.coord);
+                if d > tolerance {
+                    report(x, AtomChange::Moved(d));
+                }
+            }
+            None => report(x, AtomChange::Removed),
+        }
+    }
+    for y in b.atoms {
+        if !a.atoms.iter().any(|x| x.id1 == y.id1 && x.name == y.name) {
+            report(y, AtomChange::Added);
+        }
+    }
+}
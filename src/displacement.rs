@@ -0,0 +1,166 @@
+// Copyright (c) 2020 Tianyi Shi
+//
+// This software is released under the MIT License.
+// https://opensource.org/licenses/MIT
+
+//! Anisotropic displacement parameters (ADPs).
+//!
+//! ANISOU records store the elements of the symmetric displacement tensor U as integers, in units
+//! of 10⁻⁴ Å². [`AnisotropicDisplacement`] holds the tensor in Å² and derives the quantities used
+//! in refinement quality control:
+//!
+//! - the equivalent isotropic displacement `U_eq = (U11 + U22 + U33) / 3` and the equivalent
+//!   isotropic B factor `B_eq = 8π² U_eq`;
+//! - the principal axes, i.e. the eigenvalues and eigenvectors of U;
+//! - the anisotropy, the ratio of the smallest to the largest eigenvalue, which is 1 for an
+//!   isotropic atom and approaches 0 for a flat or elongated one.
+//!
+//! [`AnisotropicDisplacement`]: struct.AnisotropicDisplacement.html
+
+use crate::coordinate::Sigatm;
+use crate::entry::Entry;
+use crate::geometry::jacobi_eigen;
+use crate::types::{Anisou, AtomSerial};
+use std::collections::HashMap;
+use std::f32::consts::PI;
+
+/// A symmetric 3 × 3 displacement tensor in Å².
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AnisotropicDisplacement {
+    pub u: [[f32; 3]; 3],
+}
+
+impl From<&Anisou> for AnisotropicDisplacement {
+    fn from(a: &Anisou) -> Self {
+        let s = |x: i32| x as f32 * 1e-4;
+        Self {
+            u: [
+                [s(a.u11), s(a.u12), s(a.u13)],
+                [s(a.u12), s(a.u22), s(a.u23)],
+                [s(a.u13), s(a.u23), s(a.u33)],
+            ],
+        }
+    }
+}
+
+impl AnisotropicDisplacement {
+    /// Equivalent isotropic displacement in Å²
+    pub fn u_eq(&self) -> f32 {
+        (self.u[0][0] + self.u[1][1] + self.u[2][2]) / 3.0
+    }
+
+    /// Equivalent isotropic B factor in Å²
+    pub fn b_eq(&self) -> f32 {
+        8.0 * PI * PI * self.u_eq()
+    }
+
+    /// Eigenvalues (mean square displacements along the axes, in Å²) and unit eigenvectors of
+    /// the tensor, sorted by decreasing eigenvalue.
+    pub fn principal_axes(&self) -> [(f32, [f32; 3]); 3] {
+        let mut u = [[0f64; 3]; 3];
+        for (row, urow) in u.iter_mut().zip(&self.u) {
+            for (x, &ux) in row.iter_mut().zip(urow) {
+                *x = ux as f64;
+            }
+        }
+        let (values, vectors) = jacobi_eigen(u);
+        let axis = |i: usize| {
+            let v = |k: usize| vectors[k][i] as f32;
+            (values[i] as f32, [v(0), v(1), v(2)])
+        };
+        let mut axes = [axis(0), axis(1), axis(2)];
+        axes.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+        axes
+    }
+
+    /// The ratio of the smallest to the largest eigenvalue. Not meaningful (`NaN` or negative)
+    /// for tensors that are not positive definite, which are physically impossible.
+    pub fn anisotropy(&self) -> f32 {
+        let axes = self.principal_axes();
+        axes[2].0 / axes[0].0
+    }
+
+    /// Whether all eigenvalues are positive, as they must be for the tensor to describe a
+    /// displacement.
+    pub fn is_positive_definite(&self) -> bool {
+        self.principal_axes()[2].0 > 0.0
+    }
+}
+
+impl Entry {
+    /// The anisotropic displacement of each atom of the model at `model` (an index in
+    /// `structure.models`), in the order of `Model::atoms`. `None` for atoms without ANISOU.
+    pub fn displacements(&self, model: usize) -> Vec<Option<AnisotropicDisplacement>> {
+        let model = &self.structure.models[model];
+        let by_serial: HashMap<AtomSerial, &Anisou> =
+            model.anisou.iter().map(|a| (a.id, a)).collect();
+        model
+            .atoms
+            .iter()
+            .map(|atom| by_serial.get(&atom.id).map(|&a| a.into()))
+            .collect()
+    }
+
+    /// The standard deviations of the anisotropic displacement of each atom, from SIGUIJ, in the
+    /// order of `Model::atoms`.
+    pub fn displacement_uncertainties(&self, model: usize) -> Vec<Option<AnisotropicDisplacement>> {
+        let by_serial: HashMap<AtomSerial, &Anisou> = self.model_info[model]
+            .siguij
+            .iter()
+            .map(|a| (a.id, a))
+            .collect();
+        self.structure.models[model]
+            .atoms
+            .iter()
+            .map(|atom| by_serial.get(&atom.id).map(|&a| a.into()))
+            .collect()
+    }
+
+    /// The standard deviations of the atomic parameters of each atom, from SIGATM, in the order
+    /// of `Model::atoms`.
+    pub fn atom_uncertainties(&self, model: usize) -> Vec<Option<&Sigatm>> {
+        let by_serial: HashMap<AtomSerial, &Sigatm> = self.model_info[model]
+            .sigatm
+            .iter()
+            .map(|s| (s.id, s))
+            .collect();
+        self.structure.models[model]
+            .atoms
+            .iter()
+            .map(|atom| by_serial.get(&atom.id).copied())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_principal_axes() {
+        let anisou = Anisou {
+            id: 1,
+            u11: 5629,
+            u22: 10666,
+            u33: 9337,
+            u12: 1386,
+            u13: -676,
+            u23: -246,
+        };
+        let adp = AnisotropicDisplacement::from(&anisou);
+        assert!((adp.u_eq() - 0.8544).abs() < 1e-4);
+        assert!((adp.b_eq() - 67.46).abs() < 0.01);
+        let axes = adp.principal_axes();
+        let trace: f32 = axes.iter().map(|a| a.0).sum();
+        assert!((trace - 3.0 * adp.u_eq()).abs() < 1e-5);
+        for (value, vector) in axes.iter() {
+            // * U v = λ v
+            for i in 0..3 {
+                let uv: f32 = (0..3).map(|j| adp.u[i][j] * vector[j]).sum();
+                assert!((uv - value * vector[i]).abs() < 1e-5);
+            }
+        }
+        assert!(adp.anisotropy() > 0.0 && adp.anisotropy() < 1.0);
+        assert!(adp.is_positive_definite());
+    }
+}
@@ -0,0 +1,707 @@
+// Copyright (c) 2020 Tianyi Shi
+//
+// This software is released under the MIT License.
+// https://opensource.org/licenses/MIT
+
+//! Assignment of secondary structure from backbone coordinates, following DSSP (Kabsch & Sander,
+//! 1983).
+//!
+//! Backbone hydrogen bonds are found with the electrostatic energy of DSSP, placing the amide
+//! hydrogen opposite the carbonyl oxygen of the preceding residue. From these, DSSP derives
+//!
+//! - *n*-turns: an H-bond from the C=O of residue *i* to the N-H of residue *i* + *n*;
+//! - helices: two consecutive *n*-turns make a minimal helix of *n* residues, which is an α helix
+//!   (H) for *n* = 4, a 3₁₀ helix (G) for *n* = 3 and a π helix (I) for *n* = 5;
+//! - β-bridges: pairs of residues joined by a parallel or antiparallel H-bond pattern. Bridges
+//!   form ladders, ladders joined by β-bulges form strands (E), isolated bridges are marked B, and
+//!   ladders sharing residues form sheets;
+//! - turns (T), which are *n*-turns outside any helix, and bends (S), where the chain bends by
+//!   more than 70°.
+//!
+//! Where assignments overlap, H takes precedence over E and B, which take precedence over G, I, T
+//! and S, in the order of the original program.
+//!
+//! The result is available per residue and as the `Helix` and `Sheet` types that
+//! [`HelixParser`](../secondary_structure/struct.HelixParser.html) and
+//! [`SheetParser`](../secondary_structure/struct.SheetParser.html) produce.
+
+use crate::geometry::{angle, distance, Coord};
+use crate::hierarchy::{ModelView, ResidueView};
+use crate::types::{
+    AtomName, Helix, HelixClass, Registration, Residue, ResidueSerial, Sense, Sheet, Strand,
+};
+
+/// Hydrogen bonds are those with an energy below this, in kcal/mol.
+const HBOND_ENERGY_CUTOFF: f32 = -0.5;
+/// The energy assigned to H-bonds between atoms closer than `MIN_DISTANCE`.
+const MIN_HBOND_ENERGY: f32 = -9.9;
+const MIN_DISTANCE: f32 = 0.5;
+/// Pairs of residues whose Cα atoms are further apart than this, in Å, are not H-bonded.
+const MAX_CA_DISTANCE: f32 = 9.0;
+/// The maximum length of the C-N bond between consecutive residues, in Å.
+const MAX_PEPTIDE_BOND_LENGTH: f32 = 2.5;
+
+/// The secondary structure of a residue, as the one-letter codes of DSSP.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecondaryStructure {
+    /// α helix (H)
+    AlphaHelix,
+    /// Isolated β-bridge (B)
+    Bridge,
+    /// Extended strand in a β-ladder (E)
+    Strand,
+    /// 3₁₀ helix (G)
+    Helix310,
+    /// π helix (I)
+    PiHelix,
+    /// H-bonded turn (T)
+    Turn,
+    /// Bend (S)
+    Bend,
+    /// None of the above (blank)
+    Coil,
+}
+
+impl SecondaryStructure {
+    pub fn code(self) -> char {
+        match self {
+            Self::AlphaHelix => 'H',
+            Self::Bridge => 'B',
+            Self::Strand => 'E',
+            Self::Helix310 => 'G',
+            Self::PiHelix => 'I',
+            Self::Turn => 'T',
+            Self::Bend => 'S',
+            Self::Coil => ' ',
+        }
+    }
+
+    /// The reduction to three states: `H` for H, G and I, `E` for E and B, and `C` for the rest.
+    pub fn three_state(self) -> char {
+        match self {
+            Self::AlphaHelix | Self::Helix310 | Self::PiHelix => 'H',
+            Self::Strand | Self::Bridge => 'E',
+            _ => 'C',
+        }
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DsspResidue {
+    pub chain: char,
+    pub sequence_number: ResidueSerial,
+    pub insertion_code: char,
+    pub structure: SecondaryStructure,
+    /// Index of the sheet in `Dssp::sheets`, for residues in a β-ladder
+    pub sheet: Option<usize>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone)]
+pub struct Dssp {
+    /// The amino acid residues with a complete backbone (N, CA, C and O), in the order of the
+    /// chains of the model
+    pub residues: Vec<DsspResidue>,
+    #[cfg_attr(feature = "serde", serde(deserialize_with = "crate::json::helices"))]
+    pub helices: Vec<Helix>,
+    #[cfg_attr(feature = "serde", serde(deserialize_with = "crate::json::sheets"))]
+    pub sheets: Vec<Sheet>,
+}
+
+struct Backbone<'a> {
+    residue: &'a ResidueView<'a>,
+    n: Coord,
+    ca: Coord,
+    c: Coord,
+    o: Coord,
+    h: Option<Coord>,
+    proline: bool,
+    /// Whether the residue is not bonded to the preceding one
+    chain_break: bool,
+    /// The two lowest-energy H-bonds from the N-H of the residue: (acceptor, energy)
+    acceptors: [(usize, f32); 2],
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BridgeKind {
+    Parallel,
+    Antiparallel,
+}
+
+/// A run of β-bridges (i, j), possibly joined with others across β-bulges.
+struct Ladder {
+    kind: BridgeKind,
+    pairs: Vec<(usize, usize)>,
+    bulge: bool,
+}
+
+impl Ladder {
+    fn i_range(&self) -> (usize, usize) {
+        (self.pairs[0].0, self.pairs[self.pairs.len() - 1].0)
+    }
+    fn j_range(&self) -> (usize, usize) {
+        let (a, b) = (self.pairs[0].1, self.pairs[self.pairs.len() - 1].1);
+        (a.min(b), a.max(b))
+    }
+    fn is_strand(&self) -> bool {
+        self.pairs.len() > 1 || self.bulge
+    }
+}
+
+fn hbond_energy(donor: &Backbone, acceptor: &Backbone) -> f32 {
+    let h = match donor.h {
+        Some(h) if !donor.proline => h,
+        _ => return 0.0,
+    };
+    let d_on = distance(&acceptor.o, &donor.n);
+    let d_ch = distance(&acceptor.c, &h);
+    let d_oh = distance(&acceptor.o, &h);
+    let d_cn = distance(&acceptor.c, &donor.n);
+    if d_on < MIN_DISTANCE || d_ch < MIN_DISTANCE || d_oh < MIN_DISTANCE || d_cn < MIN_DISTANCE {
+        MIN_HBOND_ENERGY
+    } else {
+        // * q1 q2 f = 0.42 e × 0.20 e × 332 kcal Å / (mol e²)
+        0.084 * 332.0 * (1.0 / d_on + 1.0 / d_ch - 1.0 / d_oh - 1.0 / d_cn)
+    }
+}
+
+fn backbone<'a>(residue: &'a ResidueView<'a>) -> Option<Backbone<'a>> {
+    if !matches!(residue.residue(), Residue::AminoAcid(_)) {
+        return None;
+    }
+    Some(Backbone {
+        residue,
+        n: residue.atom("N")?.coord,
+        ca: residue.atom("CA")?.coord,
+        c: residue.atom("C")?.coord,
+        o: residue.atom("O")?.coord,
+        h: None,
+        proline: residue.name() == "PRO",
+        chain_break: true,
+        acceptors: [(0, 0.0); 2],
+    })
+}
+
+struct Calculation<'a> {
+    residues: Vec<Backbone<'a>>,
+}
+
+impl<'a> Calculation<'a> {
+    fn new(model: &'a ModelView<'a>) -> Self {
+        let mut residues: Vec<Backbone> = Vec::new();
+        for chain in &model.chains {
+            let mut first = true;
+            for residue in chain.polymer.iter().filter_map(backbone) {
+                let mut residue = residue;
+                if let Some(prev) = residues.last().filter(|_| !first) {
+                    if distance(&prev.c, &residue.n) <= MAX_PEPTIDE_BOND_LENGTH {
+                        residue.chain_break = false;
+                        let co = [
+                            prev.c[0] - prev.o[0],
+                            prev.c[1] - prev.o[1],
+                            prev.c[2] - prev.o[2],
+                        ];
+                        let len = distance(&prev.c, &prev.o);
+                        residue.h = Some([
+                            residue.n[0] + co[0] / len,
+                            residue.n[1] + co[1] / len,
+                            residue.n[2] + co[2] / len,
+                        ]);
+                    }
+                }
+                residues.push(residue);
+                first = false;
+            }
+        }
+        let mut calc = Self { residues };
+        calc.find_hbonds();
+        calc
+    }
+
+    fn find_hbonds(&mut self) {
+        let n = self.residues.len();
+        for i in 0..n {
+            for j in i + 1..n {
+                if distance(&self.residues[i].ca, &self.residues[j].ca) >= MAX_CA_DISTANCE {
+                    continue;
+                }
+                let e = hbond_energy(&self.residues[i], &self.residues[j]);
+                self.record_hbond(i, j, e);
+                if j != i + 1 {
+                    let e = hbond_energy(&self.residues[j], &self.residues[i]);
+                    self.record_hbond(j, i, e);
+                }
+            }
+        }
+    }
+
+    fn record_hbond(&mut self, donor: usize, acceptor: usize, energy: f32) {
+        let acceptors = &mut self.residues[donor].acceptors;
+        if energy < acceptors[0].1 {
+            acceptors[1] = acceptors[0];
+            acceptors[0] = (acceptor, energy);
+        } else if energy < acceptors[1].1 {
+            acceptors[1] = (acceptor, energy);
+        }
+    }
+
+    /// Whether the C=O of residue `o` is H-bonded to the N-H of residue `nh`.
+    fn hbond(&self, o: usize, nh: usize) -> bool {
+        self.residues.get(nh).map(|r| {
+            r.acceptors
+                .iter()
+                .any(|&(a, e)| a == o && e < HBOND_ENERGY_CUTOFF)
+        }) == Some(true)
+    }
+
+    /// Whether residues `a` to `b` form a continuous chain.
+    fn no_break(&self, a: usize, b: usize) -> bool {
+        b < self.residues.len() && (a + 1..=b).all(|i| !self.residues[i].chain_break)
+    }
+
+    fn turns(&self, n: usize) -> Vec<bool> {
+        (0..self.residues.len())
+            .map(|i| self.no_break(i, i + n) && self.hbond(i, i + n))
+            .collect()
+    }
+
+    fn bridge(&self, i: usize, j: usize) -> Option<BridgeKind> {
+        if i < 1 || j < 1 || !self.no_break(i - 1, i + 1) || !self.no_break(j - 1, j + 1) {
+            return None;
+        }
+        if (self.hbond(i - 1, j) && self.hbond(j, i + 1))
+            || (self.hbond(j - 1, i) && self.hbond(i, j + 1))
+        {
+            Some(BridgeKind::Parallel)
+        } else if (self.hbond(i, j) && self.hbond(j, i))
+            || (self.hbond(i - 1, j + 1) && self.hbond(j - 1, i + 1))
+        {
+            Some(BridgeKind::Antiparallel)
+        } else {
+            None
+        }
+    }
+
+    fn ladders(&self) -> Vec<Ladder> {
+        let n = self.residues.len();
+        let mut ladders: Vec<Ladder> = Vec::new();
+        for i in 1..n.saturating_sub(1) {
+            for j in i + 3..n.saturating_sub(1) {
+                let kind = match self.bridge(i, j) {
+                    Some(kind) => kind,
+                    None => continue,
+                };
+                let extends = |l: &Ladder| {
+                    let (li, lj) = l.pairs[l.pairs.len() - 1];
+                    l.kind == kind
+                        && li + 1 == i
+                        && match kind {
+                            BridgeKind::Parallel => lj + 1 == j,
+                            BridgeKind::Antiparallel => lj == j + 1,
+                        }
+                };
+                match ladders.iter_mut().find(|l| extends(l)) {
+                    Some(ladder) => ladder.pairs.push((i, j)),
+                    None => ladders.push(Ladder {
+                        kind,
+                        pairs: vec![(i, j)],
+                        bulge: false,
+                    }),
+                }
+            }
+        }
+        // * join ladders separated by a β-bulge: a gap of at most 4 residues on one strand and
+        // * at most 1 on the other
+        let mut k = 0;
+        while k < ladders.len() {
+            let joined = (k + 1..ladders.len()).find(|&m| self.bulge(&ladders[k], &ladders[m]));
+            match joined {
+                Some(m) => {
+                    let next = ladders.remove(m);
+                    ladders[k].pairs.extend(next.pairs);
+                    ladders[k].bulge = true;
+                }
+                None => k += 1,
+            }
+        }
+        ladders
+    }
+
+    fn bulge(&self, a: &Ladder, b: &Ladder) -> bool {
+        if a.kind != b.kind {
+            return false;
+        }
+        let (ai, aj) = a.pairs[a.pairs.len() - 1];
+        let (bi, bj) = b.pairs[0];
+        if bi <= ai || !self.no_break(ai, bi) {
+            return false;
+        }
+        let (j_from, j_to) = match a.kind {
+            BridgeKind::Parallel => (aj, bj),
+            BridgeKind::Antiparallel => (bj, aj),
+        };
+        if j_to <= j_from || !self.no_break(j_from, j_to) {
+            return false;
+        }
+        let (di, dj) = (bi - ai, j_to - j_from);
+        (di < 6 && dj < 3) || (di < 3 && dj < 6)
+    }
+
+    fn bend(&self, i: usize) -> bool {
+        i >= 2
+            && self.no_break(i - 2, i + 2)
+            && 180.0
+                - angle(
+                    &self.residues[i - 2].ca,
+                    &self.residues[i].ca,
+                    &self.residues[i + 2].ca,
+                )
+                > 70.0
+    }
+}
+
+impl<'a> ModelView<'a> {
+    /// Assigns the secondary structure of the amino acid residues of the polymers.
+    pub fn dssp(&self) -> Dssp {
+        let calc = Calculation::new(self);
+        let n = calc.residues.len();
+        let mut ss = vec![SecondaryStructure::Coil; n];
+        let turns = [calc.turns(3), calc.turns(4), calc.turns(5)];
+
+        let minimal_helices = |len: usize| -> Vec<usize> {
+            let turns = &turns[len - 3];
+            (1..n).filter(|&i| turns[i - 1] && turns[i]).collect()
+        };
+        for i in minimal_helices(4) {
+            for s in &mut ss[i..i + 4] {
+                *s = SecondaryStructure::AlphaHelix;
+            }
+        }
+
+        let ladders = calc.ladders();
+        let mut sheet_of_ladder: Vec<usize> = (0..ladders.len()).collect();
+        assign_ladders(&mut ss, &ladders);
+        // * ladders sharing a residue belong to the same sheet
+        let residues_of = |l: &Ladder| {
+            let ((i0, i1), (j0, j1)) = (l.i_range(), l.j_range());
+            (i0..=i1).chain(j0..=j1).collect::<Vec<_>>()
+        };
+        for a in 0..ladders.len() {
+            for b in a + 1..ladders.len() {
+                let (ra, rb) = (residues_of(&ladders[a]), residues_of(&ladders[b]));
+                if ra.iter().any(|r| rb.contains(r)) {
+                    let (sa, sb) = (sheet_of_ladder[a], sheet_of_ladder[b]);
+                    for s in sheet_of_ladder.iter_mut().filter(|s| **s == sb) {
+                        *s = sa;
+                    }
+                }
+            }
+        }
+
+        for (len, class) in &[
+            (3, SecondaryStructure::Helix310),
+            (5, SecondaryStructure::PiHelix),
+        ] {
+            for i in minimal_helices(*len) {
+                if ss[i..i + len]
+                    .iter()
+                    .all(|&s| s == SecondaryStructure::Coil || s == *class)
+                {
+                    for s in &mut ss[i..i + len] {
+                        *s = *class;
+                    }
+                }
+            }
+        }
+        for (k, turns) in turns.iter().enumerate() {
+            for i in (0..n).filter(|&i| turns[i]) {
+                for s in &mut ss[i + 1..i + k + 3] {
+                    if *s == SecondaryStructure::Coil {
+                        *s = SecondaryStructure::Turn;
+                    }
+                }
+            }
+        }
+        for (i, s) in ss.iter_mut().enumerate() {
+            if *s == SecondaryStructure::Coil && calc.bend(i) {
+                *s = SecondaryStructure::Bend;
+            }
+        }
+
+        // * number the sheets that contain a strand, which helices may have taken over
+        let is_strand = |k: &usize| ss[*k] == SecondaryStructure::Strand;
+        let mut sheet_ids: Vec<usize> = Vec::new();
+        for (l, ladder) in ladders.iter().enumerate() {
+            if ladder.is_strand()
+                && residues_of(ladder).iter().any(is_strand)
+                && !sheet_ids.contains(&sheet_of_ladder[l])
+            {
+                sheet_ids.push(sheet_of_ladder[l]);
+            }
+        }
+        let mut sheet_of_residue = vec![None; n];
+        for (l, ladder) in ladders.iter().enumerate() {
+            if let Some(sheet) = sheet_ids.iter().position(|&s| s == sheet_of_ladder[l]) {
+                for k in residues_of(ladder).into_iter().filter(is_strand) {
+                    sheet_of_residue[k] = Some(sheet);
+                }
+            }
+        }
+
+        let residues = calc
+            .residues
+            .iter()
+            .zip(ss.iter())
+            .zip(sheet_of_residue.iter())
+            .map(|((r, &structure), &sheet)| DsspResidue {
+                chain: r.residue.chain(),
+                sequence_number: r.residue.sequence_number(),
+                insertion_code: r.residue.insertion_code(),
+                structure,
+                sheet,
+            })
+            .collect();
+        let helices = helices(&calc, &ss);
+        let sheets = sheet_ids
+            .iter()
+            .enumerate()
+            .filter_map(|(id, &s)| {
+                let ladders: Vec<&Ladder> = ladders
+                    .iter()
+                    .enumerate()
+                    .filter(|(l, _)| sheet_of_ladder[*l] == s)
+                    .map(|(_, l)| l)
+                    .collect();
+                sheet(&calc, id, &ladders, &sheet_of_residue)
+            })
+            .collect();
+        Dssp {
+            residues,
+            helices,
+            sheets,
+        }
+    }
+}
+
+/// Marks the residues of ladders as E, and those of isolated bridges as B, except where an α helix
+/// (H) has already been assigned.
+fn assign_ladders(ss: &mut [SecondaryStructure], ladders: &[Ladder]) {
+    for ladder in ladders {
+        let ((i0, i1), (j0, j1)) = (ladder.i_range(), ladder.j_range());
+        if ladder.is_strand() {
+            for k in (i0..=i1).chain(j0..=j1) {
+                if ss[k] != SecondaryStructure::AlphaHelix {
+                    ss[k] = SecondaryStructure::Strand;
+                }
+            }
+        } else {
+            for k in [i0, j0].iter().copied() {
+                if ss[k] == SecondaryStructure::Coil {
+                    ss[k] = SecondaryStructure::Bridge;
+                }
+            }
+        }
+    }
+}
+
+fn position(calc: &Calculation, i: usize) -> (char, ResidueSerial) {
+    let r = calc.residues[i].residue;
+    (r.chain(), r.sequence_number())
+}
+
+/// Runs of H, G and I as HELIX records.
+fn helices(calc: &Calculation, ss: &[SecondaryStructure]) -> Vec<Helix> {
+    let mut helices = Vec::new();
+    let mut start = 0;
+    while start < ss.len() {
+        let class = match ss[start] {
+            SecondaryStructure::AlphaHelix => HelixClass::RightHandedAlpha,
+            SecondaryStructure::Helix310 => HelixClass::RightHanded310,
+            SecondaryStructure::PiHelix => HelixClass::RightHandedPi,
+            _ => {
+                start += 1;
+                continue;
+            }
+        };
+        let mut end = start;
+        while end + 1 < ss.len() && ss[end + 1] == ss[start] && calc.no_break(end, end + 1) {
+            end += 1;
+        }
+        helices.push(Helix {
+            id: (helices.len() + 1).to_string(),
+            class,
+            start: position(calc, start),
+            end: position(calc, end),
+            comment: String::new(),
+        });
+        start = end + 1;
+    }
+    helices
+}
+
+/// The SHEET records of the sheet formed by `ladders`, with strands listed from one edge.
+fn sheet(
+    calc: &Calculation,
+    id: usize,
+    ladders: &[&Ladder],
+    sheet_of_residue: &[Option<usize>],
+) -> Option<Sheet> {
+    // * strands are maximal runs of consecutive residues of the sheet
+    let mut strands: Vec<(usize, usize)> = Vec::new();
+    for (i, &sheet) in sheet_of_residue.iter().enumerate() {
+        if sheet != Some(id) {
+            continue;
+        }
+        match strands.last_mut() {
+            Some((_, end)) if *end + 1 == i && calc.no_break(*end, i) => *end = i,
+            _ => strands.push((i, i)),
+        }
+    }
+    let strand_of = |k: usize| strands.iter().position(|&(a, b)| a <= k && k <= b);
+    // * (strand, strand, kind) for each pair of strands joined by a ladder
+    let mut edges: Vec<(usize, usize, BridgeKind)> = Vec::new();
+    for ladder in ladders {
+        let (i, j) = ladder.pairs[0];
+        if let (Some(a), Some(b)) = (strand_of(i), strand_of(j)) {
+            edges.push((a, b, ladder.kind));
+        }
+    }
+    let neighbours = |s: usize| -> Vec<(usize, BridgeKind)> {
+        edges
+            .iter()
+            .filter_map(|&(a, b, kind)| match s {
+                _ if s == a => Some((b, kind)),
+                _ if s == b => Some((a, kind)),
+                _ => None,
+            })
+            .collect()
+    };
+
+    // * walk from an edge strand, stepping to an unvisited neighbour of the latest strand that
+    // * has one
+    let first = (0..strands.len())
+        .find(|&s| neighbours(s).len() == 1)
+        .unwrap_or(0);
+    let mut order = vec![(first, None)];
+    while order.len() < strands.len() {
+        let next = order.iter().rev().find_map(|&(s, _)| {
+            neighbours(s)
+                .into_iter()
+                .find(|(t, _)| !order.iter().any(|(o, _)| o == t))
+                .map(|(t, kind)| (t, Some((s, kind))))
+        });
+        match next {
+            Some(next) => order.push(next),
+            None => break,
+        }
+    }
+    if order.len() < 2 {
+        return None;
+    }
+
+    let mut sheet = Sheet {
+        id: sheet_label(id),
+        strands: Vec::new(),
+        registration: Vec::new(),
+    };
+    for &(s, prev) in &order {
+        let (start, end) = strands[s];
+        let sense = match prev {
+            None => Sense::Unknown,
+            Some((_, BridgeKind::Parallel)) => Sense::Parallel,
+            Some((_, BridgeKind::Antiparallel)) => Sense::Antiparallel,
+        };
+        sheet.strands.push(Strand {
+            start: position(calc, start),
+            end: position(calc, end),
+            sense,
+        });
+        if let Some((p, _)) = prev {
+            if let Some(registration) = registration(calc, strands[s], strands[p]) {
+                sheet.registration.push(registration);
+            }
+        }
+    }
+    Some(sheet)
+}
+
+/// An H-bond between the current and the previous strand.
+fn registration(
+    calc: &Calculation,
+    (c0, c1): (usize, usize),
+    (p0, p1): (usize, usize),
+) -> Option<Registration> {
+    let n = AtomName(*b"N   ");
+    let o = AtomName(*b"O   ");
+    for c in c0..=c1 {
+        for p in p0..=p1 {
+            let (chain_c, seq_c) = position(calc, c);
+            let (chain_p, seq_p) = position(calc, p);
+            if calc.hbond(p, c) {
+                return Some(Registration {
+                    curr: (n, chain_c, seq_c),
+                    prev: (o, chain_p, seq_p),
+                });
+            }
+            if calc.hbond(c, p) {
+                return Some(Registration {
+                    curr: (o, chain_c, seq_c),
+                    prev: (n, chain_p, seq_p),
+                });
+            }
+        }
+    }
+    None
+}
+
+/// Sheet identifiers A to Z, then numbers.
+fn sheet_label(id: usize) -> String {
+    if id < 26 {
+        ((b'A' + id as u8) as char).to_string()
+    } else {
+        (id + 1).to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_precedence() {
+        use SecondaryStructure::*;
+        let mut ss = vec![Coil; 12];
+        for s in &mut ss[2..6] {
+            *s = AlphaHelix;
+        }
+        let ladders = [
+            // * a ladder whose first strand overlaps the helix
+            Ladder {
+                kind: BridgeKind::Antiparallel,
+                pairs: vec![(4, 10), (5, 9), (6, 8)],
+                bulge: false,
+            },
+            // * isolated bridges, one of them in the helix and one in a strand
+            Ladder {
+                kind: BridgeKind::Parallel,
+                pairs: vec![(3, 11)],
+                bulge: false,
+            },
+            Ladder {
+                kind: BridgeKind::Parallel,
+                pairs: vec![(0, 9)],
+                bulge: false,
+            },
+        ];
+        assign_ladders(&mut ss, &ladders);
+        assert_eq!(
+            ss,
+            [
+                Bridge, Coil, AlphaHelix, AlphaHelix, AlphaHelix, AlphaHelix, Strand, Coil, Strand,
+                Strand, Strand, Bridge
+            ]
+        );
+    }
+}
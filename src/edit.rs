@@ -0,0 +1,655 @@
+// Copyright (c) 2020 Tianyi Shi
+//
+// This software is released under the MIT License.
+// https://opensource.org/licenses/MIT
+
+//! Editing the coordinates of an [`Entry`] while keeping the records that refer to them
+//! consistent.
+//!
+//! | Operation                          | Records updated besides ATOM/HETATM                       |
+//! |------------------------------------|-----------------------------------------------------------|
+//! | `renumber_residues`                | TER, SSBOND, LINK, HELIX, SHEET                           |
+//! | `rename_chain`, `merge_chains`     | TER, SEQRES, COMPND, SSBOND, LINK, HELIX, SHEET           |
+//! | `remove_waters`, `remove_hydrogens`, `remove_alt_locs`, `strip_ligands` | TER, CONECT, ANISOU, SIGATM, SIGUIJ (see [`Entry::retain_atoms`]) |
+//! | `insert_atom`                      | TER                                                       |
+//! | `renumber_atoms`                   | TER, CONECT, ANISOU, SIGATM, SIGUIJ                       |
+//!
+//! Removing and inserting atoms keeps the serial numbers of the others; `renumber_atoms` makes
+//! them consecutive again.
+//!
+//! [`Entry`]: ../entry/struct.Entry.html
+//! [`Entry::retain_atoms`]: ../entry/struct.Entry.html#method.retain_atoms
+
+use crate::alignment::Aligner;
+use crate::element::ChemicalElement;
+use crate::entry::{Entry, ModelInfo};
+use crate::hierarchy::{first_alt_locs, ModelView};
+use crate::types::{Atom, AtomSerial, Residue, ResidueSerial};
+use crate::writer::residue_name;
+use std::collections::{HashMap, HashSet};
+
+/// A residue by chain identifier, sequence number and insertion code.
+pub type ResidueKey = (char, ResidueSerial, char);
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Renumbering {
+    /// Add an offset to every residue number, keeping insertion codes.
+    Offset(i64),
+    /// Number the residues of each chain consecutively from a start, polymer first, clearing
+    /// insertion codes.
+    Sequential(ResidueSerial),
+    /// Number the polymer residues of each chain by their position in SEQRES, found by aligning
+    /// the observed sequence to it, clearing insertion codes. Other residues are left as they are.
+    FromSeqres,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EditError(pub String);
+
+impl std::fmt::Display for EditError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "cannot edit: {}", self.0)
+    }
+}
+
+impl std::error::Error for EditError {}
+
+impl Entry {
+    /// Renumbers the residues of `chain`, or of all chains if `None`. The residues are those of
+    /// the first model; the same numbers are given to them in the other models. Nothing is
+    /// changed if two residues would end up with the same number.
+    pub fn renumber_residues(
+        &mut self,
+        chain: Option<char>,
+        renumbering: Renumbering,
+    ) -> Result<(), EditError> {
+        let mut map: HashMap<ResidueKey, (ResidueSerial, char)> = HashMap::new();
+        if let Some(view) = self.hierarchy().into_iter().next() {
+            for c in view
+                .chains
+                .iter()
+                .filter(|c| !matches!(chain, Some(id) if id != c.id))
+            {
+                let residues = c.polymer.iter().chain(&c.non_polymer);
+                let key = |r: &crate::hierarchy::ResidueView| {
+                    (r.chain(), r.sequence_number(), r.insertion_code())
+                };
+                match renumbering {
+                    Renumbering::Offset(offset) => {
+                        for r in residues {
+                            let n = r.sequence_number() as i64 + offset;
+                            if n < 0 {
+                                return Err(EditError(format!(
+                                    "residue {} of chain {} would get the negative number {}",
+                                    r.sequence_number(),
+                                    c.id,
+                                    n
+                                )));
+                            }
+                            map.insert(key(r), (n as ResidueSerial, r.insertion_code()));
+                        }
+                    }
+                    Renumbering::Sequential(start) => {
+                        for (i, r) in residues.enumerate() {
+                            map.insert(key(r), (start + i as ResidueSerial, ' '));
+                        }
+                    }
+                    Renumbering::FromSeqres => {
+                        for (r, n) in self.seqres_positions(&view, c.id)? {
+                            map.insert(key(&c.polymer[r]), (n, ' '));
+                        }
+                    }
+                }
+            }
+        }
+        // * residues not renumbered keep their numbers, and must not collide with the new ones
+        let mut taken: HashSet<ResidueKey> = HashSet::new();
+        for model in &self.structure.models {
+            for atom in &model.atoms {
+                let key = (atom.chain, atom.sequence_number, atom.insertion_code);
+                if !map.contains_key(&key) {
+                    taken.insert(key);
+                }
+            }
+        }
+        let mut new: HashMap<ResidueKey, ResidueKey> = HashMap::new();
+        for (&old, &(n, icode)) in &map {
+            let renumbered = (old.0, n, icode);
+            if taken.contains(&renumbered) {
+                return Err(EditError(format!(
+                    "residue {}{} of chain {} already exists",
+                    n,
+                    icode.to_string().trim(),
+                    old.0
+                )));
+            }
+            if let Some(other) = new.insert(renumbered, old) {
+                return Err(EditError(format!(
+                    "residues {} and {} of chain {} would both be numbered {}",
+                    other.1, old.1, old.0, n
+                )));
+            }
+        }
+        self.map_residues(|key| map.get(&key).map(|&(n, icode)| (key.0, n, icode)));
+        Ok(())
+    }
+
+    /// The index in `polymer` and the position in SEQRES (from 1) of each aligned polymer
+    /// residue of `chain`.
+    fn seqres_positions(
+        &self,
+        view: &ModelView,
+        chain: char,
+    ) -> Result<Vec<(usize, ResidueSerial)>, EditError> {
+        let polymer = &view.chain(chain).unwrap().polymer;
+        let (seqres, nucleic_acid): (String, bool) =
+            match self.structure.chains_aa.iter().find(|c| c.id == chain) {
+                Some(c) => (
+                    c.seq.iter().map(|aa| self.amino_acid_code(aa)).collect(),
+                    false,
+                ),
+                None => match self.structure.chains_nuc.iter().find(|c| c.id == chain) {
+                    Some(c) => (
+                        c.seq.iter().map(|n| self.nucleotide_code(n)).collect(),
+                        true,
+                    ),
+                    None => return Err(EditError(format!("chain {} has no SEQRES", chain))),
+                },
+            };
+        // * only residues of the kind of the SEQRES take part in the alignment
+        let mut residues = Vec::new();
+        let mut observed = String::new();
+        for (i, r) in polymer.iter().enumerate() {
+            match (r.residue(), nucleic_acid) {
+                (Residue::AminoAcid(aa), false) => observed.push(self.amino_acid_code(aa)),
+                (Residue::Nucleotide(n), true) => observed.push(self.nucleotide_code(n)),
+                _ => continue,
+            }
+            residues.push(i);
+        }
+        let aligner = if nucleic_acid {
+            Aligner::nucleotide()
+        } else {
+            Aligner::default()
+        };
+        let alignment = aligner.align(&seqres, &observed);
+        let (mut s, mut o) = (alignment.a_range.start, alignment.b_range.start);
+        let mut positions = Vec::new();
+        for (a, b) in alignment.a.bytes().zip(alignment.b.bytes()) {
+            match (a, b) {
+                (b'-', _) => {
+                    return Err(EditError(format!(
+                        "residues of chain {} are missing from SEQRES",
+                        chain
+                    )))
+                }
+                (_, b'-') => s += 1,
+                _ => {
+                    positions.push((residues[o], s as ResidueSerial + 1));
+                    s += 1;
+                    o += 1;
+                }
+            }
+        }
+        Ok(positions)
+    }
+
+    /// Changes the identifier of chain `from` to `to`, which must not be in use.
+    pub fn rename_chain(&mut self, from: char, to: char) -> Result<(), EditError> {
+        if from == to {
+            return Ok(());
+        }
+        if self.chain_ids().contains(&to) {
+            return Err(EditError(format!(
+                "chain {} already exists; use merge_chains to combine chains",
+                to
+            )));
+        }
+        self.relabel_chain(from, to);
+        for c in &mut self.structure.chains_aa {
+            if c.id == from {
+                c.id = to;
+            }
+        }
+        for c in &mut self.structure.chains_nuc {
+            if c.id == from {
+                c.id = to;
+            }
+        }
+        for compound in &mut self.title_section.compounds {
+            for c in &mut compound.chains {
+                if *c == from {
+                    *c = to;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Moves the residues of chain `from` into chain `into`: its polymer residues after the
+    /// polymer of `into`, and its other residues after those of `into`. The residues of the two
+    /// chains must have distinct numbers. The SEQRES of `from` is appended to that of `into`.
+    pub fn merge_chains(&mut self, from: char, into: char) -> Result<(), EditError> {
+        if from == into {
+            return Ok(());
+        }
+        for model in &self.structure.models {
+            let residues = |chain: char| -> HashSet<(ResidueSerial, char)> {
+                model
+                    .atoms
+                    .iter()
+                    .filter(|a| a.chain == chain)
+                    .map(|a| (a.sequence_number, a.insertion_code))
+                    .collect()
+            };
+            if let Some(&(n, icode)) = residues(from).intersection(&residues(into)).next() {
+                return Err(EditError(format!(
+                    "residue {}{} is in both chain {} and chain {}",
+                    n,
+                    icode.to_string().trim(),
+                    from,
+                    into
+                )));
+            }
+        }
+        let views: Vec<(Vec<usize>, Vec<usize>)> = self
+            .hierarchy()
+            .iter()
+            .map(|view| {
+                let atoms = |chain: char, polymer: bool| -> Vec<usize> {
+                    view.chain(chain).map_or(Vec::new(), |c| {
+                        let residues = if polymer { &c.polymer } else { &c.non_polymer };
+                        residues
+                            .iter()
+                            .flat_map(|r| r.offset..r.offset + r.atoms.len())
+                            .collect()
+                    })
+                };
+                let (into_polymer, into_other) = (atoms(into, true), atoms(into, false));
+                let (from_polymer, from_other) = (atoms(from, true), atoms(from, false));
+                // * the atoms of `from` move behind the corresponding atoms of `into`, if any
+                let mut moved = vec![false; view.atoms.len()];
+                let mut behind: HashMap<usize, &[usize]> = HashMap::new();
+                for (anchor, group) in &[(&into_polymer, &from_polymer), (&into_other, &from_other)]
+                {
+                    if let Some(&last) = anchor.last() {
+                        for &i in group.iter() {
+                            moved[i] = true;
+                        }
+                        behind.insert(last, group);
+                    }
+                }
+                let mut order = Vec::with_capacity(view.atoms.len());
+                for (i, &m) in moved.iter().enumerate() {
+                    if !m {
+                        order.push(i);
+                    }
+                    if let Some(group) = behind.get(&i) {
+                        order.extend_from_slice(group);
+                    }
+                }
+                let polymer = into_polymer.into_iter().chain(from_polymer).collect();
+                (order, polymer)
+            })
+            .collect();
+        for (m, (order, polymer)) in views.into_iter().enumerate() {
+            self.sync_elements(m);
+            let model = &mut self.structure.models[m];
+            let info = &mut self.model_info[m];
+            // * the TER of the merged polymer follows its last atom in the new order
+            let position: Vec<usize> = {
+                let mut position = vec![0; order.len()];
+                for (new, &old) in order.iter().enumerate() {
+                    position[old] = new;
+                }
+                position
+            };
+            let last = polymer.iter().map(|&i| position[i]).max();
+            let mut ter = None;
+            let mut ters = Vec::new();
+            for (preceding, t) in std::mem::take(&mut info.ters) {
+                match model.atoms.get(preceding.wrapping_sub(1)).map(|a| a.chain) {
+                    Some(c) if c == from || c == into => {
+                        if c == into || ter.is_none() {
+                            ter = Some(t);
+                        }
+                    }
+                    Some(_) => ters.push((position[preceding - 1] + 1, t)),
+                    None => ters.push((preceding, t)),
+                }
+            }
+            let atoms = std::mem::take(&mut model.atoms);
+            model.atoms = order.iter().map(|&i| atoms[i].clone()).collect();
+            let elements = std::mem::take(&mut info.elements);
+            info.elements = order.iter().map(|&i| elements[i]).collect();
+            if let (Some(mut t), Some(last)) = (ter, last) {
+                let atom = &model.atoms[last];
+                t.chain = into;
+                if t.sequence_number.is_some() {
+                    t.residue_name = residue_name(&atom.residue);
+                    t.sequence_number = Some(atom.sequence_number);
+                    t.insertion_code = atom.insertion_code;
+                }
+                if t.id.is_some() {
+                    t.id = Some(atom.id + 1);
+                }
+                ters.push((last + 1, t));
+            }
+            ters.sort_by_key(|(preceding, _)| *preceding);
+            info.ters = ters;
+        }
+        self.relabel_chain(from, into);
+        let moved: Vec<_> = self
+            .structure
+            .chains_aa
+            .iter()
+            .filter(|c| c.id == from)
+            .flat_map(|c| c.seq.clone())
+            .collect();
+        self.structure.chains_aa.retain(|c| c.id != from);
+        match self.structure.chains_aa.iter_mut().find(|c| c.id == into) {
+            Some(c) => c.seq.extend(moved),
+            None if !moved.is_empty() => self.structure.chains_aa.push(crate::types::Chain {
+                id: into,
+                seq: moved,
+            }),
+            None => {}
+        }
+        let moved: Vec<_> = self
+            .structure
+            .chains_nuc
+            .iter()
+            .filter(|c| c.id == from)
+            .flat_map(|c| c.seq.clone())
+            .collect();
+        self.structure.chains_nuc.retain(|c| c.id != from);
+        match self.structure.chains_nuc.iter_mut().find(|c| c.id == into) {
+            Some(c) => c.seq.extend(moved),
+            None if !moved.is_empty() => self.structure.chains_nuc.push(crate::types::Chain {
+                id: into,
+                seq: moved,
+            }),
+            None => {}
+        }
+        for compound in &mut self.title_section.compounds {
+            compound.chains.retain(|&c| c != from);
+        }
+        Ok(())
+    }
+
+    /// Removes all water molecules.
+    pub fn remove_waters(&mut self) {
+        self.retain_atoms(|_, _, atom| !matches!(atom.residue, Residue::Water));
+    }
+
+    /// Removes all hydrogen and deuterium atoms.
+    pub fn remove_hydrogens(&mut self) {
+        let hydrogen: Vec<Vec<bool>> = (0..self.structure.models.len())
+            .map(|m| {
+                (0..self.structure.models[m].atoms.len())
+                    .map(|i| self.is_hydrogen(m, i))
+                    .collect()
+            })
+            .collect();
+        self.retain_atoms(|m, i, _| !hydrogen[m][i]);
+    }
+
+    /// Keeps only the first alternate location of each residue, and clears the alternate
+    /// location indicator of the atoms that remain.
+    pub fn remove_alt_locs(&mut self) {
+        let first: Vec<Vec<bool>> = self
+            .structure
+            .models
+            .iter()
+            .map(|m| first_alt_locs(&m.atoms))
+            .collect();
+        self.retain_atoms(|m, i, _| first[m][i]);
+        for model in &mut self.structure.models {
+            for atom in &mut model.atoms {
+                atom.id1 = ' ';
+            }
+        }
+    }
+
+    /// Removes the non-polymer residues other than water: ligands and ions.
+    pub fn strip_ligands(&mut self) {
+        let ligands: Vec<Vec<bool>> = self
+            .hierarchy()
+            .iter()
+            .map(|view| {
+                let mut ligand = vec![false; view.atoms.len()];
+                for r in view.chains.iter().flat_map(|c| &c.non_polymer) {
+                    if !matches!(r.residue(), Residue::Water) {
+                        for l in &mut ligand[r.offset..r.offset + r.atoms.len()] {
+                            *l = true;
+                        }
+                    }
+                }
+                ligand
+            })
+            .collect();
+        self.retain_atoms(|m, i, _| !ligands[m][i]);
+    }
+
+    /// Inserts `atom` into the model at index `model`: after the last atom of its residue, or,
+    /// for a new residue, after the last atom of its chain with a lower residue number, or else
+    /// at the end of the model. The serial number of the atom is kept. Returns the index of the
+    /// atom in the model.
+    pub fn insert_atom(
+        &mut self,
+        model: usize,
+        atom: Atom,
+        element: Option<ChemicalElement>,
+    ) -> usize {
+        self.sync_elements(model);
+        let atoms = &self.structure.models[model].atoms;
+        let residue = |a: &Atom| (a.sequence_number, a.insertion_code);
+        let same_residue = atoms
+            .iter()
+            .rposition(|a| a.chain == atom.chain && residue(a) == residue(&atom));
+        let before = atoms
+            .iter()
+            .rposition(|a| a.chain == atom.chain && residue(a) < residue(&atom));
+        let index = match same_residue.or(before) {
+            Some(i) => i + 1,
+            None => atoms.len(),
+        };
+        // * a TER right after the atom it is placed behind moves with it if it is of the same chain
+        let ter_moves = index > 0 && atoms[index - 1].chain == atom.chain && same_residue.is_some();
+        let info = &mut self.model_info[model];
+        for (preceding, _) in &mut info.ters {
+            if *preceding > index || (*preceding == index && ter_moves) {
+                *preceding += 1;
+            }
+        }
+        info.elements.insert(index, element);
+        let atoms = &mut self.structure.models[model].atoms;
+        if let Some(e) = element {
+            let mut atom = atom;
+            atom.element = e.into();
+            atoms.insert(index, atom);
+        } else {
+            atoms.insert(index, atom);
+        }
+        index
+    }
+
+    /// Numbers the atoms of each model consecutively from `start`, with each TER record taking
+    /// the number after the atom that precedes it, as in files from the wwPDB. CONECT records
+    /// follow the numbers of the first model.
+    pub fn renumber_atoms(&mut self, start: AtomSerial) {
+        let mut first: Option<HashMap<AtomSerial, AtomSerial>> = None;
+        for (model, info) in self.structure.models.iter_mut().zip(&mut self.model_info) {
+            let mut serials: HashMap<AtomSerial, AtomSerial> = HashMap::new();
+            let mut serial = start;
+            let mut ters = info.ters.iter_mut().peekable();
+            for (i, atom) in model.atoms.iter_mut().enumerate() {
+                serials.entry(atom.id).or_insert(serial);
+                atom.id = serial;
+                serial += 1;
+                while let Some((_, ter)) = ters.next_if(|(preceding, _)| *preceding <= i + 1) {
+                    if ter.id.is_some() {
+                        ter.id = Some(serial);
+                        serial += 1;
+                    }
+                }
+            }
+            update_serials(info, model, &serials);
+            if first.is_none() {
+                first = Some(serials);
+            }
+        }
+        if let Some(serials) = first {
+            for [a, b] in &mut self.structure.connect {
+                *a = serials.get(a).copied().unwrap_or(*a);
+                *b = serials.get(b).copied().unwrap_or(*b);
+            }
+        }
+    }
+
+    /// All chain identifiers in use, in the coordinates or in SEQRES.
+    fn chain_ids(&self) -> HashSet<char> {
+        let mut ids: HashSet<char> = self
+            .structure
+            .models
+            .iter()
+            .flat_map(|m| m.atoms.iter().map(|a| a.chain))
+            .collect();
+        ids.extend(self.structure.chains_aa.iter().map(|c| c.id));
+        ids.extend(self.structure.chains_nuc.iter().map(|c| c.id));
+        ids
+    }
+
+    /// Changes the chain identifier in the atoms and in the records that refer to residues.
+    fn relabel_chain(&mut self, from: char, to: char) {
+        self.map_residues(|(chain, n, icode)| (chain == from).then_some((to, n, icode)));
+        for model in &mut self.structure.models {
+            for atom in model.atoms.iter_mut().filter(|a| a.chain == from) {
+                atom.chain = to;
+            }
+        }
+        for info in &mut self.model_info {
+            for (_, ter) in info.ters.iter_mut().filter(|(_, t)| t.chain == from) {
+                ter.chain = to;
+            }
+        }
+    }
+
+    /// Applies `f` to every residue in the atoms, TER, SSBOND, LINK, HELIX and SHEET records;
+    /// residues for which it returns `None` are left unchanged.
+    fn map_residues(&mut self, f: impl Fn(ResidueKey) -> Option<ResidueKey>) {
+        for model in &mut self.structure.models {
+            for atom in &mut model.atoms {
+                if let Some((chain, n, icode)) =
+                    f((atom.chain, atom.sequence_number, atom.insertion_code))
+                {
+                    atom.chain = chain;
+                    atom.sequence_number = n;
+                    atom.insertion_code = icode;
+                }
+            }
+        }
+        for info in &mut self.model_info {
+            for (_, ter) in &mut info.ters {
+                if let Some(n) = ter.sequence_number {
+                    if let Some((chain, n, icode)) = f((ter.chain, n, ter.insertion_code)) {
+                        ter.chain = chain;
+                        ter.sequence_number = Some(n);
+                        ter.insertion_code = icode;
+                    }
+                }
+            }
+        }
+        // * SSBOND keeps no insertion codes; a blank one is assumed
+        for ssbond in &mut self.structure.ssbonds {
+            for (chain, n) in [&mut ssbond.a, &mut ssbond.b] {
+                if let Some((c, m, _)) = f((*chain, *n, ' ')) {
+                    *chain = c;
+                    *n = m;
+                }
+            }
+        }
+        for link in &mut self.links {
+            for end in [&mut link.a, &mut link.b] {
+                if let Some((chain, n, icode)) =
+                    f((end.chain, end.sequence_number, end.insertion_code))
+                {
+                    end.chain = chain;
+                    end.sequence_number = n;
+                    end.insertion_code = icode;
+                }
+            }
+        }
+        let map = |residue: &mut (char, ResidueSerial), icode: &mut char| {
+            if let Some((c, n, i)) = f((residue.0, residue.1, *icode)) {
+                *residue = (c, n);
+                *icode = i;
+            }
+        };
+        for (h, helix) in self.structure.helices.iter_mut().enumerate() {
+            let mut details = self.helix_details.get(h).cloned().unwrap_or_default();
+            map(&mut helix.start, &mut details.start.insertion_code);
+            map(&mut helix.end, &mut details.end.insertion_code);
+            if let Some(d) = self.helix_details.get_mut(h) {
+                *d = details;
+            }
+        }
+        for (s, sheet) in self.structure.sheets.iter_mut().enumerate() {
+            let registrations = &mut sheet.registration;
+            for (k, strand) in sheet.strands.iter_mut().enumerate() {
+                let mut details = self
+                    .sheet_details
+                    .get(s)
+                    .and_then(|d| d.get(k))
+                    .cloned()
+                    .unwrap_or_default();
+                map(&mut strand.start, &mut details.start.insertion_code);
+                map(&mut strand.end, &mut details.end.insertion_code);
+                // * the registration of each strand but the first
+                if let Some(registration) = k.checked_sub(1).and_then(|r| registrations.get_mut(r))
+                {
+                    let (mut curr_icode, mut prev_icode) =
+                        details.registration_insertion_codes.unwrap_or((' ', ' '));
+                    let mut curr = (registration.curr.1, registration.curr.2);
+                    let mut prev = (registration.prev.1, registration.prev.2);
+                    map(&mut curr, &mut curr_icode);
+                    map(&mut prev, &mut prev_icode);
+                    registration.curr.1 = curr.0;
+                    registration.curr.2 = curr.1;
+                    registration.prev.1 = prev.0;
+                    registration.prev.2 = prev.1;
+                    if details.registration_insertion_codes.is_some() {
+                        details.registration_insertion_codes = Some((curr_icode, prev_icode));
+                    }
+                }
+                if let Some(d) = self.sheet_details.get_mut(s).and_then(|d| d.get_mut(k)) {
+                    *d = details;
+                }
+            }
+        }
+    }
+}
+
+/// Changes the serial numbers in the TER, ANISOU, SIGATM and SIGUIJ records of a model.
+fn update_serials(
+    info: &mut ModelInfo,
+    model: &mut crate::types::Model,
+    serials: &HashMap<AtomSerial, AtomSerial>,
+) {
+    let new = |id: &mut AtomSerial| {
+        if let Some(&n) = serials.get(id) {
+            *id = n;
+        }
+    };
+    for anisou in &mut model.anisou {
+        new(&mut anisou.id);
+    }
+    for sig in &mut info.sigatm {
+        new(&mut sig.id);
+    }
+    for sig in &mut info.siguij {
+        new(&mut sig.id);
+    }
+}
@@ -0,0 +1,360 @@
+// Copyright (c) 2020 Tianyi Shi
+//
+// This software is released under the MIT License.
+// https://opensource.org/licenses/MIT
+
+//! Chemical elements and their properties.
+//!
+//! [`Element`] from `protein-core` only covers the elements most common in biomolecules.
+//! [`ChemicalElement`] covers the whole periodic table, plus deuterium (`D`), which the PDB format
+//! writes as an element of its own. Atomic masses are the IUPAC standard atomic weights (mass
+//! number of the most stable isotope for elements without one), covalent radii are from Cordero
+//! et al. (2008), and van der Waals radii are from Bondi (1964) where available, otherwise as
+//! tabulated by Open Babel, with 2.0 Å for elements that have no established value.
+//!
+//! [`Element`]: ../../protein_core/structure/element/enum.Element.html
+//! [`ChemicalElement`]: struct.ChemicalElement.html
+
+use crate::types::{Element, Residue};
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ElementData {
+    pub atomic_number: u8,
+    pub symbol: &'static str,
+    pub name: &'static str,
+    /// Atomic mass in Dalton
+    pub mass: f32,
+    /// Covalent radius in Å
+    pub covalent_radius: f32,
+    /// Van der Waals radius in Å
+    pub vdw_radius: f32,
+}
+
+const fn e(
+    atomic_number: u8,
+    symbol: &'static str,
+    name: &'static str,
+    mass: f32,
+    covalent_radius: f32,
+    vdw_radius: f32,
+) -> ElementData {
+    ElementData {
+        atomic_number,
+        symbol,
+        name,
+        mass,
+        covalent_radius,
+        vdw_radius,
+    }
+}
+
+/// Elements by atomic number (hydrogen at index 0), followed by deuterium.
+pub static PERIODIC_TABLE: [ElementData; 119] = [
+    e(1, "H", "Hydrogen", 1.008, 0.31, 1.20),
+    e(2, "He", "Helium", 4.0026, 0.28, 1.40),
+    e(3, "Li", "Lithium", 6.94, 1.28, 1.82),
+    e(4, "Be", "Beryllium", 9.0122, 0.96, 1.53),
+    e(5, "B", "Boron", 10.81, 0.84, 1.92),
+    e(6, "C", "Carbon", 12.011, 0.76, 1.70),
+    e(7, "N", "Nitrogen", 14.007, 0.71, 1.55),
+    e(8, "O", "Oxygen", 15.999, 0.66, 1.52),
+    e(9, "F", "Fluorine", 18.998, 0.57, 1.47),
+    e(10, "Ne", "Neon", 20.180, 0.58, 1.54),
+    e(11, "Na", "Sodium", 22.990, 1.66, 2.27),
+    e(12, "Mg", "Magnesium", 24.305, 1.41, 1.73),
+    e(13, "Al", "Aluminium", 26.982, 1.21, 1.84),
+    e(14, "Si", "Silicon", 28.085, 1.11, 2.10),
+    e(15, "P", "Phosphorus", 30.974, 1.07, 1.80),
+    e(16, "S", "Sulfur", 32.06, 1.05, 1.80),
+    e(17, "Cl", "Chlorine", 35.45, 1.02, 1.75),
+    e(18, "Ar", "Argon", 39.948, 1.06, 1.88),
+    e(19, "K", "Potassium", 39.098, 2.03, 2.75),
+    e(20, "Ca", "Calcium", 40.078, 1.76, 2.31),
+    e(21, "Sc", "Scandium", 44.956, 1.70, 2.30),
+    e(22, "Ti", "Titanium", 47.867, 1.60, 2.15),
+    e(23, "V", "Vanadium", 50.942, 1.53, 2.05),
+    e(24, "Cr", "Chromium", 51.996, 1.39, 2.05),
+    e(25, "Mn", "Manganese", 54.938, 1.39, 2.05),
+    e(26, "Fe", "Iron", 55.845, 1.32, 2.05),
+    e(27, "Co", "Cobalt", 58.933, 1.26, 2.00),
+    e(28, "Ni", "Nickel", 58.693, 1.24, 1.63),
+    e(29, "Cu", "Copper", 63.546, 1.32, 1.40),
+    e(30, "Zn", "Zinc", 65.38, 1.22, 1.39),
+    e(31, "Ga", "Gallium", 69.723, 1.22, 1.87),
+    e(32, "Ge", "Germanium", 72.630, 1.20, 2.11),
+    e(33, "As", "Arsenic", 74.922, 1.19, 1.85),
+    e(34, "Se", "Selenium", 78.971, 1.20, 1.90),
+    e(35, "Br", "Bromine", 79.904, 1.20, 1.85),
+    e(36, "Kr", "Krypton", 83.798, 1.16, 2.02),
+    e(37, "Rb", "Rubidium", 85.468, 2.20, 3.03),
+    e(38, "Sr", "Strontium", 87.62, 1.95, 2.49),
+    e(39, "Y", "Yttrium", 88.906, 1.90, 2.40),
+    e(40, "Zr", "Zirconium", 91.224, 1.75, 2.30),
+    e(41, "Nb", "Niobium", 92.906, 1.64, 2.15),
+    e(42, "Mo", "Molybdenum", 95.95, 1.54, 2.10),
+    e(43, "Tc", "Technetium", 98.0, 1.47, 2.05),
+    e(44, "Ru", "Ruthenium", 101.07, 1.46, 2.05),
+    e(45, "Rh", "Rhodium", 102.91, 1.42, 2.00),
+    e(46, "Pd", "Palladium", 106.42, 1.39, 1.63),
+    e(47, "Ag", "Silver", 107.87, 1.45, 1.72),
+    e(48, "Cd", "Cadmium", 112.41, 1.44, 1.58),
+    e(49, "In", "Indium", 114.82, 1.42, 1.93),
+    e(50, "Sn", "Tin", 118.71, 1.39, 2.17),
+    e(51, "Sb", "Antimony", 121.76, 1.39, 2.06),
+    e(52, "Te", "Tellurium", 127.60, 1.38, 2.06),
+    e(53, "I", "Iodine", 126.90, 1.39, 1.98),
+    e(54, "Xe", "Xenon", 131.29, 1.40, 2.16),
+    e(55, "Cs", "Caesium", 132.91, 2.44, 3.43),
+    e(56, "Ba", "Barium", 137.33, 2.15, 2.68),
+    e(57, "La", "Lanthanum", 138.91, 2.07, 2.50),
+    e(58, "Ce", "Cerium", 140.12, 2.04, 2.48),
+    e(59, "Pr", "Praseodymium", 140.91, 2.03, 2.47),
+    e(60, "Nd", "Neodymium", 144.24, 2.01, 2.45),
+    e(61, "Pm", "Promethium", 145.0, 1.99, 2.43),
+    e(62, "Sm", "Samarium", 150.36, 1.98, 2.42),
+    e(63, "Eu", "Europium", 151.96, 1.98, 2.40),
+    e(64, "Gd", "Gadolinium", 157.25, 1.96, 2.38),
+    e(65, "Tb", "Terbium", 158.93, 1.94, 2.37),
+    e(66, "Dy", "Dysprosium", 162.50, 1.92, 2.35),
+    e(67, "Ho", "Holmium", 164.93, 1.92, 2.33),
+    e(68, "Er", "Erbium", 167.26, 1.89, 2.32),
+    e(69, "Tm", "Thulium", 168.93, 1.90, 2.30),
+    e(70, "Yb", "Ytterbium", 173.05, 1.87, 2.28),
+    e(71, "Lu", "Lutetium", 174.97, 1.87, 2.27),
+    e(72, "Hf", "Hafnium", 178.49, 1.75, 2.25),
+    e(73, "Ta", "Tantalum", 180.95, 1.70, 2.20),
+    e(74, "W", "Tungsten", 183.84, 1.62, 2.10),
+    e(75, "Re", "Rhenium", 186.21, 1.51, 2.05),
+    e(76, "Os", "Osmium", 190.23, 1.44, 2.00),
+    e(77, "Ir", "Iridium", 192.22, 1.41, 2.00),
+    e(78, "Pt", "Platinum", 195.08, 1.36, 1.75),
+    e(79, "Au", "Gold", 196.97, 1.36, 1.66),
+    e(80, "Hg", "Mercury", 200.59, 1.32, 1.55),
+    e(81, "Tl", "Thallium", 204.38, 1.45, 1.96),
+    e(82, "Pb", "Lead", 207.2, 1.46, 2.02),
+    e(83, "Bi", "Bismuth", 208.98, 1.48, 2.07),
+    e(84, "Po", "Polonium", 209.0, 1.40, 1.97),
+    e(85, "At", "Astatine", 210.0, 1.50, 2.02),
+    e(86, "Rn", "Radon", 222.0, 1.50, 2.20),
+    e(87, "Fr", "Francium", 223.0, 2.60, 3.48),
+    e(88, "Ra", "Radium", 226.0, 2.21, 2.83),
+    e(89, "Ac", "Actinium", 227.0, 2.15, 2.00),
+    e(90, "Th", "Thorium", 232.04, 2.06, 2.40),
+    e(91, "Pa", "Protactinium", 231.04, 2.00, 2.00),
+    e(92, "U", "Uranium", 238.03, 1.96, 1.86),
+    e(93, "Np", "Neptunium", 237.0, 1.90, 2.00),
+    e(94, "Pu", "Plutonium", 244.0, 1.87, 2.00),
+    e(95, "Am", "Americium", 243.0, 1.80, 2.00),
+    e(96, "Cm", "Curium", 247.0, 1.69, 2.00),
+    e(97, "Bk", "Berkelium", 247.0, 1.50, 2.00),
+    e(98, "Cf", "Californium", 251.0, 1.50, 2.00),
+    e(99, "Es", "Einsteinium", 252.0, 1.50, 2.00),
+    e(100, "Fm", "Fermium", 257.0, 1.50, 2.00),
+    e(101, "Md", "Mendelevium", 258.0, 1.50, 2.00),
+    e(102, "No", "Nobelium", 259.0, 1.50, 2.00),
+    e(103, "Lr", "Lawrencium", 266.0, 1.50, 2.00),
+    e(104, "Rf", "Rutherfordium", 267.0, 1.50, 2.00),
+    e(105, "Db", "Dubnium", 268.0, 1.50, 2.00),
+    e(106, "Sg", "Seaborgium", 269.0, 1.50, 2.00),
+    e(107, "Bh", "Bohrium", 270.0, 1.50, 2.00),
+    e(108, "Hs", "Hassium", 277.0, 1.50, 2.00),
+    e(109, "Mt", "Meitnerium", 278.0, 1.50, 2.00),
+    e(110, "Ds", "Darmstadtium", 281.0, 1.50, 2.00),
+    e(111, "Rg", "Roentgenium", 282.0, 1.50, 2.00),
+    e(112, "Cn", "Copernicium", 285.0, 1.50, 2.00),
+    e(113, "Nh", "Nihonium", 286.0, 1.50, 2.00),
+    e(114, "Fl", "Flerovium", 289.0, 1.50, 2.00),
+    e(115, "Mc", "Moscovium", 290.0, 1.50, 2.00),
+    e(116, "Lv", "Livermorium", 293.0, 1.50, 2.00),
+    e(117, "Ts", "Tennessine", 294.0, 1.50, 2.00),
+    e(118, "Og", "Oganesson", 294.0, 1.50, 2.00),
+    e(1, "D", "Deuterium", 2.014, 0.31, 1.20),
+];
+
+/// An element of the periodic table, or deuterium.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ChemicalElement(u8);
+
+impl ChemicalElement {
+    pub const HYDROGEN: Self = Self(0);
+    pub const DEUTERIUM: Self = Self(118);
+
+    /// Looks up an element by its symbol, ignoring case and surrounding blanks.
+    pub fn from_symbol(symbol: &str) -> Option<Self> {
+        let symbol = symbol.trim();
+        PERIODIC_TABLE
+            .iter()
+            .position(|e| e.symbol.eq_ignore_ascii_case(symbol))
+            .map(|i| Self(i as u8))
+    }
+
+    pub fn from_atomic_number(atomic_number: u8) -> Option<Self> {
+        match atomic_number {
+            1..=118 => Some(Self(atomic_number - 1)),
+            _ => None,
+        }
+    }
+
+    /// Converts an [`Element`] from `protein-core`; `None` for `Element::Unknown`.
+    ///
+    /// [`Element`]: ../../protein_core/structure/element/enum.Element.html
+    pub fn from_element(element: Element) -> Option<Self> {
+        match element {
+            Element::Unknown => None,
+            _ => Self::from_symbol(&format!("{:?}", element)),
+        }
+    }
+
+    /// Infers the element from the atom name (columns 13 - 16) for records whose element columns
+    /// are blank. The element symbol is right-justified in columns 13 - 14, so that the
+    /// one-letter elements of standard residues start at column 14, except for names of four
+    /// characters, which start at column 13 and are mostly hydrogens.
+    pub fn from_atom_name(name: &[u8], residue: &Residue) -> Option<Self> {
+        let name = name.get(..4)?;
+        let one_letter = |c: u8| Self::from_symbol(std::str::from_utf8(&[c]).ok()?);
+        if name[0] == b' ' || name[0].is_ascii_digit() {
+            // * e.g. " CA ", "1HG1"
+            return one_letter(name[1]);
+        }
+        let polymer = matches!(residue, Residue::AminoAcid(_) | Residue::Nucleotide(_));
+        if (polymer && b"HCNOSP".contains(&name[0])) || (name[0] == b'H' && name[3] != b' ') {
+            // * e.g. "HG21", "HO2'"
+            return one_letter(name[0]);
+        }
+        std::str::from_utf8(&name[..2])
+            .ok()
+            .and_then(Self::from_symbol)
+            .or_else(|| one_letter(name[0]))
+    }
+
+    pub fn data(self) -> &'static ElementData {
+        &PERIODIC_TABLE[self.0 as usize]
+    }
+
+    pub fn atomic_number(self) -> u8 {
+        self.data().atomic_number
+    }
+
+    pub fn symbol(self) -> &'static str {
+        self.data().symbol
+    }
+
+    pub fn name(self) -> &'static str {
+        self.data().name
+    }
+
+    /// Atomic mass in Dalton
+    pub fn mass(self) -> f32 {
+        self.data().mass
+    }
+
+    /// Covalent radius in Å
+    pub fn covalent_radius(self) -> f32 {
+        self.data().covalent_radius
+    }
+
+    /// Van der Waals radius in Å
+    pub fn vdw_radius(self) -> f32 {
+        self.data().vdw_radius
+    }
+
+    /// Hydrogen or deuterium
+    pub fn is_hydrogen(self) -> bool {
+        self.atomic_number() == 1
+    }
+
+    /// Any element other than the nonmetals, metalloids and noble gases
+    pub fn is_metal(self) -> bool {
+        !matches!(
+            self.atomic_number(),
+            1 | 2 | 5..=10 | 14..=18 | 32..=36 | 51..=54 | 85 | 86 | 117 | 118
+        )
+    }
+}
+
+/// Written as the symbol, e.g. `"Fe"` or `"D"`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for ChemicalElement {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.symbol())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for ChemicalElement {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let symbol = String::deserialize(deserializer)?;
+        Self::from_symbol(&symbol)
+            .ok_or_else(|| serde::de::Error::custom(format!("unknown element {:?}", symbol)))
+    }
+}
+
+impl From<ChemicalElement> for Element {
+    /// Elements not covered by `Element` become `Element::Unknown`; deuterium becomes
+    /// `Element::H`.
+    fn from(element: ChemicalElement) -> Self {
+        match element.atomic_number() {
+            1 => Element::H,
+            6 => Element::C,
+            7 => Element::N,
+            8 => Element::O,
+            9 => Element::F,
+            11 => Element::Na,
+            12 => Element::Mg,
+            13 => Element::Al,
+            15 => Element::P,
+            16 => Element::S,
+            17 => Element::Cl,
+            19 => Element::K,
+            20 => Element::Ca,
+            23 => Element::V,
+            24 => Element::Cr,
+            25 => Element::Mn,
+            26 => Element::Fe,
+            27 => Element::Co,
+            29 => Element::Cu,
+            30 => Element::Zn,
+            34 => Element::Se,
+            53 => Element::I,
+            _ => Element::Unknown,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{AminoAcid, StandardAminoAcid};
+    #[test]
+    fn test_element() {
+        for (i, e) in PERIODIC_TABLE[..118].iter().enumerate() {
+            assert_eq!(e.atomic_number as usize, i + 1);
+        }
+        assert_eq!(
+            ChemicalElement::from_symbol("BR").unwrap().name(),
+            "Bromine"
+        );
+        assert_eq!(
+            ChemicalElement::from_symbol("Hg ").unwrap().atomic_number(),
+            80
+        );
+        assert_eq!(
+            ChemicalElement::from_symbol(" D"),
+            Some(ChemicalElement::DEUTERIUM)
+        );
+        assert_eq!(ChemicalElement::from_symbol("X1"), None);
+        let ala = Residue::AminoAcid(AminoAcid::Standard(StandardAminoAcid::Ala));
+        let het = Residue::Other("HEM".to_owned());
+        let from_name = |name: &[u8], residue| ChemicalElement::from_atom_name(name, residue);
+        assert_eq!(from_name(b" CA ", &ala).unwrap().symbol(), "C");
+        assert_eq!(from_name(b"HG21", &ala).unwrap().symbol(), "H");
+        assert_eq!(from_name(b"1HG1", &ala).unwrap().symbol(), "H");
+        assert_eq!(from_name(b"CA  ", &het).unwrap().symbol(), "Ca");
+        assert_eq!(from_name(b"FE  ", &het).unwrap().symbol(), "Fe");
+        assert_eq!(from_name(b"C10 ", &het).unwrap().symbol(), "C");
+        let is_metal = |symbol| ChemicalElement::from_symbol(symbol).unwrap().is_metal();
+        assert!(is_metal("ZN") && is_metal("Na") && is_metal("Fe"));
+        assert!(!is_metal("C") && !is_metal("Se") && !is_metal("Cl"));
+    }
+}
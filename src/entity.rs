@@ -0,0 +1,113 @@
+// Copyright (c) 2020 Tianyi Shi
+//
+// This software is released under the MIT License.
+// https://opensource.org/licenses/MIT
+
+//! Molecular entities, the equivalent of mmCIF's `_entity` category.
+//!
+//! An entity is a chemically distinct molecule. Each `MOL_ID` group of the COMPND record describes
+//! one entity, and all chains listed in its `CHAIN` token are copies of it (e.g. the four chains of
+//! a homo-tetramer belong to the same entity). The matching `MOL_ID` group of the SOURCE record
+//! gives its origin, and the SEQRES records of any of its chains give its sequence.
+
+use crate::entry::Entry;
+use crate::types::{AminoAcid, Chain, Monomer, Nucleotide};
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone)]
+pub struct Entity {
+    /// The `MOL_ID` of the COMPND and SOURCE records.
+    pub id: u32,
+    /// The `MOLECULE` token of the COMPND record.
+    pub description: Option<String>,
+    pub chains: Vec<char>,
+    /// The sequence from SEQRES, if any of `chains` has SEQRES records.
+    pub polymer: Option<Polymer>,
+    pub organism_scientific: Option<String>,
+    pub organism_taxid: Vec<u32>,
+    pub expression_system: Option<String>,
+    pub synthetic: bool,
+    pub ec: Vec<String>,
+    pub engineered: bool,
+    pub mutation: bool,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Polymer {
+    Peptide(
+        #[cfg_attr(
+            feature = "serde",
+            serde(deserialize_with = "crate::json::amino_acids")
+        )]
+        Vec<AminoAcid>,
+    ),
+    NucleicAcid(
+        #[cfg_attr(
+            feature = "serde",
+            serde(deserialize_with = "crate::json::nucleotides")
+        )]
+        Vec<Nucleotide>,
+    ),
+}
+
+impl Entity {
+    /// The chain that stands for all copies of this entity, i.e. the first one listed in COMPND.
+    pub fn representative_chain(&self) -> Option<char> {
+        self.chains.first().copied()
+    }
+
+    pub fn contains_chain(&self, chain: char) -> bool {
+        self.chains.contains(&chain)
+    }
+}
+
+impl Entry {
+    /// Groups the chains of the entry into [`Entity`]s, in the order of their `MOL_ID`s.
+    pub fn entities(&self) -> Vec<Entity> {
+        let title_section = &self.title_section;
+        title_section
+            .compounds
+            .iter()
+            .map(|compound| {
+                let source = title_section
+                    .sources
+                    .iter()
+                    .find(|source| source.mol_id == compound.mol_id);
+                let polymer = find_sequence(&self.structure.chains_aa, &compound.chains)
+                    .map(Polymer::Peptide)
+                    .or_else(|| {
+                        find_sequence(&self.structure.chains_nuc, &compound.chains)
+                            .map(Polymer::NucleicAcid)
+                    });
+                Entity {
+                    id: compound.mol_id,
+                    description: compound.molecule.clone(),
+                    chains: compound.chains.clone(),
+                    polymer,
+                    organism_scientific: source.and_then(|s| s.organism_scientific.clone()),
+                    organism_taxid: source.map(|s| s.organism_taxid.clone()).unwrap_or_default(),
+                    expression_system: source.and_then(|s| s.expression_system.clone()),
+                    synthetic: source.map(|s| s.synthetic).unwrap_or(false),
+                    ec: compound.ec.clone(),
+                    engineered: compound.engineered,
+                    mutation: compound.mutation,
+                }
+            })
+            .collect()
+    }
+
+    /// The entity that `chain` is a copy of.
+    pub fn entity_of_chain(&self, chain: char) -> Option<Entity> {
+        self.entities()
+            .into_iter()
+            .find(|entity| entity.contains_chain(chain))
+    }
+}
+
+fn find_sequence<T: Monomer + Clone>(chains: &[Chain<T>], ids: &[char]) -> Option<Vec<T>> {
+    chains
+        .iter()
+        .find(|chain| ids.contains(&chain.id))
+        .map(|chain| chain.seq.clone())
+}
@@ -0,0 +1,191 @@
+// Copyright (c) 2020 Tianyi Shi
+//
+// This software is released under the MIT License.
+// https://opensource.org/licenses/MIT
+
+//! A parsed PDB entry.
+//!
+//! [`Structure`] (provided by `protein-core`) holds the data that describes a structure
+//! independently of the file format it was read from. `Entry` wraps it together with the
+//! PDB-specific records that `Structure` has no place for.
+//!
+//! [`Structure`]: ../../protein_core/structure/struct.Structure.html
+
+use crate::bookkeeping::Bookkeeping;
+use crate::coordinate::{Sigatm, Ter};
+use crate::element::ChemicalElement;
+use crate::secondary_structure::{HelixDetails, Link, StrandDetails};
+use crate::title_section::TitleSection;
+use crate::types::{Anisou, Atom, AtomSerial, Model, Structure};
+use crate::writer::residue_name;
+use std::collections::HashSet;
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Default)]
+pub struct Entry {
+    #[cfg_attr(feature = "serde", serde(deserialize_with = "crate::json::structure"))]
+    pub structure: Structure,
+    pub title_section: TitleSection,
+    pub bookkeeping: Bookkeeping,
+    /// Records of each model that `Model` has no place for, in the same order as
+    /// `structure.models`.
+    pub model_info: Vec<ModelInfo>,
+    /// The fields of each HELIX record that `Helix` has no place for, in the same order as
+    /// `structure.helices`.
+    pub helix_details: Vec<HelixDetails>,
+    /// The fields of each SHEET record that `Strand` has no place for, strand by strand, in the
+    /// same order as `structure.sheets`.
+    pub sheet_details: Vec<Vec<StrandDetails>>,
+    /// The LINK records, in the order in which they appear.
+    pub links: Vec<Link>,
+}
+
+/// Records of a model that `Model` has no place for.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Default)]
+pub struct ModelInfo {
+    /// The serial number on the MODEL record. `None` if the model is not introduced by one, as in
+    /// files with a single model.
+    pub serial: Option<u32>,
+    /// Whether the model is closed by an ENDMDL record.
+    pub ended: bool,
+    /// TER records, each with the number of atoms of the model that precede it.
+    pub ters: Vec<(usize, Ter)>,
+    /// The element of each atom, which `Atom::element` can only hold for the most common
+    /// elements. Kept in step with `Model::atoms` by the methods of `Entry` that add, remove or
+    /// reorder atoms.
+    pub(crate) elements: Vec<Option<ChemicalElement>>,
+    /// SIGATM records
+    pub sigatm: Vec<Sigatm>,
+    /// SIGUIJ records, which share the layout of ANISOU
+    #[cfg_attr(feature = "serde", serde(deserialize_with = "crate::json::anisou"))]
+    pub siguij: Vec<Anisou>,
+}
+
+impl ModelInfo {
+    /// The element of each atom of the model; read single atoms with
+    /// [`Entry::element`](struct.Entry.html#method.element), which also covers atoms added to
+    /// `Model::atoms` directly.
+    pub fn elements(&self) -> &[Option<ChemicalElement>] {
+        &self.elements
+    }
+}
+
+impl Entry {
+    /// The serial number of the model at `index` in `structure.models`: that on its MODEL record,
+    /// or `index + 1` if it has none.
+    pub fn model_serial(&self, index: usize) -> u32 {
+        self.model_info
+            .get(index)
+            .and_then(|info| info.serial)
+            .unwrap_or(index as u32 + 1)
+    }
+
+    /// The index in `structure.models` of the model with serial number `serial`.
+    pub fn model_index(&self, serial: u32) -> Option<usize> {
+        (0..self.structure.models.len()).find(|&i| self.model_serial(i) == serial)
+    }
+
+    /// The element of the atom at `atom` in the model at `model` (indices in `structure.models`
+    /// and `Model::atoms`). Falls back to `Atom::element` for atoms added after parsing.
+    pub fn element(&self, model: usize, atom: usize) -> Option<ChemicalElement> {
+        match self
+            .model_info
+            .get(model)
+            .and_then(|m| m.elements.get(atom))
+        {
+            Some(&element) => element,
+            None => ChemicalElement::from_element(self.structure.models[model].atoms[atom].element),
+        }
+    }
+
+    /// Brings the elements of the model at `model` back in step with its atoms if `Model::atoms`
+    /// was changed directly, falling back to `Atom::element` for the atoms past the known ones.
+    pub(crate) fn sync_elements(&mut self, model: usize) {
+        let len = self.structure.models[model].atoms.len();
+        match self.model_info.get(model) {
+            Some(info) if info.elements.len() != len => {}
+            _ => return,
+        }
+        let elements = (0..len).map(|i| self.element(model, i)).collect();
+        self.model_info[model].elements = elements;
+    }
+
+    /// Whether the atom at `atom` in the model at `model` is a hydrogen or deuterium.
+    pub fn is_hydrogen(&self, model: usize, atom: usize) -> bool {
+        matches!(self.element(model, atom), Some(e) if e.is_hydrogen())
+    }
+
+    /// The model with serial number `serial`.
+    pub fn model(&self, serial: u32) -> Option<&Model> {
+        self.model_index(serial).map(|i| &self.structure.models[i])
+    }
+
+    /// Keeps only the atoms for which `keep(model, index, atom)` is true, where `model` and `index`
+    /// are indices in `structure.models` and `Model::atoms`. The ANISOU, SIGATM and SIGUIJ records
+    /// of removed atoms are removed with them, as are CONECT records that refer to an atom no
+    /// longer present in any model, and TER records that no longer follow an atom of their chain;
+    /// the others are updated to name the residue that now precedes them.
+    pub fn retain_atoms(&mut self, mut keep: impl FnMut(usize, usize, &Atom) -> bool) {
+        let mut serials: HashSet<AtomSerial> = HashSet::new();
+        for m in 0..self.structure.models.len() {
+            self.sync_elements(m);
+        }
+        for (m, model) in self.structure.models.iter_mut().enumerate() {
+            let kept: Vec<bool> = model
+                .atoms
+                .iter()
+                .enumerate()
+                .map(|(i, atom)| keep(m, i, atom))
+                .collect();
+            let ids: HashSet<AtomSerial> = model
+                .atoms
+                .iter()
+                .zip(&kept)
+                .filter(|(_, &k)| k)
+                .map(|(a, _)| a.id)
+                .collect();
+            if let Some(info) = self.model_info.get_mut(m) {
+                // * the number of kept atoms before each position
+                let mut before = vec![0; kept.len() + 1];
+                for (i, &k) in kept.iter().enumerate() {
+                    before[i + 1] = before[i] + k as usize;
+                }
+                let atoms = &model.atoms;
+                let ters = std::mem::take(&mut info.ters);
+                info.ters = ters
+                    .into_iter()
+                    .filter(|(preceding, _)| *preceding <= atoms.len())
+                    .filter_map(|(preceding, mut ter)| {
+                        let chain = atoms.get(preceding.checked_sub(1)?)?.chain;
+                        let last = &atoms[kept[..preceding].iter().rposition(|&k| k)?];
+                        if last.chain != chain {
+                            return None;
+                        }
+                        // * the residue that now ends the chain
+                        if ter.id.is_some() {
+                            ter.id = Some(last.id + 1);
+                        }
+                        if ter.sequence_number.is_some() {
+                            ter.residue_name = residue_name(&last.residue);
+                            ter.sequence_number = Some(last.sequence_number);
+                            ter.insertion_code = last.insertion_code;
+                        }
+                        Some((before[preceding], ter))
+                    })
+                    .collect();
+                let mut k = kept.iter();
+                info.elements.retain(|_| *k.next().unwrap());
+                info.sigatm.retain(|s| ids.contains(&s.id));
+                info.siguij.retain(|s| ids.contains(&s.id));
+            }
+            let mut k = kept.iter();
+            model.atoms.retain(|_| *k.next().unwrap());
+            model.anisou.retain(|a| ids.contains(&a.id));
+            serials.extend(ids);
+        }
+        self.structure
+            .connect
+            .retain(|[a, b]| serials.contains(a) && serials.contains(b));
+    }
+}
@@ -0,0 +1,504 @@
+// Copyright (c) 2020 Tianyi Shi
+//
+// This software is released under the MIT License.
+// https://opensource.org/licenses/MIT
+
+//! Geometry of atomic coordinates: distances, angles and dihedrals, centroids, radii of gyration
+//! and bounding boxes, the optimal superposition of two sets of atoms, and the search for points
+//! within a distance of each other.
+//!
+//! The functions accept anything with a [`Position`], which includes `Atom` and plain `[f32; 3]`
+//! coordinates. Angles are in degrees and lengths in Å.
+//!
+//! Superposition uses the quaternion method of Horn (1987), which gives the same least-squares
+//! rotation as the Kabsch algorithm without the special handling of reflections.
+//!
+//! [`Position`]: trait.Position.html
+
+use crate::types::{Atom, ResidueSerial};
+use std::collections::{HashMap, HashSet};
+
+pub type Coord = [f32; 3];
+
+/// Something with a position in space.
+pub trait Position {
+    fn position(&self) -> Coord;
+}
+
+impl Position for Atom {
+    fn position(&self) -> Coord {
+        self.coord
+    }
+}
+
+impl Position for Coord {
+    fn position(&self) -> Coord {
+        *self
+    }
+}
+
+impl<T: Position> Position for &T {
+    fn position(&self) -> Coord {
+        (*self).position()
+    }
+}
+
+pub(crate) fn sub(a: Coord, b: Coord) -> Coord {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+pub(crate) fn dot(a: Coord, b: Coord) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+pub(crate) fn cross(a: Coord, b: Coord) -> Coord {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+pub(crate) fn norm(a: Coord) -> f32 {
+    dot(a, a).sqrt()
+}
+
+pub fn distance(a: &impl Position, b: &impl Position) -> f32 {
+    norm(sub(a.position(), b.position()))
+}
+
+/// The angle a-b-c, between 0 and 180°.
+pub fn angle(a: &impl Position, b: &impl Position, c: &impl Position) -> f32 {
+    let ba = sub(a.position(), b.position());
+    let bc = sub(c.position(), b.position());
+    let cos = dot(ba, bc) / (norm(ba) * norm(bc));
+    cos.clamp(-1.0, 1.0).acos().to_degrees()
+}
+
+/// The dihedral angle a-b-c-d, between -180 and 180°, following the IUPAC sign convention
+/// (positive if a → d is clockwise when viewed along b → c).
+pub fn dihedral(a: &impl Position, b: &impl Position, c: &impl Position, d: &impl Position) -> f32 {
+    let b1 = sub(b.position(), a.position());
+    let b2 = sub(c.position(), b.position());
+    let b3 = sub(d.position(), c.position());
+    let n2 = cross(b2, b3);
+    let x = dot(cross(b1, b2), n2);
+    let y = norm(b2) * dot(b1, n2);
+    y.atan2(x).to_degrees()
+}
+
+/// The mean position. `None` if there are no points.
+pub fn centroid<P: Position>(points: &[P]) -> Option<Coord> {
+    if points.is_empty() {
+        return None;
+    }
+    let mut sum = [0f64; 3];
+    for p in points {
+        let p = p.position();
+        for k in 0..3 {
+            sum[k] += p[k] as f64;
+        }
+    }
+    let n = points.len() as f64;
+    Some([
+        (sum[0] / n) as f32,
+        (sum[1] / n) as f32,
+        (sum[2] / n) as f32,
+    ])
+}
+
+/// The root mean square distance of the points from their centroid. `None` if there are no
+/// points.
+pub fn radius_of_gyration<P: Position>(points: &[P]) -> Option<f32> {
+    let c = centroid(points)?;
+    let sum: f64 = points
+        .iter()
+        .map(|p| {
+            let d = sub(p.position(), c);
+            dot(d, d) as f64
+        })
+        .sum();
+    Some((sum / points.len() as f64).sqrt() as f32)
+}
+
+/// The smallest and largest coordinates along each axis. `None` if there are no points.
+pub fn bounding_box<P: Position>(points: &[P]) -> Option<(Coord, Coord)> {
+    let first = points.first()?.position();
+    Some(points.iter().fold((first, first), |(mut min, mut max), p| {
+        let p = p.position();
+        for k in 0..3 {
+            min[k] = min[k].min(p[k]);
+            max[k] = max[k].max(p[k]);
+        }
+        (min, max)
+    }))
+}
+
+/// The root mean square deviation of paired points, without superposition. `None` if the slices
+/// are empty or differ in length.
+pub fn rmsd<P: Position, Q: Position>(a: &[P], b: &[Q]) -> Option<f32> {
+    if a.is_empty() || a.len() != b.len() {
+        return None;
+    }
+    let sum: f64 = a
+        .iter()
+        .zip(b)
+        .map(|(p, q)| {
+            let d = sub(p.position(), q.position());
+            dot(d, d) as f64
+        })
+        .sum();
+    Some((sum / a.len() as f64).sqrt() as f32)
+}
+
+/// The rigid-body transformation that best superposes one set of points onto another.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Superposition {
+    /// Rotation matrix, applied to column vectors
+    pub rotation: [[f32; 3]; 3],
+    /// Translation, applied after the rotation
+    pub translation: Coord,
+    /// Root mean square deviation after superposition
+    pub rmsd: f32,
+    /// Number of pairs of points superposed
+    pub pairs: usize,
+}
+
+impl Superposition {
+    /// Moves a point of the mobile set onto the target.
+    pub fn apply(&self, p: &impl Position) -> Coord {
+        let p = p.position();
+        let r = &self.rotation;
+        let mut out = self.translation;
+        for (o, row) in out.iter_mut().zip(r) {
+            *o += dot(*row, p);
+        }
+        out
+    }
+}
+
+/// Superposes `mobile` onto `target`, pairing the points by their order. `None` if the slices
+/// are empty or differ in length.
+pub fn superpose<P: Position, Q: Position>(mobile: &[P], target: &[Q]) -> Option<Superposition> {
+    if mobile.is_empty() || mobile.len() != target.len() {
+        return None;
+    }
+    let cm = centroid(mobile)?;
+    let ct = centroid(target)?;
+    // * correlation matrix s[a][b] = Σ mobile_a target_b and the inner products of both sets
+    let mut s = [[0f64; 3]; 3];
+    let mut g = 0f64;
+    for (p, q) in mobile.iter().zip(target) {
+        let p = sub(p.position(), cm);
+        let q = sub(q.position(), ct);
+        for a in 0..3 {
+            for b in 0..3 {
+                s[a][b] += p[a] as f64 * q[b] as f64;
+            }
+        }
+        g += dot(p, p) as f64 + dot(q, q) as f64;
+    }
+    let [[xx, xy, xz], [yx, yy, yz], [zx, zy, zz]] = s;
+    let n = [
+        [xx + yy + zz, yz - zy, zx - xz, xy - yx],
+        [yz - zy, xx - yy - zz, xy + yx, zx + xz],
+        [zx - xz, xy + yx, -xx + yy - zz, yz + zy],
+        [xy - yx, zx + xz, yz + zy, -xx - yy + zz],
+    ];
+    let (values, vectors) = jacobi_eigen(n);
+    let best = (0..4)
+        .max_by(|&i, &j| values[i].partial_cmp(&values[j]).unwrap())
+        .unwrap();
+    let [q0, q1, q2, q3] = [
+        vectors[0][best],
+        vectors[1][best],
+        vectors[2][best],
+        vectors[3][best],
+    ];
+    let rotation = [
+        [
+            q0 * q0 + q1 * q1 - q2 * q2 - q3 * q3,
+            2.0 * (q1 * q2 - q0 * q3),
+            2.0 * (q1 * q3 + q0 * q2),
+        ],
+        [
+            2.0 * (q1 * q2 + q0 * q3),
+            q0 * q0 - q1 * q1 + q2 * q2 - q3 * q3,
+            2.0 * (q2 * q3 - q0 * q1),
+        ],
+        [
+            2.0 * (q1 * q3 - q0 * q2),
+            2.0 * (q2 * q3 + q0 * q1),
+            q0 * q0 - q1 * q1 - q2 * q2 + q3 * q3,
+        ],
+    ];
+    let rotation = [
+        [
+            rotation[0][0] as f32,
+            rotation[0][1] as f32,
+            rotation[0][2] as f32,
+        ],
+        [
+            rotation[1][0] as f32,
+            rotation[1][1] as f32,
+            rotation[1][2] as f32,
+        ],
+        [
+            rotation[2][0] as f32,
+            rotation[2][1] as f32,
+            rotation[2][2] as f32,
+        ],
+    ];
+    let msd = (g - 2.0 * values[best]) / mobile.len() as f64;
+    let mut translation = ct;
+    for (t, row) in translation.iter_mut().zip(&rotation) {
+        *t -= dot(*row, cm);
+    }
+    Some(Superposition {
+        rotation,
+        translation,
+        rmsd: msd.max(0.0).sqrt() as f32,
+        pairs: mobile.len(),
+    })
+}
+
+/// Superposes the atoms of `mobile` onto those of `target`, pairing atoms with the same chain
+/// identifier, residue sequence number, insertion code and atom name. Atoms without a partner
+/// are left out, as are alternate locations other than the first. `None` if no atom has a
+/// partner.
+pub fn superpose_atoms(mobile: &[Atom], target: &[Atom]) -> Option<Superposition> {
+    let (mobile, target) = pair_atoms(mobile, target);
+    superpose(&mobile, &target)
+}
+
+type AtomKey = (char, ResidueSerial, char, [u8; 4]);
+
+fn atom_key(atom: &Atom) -> AtomKey {
+    (
+        atom.chain,
+        atom.sequence_number,
+        atom.insertion_code,
+        atom.name.0,
+    )
+}
+
+/// Pairs the atoms of two selections by chain, residue and atom name, in the order of `a`.
+pub fn pair_atoms<'a, 'b>(a: &'a [Atom], b: &'b [Atom]) -> (Vec<&'a Atom>, Vec<&'b Atom>) {
+    let mut by_key: HashMap<AtomKey, &Atom> = HashMap::new();
+    for atom in b {
+        by_key.entry(atom_key(atom)).or_insert(atom);
+    }
+    let mut seen = HashSet::new();
+    a.iter()
+        .filter(|atom| seen.insert(atom_key(atom)))
+        .filter_map(|atom| by_key.get(&atom_key(atom)).map(|&partner| (atom, partner)))
+        .unzip()
+}
+
+/// A grid of cubic cells over a set of points, for finding the points near a given position
+/// without comparing it with every point.
+#[derive(Debug, Clone)]
+pub struct NeighborSearch<'a> {
+    coords: &'a [Coord],
+    cell_size: f32,
+    cells: HashMap<(i32, i32, i32), Vec<usize>>,
+}
+
+impl<'a> NeighborSearch<'a> {
+    /// Indexes all points. Searches are fastest for radii up to `cell_size`.
+    pub fn new(coords: &'a [Coord], cell_size: f32) -> Self {
+        Self::with_indices(coords, 0..coords.len(), cell_size)
+    }
+
+    /// Indexes only the points at `indices`.
+    pub fn with_indices(
+        coords: &'a [Coord],
+        indices: impl IntoIterator<Item = usize>,
+        cell_size: f32,
+    ) -> Self {
+        let mut search = Self {
+            coords,
+            cell_size,
+            cells: HashMap::new(),
+        };
+        for i in indices {
+            let cell = search.cell(&coords[i]);
+            search.cells.entry(cell).or_default().push(i);
+        }
+        search
+    }
+
+    fn cell(&self, p: &Coord) -> (i32, i32, i32) {
+        let f = |x: f32| (x / self.cell_size).floor() as i32;
+        (f(p[0]), f(p[1]), f(p[2]))
+    }
+
+    /// The indices of the indexed points within `radius` of `p`, in no particular order.
+    pub fn within(&self, p: &impl Position, radius: f32) -> Vec<usize> {
+        let p = p.position();
+        let (x, y, z) = self.cell(&p);
+        let reach = (radius / self.cell_size).ceil() as i32;
+        let mut found = Vec::new();
+        for dx in -reach..=reach {
+            for dy in -reach..=reach {
+                for dz in -reach..=reach {
+                    for &i in self
+                        .cells
+                        .get(&(x + dx, y + dy, z + dz))
+                        .into_iter()
+                        .flatten()
+                    {
+                        let d = sub(self.coords[i], p);
+                        if dot(d, d) <= radius * radius {
+                            found.push(i);
+                        }
+                    }
+                }
+            }
+        }
+        found
+    }
+
+    /// All pairs `(i, j)` of indexed points with `i < j` that are within `radius` of each other.
+    pub fn pairs(&self, radius: f32) -> Vec<(usize, usize)> {
+        let mut pairs: Vec<(usize, usize)> = self
+            .cells
+            .values()
+            .flatten()
+            .flat_map(|&i| {
+                self.within(&self.coords[i], radius)
+                    .into_iter()
+                    .filter(move |&j| i < j)
+                    .map(move |j| (i, j))
+            })
+            .collect();
+        pairs.sort_unstable();
+        pairs
+    }
+}
+
+/// Diagonalizes a symmetric matrix with the cyclic Jacobi method. Returns the eigenvalues and a
+/// matrix whose columns are the corresponding eigenvectors.
+pub(crate) fn jacobi_eigen<const N: usize>(mut a: [[f64; N]; N]) -> ([f64; N], [[f64; N]; N]) {
+    let mut v = [[0.0; N]; N];
+    for (i, row) in v.iter_mut().enumerate() {
+        row[i] = 1.0;
+    }
+    for _ in 0..50 {
+        let off: f64 = a
+            .iter()
+            .enumerate()
+            .flat_map(|(p, row)| &row[p + 1..])
+            .map(|x| x * x)
+            .sum();
+        if off < 1e-24 {
+            break;
+        }
+        for p in 0..N {
+            for q in p + 1..N {
+                if a[p][q].abs() < 1e-30 {
+                    continue;
+                }
+                let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+                for row in a.iter_mut() {
+                    let (akp, akq) = (row[p], row[q]);
+                    row[p] = c * akp - s * akq;
+                    row[q] = s * akp + c * akq;
+                }
+                let (ap, aq) = (a[p], a[q]);
+                for (k, (apk, aqk)) in ap.iter().zip(aq.iter()).enumerate() {
+                    a[p][k] = c * apk - s * aqk;
+                    a[q][k] = s * apk + c * aqk;
+                }
+                for row in v.iter_mut() {
+                    let (vkp, vkq) = (row[p], row[q]);
+                    row[p] = c * vkp - s * vkq;
+                    row[q] = s * vkp + c * vkq;
+                }
+            }
+        }
+    }
+    let mut values = [0.0; N];
+    for (i, value) in values.iter_mut().enumerate() {
+        *value = a[i][i];
+    }
+    (values, v)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_internal_coordinates() {
+        let a = [1.0, 0.0, 0.0];
+        let b = [0.0, 0.0, 0.0];
+        let c = [0.0, 1.0, 0.0];
+        let d = [0.0, 1.0, 1.0];
+        assert!((distance(&a, &c) - 2f32.sqrt()).abs() < 1e-6);
+        assert!((angle(&a, &b, &c) - 90.0).abs() < 1e-4);
+        assert!((dihedral(&a, &b, &c, &d) + 90.0).abs() < 1e-4);
+        assert!((dihedral(&a, &b, &c, &[1.0, 1.0, 0.0])).abs() < 1e-4);
+        assert_eq!(centroid(&[a, c]), Some([0.5, 0.5, 0.0]));
+        assert_eq!(bounding_box(&[a, b, d]), Some((b, [1.0, 1.0, 1.0])));
+        assert!((radius_of_gyration(&[a, [-1.0, 0.0, 0.0]]).unwrap() - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_superpose() {
+        let target = [
+            [1.0, 2.0, 3.0],
+            [-2.0, 0.5, 1.0],
+            [0.0, -1.5, 2.5],
+            [3.0, 1.0, -1.0],
+            [0.5, 0.5, 0.5],
+        ];
+        // * rotate 60° about z and translate
+        let (sin, cos) = 60f32.to_radians().sin_cos();
+        let mobile: Vec<Coord> = target
+            .iter()
+            .map(|p| {
+                [
+                    cos * p[0] - sin * p[1] + 4.0,
+                    sin * p[0] + cos * p[1] - 2.0,
+                    p[2] + 1.0,
+                ]
+            })
+            .collect();
+        let s = superpose(&mobile, &target).unwrap();
+        assert!(s.rmsd < 1e-3);
+        assert_eq!(s.pairs, 5);
+        for (m, t) in mobile.iter().zip(target.iter()) {
+            assert!(distance(&s.apply(m), t) < 1e-3);
+        }
+        assert!(rmsd(&mobile, &target).unwrap() > 1.0);
+        assert_eq!(superpose(&mobile[..2], &target), None);
+    }
+
+    #[test]
+    fn test_neighbor_search() {
+        let coords: Vec<Coord> = (0..50)
+            .map(|i| {
+                let i = i as f32;
+                [(i * 0.7).sin() * 5.0, (i * 1.3).cos() * 5.0, i * 0.3]
+            })
+            .collect();
+        let search = NeighborSearch::new(&coords, 2.0);
+        let mut brute = Vec::new();
+        for i in 0..coords.len() {
+            for j in i + 1..coords.len() {
+                if distance(&coords[i], &coords[j]) <= 3.0 {
+                    brute.push((i, j));
+                }
+            }
+        }
+        assert_eq!(search.pairs(3.0), brute);
+        let mut near = search.within(&coords[0], 3.0);
+        near.sort_unstable();
+        assert_eq!(near[0], 0);
+        assert_eq!(near.len(), 1 + brute.iter().filter(|p| p.0 == 0).count());
+    }
+}
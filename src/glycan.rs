@@ -0,0 +1,419 @@
+// Copyright (c) 2020 Tianyi Shi
+//
+// This software is released under the MIT License.
+// https://opensource.org/licenses/MIT
+
+//! Carbohydrates and the glycans they form, such as those attached to glycoproteins.
+//!
+//! Monosaccharides are recognised by their residue names, from a built-in list of the common
+//! components of the Chemical Component Dictionary. Each name fixes the monosaccharide, its
+//! absolute configuration and the configuration of its anomeric carbon: NAG is β-D-GlcNAc and
+//! NDG is α-D-GlcNAc.
+//!
+//! The anomeric carbon (C1, or C2 for the sialic acids and ketoses) of a monosaccharide is linked
+//! to the atom of another residue given by a LINK record, or else to the nearest oxygen or
+//! nitrogen atom of another residue no further than 2 Å from it. Following the links from each
+//! monosaccharide to the residue it is attached to gives a tree, whose root is attached to the
+//! glycosylation site (the ND2 of an asparagine, or the OG or OG1 of a serine or threonine), to
+//! another non-carbohydrate residue, or to nothing.
+//!
+//! Glycans are written in the IUPAC condensed notation, e.g.
+//! `Man(a1-6)[Man(a1-3)]Man(b1-4)GlcNAc(b1-4)GlcNAc(b1-N)Asn`. The main chain of a branched
+//! residue continues with its longest branch; the other branches are enclosed in square brackets.
+//! The D- and L- prefixes are left out, as is usual for the configurations found in nature.
+
+use crate::entry::Entry;
+use crate::geometry::{distance, Coord, NeighborSearch};
+use crate::hierarchy::{first_alt_locs, ModelView, ResidueView};
+use crate::interaction::Partner;
+use crate::types::{Residue, ResidueSerial};
+use std::fmt;
+
+/// The maximum length, in Å, of a glycosidic bond found from the coordinates.
+const GLYCOSIDIC_BOND_CUTOFF: f32 = 2.0;
+
+/// The configuration of the anomeric carbon.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Anomer {
+    Alpha,
+    Beta,
+}
+
+impl Anomer {
+    /// The letter used in the IUPAC condensed notation.
+    pub fn code(self) -> char {
+        match self {
+            Anomer::Alpha => 'a',
+            Anomer::Beta => 'b',
+        }
+    }
+}
+
+/// A monosaccharide, as identified by its residue name.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Monosaccharide {
+    /// The IUPAC abbreviation, e.g. `GlcNAc`
+    pub name: String,
+    pub anomer: Anomer,
+    /// The number of the anomeric carbon
+    pub anomeric_carbon: u8,
+}
+
+#[rustfmt::skip]
+const MONOSACCHARIDES: [(&str, &str, Anomer, u8); 26] = [
+    ("NAG", "GlcNAc", Anomer::Beta, 1),
+    ("NDG", "GlcNAc", Anomer::Alpha, 1),
+    ("NGA", "GalNAc", Anomer::Beta, 1),
+    ("A2G", "GalNAc", Anomer::Alpha, 1),
+    ("BMA", "Man", Anomer::Beta, 1),
+    ("MAN", "Man", Anomer::Alpha, 1),
+    ("BGC", "Glc", Anomer::Beta, 1),
+    ("GLC", "Glc", Anomer::Alpha, 1),
+    ("GAL", "Gal", Anomer::Beta, 1),
+    ("GLA", "Gal", Anomer::Alpha, 1),
+    ("FUL", "Fuc", Anomer::Beta, 1),
+    ("FUC", "Fuc", Anomer::Alpha, 1),
+    ("RAM", "Rha", Anomer::Alpha, 1),
+    ("XYP", "Xyl", Anomer::Beta, 1),
+    ("XYS", "Xyl", Anomer::Alpha, 1),
+    ("BDP", "GlcA", Anomer::Beta, 1),
+    ("GCU", "GlcA", Anomer::Alpha, 1),
+    ("IDR", "IdoA", Anomer::Alpha, 1),
+    ("Z9N", "Ara", Anomer::Alpha, 1),
+    ("SIA", "Neu5Ac", Anomer::Alpha, 2),
+    ("SLB", "Neu5Ac", Anomer::Beta, 2),
+    ("NGC", "Neu5Gc", Anomer::Alpha, 2),
+    ("KDN", "Kdn", Anomer::Alpha, 2),
+    ("FRU", "Fru", Anomer::Beta, 2),
+    ("BGS", "Glc6S", Anomer::Beta, 1),
+    ("MMA", "Man1Me", Anomer::Alpha, 1),
+];
+
+/// The monosaccharide with the residue name `name`, if it is in the built-in list.
+pub fn monosaccharide(name: &str) -> Option<Monosaccharide> {
+    MONOSACCHARIDES.iter().find(|(code, ..)| *code == name).map(
+        |&(_, name, anomer, anomeric_carbon)| Monosaccharide {
+            name: name.to_owned(),
+            anomer,
+            anomeric_carbon,
+        },
+    )
+}
+
+/// A monosaccharide of a glycan.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct GlycanResidue {
+    pub chain: char,
+    pub sequence_number: ResidueSerial,
+    pub insertion_code: char,
+    pub residue_name: String,
+    pub monosaccharide: Monosaccharide,
+    /// The index in `Glycan::residues` of the residue this one is attached to; `None` for the
+    /// root
+    pub parent: Option<usize>,
+    /// The atom the anomeric carbon is linked to, on the parent or, for the root, on the
+    /// glycosylation site
+    pub linked_atom: Option<String>,
+    /// Whether the linkage is given by a LINK record rather than found from the coordinates
+    pub from_link: bool,
+}
+
+impl GlycanResidue {
+    /// The linkage in the IUPAC condensed notation, e.g. `b1-4`, or `b1-N` for the root.
+    pub fn linkage(&self) -> Option<String> {
+        let atom = self.linked_atom.as_ref()?;
+        // * positions on a monosaccharide are numbered; those on other residues are not
+        let position: String = if self.parent.is_some() {
+            atom.chars().filter(char::is_ascii_digit).collect()
+        } else {
+            atom.chars().take(1).collect()
+        };
+        Some(format!(
+            "{}{}-{}",
+            self.monosaccharide.anomer.code(),
+            self.monosaccharide.anomeric_carbon,
+            if position.is_empty() { "?" } else { &position }
+        ))
+    }
+}
+
+/// A tree of monosaccharides.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Glycan {
+    /// The residue the root is attached to, with the linked atom, if any
+    pub site: Option<Partner>,
+    /// The monosaccharides, parents before their children; the root comes first
+    pub residues: Vec<GlycanResidue>,
+}
+
+impl Glycan {
+    /// The indices of the children of the residue at `parent`.
+    pub fn children(&self, parent: usize) -> impl Iterator<Item = usize> + '_ {
+        (0..self.residues.len()).filter(move |&i| self.residues[i].parent == Some(parent))
+    }
+
+    /// The number of residues on the longest path from the residue at `i` to a leaf.
+    fn depth(&self, i: usize) -> usize {
+        1 + self.children(i).map(|c| self.depth(c)).max().unwrap_or(0)
+    }
+
+    fn size(&self, i: usize) -> usize {
+        1 + self.children(i).map(|c| self.size(c)).sum::<usize>()
+    }
+
+    /// The position a residue is attached at, for ordering branches.
+    fn position(&self, i: usize) -> u32 {
+        self.residues[i]
+            .linked_atom
+            .as_ref()
+            .and_then(|a| a.trim_start_matches(char::is_alphabetic).parse().ok())
+            .unwrap_or(0)
+    }
+
+    /// The glycan in the IUPAC condensed notation, from the non-reducing ends to the root and
+    /// the glycosylation site.
+    pub fn iupac_condensed(&self) -> String {
+        if self.residues.is_empty() {
+            return String::new();
+        }
+        let mut s = String::new();
+        self.write_branch(0, &mut s);
+        if let Some(site) = &self.site {
+            let mut name = site.residue_name.to_lowercase();
+            if let Some(first) = name.get_mut(..1) {
+                first.make_ascii_uppercase();
+            }
+            s.push_str(&name);
+        }
+        s
+    }
+
+    fn write_branch(&self, i: usize, s: &mut String) {
+        let mut children: Vec<usize> = self.children(i).collect();
+        children.sort_by_key(|&c| (self.depth(c), self.size(c), self.position(c)));
+        if let Some(main) = children.pop() {
+            self.write_branch(main, s);
+            for &side in children.iter().rev() {
+                s.push('[');
+                self.write_branch(side, s);
+                s.push(']');
+            }
+        }
+        let residue = &self.residues[i];
+        s.push_str(&residue.monosaccharide.name);
+        if let Some(linkage) = residue.linkage() {
+            s.push('(');
+            s.push_str(&linkage);
+            s.push(')');
+        }
+    }
+}
+
+impl fmt::Display for Glycan {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.iupac_condensed())
+    }
+}
+
+fn atom_name(residue: &ResidueView, i: usize) -> String {
+    String::from_utf8_lossy(&residue.atoms[i - residue.offset].name.0)
+        .trim()
+        .to_owned()
+}
+
+/// The index in the model of the first atom of `residue` named `name`.
+fn find_atom(residue: &ResidueView, name: &str) -> Option<usize> {
+    let atom = residue.atom(name)?;
+    let k = residue.atoms.iter().position(|a| std::ptr::eq(a, atom))?;
+    Some(residue.offset + k)
+}
+
+impl Entry {
+    /// The glycans of the model at `model` (an index in `structure.models`), in the order of
+    /// their roots.
+    pub fn glycans(&self, model: usize) -> Vec<Glycan> {
+        let view = ModelView::new(
+            model,
+            &self.structure.models[model],
+            &self.model_info[model],
+        );
+        let residues: Vec<&ResidueView> = view.chains.iter().flat_map(|c| c.residues()).collect();
+        let sugars: Vec<(usize, Monosaccharide)> = residues
+            .iter()
+            .enumerate()
+            .filter_map(|(r, residue)| Some((r, monosaccharide(&residue.name())?)))
+            .collect();
+        let residue_of = |key: (char, ResidueSerial, char)| {
+            residues
+                .iter()
+                .position(|r| (r.chain(), r.sequence_number(), r.insertion_code()) == key)
+        };
+
+        let atoms = view.atoms;
+        let coords: Vec<Coord> = atoms.iter().map(|a| a.coord).collect();
+        let first = first_alt_locs(atoms);
+        let mut owner = vec![0; atoms.len()];
+        for (r, residue) in residues.iter().enumerate() {
+            for o in &mut owner[residue.offset..residue.offset + residue.atoms.len()] {
+                *o = r;
+            }
+        }
+        let acceptors = (0..atoms.len()).filter(|&i| {
+            first[i]
+                && !matches!(residues[owner[i]].residue(), Residue::Water)
+                && matches!(self.element(model, i), Some(e) if matches!(e.symbol(), "O" | "N"))
+        });
+        let search = NeighborSearch::with_indices(&coords, acceptors, GLYCOSIDIC_BOND_CUTOFF);
+
+        // * the residue and atom each monosaccharide is linked to, and whether by a LINK record
+        let linked: Vec<Option<(usize, usize, bool)>> = sugars
+            .iter()
+            .map(|(r, sugar)| {
+                let r = *r;
+                let residue = residues[r];
+                let carbon_name = format!("C{}", sugar.anomeric_carbon);
+                let carbon = residue.atom(&carbon_name)?;
+                let key = (r, carbon_name.as_str());
+                let from_link = self.links.iter().find_map(|link| {
+                    if link.a.symmetry != link.b.symmetry {
+                        return None;
+                    }
+                    [(&link.a, &link.b), (&link.b, &link.a)]
+                        .iter()
+                        .find_map(|(this, other)| {
+                            let this_residue =
+                                residue_of((this.chain, this.sequence_number, this.insertion_code));
+                            if (this_residue?, this.name.as_str()) != key {
+                                return None;
+                            }
+                            let o = residue_of((
+                                other.chain,
+                                other.sequence_number,
+                                other.insertion_code,
+                            ))?;
+                            Some((o, find_atom(residues[o], &other.name)?, true))
+                        })
+                });
+                from_link.or_else(|| {
+                    search
+                        .within(carbon, GLYCOSIDIC_BOND_CUTOFF)
+                        .into_iter()
+                        .filter(|&i| owner[i] != r)
+                        .map(|i| (i, distance(carbon, &coords[i])))
+                        .min_by(|a, b| a.1.total_cmp(&b.1))
+                        .map(|(i, _)| (owner[i], i, false))
+                })
+            })
+            .collect();
+
+        let sugar_of = |r: usize| sugars.iter().position(|&(s, _)| s == r);
+        let parent = |s: usize| linked[s].and_then(|(r, _, _)| sugar_of(r));
+        let roots = (0..sugars.len()).filter(|&s| {
+            // * a monosaccharide whose parents lead back to itself is not part of a tree
+            let mut p = parent(s);
+            let mut steps = 0;
+            while let Some(q) = p {
+                if q == s || steps > sugars.len() {
+                    return false;
+                }
+                p = parent(q);
+                steps += 1;
+            }
+            parent(s).is_none()
+        });
+
+        let mut glycans = Vec::new();
+        for root in roots {
+            let site = linked[root].map(|(r, i, _)| Partner {
+                chain: residues[r].chain(),
+                sequence_number: residues[r].sequence_number(),
+                insertion_code: residues[r].insertion_code(),
+                residue_name: residues[r].name(),
+                atoms: vec![atom_name(residues[r], i)],
+                indices: vec![i],
+            });
+            // * breadth first, so that parents come before their children
+            let mut order = vec![root];
+            let mut k = 0;
+            while k < order.len() {
+                let s = order[k];
+                order.extend((0..sugars.len()).filter(|&c| parent(c) == Some(s)));
+                k += 1;
+            }
+            let members = order
+                .iter()
+                .map(|&s| {
+                    let (r, ref monosaccharide) = sugars[s];
+                    let residue = residues[r];
+                    GlycanResidue {
+                        chain: residue.chain(),
+                        sequence_number: residue.sequence_number(),
+                        insertion_code: residue.insertion_code(),
+                        residue_name: residue.name(),
+                        monosaccharide: monosaccharide.clone(),
+                        parent: parent(s).and_then(|p| order.iter().position(|&o| o == p)),
+                        linked_atom: linked[s].map(|(r, i, _)| atom_name(residues[r], i)),
+                        from_link: matches!(linked[s], Some((_, _, true))),
+                    }
+                })
+                .collect();
+            glycans.push(Glycan {
+                site,
+                residues: members,
+            });
+        }
+        glycans
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn residue(name: &str, parent: Option<usize>, atom: &str) -> GlycanResidue {
+        GlycanResidue {
+            chain: 'A',
+            sequence_number: 1,
+            insertion_code: ' ',
+            residue_name: name.to_owned(),
+            monosaccharide: monosaccharide(name).unwrap(),
+            parent,
+            linked_atom: Some(atom.to_owned()),
+            from_link: true,
+        }
+    }
+
+    #[test]
+    fn test_iupac_condensed() {
+        let mut glycan = Glycan {
+            site: Some(Partner {
+                chain: 'A',
+                sequence_number: 17,
+                insertion_code: ' ',
+                residue_name: "ASN".to_owned(),
+                atoms: vec!["ND2".to_owned()],
+                indices: vec![0],
+            }),
+            residues: vec![
+                residue("NAG", None, "ND2"),
+                residue("NAG", Some(0), "O4"),
+                residue("FUC", Some(0), "O6"),
+                residue("BMA", Some(1), "O4"),
+                residue("MAN", Some(3), "O3"),
+                residue("MAN", Some(3), "O6"),
+            ],
+        };
+        assert_eq!(
+            glycan.to_string(),
+            "Man(a1-6)[Man(a1-3)]Man(b1-4)GlcNAc(b1-4)[Fuc(a1-6)]GlcNAc(b1-N)Asn"
+        );
+        glycan.site = None;
+        glycan.residues[0].linked_atom = None;
+        glycan.residues.truncate(2);
+        assert_eq!(glycan.to_string(), "GlcNAc(b1-4)GlcNAc");
+    }
+}
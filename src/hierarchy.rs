@@ -0,0 +1,189 @@
+// Copyright (c) 2020 Tianyi Shi
+//
+// This software is released under the MIT License.
+// https://opensource.org/licenses/MIT
+
+//! A hierarchical view (model → chain → residue → atom) of the coordinates of an [`Entry`].
+//!
+//! The atoms of a model are stored as a flat list. The view groups consecutive atoms into
+//! residues and the residues into chains, separating each chain into its polymer and non-polymer
+//! parts. The polymer part of a chain ends at its TER record; ligands, ions and waters that
+//! share the chain identifier follow it. If a chain has no TER record, its amino acid and
+//! nucleotide residues are taken as the polymer.
+//!
+//! [`Entry`]: ../entry/struct.Entry.html
+
+use crate::coordinate::Ter;
+use crate::entry::{Entry, ModelInfo};
+use crate::types::{Atom, Model, Residue, ResidueSerial};
+use crate::writer::residue_name;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone)]
+pub struct ModelView<'a> {
+    /// Index of the model in `structure.models`
+    pub index: usize,
+    /// Serial number of the model; see [`Entry::model_serial`](../entry/struct.Entry.html#method.model_serial)
+    pub serial: u32,
+    pub atoms: &'a [Atom],
+    /// Chains in the order in which their first atoms appear
+    pub chains: Vec<ChainView<'a>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ChainView<'a> {
+    pub id: char,
+    /// Residues up to the TER record of the chain
+    pub polymer: Vec<ResidueView<'a>>,
+    /// Ligands, ions and waters bearing the identifier of the chain
+    pub non_polymer: Vec<ResidueView<'a>>,
+    /// The TER record that terminates the polymer
+    pub ter: Option<&'a Ter>,
+}
+
+/// A run of consecutive atoms with the same chain identifier, sequence number and insertion code.
+#[derive(Debug, Clone)]
+pub struct ResidueView<'a> {
+    /// Index of the first atom of the residue in the model
+    pub offset: usize,
+    pub atoms: &'a [Atom],
+}
+
+impl<'a> ResidueView<'a> {
+    pub fn residue(&self) -> &'a Residue {
+        &self.atoms[0].residue
+    }
+    pub fn chain(&self) -> char {
+        self.atoms[0].chain
+    }
+    pub fn sequence_number(&self) -> ResidueSerial {
+        self.atoms[0].sequence_number
+    }
+    pub fn insertion_code(&self) -> char {
+        self.atoms[0].insertion_code
+    }
+    /// The residue name as written in columns 18 - 20.
+    pub fn name(&self) -> String {
+        residue_name(self.residue())
+    }
+    /// The first atom named `name`, leading and trailing spaces ignored. Later alternate
+    /// locations of the atom are not considered.
+    pub fn atom(&self, name: &str) -> Option<&'a Atom> {
+        self.atoms
+            .iter()
+            .find(|a| std::str::from_utf8(&a.name.0).map(str::trim) == Ok(name))
+    }
+}
+
+impl<'a> ChainView<'a> {
+    pub fn residues(&self) -> impl Iterator<Item = &ResidueView<'a>> {
+        self.polymer.iter().chain(self.non_polymer.iter())
+    }
+}
+
+impl<'a> ModelView<'a> {
+    pub fn new(index: usize, model: &'a Model, info: &'a ModelInfo) -> Self {
+        let atoms = &model.atoms[..];
+        // * the chain of a TER is that of the atom preceding it, which also copes with bare TERs
+        let ters: Vec<(char, usize, &Ter)> = info
+            .ters
+            .iter()
+            .filter(|(preceding, _)| *preceding > 0 && *preceding <= atoms.len())
+            .map(|(preceding, ter)| (atoms[preceding - 1].chain, *preceding, ter))
+            .collect();
+
+        let mut chains: Vec<ChainView> = Vec::new();
+        let mut start = 0;
+        while start < atoms.len() {
+            let first = &atoms[start];
+            let len = atoms[start..]
+                .iter()
+                .take_while(|a| {
+                    a.chain == first.chain
+                        && a.sequence_number == first.sequence_number
+                        && a.insertion_code == first.insertion_code
+                })
+                .count();
+            let residue = ResidueView {
+                offset: start,
+                atoms: &atoms[start..start + len],
+            };
+            let ter = ters
+                .iter()
+                .rev()
+                .find(|(chain, _, _)| *chain == first.chain);
+            let is_polymer = match ter {
+                Some((_, end, _)) => start < *end,
+                None => matches!(
+                    first.residue,
+                    Residue::AminoAcid(_) | Residue::Nucleotide(_)
+                ),
+            };
+            let chain = match chains.iter().position(|c| c.id == first.chain) {
+                Some(i) => &mut chains[i],
+                None => {
+                    chains.push(ChainView {
+                        id: first.chain,
+                        polymer: Vec::new(),
+                        non_polymer: Vec::new(),
+                        ter: ter.map(|(_, _, ter)| *ter),
+                    });
+                    chains.last_mut().unwrap()
+                }
+            };
+            if is_polymer {
+                chain.polymer.push(residue);
+            } else {
+                chain.non_polymer.push(residue);
+            }
+            start += len;
+        }
+        Self {
+            index,
+            serial: info.serial.unwrap_or(index as u32 + 1),
+            atoms,
+            chains,
+        }
+    }
+
+    pub fn chain(&self, id: char) -> Option<&ChainView<'a>> {
+        self.chains.iter().find(|c| c.id == id)
+    }
+}
+
+impl Entry {
+    /// The hierarchical view of each model.
+    pub fn hierarchy(&self) -> Vec<ModelView<'_>> {
+        self.structure
+            .models
+            .iter()
+            .zip(self.model_info.iter())
+            .enumerate()
+            .map(|(index, (model, info))| ModelView::new(index, model, info))
+            .collect()
+    }
+
+    /// The hierarchical view of the model with serial number `serial`.
+    pub fn model_view(&self, serial: u32) -> Option<ModelView<'_>> {
+        let index = self.model_index(serial)?;
+        let info = self.model_info.get(index)?;
+        Some(ModelView::new(index, &self.structure.models[index], info))
+    }
+}
+
+/// Whether each atom is the first alternate location of its residue, or has none.
+pub(crate) fn first_alt_locs(atoms: &[Atom]) -> Vec<bool> {
+    let mut first: HashMap<(char, ResidueSerial, char), char> = HashMap::new();
+    atoms
+        .iter()
+        .map(|a| {
+            if a.id1 == ' ' {
+                return true;
+            }
+            let alt_loc = first
+                .entry((a.chain, a.sequence_number, a.insertion_code))
+                .or_insert(a.id1);
+            *alt_loc == a.id1
+        })
+        .collect()
+}
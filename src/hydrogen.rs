@@ -0,0 +1,559 @@
+// Copyright (c) 2020 Tianyi Shi
+//
+// This software is released under the MIT License.
+// https://opensource.org/licenses/MIT
+
+//! Idealized hydrogen atoms for the standard amino acids.
+//!
+//! Each hydrogen is placed from the heavy atoms it is bonded to, at the distance of the nucleus
+//! (X-H of 1.09 Å for carbon, 1.01 Å for nitrogen, 0.96 Å for oxygen and 1.34 Å for sulfur) and
+//! with ideal angles: tetrahedral at sp3 atoms and trigonal at sp2 atoms. Methyl, amine and
+//! hydroxyl hydrogens, whose positions depend on a rotation about the bond, are staggered. The
+//! names follow the remediated (version 3) PDB convention, e.g. HB2 and HB3, HG21 - HG23.
+//!
+//! The N-terminus is taken as NH3+, the C-terminus as carboxylate, and Cys is only given HG if
+//! its SG is neither in a disulfide nor coordinating a metal ion. The protonation states of His,
+//! Asp, Glu and Lys are set by [`Protonation`]. Hydrogens already present are kept and not
+//! added again.
+//!
+//! [`Protonation`]: struct.Protonation.html
+
+use crate::element::ChemicalElement;
+use crate::entry::Entry;
+use crate::geometry::{cross, distance, norm, sub, Coord};
+use crate::hierarchy::{ModelView, ResidueView};
+use crate::torsion::peptide_bonded;
+use crate::types::{AminoAcid, Atom, AtomName, Element, Residue, StandardAminoAcid};
+use std::str::FromStr;
+
+const TETRAHEDRAL: f32 = 109.47;
+const TRIGONAL: f32 = 120.0;
+const STAGGERED: &[f32] = &[60.0, -60.0, 180.0];
+const PLANAR: &[f32] = &[0.0, 180.0];
+/// The maximum SG-SG distance, in Å, of a disulfide bond.
+const DISULFIDE_CUTOFF: f32 = 2.5;
+/// The maximum distance, in Å, between SG and a metal ion it coordinates.
+const METAL_CUTOFF: f32 = 3.0;
+
+/// Which nitrogen of the imidazole ring of His carries a hydrogen.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistidineState {
+    /// ND1 (HID)
+    Delta,
+    /// NE2 (HIE)
+    Epsilon,
+    /// Both, leaving the ring positively charged (HIP)
+    Protonated,
+}
+
+/// The protonation states of the titratable side chains. The default is the predominant state
+/// at neutral pH.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Protonation {
+    pub histidine: HistidineState,
+    /// Asp carries HD2 on OD2 (ASH)
+    pub protonated_asp: bool,
+    /// Glu carries HE2 on OE2 (GLH)
+    pub protonated_glu: bool,
+    /// Lys carries three hydrogens on NZ, rather than two (LYN)
+    pub protonated_lys: bool,
+}
+
+impl Default for Protonation {
+    fn default() -> Self {
+        Self {
+            histidine: HistidineState::Epsilon,
+            protonated_asp: false,
+            protonated_glu: false,
+            protonated_lys: true,
+        }
+    }
+}
+
+/// How the hydrogens bonded to an atom are placed.
+#[derive(Debug, Clone, Copy)]
+enum Placement {
+    /// One hydrogen pointing away from the two or three other atoms bonded to the parent
+    Opposite(&'static [&'static str]),
+    /// Two hydrogens on a tetrahedral parent bonded to two other atoms
+    Pair(&'static str, &'static str),
+    /// One hydrogen per dihedral `b`-`a`-parent-H, at the angle `a`-parent-H
+    Torsion {
+        a: &'static str,
+        b: &'static str,
+        angle: f32,
+        dihedrals: &'static [f32],
+    },
+}
+
+/// The hydrogens bonded to `parent`. The name `-C` stands for the C of the preceding residue.
+#[derive(Debug, Clone, Copy)]
+struct Rule {
+    parent: &'static str,
+    names: &'static [&'static str],
+    placement: Placement,
+}
+
+const fn opposite(
+    parent: &'static str,
+    name: &'static [&'static str],
+    bonded: &'static [&'static str],
+) -> Rule {
+    Rule {
+        parent,
+        names: name,
+        placement: Placement::Opposite(bonded),
+    }
+}
+
+const fn pair(
+    parent: &'static str,
+    names: &'static [&'static str],
+    a: &'static str,
+    b: &'static str,
+) -> Rule {
+    Rule {
+        parent,
+        names,
+        placement: Placement::Pair(a, b),
+    }
+}
+
+/// Staggered hydrogens of a methyl, amine or hydroxyl group, at dihedrals of 60, -60 and 180°
+/// from `b`.
+const fn staggered(
+    parent: &'static str,
+    names: &'static [&'static str],
+    a: &'static str,
+    b: &'static str,
+) -> Rule {
+    Rule {
+        parent,
+        names,
+        placement: Placement::Torsion {
+            a,
+            b,
+            angle: TETRAHEDRAL,
+            dihedrals: STAGGERED,
+        },
+    }
+}
+
+/// Hydrogens of a planar amine group, the first one cis to `b`.
+const fn planar(
+    parent: &'static str,
+    names: &'static [&'static str],
+    a: &'static str,
+    b: &'static str,
+) -> Rule {
+    Rule {
+        parent,
+        names,
+        placement: Placement::Torsion {
+            a,
+            b,
+            angle: TRIGONAL,
+            dihedrals: PLANAR,
+        },
+    }
+}
+
+/// The hydrogen of a carboxylic acid or phenol, in the plane of and cis to `b`.
+const fn acid(
+    parent: &'static str,
+    name: &'static [&'static str],
+    a: &'static str,
+    b: &'static str,
+) -> Rule {
+    Rule {
+        parent,
+        names: name,
+        placement: Placement::Torsion {
+            a,
+            b,
+            angle: TETRAHEDRAL,
+            dihedrals: &[0.0],
+        },
+    }
+}
+
+const HB: Rule = pair("CB", &["HB2", "HB3"], "CA", "CG");
+const HD1_AROMATIC: Rule = opposite("CD1", &["HD1"], &["CG", "CE1"]);
+const HD2_AROMATIC: Rule = opposite("CD2", &["HD2"], &["CG", "CE2"]);
+const HE1_AROMATIC: Rule = opposite("CE1", &["HE1"], &["CD1", "CZ"]);
+const HE2_AROMATIC: Rule = opposite("CE2", &["HE2"], &["CD2", "CZ"]);
+
+/// The side-chain hydrogens of a standard amino acid, HA included.
+fn side_chain(aa: &StandardAminoAcid, protonation: &Protonation) -> Vec<Rule> {
+    use StandardAminoAcid::*;
+    let mut rules = match aa {
+        Gly => return vec![pair("CA", &["HA2", "HA3"], "N", "C")],
+        Ala => vec![staggered("CB", &["HB1", "HB2", "HB3"], "CA", "N")],
+        Arg => vec![
+            HB,
+            pair("CG", &["HG2", "HG3"], "CB", "CD"),
+            pair("CD", &["HD2", "HD3"], "CG", "NE"),
+            opposite("NE", &["HE"], &["CD", "CZ"]),
+            planar("NH1", &["HH11", "HH12"], "CZ", "NE"),
+            planar("NH2", &["HH21", "HH22"], "CZ", "NE"),
+        ],
+        Asn => vec![HB, planar("ND2", &["HD21", "HD22"], "CG", "CB")],
+        Asp => {
+            let mut rules = vec![HB];
+            if protonation.protonated_asp {
+                rules.push(acid("OD2", &["HD2"], "CG", "OD1"));
+            }
+            rules
+        }
+        Cys => vec![
+            pair("CB", &["HB2", "HB3"], "CA", "SG"),
+            staggered("SG", &["HG"], "CB", "CA"),
+        ],
+        Gln => vec![
+            HB,
+            pair("CG", &["HG2", "HG3"], "CB", "CD"),
+            planar("NE2", &["HE21", "HE22"], "CD", "CG"),
+        ],
+        Glu => {
+            let mut rules = vec![HB, pair("CG", &["HG2", "HG3"], "CB", "CD")];
+            if protonation.protonated_glu {
+                rules.push(acid("OE2", &["HE2"], "CD", "OE1"));
+            }
+            rules
+        }
+        His => {
+            let mut rules = vec![
+                HB,
+                opposite("CD2", &["HD2"], &["CG", "NE2"]),
+                opposite("CE1", &["HE1"], &["ND1", "NE2"]),
+            ];
+            if protonation.histidine != HistidineState::Epsilon {
+                rules.push(opposite("ND1", &["HD1"], &["CG", "CE1"]));
+            }
+            if protonation.histidine != HistidineState::Delta {
+                rules.push(opposite("NE2", &["HE2"], &["CD2", "CE1"]));
+            }
+            rules
+        }
+        Ile => vec![
+            opposite("CB", &["HB"], &["CA", "CG1", "CG2"]),
+            pair("CG1", &["HG12", "HG13"], "CB", "CD1"),
+            staggered("CG2", &["HG21", "HG22", "HG23"], "CB", "CA"),
+            staggered("CD1", &["HD11", "HD12", "HD13"], "CG1", "CB"),
+        ],
+        Leu => vec![
+            HB,
+            opposite("CG", &["HG"], &["CB", "CD1", "CD2"]),
+            staggered("CD1", &["HD11", "HD12", "HD13"], "CG", "CB"),
+            staggered("CD2", &["HD21", "HD22", "HD23"], "CG", "CB"),
+        ],
+        Lys => vec![
+            HB,
+            pair("CG", &["HG2", "HG3"], "CB", "CD"),
+            pair("CD", &["HD2", "HD3"], "CG", "CE"),
+            pair("CE", &["HE2", "HE3"], "CD", "NZ"),
+            if protonation.protonated_lys {
+                staggered("NZ", &["HZ1", "HZ2", "HZ3"], "CE", "CD")
+            } else {
+                staggered("NZ", &["HZ1", "HZ2"], "CE", "CD")
+            },
+        ],
+        Met => vec![
+            HB,
+            pair("CG", &["HG2", "HG3"], "CB", "SD"),
+            staggered("CE", &["HE1", "HE2", "HE3"], "SD", "CG"),
+        ],
+        Mse => vec![
+            HB,
+            pair("CG", &["HG2", "HG3"], "CB", "SE"),
+            staggered("CE", &["HE1", "HE2", "HE3"], "SE", "CG"),
+        ],
+        Phe => vec![
+            HB,
+            HD1_AROMATIC,
+            HD2_AROMATIC,
+            HE1_AROMATIC,
+            HE2_AROMATIC,
+            opposite("CZ", &["HZ"], &["CE1", "CE2"]),
+        ],
+        Pro => vec![
+            HB,
+            pair("CG", &["HG2", "HG3"], "CB", "CD"),
+            pair("CD", &["HD2", "HD3"], "CG", "N"),
+        ],
+        Ser => vec![
+            pair("CB", &["HB2", "HB3"], "CA", "OG"),
+            staggered("OG", &["HG"], "CB", "CA"),
+        ],
+        Thr => vec![
+            opposite("CB", &["HB"], &["CA", "OG1", "CG2"]),
+            staggered("OG1", &["HG1"], "CB", "CA"),
+            staggered("CG2", &["HG21", "HG22", "HG23"], "CB", "CA"),
+        ],
+        Trp => vec![
+            HB,
+            opposite("CD1", &["HD1"], &["CG", "NE1"]),
+            opposite("NE1", &["HE1"], &["CD1", "CE2"]),
+            opposite("CE3", &["HE3"], &["CD2", "CZ3"]),
+            opposite("CZ2", &["HZ2"], &["CE2", "CH2"]),
+            opposite("CZ3", &["HZ3"], &["CE3", "CH2"]),
+            opposite("CH2", &["HH2"], &["CZ2", "CZ3"]),
+        ],
+        Tyr => vec![
+            HB,
+            HD1_AROMATIC,
+            HD2_AROMATIC,
+            HE1_AROMATIC,
+            HE2_AROMATIC,
+            acid("OH", &["HH"], "CZ", "CE1"),
+        ],
+        Val => vec![
+            opposite("CB", &["HB"], &["CA", "CG1", "CG2"]),
+            staggered("CG1", &["HG11", "HG12", "HG13"], "CB", "CA"),
+            staggered("CG2", &["HG21", "HG22", "HG23"], "CB", "CA"),
+        ],
+        Sec => vec![pair("CB", &["HB2", "HB3"], "CA", "SE")],
+        Pyl => vec![],
+    };
+    rules.push(opposite("CA", &["HA"], &["N", "C", "CB"]));
+    rules
+}
+
+/// The length, in Å, of the bond between a hydrogen and the atom named `parent`.
+fn bond_length(parent: &str) -> f32 {
+    if parent.starts_with("SE") {
+        return 1.47;
+    }
+    match parent.as_bytes()[0] {
+        b'N' => 1.01,
+        b'O' => 0.96,
+        b'S' => 1.34,
+        _ => 1.09,
+    }
+}
+
+fn unit(v: Coord) -> Coord {
+    let n = norm(v);
+    [v[0] / n, v[1] / n, v[2] / n]
+}
+
+/// `p + Σ kᵢvᵢ`
+fn combine(p: Coord, terms: &[(f32, Coord)]) -> Coord {
+    let mut r = p;
+    for (k, v) in terms {
+        for (r, v) in r.iter_mut().zip(v) {
+            *r += k * v;
+        }
+    }
+    r
+}
+
+/// The positions of the hydrogens bonded to `parent`, one per name; `None` if an atom is missing.
+fn place(rule: &Rule, atom: impl Fn(&str) -> Option<Coord>) -> Option<Vec<Coord>> {
+    let p = atom(rule.parent)?;
+    let length = bond_length(rule.parent);
+    let towards = |name: &str| atom(name).map(|a| unit(sub(a, p)));
+    let positions = match rule.placement {
+        Placement::Opposite(bonded) => {
+            let mut sum = [0.0; 3];
+            for &name in bonded {
+                sum = combine(sum, &[(1.0, towards(name)?)]);
+            }
+            vec![combine(p, &[(-length, unit(sum))])]
+        }
+        Placement::Pair(a, b) => {
+            let (a, b) = (towards(a)?, towards(b)?);
+            let bisector = unit(combine([0.0; 3], &[(-1.0, a), (-1.0, b)]));
+            let normal = unit(cross(a, b));
+            let (sin, cos) = (TETRAHEDRAL / 2.0).to_radians().sin_cos();
+            vec![
+                combine(p, &[(length * cos, bisector), (-length * sin, normal)]),
+                combine(p, &[(length * cos, bisector), (length * sin, normal)]),
+            ]
+        }
+        Placement::Torsion {
+            a,
+            b,
+            angle,
+            dihedrals,
+        } => {
+            let (a, b) = (atom(a)?, atom(b)?);
+            // * a local frame at the parent, with x along a → parent and b in the xy plane
+            let x = unit(sub(p, a));
+            let ab = sub(a, b);
+            let z = unit(cross(ab, x));
+            let y = cross(z, x);
+            let (sin, cos) = (180.0 - angle).to_radians().sin_cos();
+            dihedrals
+                .iter()
+                .map(|d| {
+                    let (sin_d, cos_d) = d.to_radians().sin_cos();
+                    combine(
+                        p,
+                        &[
+                            (length * cos, x),
+                            (length * sin * cos_d, y),
+                            (length * sin * sin_d, z),
+                        ],
+                    )
+                })
+                .collect()
+        }
+    };
+    Some(positions)
+}
+
+/// The hydrogens of the standard amino acid `residue`, which follows `previous` in its chain and
+/// is the first residue of the chain if `n_terminal`. Its SG, if any, is deprotonated if
+/// `thiolate`.
+fn residue_hydrogens(
+    residue: &ResidueView,
+    previous: Option<&ResidueView>,
+    n_terminal: bool,
+    thiolate: bool,
+    protonation: &Protonation,
+) -> Vec<Atom> {
+    let aa = match residue.residue() {
+        Residue::AminoAcid(AminoAcid::Standard(aa)) => aa,
+        _ => return Vec::new(),
+    };
+    let mut rules = side_chain(aa, protonation);
+    if thiolate {
+        rules.retain(|r| r.parent != "SG");
+    }
+    let previous = previous.filter(|p| peptide_bonded(p, residue));
+    match (aa, previous) {
+        (StandardAminoAcid::Pro, Some(_)) => {}
+        (_, Some(_)) => rules.push(opposite("N", &["H"], &["CA", "-C"])),
+        (StandardAminoAcid::Pro, None) if n_terminal => {
+            rules.push(pair("N", &["H2", "H3"], "CA", "CD"))
+        }
+        (_, None) if n_terminal => rules.push(staggered("N", &["H1", "H2", "H3"], "CA", "C")),
+        _ => {}
+    }
+    let atom = |name: &str| match name {
+        "-C" => previous.and_then(|p| p.atom("C")).map(|a| a.coord),
+        _ => residue.atom(name).map(|a| a.coord),
+    };
+    let mut hydrogens = Vec::new();
+    for rule in &rules {
+        let missing: Vec<&str> = rule
+            .names
+            .iter()
+            .copied()
+            .filter(|n| residue.atom(n).is_none())
+            .collect();
+        if missing.is_empty() {
+            continue;
+        }
+        let (parent, positions) = match (residue.atom(rule.parent), place(rule, atom)) {
+            (Some(parent), Some(positions)) => (parent, positions),
+            _ => continue,
+        };
+        for (name, coord) in rule.names.iter().zip(positions) {
+            if !missing.contains(name) {
+                continue;
+            }
+            hydrogens.push(Atom {
+                name: AtomName::from_str(name).unwrap(),
+                coord,
+                element: Element::H,
+                charge: 0,
+                ..parent.clone()
+            });
+        }
+    }
+    hydrogens
+}
+
+/// The hydrogens of each residue of a model, with the index in the model of the atom they
+/// follow.
+fn model_hydrogens(view: &ModelView, protonation: &Protonation) -> Vec<(usize, Vec<Atom>)> {
+    let sulfurs: Vec<&Atom> = view
+        .atoms
+        .iter()
+        .filter(|a| {
+            matches!(
+                a.residue,
+                Residue::AminoAcid(AminoAcid::Standard(StandardAminoAcid::Cys))
+            ) && std::str::from_utf8(&a.name.0).map(str::trim) == Ok("SG")
+        })
+        .collect();
+    let metals: Vec<&Atom> = view
+        .atoms
+        .iter()
+        .filter(|a| matches!(ChemicalElement::from_element(a.element), Some(e) if e.is_metal()))
+        .collect();
+    let mut additions = Vec::new();
+    for chain in &view.chains {
+        let residues = chain
+            .polymer
+            .iter()
+            .enumerate()
+            .map(|(i, r)| (r, i.checked_sub(1).map(|p| &chain.polymer[p]), i == 0))
+            .chain(chain.non_polymer.iter().map(|r| (r, None, true)));
+        for (residue, previous, n_terminal) in residues {
+            let thiolate = matches!(residue.atom("SG"), Some(sg) if sulfurs
+                .iter()
+                .any(|s| !std::ptr::eq(*s, sg) && distance(*s, sg) <= DISULFIDE_CUTOFF)
+                || metals.iter().any(|m| distance(*m, sg) <= METAL_CUTOFF));
+            let hydrogens = residue_hydrogens(residue, previous, n_terminal, thiolate, protonation);
+            if !hydrogens.is_empty() {
+                additions.push((residue.offset + residue.atoms.len(), hydrogens));
+            }
+        }
+    }
+    additions.sort_by_key(|(end, _)| *end);
+    additions
+}
+
+impl Entry {
+    /// Adds the missing hydrogens of the standard amino acids, each after the last atom of its
+    /// residue, and renumbers the atoms of the models from 1. Returns the number of hydrogens
+    /// added.
+    pub fn add_hydrogens(&mut self, protonation: &Protonation) -> usize {
+        let additions: Vec<Vec<(usize, Vec<Atom>)>> = self
+            .hierarchy()
+            .iter()
+            .map(|view| model_hydrogens(view, protonation))
+            .collect();
+        let mut count = 0;
+        for (m, additions) in additions.into_iter().enumerate() {
+            if additions.is_empty() {
+                continue;
+            }
+            self.sync_elements(m);
+            let model = &mut self.structure.models[m];
+            let info = &mut self.model_info[m];
+            // * the number of hydrogens added up to each position of the original atoms
+            let mut added_before = vec![0; model.atoms.len() + 1];
+            let mut atoms = Vec::new();
+            let mut elements = Vec::new();
+            let mut additions = additions.into_iter().peekable();
+            let mut added = 0;
+            for (i, atom) in std::mem::take(&mut model.atoms).into_iter().enumerate() {
+                atoms.push(atom);
+                elements.push(info.elements[i]);
+                while let Some((_, hydrogens)) = additions.next_if(|(end, _)| *end == i + 1) {
+                    added += hydrogens.len();
+                    elements.extend(hydrogens.iter().map(|_| Some(ChemicalElement::HYDROGEN)));
+                    atoms.extend(hydrogens);
+                }
+                added_before[i + 1] = added;
+            }
+            // * a TER after the last atom of a residue follows its hydrogens too
+            for (preceding, _) in &mut info.ters {
+                *preceding += added_before[(*preceding).min(atoms.len() - added)];
+            }
+            model.atoms = atoms;
+            info.elements = elements;
+            count += added;
+        }
+        if count > 0 {
+            self.renumber_atoms(1);
+        }
+        count
+    }
+}
@@ -0,0 +1,603 @@
+// Copyright (c) 2020 Tianyi Shi
+//
+// This software is released under the MIT License.
+// https://opensource.org/licenses/MIT
+
+//! Non-covalent interactions between the residues of a model, detected from the coordinates of
+//! the heavy atoms alone:
+//!
+//! - hydrogen bonds between the donors and acceptors of amino acids and waters, with the donor and
+//!   acceptor 2.5 - 3.5 Å apart and, where they are bonded to another heavy atom, the angles at
+//!   the donor and acceptor no smaller than 90°;
+//! - salt bridges, with an atom of a basic side chain (Arg, Lys, His) within 4 Å of an oxygen of an
+//!   acidic side chain (Asp, Glu) or a C-terminus, reported once per pair of residues;
+//! - π stacking of the aromatic rings of Phe, Tyr, Trp and His, with centroids within 5.5 Å and
+//!   planes either parallel or perpendicular to within 30°;
+//! - cation–π interactions between Lys NZ or Arg CZ and an aromatic ring, with the cation within
+//!   6 Å of the centroid and no more than 2 Å from the ring normal;
+//! - metal coordination, with a nitrogen, oxygen or sulfur of another residue within 3 Å of a
+//!   metal;
+//! - disulfide bonds, with the SG atoms of two cysteines within 2.5 Å, which can be checked
+//!   against the SSBOND records.
+//!
+//! Alternate locations other than the first of each residue are ignored. With the `serde`
+//! feature, the interactions can be serialized.
+
+use crate::entry::Entry;
+use crate::geometry::{angle, centroid, cross, distance, dot, norm, sub, Coord, NeighborSearch};
+use crate::hierarchy::{first_alt_locs, ModelView, ResidueView};
+use crate::types::{AminoAcid, Atom, Residue, ResidueSerial, StandardAminoAcid};
+use std::collections::{HashMap, HashSet};
+
+const HYDROGEN_BOND_MIN_DISTANCE: f32 = 2.5;
+const HYDROGEN_BOND_MAX_DISTANCE: f32 = 3.5;
+const SALT_BRIDGE_DISTANCE: f32 = 4.0;
+const STACKING_DISTANCE: f32 = 5.5;
+const STACKING_ANGLE_TOLERANCE: f32 = 30.0;
+const CATION_PI_DISTANCE: f32 = 6.0;
+const CATION_PI_OFFSET: f32 = 2.0;
+const METAL_COORDINATION_DISTANCE: f32 = 3.0;
+const DISULFIDE_DISTANCE: f32 = 2.5;
+
+/// The part of a residue a hydrogen bonding atom belongs to.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Moiety {
+    Backbone,
+    SideChain,
+    Water,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stacking {
+    /// Face to face, possibly offset
+    Parallel,
+    /// Edge to face
+    TShaped,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InteractionKind {
+    /// From the donor `a` to the acceptor `b`
+    HydrogenBond {
+        donor: Moiety,
+        acceptor: Moiety,
+    },
+    /// Between the basic `a` and the acidic `b`
+    SaltBridge,
+    PiStacking(Stacking),
+    /// Between the cation `a` and the ring `b`
+    CationPi,
+    /// Between the metal `a` and the ligand atom `b`
+    MetalCoordination,
+    Disulfide,
+}
+
+/// The atoms of a residue taking part in an interaction.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Partner {
+    pub chain: char,
+    pub sequence_number: ResidueSerial,
+    pub insertion_code: char,
+    pub residue_name: String,
+    /// The name of the interacting atom, or those of the atoms of an aromatic ring
+    pub atoms: Vec<String>,
+    /// The indices of these atoms in `Model::atoms`
+    pub indices: Vec<usize>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Interaction {
+    pub kind: InteractionKind,
+    pub a: Partner,
+    pub b: Partner,
+    /// The distance between the interacting atoms, or ring centroids, in Å
+    pub distance: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Role {
+    Donor,
+    Acceptor,
+    Both,
+}
+
+/// The hydrogen bond donors and acceptors of the side chain of each amino acid, with the heavy
+/// atoms they are bonded to.
+fn side_chain_polar_atoms(aa: &StandardAminoAcid) -> &'static [(&'static str, &'static str, Role)] {
+    use Role::*;
+    use StandardAminoAcid::*;
+    match aa {
+        Arg => &[
+            ("NE", "CD", Donor),
+            ("NH1", "CZ", Donor),
+            ("NH2", "CZ", Donor),
+        ],
+        Asn => &[("OD1", "CG", Acceptor), ("ND2", "CG", Donor)],
+        Asp => &[("OD1", "CG", Acceptor), ("OD2", "CG", Acceptor)],
+        Gln => &[("OE1", "CD", Acceptor), ("NE2", "CD", Donor)],
+        Glu => &[("OE1", "CD", Acceptor), ("OE2", "CD", Acceptor)],
+        His => &[("ND1", "CG", Both), ("NE2", "CD2", Both)],
+        Lys => &[("NZ", "CE", Donor)],
+        Ser => &[("OG", "CB", Both)],
+        Thr => &[("OG1", "CB", Both)],
+        Trp => &[("NE1", "CD1", Donor)],
+        Tyr => &[("OH", "CZ", Both)],
+        _ => &[],
+    }
+}
+
+/// The atoms of the aromatic rings of each amino acid.
+fn aromatic_rings(aa: &StandardAminoAcid) -> &'static [&'static [&'static str]] {
+    use StandardAminoAcid::*;
+    match aa {
+        Phe | Tyr => &[&["CG", "CD1", "CD2", "CE1", "CE2", "CZ"]],
+        His => &[&["CG", "ND1", "CD2", "CE1", "NE2"]],
+        Trp => &[
+            &["CG", "CD1", "NE1", "CE2", "CD2"],
+            &["CD2", "CE2", "CE3", "CZ2", "CZ3", "CH2"],
+        ],
+        _ => &[],
+    }
+}
+
+fn basic_atoms(aa: &StandardAminoAcid) -> &'static [&'static str] {
+    use StandardAminoAcid::*;
+    match aa {
+        Arg => &["NE", "NH1", "NH2"],
+        Lys => &["NZ"],
+        His => &["ND1", "NE2"],
+        _ => &[],
+    }
+}
+
+fn acidic_atoms(aa: &StandardAminoAcid) -> &'static [&'static str] {
+    use StandardAminoAcid::*;
+    match aa {
+        Asp => &["OD1", "OD2"],
+        Glu => &["OE1", "OE2"],
+        _ => &[],
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct PolarAtom {
+    index: usize,
+    antecedent: Option<usize>,
+    role: Role,
+    moiety: Moiety,
+}
+
+#[derive(Debug, Clone)]
+struct Ring {
+    atoms: Vec<usize>,
+    centroid: Coord,
+    normal: Coord,
+}
+
+impl Ring {
+    fn new(atoms: Vec<usize>, coords: &[Coord]) -> Self {
+        let points: Vec<Coord> = atoms.iter().map(|&i| coords[i]).collect();
+        let centroid = centroid(&points).unwrap();
+        let normal = cross(sub(points[1], points[0]), sub(points[2], points[0]));
+        let length = norm(normal);
+        Self {
+            atoms,
+            centroid,
+            normal: [normal[0] / length, normal[1] / length, normal[2] / length],
+        }
+    }
+}
+
+/// The atoms of a model grouped by their role in interactions.
+struct Sites<'a> {
+    atoms: &'a [Atom],
+    residues: Vec<ResidueView<'a>>,
+    coords: Vec<Coord>,
+    /// The index in `residues` of the residue of each atom
+    residue_of: Vec<usize>,
+    polar: Vec<PolarAtom>,
+    basic: Vec<usize>,
+    acidic: Vec<usize>,
+    cations: Vec<usize>,
+    rings: Vec<Ring>,
+    metals: Vec<usize>,
+    /// Nitrogen, oxygen and sulfur atoms that can coordinate a metal
+    ligands: Vec<usize>,
+    sulfurs: Vec<usize>,
+}
+
+impl<'a> Sites<'a> {
+    fn residue(&self, atom: usize) -> &ResidueView<'a> {
+        &self.residues[self.residue_of[atom]]
+    }
+
+    fn partner(&self, atoms: &[usize]) -> Partner {
+        let residue = self.residue(atoms[0]);
+        Partner {
+            chain: residue.chain(),
+            sequence_number: residue.sequence_number(),
+            insertion_code: residue.insertion_code(),
+            residue_name: residue.name(),
+            atoms: atoms
+                .iter()
+                .map(|&i| {
+                    String::from_utf8_lossy(&self.atoms[i].name.0)
+                        .trim()
+                        .to_owned()
+                })
+                .collect(),
+            indices: atoms.to_vec(),
+        }
+    }
+
+    fn interaction(&self, kind: InteractionKind, a: &[usize], b: &[usize], d: f32) -> Interaction {
+        Interaction {
+            kind,
+            a: self.partner(a),
+            b: self.partner(b),
+            distance: d,
+        }
+    }
+}
+
+impl Entry {
+    fn interaction_sites(&self, model: usize) -> Sites<'_> {
+        let view = ModelView::new(
+            model,
+            &self.structure.models[model],
+            &self.model_info[model],
+        );
+        let atoms = view.atoms;
+        let first = first_alt_locs(atoms);
+        let coords: Vec<Coord> = atoms.iter().map(|a| a.coord).collect();
+        let residues: Vec<ResidueView> = view
+            .chains
+            .iter()
+            .flat_map(|c| c.residues())
+            .cloned()
+            .collect();
+        let mut sites = Sites {
+            atoms,
+            residues: residues.clone(),
+            coords,
+            residue_of: vec![0; atoms.len()],
+            polar: Vec::new(),
+            basic: Vec::new(),
+            acidic: Vec::new(),
+            cations: Vec::new(),
+            rings: Vec::new(),
+            metals: Vec::new(),
+            ligands: Vec::new(),
+            sulfurs: Vec::new(),
+        };
+        for (r, residue) in residues.iter().enumerate() {
+            let index = |name: &str| {
+                residue
+                    .atoms
+                    .iter()
+                    .enumerate()
+                    .find(|&(k, a)| {
+                        first[residue.offset + k]
+                            && std::str::from_utf8(&a.name.0).map(str::trim) == Ok(name)
+                    })
+                    .map(|(k, _)| residue.offset + k)
+            };
+            for (k, &first) in first
+                .iter()
+                .enumerate()
+                .skip(residue.offset)
+                .take(residue.atoms.len())
+            {
+                sites.residue_of[k] = r;
+                if !first {
+                    continue;
+                }
+                match self.element(model, k) {
+                    Some(e) if e.is_metal() => sites.metals.push(k),
+                    Some(e) if matches!(e.symbol(), "N" | "O" | "S") => sites.ligands.push(k),
+                    _ => {}
+                }
+            }
+            let aa = match residue.residue() {
+                Residue::AminoAcid(AminoAcid::Standard(aa)) => aa,
+                Residue::Water => {
+                    if let Some(o) = index("O") {
+                        sites.polar.push(PolarAtom {
+                            index: o,
+                            antecedent: None,
+                            role: Role::Both,
+                            moiety: Moiety::Water,
+                        });
+                    }
+                    continue;
+                }
+                _ => continue,
+            };
+            let mut polar = |atom: &str, antecedent: &str, role: Role, moiety: Moiety| {
+                if let Some(i) = index(atom) {
+                    sites.polar.push(PolarAtom {
+                        index: i,
+                        antecedent: index(antecedent),
+                        role,
+                        moiety,
+                    });
+                }
+            };
+            if !matches!(aa, StandardAminoAcid::Pro) {
+                polar("N", "CA", Role::Donor, Moiety::Backbone);
+            }
+            polar("O", "C", Role::Acceptor, Moiety::Backbone);
+            polar("OXT", "C", Role::Acceptor, Moiety::Backbone);
+            for &(atom, antecedent, role) in side_chain_polar_atoms(aa) {
+                polar(atom, antecedent, role, Moiety::SideChain);
+            }
+            sites
+                .basic
+                .extend(basic_atoms(aa).iter().filter_map(|&a| index(a)));
+            sites
+                .acidic
+                .extend(acidic_atoms(aa).iter().filter_map(|&a| index(a)));
+            sites.acidic.extend(index("OXT"));
+            match aa {
+                StandardAminoAcid::Lys => sites.cations.extend(index("NZ")),
+                StandardAminoAcid::Arg => sites.cations.extend(index("CZ")),
+                StandardAminoAcid::Cys => sites.sulfurs.extend(index("SG")),
+                _ => {}
+            }
+            for ring in aromatic_rings(aa) {
+                let ring: Option<Vec<usize>> = ring.iter().map(|&a| index(a)).collect();
+                if let Some(ring) = ring {
+                    sites.rings.push(Ring::new(ring, &sites.coords));
+                }
+            }
+        }
+        sites
+    }
+
+    /// The non-covalent interactions between the residues of the model at `model` (an index in
+    /// `structure.models`): hydrogen bonds, salt bridges, π stacking, cation–π interactions,
+    /// metal coordination and disulfide bonds, in that order.
+    pub fn interactions(&self, model: usize) -> Vec<Interaction> {
+        let sites = self.interaction_sites(model);
+        let coords = &sites.coords;
+        let same_residue = |i: usize, j: usize| sites.residue_of[i] == sites.residue_of[j];
+        let mut interactions = Vec::new();
+
+        // * hydrogen bonds
+        let polar: HashMap<usize, PolarAtom> = sites.polar.iter().map(|p| (p.index, *p)).collect();
+        let search = NeighborSearch::with_indices(
+            coords,
+            sites.polar.iter().map(|p| p.index),
+            HYDROGEN_BOND_MAX_DISTANCE,
+        );
+        let mut bonds = Vec::new();
+        for donor in sites.polar.iter().filter(|p| p.role != Role::Acceptor) {
+            let d = donor.index;
+            for a in search.within(&coords[d], HYDROGEN_BOND_MAX_DISTANCE) {
+                let acceptor = polar[&a];
+                // * a pair that could bond either way is reported once
+                if acceptor.role == Role::Donor
+                    || same_residue(d, a)
+                    || (donor.role == Role::Both && acceptor.role == Role::Both && a < d)
+                {
+                    continue;
+                }
+                let r = distance(&coords[d], &coords[a]);
+                let angle_ok = |x: usize, y: usize, antecedent: Option<usize>| !matches!(antecedent, Some(z) if angle(&coords[z], &coords[x], &coords[y]) < 90.0);
+                if r >= HYDROGEN_BOND_MIN_DISTANCE
+                    && angle_ok(d, a, donor.antecedent)
+                    && angle_ok(a, d, acceptor.antecedent)
+                {
+                    bonds.push((d, a, donor.moiety, acceptor.moiety, r));
+                }
+            }
+        }
+        bonds.sort_by_key(|b| (b.0, b.1));
+        for (d, a, donor, acceptor, r) in bonds {
+            interactions.push(sites.interaction(
+                InteractionKind::HydrogenBond { donor, acceptor },
+                &[d],
+                &[a],
+                r,
+            ));
+        }
+
+        // * salt bridges, the closest pair of atoms of each pair of residues
+        let mut bridges: HashMap<(usize, usize), (usize, usize, f32)> = HashMap::new();
+        for &b in &sites.basic {
+            for &a in &sites.acidic {
+                let r = distance(&coords[b], &coords[a]);
+                if r <= SALT_BRIDGE_DISTANCE && !same_residue(a, b) {
+                    let closest = bridges
+                        .entry((sites.residue_of[b], sites.residue_of[a]))
+                        .or_insert((b, a, r));
+                    if r < closest.2 {
+                        *closest = (b, a, r);
+                    }
+                }
+            }
+        }
+        let mut bridges: Vec<_> = bridges.into_iter().collect();
+        bridges.sort_by_key(|b| b.0);
+        for (_, (b, a, r)) in bridges {
+            interactions.push(sites.interaction(InteractionKind::SaltBridge, &[b], &[a], r));
+        }
+
+        // * π stacking
+        for (k, p) in sites.rings.iter().enumerate() {
+            for q in &sites.rings[k + 1..] {
+                let r = distance(&p.centroid, &q.centroid);
+                if r > STACKING_DISTANCE || same_residue(p.atoms[0], q.atoms[0]) {
+                    continue;
+                }
+                let tilt = dot(p.normal, q.normal).abs().min(1.0).acos().to_degrees();
+                let stacking = if tilt <= STACKING_ANGLE_TOLERANCE {
+                    Stacking::Parallel
+                } else if tilt >= 90.0 - STACKING_ANGLE_TOLERANCE {
+                    Stacking::TShaped
+                } else {
+                    continue;
+                };
+                interactions.push(sites.interaction(
+                    InteractionKind::PiStacking(stacking),
+                    &p.atoms,
+                    &q.atoms,
+                    r,
+                ));
+            }
+        }
+
+        // * cation–π
+        for &c in &sites.cations {
+            for ring in &sites.rings {
+                let v = sub(coords[c], ring.centroid);
+                let r = norm(v);
+                let height = dot(v, ring.normal);
+                let offset = (r * r - height * height).max(0.0).sqrt();
+                if r <= CATION_PI_DISTANCE
+                    && offset <= CATION_PI_OFFSET
+                    && !same_residue(c, ring.atoms[0])
+                {
+                    interactions.push(sites.interaction(
+                        InteractionKind::CationPi,
+                        &[c],
+                        &ring.atoms,
+                        r,
+                    ));
+                }
+            }
+        }
+
+        // * metal coordination
+        let search = NeighborSearch::with_indices(
+            coords,
+            sites.ligands.iter().copied(),
+            METAL_COORDINATION_DISTANCE,
+        );
+        for &m in &sites.metals {
+            let mut ligands = search.within(&coords[m], METAL_COORDINATION_DISTANCE);
+            ligands.sort_unstable();
+            for l in ligands.into_iter().filter(|&l| !same_residue(m, l)) {
+                let r = distance(&coords[m], &coords[l]);
+                interactions.push(sites.interaction(
+                    InteractionKind::MetalCoordination,
+                    &[m],
+                    &[l],
+                    r,
+                ));
+            }
+        }
+
+        // * disulfides
+        for (a, b, r) in disulfides(&sites) {
+            interactions.push(sites.interaction(InteractionKind::Disulfide, &[a], &[b], r));
+        }
+        interactions
+    }
+
+    /// Compares the SSBOND records with the disulfide bonds found in the coordinates of the model
+    /// at `model`.
+    pub fn check_disulfides(&self, model: usize) -> Vec<DisulfideIssue> {
+        let sites = self.interaction_sites(model);
+        let id = |atom: usize| {
+            let residue = sites.residue(atom);
+            (residue.chain(), residue.sequence_number())
+        };
+        let found: Vec<(SsbondResidue, SsbondResidue, f32)> = disulfides(&sites)
+            .into_iter()
+            .map(|(a, b, r)| (id(a), id(b), r))
+            .collect();
+        let declared: HashSet<_> = self
+            .structure
+            .ssbonds
+            .iter()
+            .flat_map(|s| vec![(s.a, s.b), (s.b, s.a)])
+            .collect();
+        let mut issues = Vec::new();
+        for ssbond in &self.structure.ssbonds {
+            let bonded = found
+                .iter()
+                .any(|&(a, b, _)| (a, b) == (ssbond.a, ssbond.b) || (a, b) == (ssbond.b, ssbond.a));
+            if !bonded {
+                let sulfur = |residue: SsbondResidue| {
+                    sites.sulfurs.iter().copied().find(|&s| id(s) == residue)
+                };
+                let distance = match (sulfur(ssbond.a), sulfur(ssbond.b)) {
+                    (Some(a), Some(b)) => Some(distance(&sites.coords[a], &sites.coords[b])),
+                    _ => None,
+                };
+                issues.push(DisulfideIssue::Missing {
+                    a: ssbond.a,
+                    b: ssbond.b,
+                    distance,
+                });
+            }
+        }
+        for (a, b, distance) in found {
+            if !declared.contains(&(a, b)) {
+                issues.push(DisulfideIssue::Undeclared { a, b, distance });
+            }
+        }
+        issues
+    }
+}
+
+/// Pairs of cysteine SG atoms close enough to be bonded, with their distance.
+fn disulfides(sites: &Sites) -> Vec<(usize, usize, f32)> {
+    let mut bonds = Vec::new();
+    for (k, &a) in sites.sulfurs.iter().enumerate() {
+        for &b in &sites.sulfurs[k + 1..] {
+            let r = distance(&sites.coords[a], &sites.coords[b]);
+            if r <= DISULFIDE_DISTANCE {
+                bonds.push((a, b, r));
+            }
+        }
+    }
+    bonds
+}
+
+/// A residue as identified on SSBOND records, by chain and sequence number.
+pub type SsbondResidue = (char, ResidueSerial);
+
+/// A disagreement between the SSBOND records and the coordinates.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub enum DisulfideIssue {
+    /// An SSBOND record joins two cysteines whose SG atoms are not within bonding distance.
+    /// `distance` is `None` if either SG atom has no coordinates.
+    Missing {
+        a: SsbondResidue,
+        b: SsbondResidue,
+        distance: Option<f32>,
+    },
+    /// Two SG atoms are within bonding distance but no SSBOND record joins them.
+    Undeclared {
+        a: SsbondResidue,
+        b: SsbondResidue,
+        distance: f32,
+    },
+}
+
+impl std::fmt::Display for DisulfideIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Missing { a, b, distance } => {
+                write!(f, "SSBOND {}{} - {}{}: ", a.0, a.1, b.0, b.1)?;
+                match distance {
+                    Some(d) => write!(f, "SG atoms are {:.2} Å apart", d),
+                    None => write!(f, "SG atom has no coordinates"),
+                }
+            }
+            Self::Undeclared { a, b, distance } => write!(
+                f,
+                "cysteines {}{} and {}{} are bonded ({:.2} Å) but have no SSBOND record",
+                a.0, a.1, b.0, b.1, distance
+            ),
+        }
+    }
+}
@@ -0,0 +1,678 @@
+// Copyright (c) 2020 Tianyi Shi
+//
+// This software is released under the MIT License.
+// https://opensource.org/licenses/MIT
+
+//! Reading and writing an [`Entry`] as JSON. Requires the `serde` feature.
+//!
+//! With the `serde` feature, the public types that hold data (parsed records, the results of
+//! analyses, options and errors) implement `Serialize` and `Deserialize`. The exceptions are the
+//! views of [`hierarchy`], which borrow the atoms of a model, the record parsers, which hold no
+//! data, and the built-in tables [`ElementData`], [`ResidueTemplate`] and [`ChiralCentre`],
+//! which only implement `Serialize`. The types of `protein-core`, such as `Structure`, only
+//! implement `Serialize`; the fields that hold them are deserialized through the private
+//! mirrors of this module.
+//!
+//! # Schema
+//!
+//! An entry is written as a document with two members:
+//!
+//! ```json
+//! { "schema_version": 1, "entry": { ... } }
+//! ```
+//!
+//! `entry` has a member for each field of [`Entry`], named after it: `structure`,
+//! `title_section`, `bookkeeping`, `model_info`, `helix_details`, `sheet_details` and `links`.
+//! Within it, values follow the serde defaults, which are:
+//!
+//! - structs are objects with a member for each field, in the order of declaration;
+//! - tuples, arrays and tuple structs are arrays, and newtype structs are their content, so
+//!   that an `AtomName` is an array of 4 bytes;
+//! - unit variants of enums are strings (`"Water"`), and other variants are objects with a
+//!   single member named after the variant (`{"AminoAcid": {"Standard": "Ala"}}`);
+//! - `Option`s are `null` or their content;
+//! - `char`s are strings of one character, blanks included;
+//! - maps, such as `structure.modified_aa`, are objects;
+//! - dates are strings in the ISO 8601 format, such as `"2020-01-31"`.
+//!
+//! Elements ([`ChemicalElement`]) are written as their symbols. A `Residue::Molecule`, which
+//! this crate never produces, is read back as `Residue::UnknownLigand`, as both are written as
+//! `UNL` in PDB files.
+//!
+//! # Versioning
+//!
+//! [`SCHEMA_VERSION`] is raised whenever a document written by this version could not be read by
+//! an earlier one, or the reverse: when a member is added, removed or renamed, or a value changes
+//! its representation. [`from_str`] rejects documents of any other version.
+//!
+//! | Version | Changes                |
+//! | ------- | ---------------------- |
+//! | 1       | First stable version.  |
+//!
+//! [`Entry`]: ../entry/struct.Entry.html
+//! [`hierarchy`]: ../hierarchy/index.html
+//! [`ElementData`]: ../element/struct.ElementData.html
+//! [`ChemicalElement`]: ../element/struct.ChemicalElement.html
+//! [`ResidueTemplate`]: ../template/struct.ResidueTemplate.html
+//! [`ChiralCentre`]: ../template/struct.ChiralCentre.html
+//! [`SCHEMA_VERSION`]: constant.SCHEMA_VERSION.html
+//! [`from_str`]: fn.from_str.html
+
+use crate::entry::Entry;
+use crate::types::{
+    AminoAcid, Anisou, Atom, AtomName, Authors, Chain, Connect, Cryst1, Element,
+    ExperimentalTechnique, GroupAxis, Header, Helix, HelixClass, LatticeType, Metadata, Model,
+    ModifiedAminoAcid, ModifiedNucleotide, Nucleotide, Registration, Residue, ResidueSerial, Sense,
+    Sheet, SpaceGroup, Ssbond, StandardAminoAcid, StandardNucleotide, Strand, Structure, Title,
+};
+use chrono::NaiveDate;
+use serde::de::{self, IgnoredAny, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::HashMap;
+
+/// The version of the JSON schema written by this crate.
+pub const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JsonError(pub String);
+
+impl std::fmt::Display for JsonError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "invalid JSON entry: {}", self.0)
+    }
+}
+
+impl std::error::Error for JsonError {}
+
+#[derive(Serialize)]
+struct Document<'a> {
+    schema_version: u32,
+    entry: &'a Entry,
+}
+
+/// A document being read, whose `schema_version` is checked as soon as it is met, so that a
+/// document of another version is reported as such rather than by the first member that this
+/// version cannot read.
+struct OwnedDocument(Entry);
+
+impl<'de> Deserialize<'de> for OwnedDocument {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        d.deserialize_map(DocumentVisitor)
+    }
+}
+
+struct DocumentVisitor;
+
+impl<'de> Visitor<'de> for DocumentVisitor {
+    type Value = OwnedDocument;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("a document with the members `schema_version` and `entry`")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<OwnedDocument, A::Error> {
+        let mut version = None;
+        let mut entry = None;
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "schema_version" => {
+                    let schema_version: u32 = map.next_value()?;
+                    if schema_version != SCHEMA_VERSION {
+                        return Err(de::Error::custom(format!(
+                            "schema version {} is not supported; expected {}",
+                            schema_version, SCHEMA_VERSION
+                        )));
+                    }
+                    version = Some(schema_version);
+                }
+                "entry" => entry = Some(map.next_value()?),
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        version.ok_or_else(|| de::Error::missing_field("schema_version"))?;
+        entry
+            .map(OwnedDocument)
+            .ok_or_else(|| de::Error::missing_field("entry"))
+    }
+}
+
+/// Writes `entry` as a JSON document of the current schema version.
+pub fn to_string(entry: &Entry) -> String {
+    serde_json::to_string(&document(entry)).expect("an entry is always serializable")
+}
+
+/// Like [`to_string`](fn.to_string.html), indented for reading.
+pub fn to_string_pretty(entry: &Entry) -> String {
+    serde_json::to_string_pretty(&document(entry)).expect("an entry is always serializable")
+}
+
+fn document(entry: &Entry) -> Document<'_> {
+    Document {
+        schema_version: SCHEMA_VERSION,
+        entry,
+    }
+}
+
+/// Reads an entry from a JSON document written by [`to_string`](fn.to_string.html).
+///
+/// Besides the layout, checks that the fields of the entry that run in parallel agree: that
+/// there is a `ModelInfo` for each model, an element for each atom (or none at all), a
+/// `HelixDetails` for each helix and `StrandDetails` for each strand, and that no TER record
+/// follows more atoms than its model has.
+pub fn from_str(json: &str) -> Result<Entry, JsonError> {
+    let OwnedDocument(entry) =
+        serde_json::from_str(json).map_err(|e: serde_json::Error| JsonError(e.to_string()))?;
+    check(&entry)?;
+    Ok(entry)
+}
+
+fn check(entry: &Entry) -> Result<(), JsonError> {
+    let models = &entry.structure.models;
+    if entry.model_info.len() != models.len() {
+        return Err(JsonError(format!(
+            "{} models but {} `model_info`",
+            models.len(),
+            entry.model_info.len()
+        )));
+    }
+    for (m, (model, info)) in models.iter().zip(&entry.model_info).enumerate() {
+        let atoms = model.atoms.len();
+        if !info.elements.is_empty() && info.elements.len() != atoms {
+            return Err(JsonError(format!(
+                "model {} has {} atoms but {} elements",
+                m,
+                atoms,
+                info.elements.len()
+            )));
+        }
+        if let Some((preceding, _)) = info.ters.iter().find(|(preceding, _)| *preceding > atoms) {
+            return Err(JsonError(format!(
+                "model {} has {} atoms but a TER record follows {}",
+                m, atoms, preceding
+            )));
+        }
+    }
+    let helices = entry.structure.helices.len();
+    if entry.helix_details.len() != helices {
+        return Err(JsonError(format!(
+            "{} helices but {} `helix_details`",
+            helices,
+            entry.helix_details.len()
+        )));
+    }
+    let sheets = &entry.structure.sheets;
+    if entry.sheet_details.len() != sheets.len() {
+        return Err(JsonError(format!(
+            "{} sheets but {} `sheet_details`",
+            sheets.len(),
+            entry.sheet_details.len()
+        )));
+    }
+    for (s, (sheet, details)) in sheets.iter().zip(&entry.sheet_details).enumerate() {
+        if details.len() != sheet.strands.len() {
+            return Err(JsonError(format!(
+                "sheet {} has {} strands but {} `sheet_details`",
+                s,
+                sheet.strands.len(),
+                details.len()
+            )));
+        }
+    }
+    Ok(())
+}
+
+// * Deserializers for the fields that hold types of `protein-core`, which only implement
+// * `Serialize`. Each mirror has the layout that the derived `Serialize` of its type produces.
+
+pub(crate) fn structure<'de, D: Deserializer<'de>>(d: D) -> Result<Structure, D::Error> {
+    StructureDef::deserialize(d).map(Into::into)
+}
+
+pub(crate) fn anisou<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<Anisou>, D::Error> {
+    Vec::<AnisouDef>::deserialize(d).map(convert)
+}
+
+pub(crate) fn helices<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<Helix>, D::Error> {
+    Vec::<HelixDef>::deserialize(d).map(convert)
+}
+
+pub(crate) fn sheets<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<Sheet>, D::Error> {
+    Vec::<SheetDef>::deserialize(d).map(convert)
+}
+
+pub(crate) fn amino_acids<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<AminoAcid>, D::Error> {
+    Vec::<AminoAcidDef>::deserialize(d).map(convert)
+}
+
+pub(crate) fn nucleotides<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<Nucleotide>, D::Error> {
+    Vec::<NucleotideDef>::deserialize(d).map(convert)
+}
+
+fn convert<A: Into<B>, B>(v: Vec<A>) -> Vec<B> {
+    v.into_iter().map(Into::into).collect()
+}
+
+/// A mirror of an enum of `protein-core` whose variants are all units.
+macro_rules! unit_enum {
+    ($mirror:ident => $remote:ident { $($variant:ident),* $(,)? }) => {
+        #[derive(Deserialize)]
+        enum $mirror {
+            $($variant),*
+        }
+
+        impl From<$mirror> for $remote {
+            fn from(x: $mirror) -> Self {
+                match x {
+                    $($mirror::$variant => $remote::$variant),*
+                }
+            }
+        }
+    };
+}
+
+unit_enum!(StandardAminoAcidDef => StandardAminoAcid {
+    Ala, Arg, Asn, Asp, Cys, Gln, Glu, Gly, His, Ile, Leu, Lys, Met, Phe, Pro, Ser, Thr, Trp,
+    Tyr, Val, Mse, Pyl, Sec,
+});
+unit_enum!(StandardNucleotideDef => StandardNucleotide { A, C, G, U, DA, DC, DG, DT });
+unit_enum!(ElementDef => Element {
+    H, C, O, N, P, S, Na, Mg, Cl, K, Ca, Fe, Mn, Co, Cr, I, Zn, Cu, F, Al, Se, V, Unknown,
+});
+unit_enum!(HelixClassDef => HelixClass {
+    RightHandedAlpha, RightHandedOmega, RightHandedPi, RightHandedGamma, RightHanded310,
+    LeftHandedAlpha, LeftHandedOmega, LeftHandedGamma, TwoSevenRibbonHelix, Polyproline, Unknown,
+});
+unit_enum!(SenseDef => Sense { Parallel, Antiparallel, Unknown });
+unit_enum!(LatticeTypeDef => LatticeType {
+    Primitive, SideCentered, BodyCentered, FaceCentered, Unknown,
+});
+unit_enum!(ExperimentalTechniqueDef => ExperimentalTechnique {
+    XRayDiffraction, ElectronMicroscopy, SolidStateNmr, SolutionNmr, NeutronDiffraction,
+    ElectronCrystallography, SolutionScattering, FiberDiffraction,
+});
+
+#[derive(Deserialize)]
+enum AminoAcidDef {
+    Standard(StandardAminoAcidDef),
+    Modified(String),
+    Unknown,
+}
+
+impl From<AminoAcidDef> for AminoAcid {
+    fn from(x: AminoAcidDef) -> Self {
+        match x {
+            AminoAcidDef::Standard(s) => AminoAcid::Standard(s.into()),
+            AminoAcidDef::Modified(s) => AminoAcid::Modified(s),
+            AminoAcidDef::Unknown => AminoAcid::Unknown,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+enum NucleotideDef {
+    Standard(StandardNucleotideDef),
+    Modified(String),
+    Unknown,
+}
+
+impl From<NucleotideDef> for Nucleotide {
+    fn from(x: NucleotideDef) -> Self {
+        match x {
+            NucleotideDef::Standard(s) => Nucleotide::Standard(s.into()),
+            NucleotideDef::Modified(s) => Nucleotide::Modified(s),
+            NucleotideDef::Unknown => Nucleotide::Unknown,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+enum ResidueDef {
+    AminoAcid(AminoAcidDef),
+    Nucleotide(NucleotideDef),
+    Water,
+    Metal(ElementDef),
+    Molecule(IgnoredAny),
+    Other(String),
+    UnknownAtomOrIon,
+    UnknownLigand,
+}
+
+impl From<ResidueDef> for Residue {
+    fn from(x: ResidueDef) -> Self {
+        match x {
+            ResidueDef::AminoAcid(a) => Residue::AminoAcid(a.into()),
+            ResidueDef::Nucleotide(n) => Residue::Nucleotide(n.into()),
+            ResidueDef::Water => Residue::Water,
+            ResidueDef::Metal(e) => Residue::Metal(e.into()),
+            // * `Molecule` has private fields and cannot be built outside `protein-core`
+            ResidueDef::Molecule(_) => Residue::UnknownLigand,
+            ResidueDef::Other(s) => Residue::Other(s),
+            ResidueDef::UnknownAtomOrIon => Residue::UnknownAtomOrIon,
+            ResidueDef::UnknownLigand => Residue::UnknownLigand,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct AtomDef {
+    id: u32,
+    name: [u8; 4],
+    id1: char,
+    residue: ResidueDef,
+    chain: char,
+    sequence_number: u32,
+    insertion_code: char,
+    coord: [f32; 3],
+    occupancy: f32,
+    temperature_factor: f32,
+    element: ElementDef,
+    charge: i8,
+}
+
+impl From<AtomDef> for Atom {
+    fn from(x: AtomDef) -> Self {
+        Atom {
+            id: x.id,
+            name: AtomName(x.name),
+            id1: x.id1,
+            residue: x.residue.into(),
+            chain: x.chain,
+            sequence_number: x.sequence_number,
+            insertion_code: x.insertion_code,
+            coord: x.coord,
+            occupancy: x.occupancy,
+            temperature_factor: x.temperature_factor,
+            element: x.element.into(),
+            charge: x.charge,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct AnisouDef {
+    id: u32,
+    u11: i32,
+    u22: i32,
+    u33: i32,
+    u12: i32,
+    u13: i32,
+    u23: i32,
+}
+
+impl From<AnisouDef> for Anisou {
+    fn from(x: AnisouDef) -> Self {
+        Anisou {
+            id: x.id,
+            u11: x.u11,
+            u22: x.u22,
+            u33: x.u33,
+            u12: x.u12,
+            u13: x.u13,
+            u23: x.u23,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ModelDef {
+    atoms: Vec<AtomDef>,
+    anisou: Vec<AnisouDef>,
+}
+
+impl From<ModelDef> for Model {
+    fn from(x: ModelDef) -> Self {
+        Model {
+            atoms: convert(x.atoms),
+            anisou: convert(x.anisou),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ChainDef<T> {
+    id: char,
+    seq: Vec<T>,
+}
+
+impl From<ChainDef<AminoAcidDef>> for Chain<AminoAcid> {
+    fn from(x: ChainDef<AminoAcidDef>) -> Self {
+        Chain {
+            id: x.id,
+            seq: convert(x.seq),
+        }
+    }
+}
+
+impl From<ChainDef<NucleotideDef>> for Chain<Nucleotide> {
+    fn from(x: ChainDef<NucleotideDef>) -> Self {
+        Chain {
+            id: x.id,
+            seq: convert(x.seq),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct HelixDef {
+    id: String,
+    class: HelixClassDef,
+    start: (char, ResidueSerial),
+    end: (char, ResidueSerial),
+    comment: String,
+}
+
+impl From<HelixDef> for Helix {
+    fn from(x: HelixDef) -> Self {
+        Helix {
+            id: x.id,
+            class: x.class.into(),
+            start: x.start,
+            end: x.end,
+            comment: x.comment,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct StrandDef {
+    start: (char, ResidueSerial),
+    end: (char, ResidueSerial),
+    sense: SenseDef,
+}
+
+impl From<StrandDef> for Strand {
+    fn from(x: StrandDef) -> Self {
+        Strand {
+            start: x.start,
+            end: x.end,
+            sense: x.sense.into(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct RegistrationDef {
+    curr: ([u8; 4], char, ResidueSerial),
+    prev: ([u8; 4], char, ResidueSerial),
+}
+
+impl From<RegistrationDef> for Registration {
+    fn from(x: RegistrationDef) -> Self {
+        Registration {
+            curr: (AtomName(x.curr.0), x.curr.1, x.curr.2),
+            prev: (AtomName(x.prev.0), x.prev.1, x.prev.2),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct SheetDef {
+    id: String,
+    strands: Vec<StrandDef>,
+    registration: Vec<RegistrationDef>,
+}
+
+impl From<SheetDef> for Sheet {
+    fn from(x: SheetDef) -> Self {
+        Sheet {
+            id: x.id,
+            strands: convert(x.strands),
+            registration: convert(x.registration),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct SsbondDef {
+    a: (char, ResidueSerial),
+    b: (char, ResidueSerial),
+}
+
+impl From<SsbondDef> for Ssbond {
+    fn from(x: SsbondDef) -> Self {
+        Ssbond { a: x.a, b: x.b }
+    }
+}
+
+#[derive(Deserialize)]
+struct ModifiedAminoAcidDef {
+    standard: StandardAminoAcidDef,
+    description: String,
+}
+
+impl From<ModifiedAminoAcidDef> for ModifiedAminoAcid {
+    fn from(x: ModifiedAminoAcidDef) -> Self {
+        ModifiedAminoAcid {
+            standard: x.standard.into(),
+            description: x.description,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ModifiedNucleotideDef {
+    standard: StandardNucleotideDef,
+    description: String,
+}
+
+impl From<ModifiedNucleotideDef> for ModifiedNucleotide {
+    fn from(x: ModifiedNucleotideDef) -> Self {
+        ModifiedNucleotide {
+            standard: x.standard.into(),
+            description: x.description,
+        }
+    }
+}
+
+/// A `GroupAxis`, written as its two numbers.
+type AxisDef = (u32, u32);
+
+#[derive(Deserialize)]
+struct Cryst1Def {
+    a: f32,
+    b: f32,
+    c: f32,
+    alpha: f32,
+    beta: f32,
+    gamma: f32,
+    lattice_type: LatticeTypeDef,
+    space_group: (AxisDef, Option<AxisDef>, Option<AxisDef>),
+    z: u8,
+}
+
+impl From<Cryst1Def> for Cryst1 {
+    fn from(x: Cryst1Def) -> Self {
+        let axis = |(a, b): AxisDef| GroupAxis(a, b);
+        let (first, second, third) = x.space_group;
+        Cryst1 {
+            a: x.a,
+            b: x.b,
+            c: x.c,
+            alpha: x.alpha,
+            beta: x.beta,
+            gamma: x.gamma,
+            lattice_type: x.lattice_type.into(),
+            space_group: SpaceGroup(axis(first), second.map(axis), third.map(axis)),
+            z: x.z,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct HeaderDef {
+    classification: String,
+    deposition_date: NaiveDate,
+    id_code: String,
+}
+
+impl From<HeaderDef> for Header {
+    fn from(x: HeaderDef) -> Self {
+        Header {
+            classification: x.classification,
+            deposition_date: x.deposition_date,
+            id_code: x.id_code,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct MetadataDef {
+    header: Option<HeaderDef>,
+    title: Option<Title>,
+    authors: Option<Authors>,
+    experimental_techniques: Option<Vec<ExperimentalTechniqueDef>>,
+    cryst1: Option<Cryst1Def>,
+}
+
+impl From<MetadataDef> for Metadata {
+    fn from(x: MetadataDef) -> Self {
+        Metadata {
+            header: x.header.map(Into::into),
+            title: x.title,
+            authors: x.authors,
+            experimental_techniques: x.experimental_techniques.map(convert),
+            cryst1: x.cryst1.map(Into::into),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct StructureDef {
+    chains_aa: Vec<ChainDef<AminoAcidDef>>,
+    chains_nuc: Vec<ChainDef<NucleotideDef>>,
+    helices: Vec<HelixDef>,
+    sheets: Vec<SheetDef>,
+    ssbonds: Vec<SsbondDef>,
+    modified_aa: HashMap<String, ModifiedAminoAcidDef>,
+    modified_nuc: HashMap<String, ModifiedNucleotideDef>,
+    connect: Vec<Connect>,
+    models: Vec<ModelDef>,
+    metadata: Option<MetadataDef>,
+}
+
+impl From<StructureDef> for Structure {
+    fn from(x: StructureDef) -> Self {
+        Structure {
+            chains_aa: convert(x.chains_aa),
+            chains_nuc: convert(x.chains_nuc),
+            helices: convert(x.helices),
+            sheets: convert(x.sheets),
+            ssbonds: convert(x.ssbonds),
+            modified_aa: x
+                .modified_aa
+                .into_iter()
+                .map(|(k, v)| (k, v.into()))
+                .collect(),
+            modified_nuc: x
+                .modified_nuc
+                .into_iter()
+                .map(|(k, v)| (k, v.into()))
+                .collect(),
+            connect: x.connect,
+            models: convert(x.models),
+            metadata: x.metadata.map(Into::into),
+        }
+    }
+}
@@ -9,18 +9,46 @@
 //!
 //! See [github repository](https://github.com/TianyiShi2001/nom-pdb) for examples.
 
+pub mod alignment;
+pub mod bookkeeping;
+pub mod charge;
 pub mod common;
 pub mod complete;
 pub mod coordinate;
 pub mod crystallography;
+pub mod diff;
+pub mod displacement;
+pub mod dssp;
+pub mod edit;
+pub mod element;
+pub mod entity;
+pub mod entry;
+pub mod geometry;
+pub mod glycan;
 pub mod het;
+pub mod hierarchy;
+pub mod hydrogen;
+pub mod interaction;
+#[cfg(feature = "serde")]
+pub mod json;
+pub mod mmcif;
+pub mod nucleic_acid;
 pub mod primary_structure;
 pub mod remark;
+pub mod sasa;
 pub mod secondary_structure;
+pub mod selection;
+pub mod sequence;
+pub mod template;
 pub mod title_section;
+pub mod torsion;
 pub(crate) mod types;
+pub mod validation;
+pub mod writer;
 
-pub use complete::Parser;
+pub use complete::{ParseOptions, Parser};
+pub use entry::Entry;
+pub use writer::Writer;
 
 // /// http://www.wwpdb.org/documentation/file-format-content/format33/sect1.html
 // #[derive(Eq, PartialEq, Debug, Ord, PartialOrd)]
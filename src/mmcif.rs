@@ -0,0 +1,666 @@
+// Copyright (c) 2020 Tianyi Shi
+//
+// This software is released under the MIT License.
+// https://opensource.org/licenses/MIT
+
+//! Reading and writing the coordinates of an [`Entry`] in the PDBx/mmCIF format.
+//!
+//! Besides the coordinates (`_atom_site`), the categories that hold the metadata of the HEADER,
+//! TITLE, KEYWDS, EXPDTA, AUTHOR and CRYST1 records are supported: `_entry`,
+//! `_pdbx_database_status` (the deposition date), `_struct`, `_struct_keywords`, `_exptl`,
+//! `_audit_author`, `_cell` and `_symmetry`. Other records are lost. The writer takes the `auth_*`
+//! items from the PDB fields and, as the PDB format has no equivalent of `label_asym_id`, uses the
+//! chain identifier for it as well. The reader prefers the `auth_*` items where present.
+//!
+//! A missing deposition date is read as the default date of [`Header`], which is written as
+//! neither `recvd_initial_deposition_date` nor a date in the PDB HEADER record; a missing space
+//! group is read as `P 1`.
+//!
+//! [`Entry`]: ../entry/struct.Entry.html
+//! [`Header`]: ../../protein_core/metadata/struct.Header.html
+
+use crate::common::parser::{parse_residue, split_list};
+use crate::complete::ParseOptions;
+use crate::coordinate::Ter;
+use crate::crystallography::cryst1::parse_space_group;
+use crate::element::ChemicalElement;
+use crate::entry::{Entry, ModelInfo};
+use crate::hierarchy::{ModelView, ResidueView};
+use crate::types::{
+    Atom, AtomName, Cryst1, Element, ExperimentalTechnique, GroupAxis, Header, LatticeType,
+    Metadata, Model, Residue, SpaceGroup,
+};
+use crate::writer::{is_standard, residue_name, space_group, technique};
+use chrono::NaiveDate;
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MmcifError(pub String);
+
+impl std::fmt::Display for MmcifError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "invalid mmCIF: {}", self.0)
+    }
+}
+
+impl std::error::Error for MmcifError {}
+
+const ATOM_SITE: &[&str] = &[
+    "group_PDB",
+    "id",
+    "type_symbol",
+    "label_atom_id",
+    "label_alt_id",
+    "label_comp_id",
+    "label_asym_id",
+    "label_entity_id",
+    "label_seq_id",
+    "pdbx_PDB_ins_code",
+    "Cartn_x",
+    "Cartn_y",
+    "Cartn_z",
+    "occupancy",
+    "B_iso_or_equiv",
+    "pdbx_formal_charge",
+    "auth_seq_id",
+    "auth_comp_id",
+    "auth_asym_id",
+    "auth_atom_id",
+    "pdbx_PDB_model_num",
+];
+
+/// A value quoted if it would otherwise be read as something else; a value with both kinds of
+/// quotes, or several lines, goes in a text field.
+fn quote(value: &str) -> String {
+    let special = value.is_empty()
+        || value == "."
+        || value == "?"
+        || value.contains(char::is_whitespace)
+        || value.starts_with(['_', '#', '$', '\'', '"', ';', '[', ']'])
+        || value.to_ascii_lowercase().starts_with("data_")
+        || value.to_ascii_lowercase().starts_with("loop_");
+    match (special, value.contains('\'')) {
+        (_, quotes) if value.contains('\n') || quotes && value.contains('"') => {
+            format!("\n;{}\n;", value)
+        }
+        (false, _) => value.to_owned(),
+        (true, false) => format!("'{}'", value),
+        (true, true) => format!("\"{}\"", value),
+    }
+}
+
+/// A single character field, `blank` if it is a space.
+fn char_value(c: char, blank: &str) -> String {
+    if c == ' ' {
+        blank.to_owned()
+    } else {
+        quote(&c.to_string())
+    }
+}
+
+/// The `_atom_site` rows of a residue; `label` holds the entity and the `label_seq_id` of
+/// polymer residues.
+fn write_residue<W: Write>(
+    out: &mut W,
+    entry: &Entry,
+    model: &ModelView,
+    residue: &ResidueView,
+    label: Option<(&str, usize)>,
+) -> io::Result<()> {
+    let name = quote(&residue.name());
+    for (k, atom) in residue.atoms.iter().enumerate() {
+        let element = entry.element(model.index, residue.offset + k);
+        let atom_name = quote(std::str::from_utf8(&atom.name.0).unwrap_or("").trim());
+        let group = if is_standard(&atom.residue) {
+            "ATOM"
+        } else {
+            "HETATM"
+        };
+        writeln!(
+            out,
+            "{} {} {} {} {} {} {} {} {} {} {:.3} {:.3} {:.3} {:.2} {:.2} {} {} {} {} {} {}",
+            group,
+            atom.id,
+            element.map_or("?", |e| e.symbol()),
+            atom_name,
+            char_value(atom.id1, "."),
+            name,
+            char_value(atom.chain, "."),
+            label.map_or("?", |(entity, _)| entity),
+            label.map_or(".".to_owned(), |(_, seq)| seq.to_string()),
+            char_value(atom.insertion_code, "?"),
+            atom.coord[0],
+            atom.coord[1],
+            atom.coord[2],
+            atom.occupancy,
+            atom.temperature_factor,
+            if atom.charge == 0 {
+                "?".to_owned()
+            } else {
+                atom.charge.to_string()
+            },
+            atom.sequence_number,
+            name,
+            char_value(atom.chain, "."),
+            atom_name,
+            model.serial
+        )?;
+    }
+    Ok(())
+}
+
+pub struct MmcifWriter;
+
+impl MmcifWriter {
+    pub fn write<W: Write>(entry: &Entry, out: &mut W) -> io::Result<()> {
+        let metadata = entry.structure.metadata.as_ref();
+        let id = metadata
+            .and_then(|m| m.header.as_ref())
+            .map(|h| h.id_code.clone())
+            .filter(|id| !id.is_empty())
+            .unwrap_or_else(|| "XXXX".to_owned());
+        writeln!(out, "data_{}", id)?;
+        writeln!(out, "#")?;
+        writeln!(out, "_entry.id {}", quote(&id))?;
+        writeln!(out, "#")?;
+        let header = metadata.and_then(|m| m.header.as_ref());
+        if let Some(header) =
+            header.filter(|h| h.deposition_date != Header::default().deposition_date)
+        {
+            writeln!(out, "_pdbx_database_status.entry_id {}", quote(&id))?;
+            writeln!(
+                out,
+                "_pdbx_database_status.recvd_initial_deposition_date {}",
+                header.deposition_date.format("%Y-%m-%d")
+            )?;
+            writeln!(out, "#")?;
+        }
+        if let Some(title) = metadata.and_then(|m| m.title.as_ref()) {
+            writeln!(out, "_struct.entry_id {}", quote(&id))?;
+            writeln!(out, "_struct.title {}", quote(title))?;
+            writeln!(out, "#")?;
+        }
+        let classification = header
+            .map(|h| h.classification.as_str())
+            .filter(|c| !c.is_empty());
+        let keywords = entry.title_section.keywords.as_ref();
+        if classification.is_some() || keywords.is_some() {
+            writeln!(out, "_struct_keywords.entry_id {}", quote(&id))?;
+            if let Some(classification) = classification {
+                writeln!(
+                    out,
+                    "_struct_keywords.pdbx_keywords {}",
+                    quote(classification)
+                )?;
+            }
+            if let Some(keywords) = keywords {
+                let keywords: Vec<String> =
+                    keywords.iter().map(|k| k.replace(',', "\\,")).collect();
+                writeln!(out, "_struct_keywords.text {}", quote(&keywords.join(", ")))?;
+            }
+            writeln!(out, "#")?;
+        }
+        if let Some(techniques) = metadata.and_then(|m| m.experimental_techniques.as_ref()) {
+            writeln!(out, "loop_")?;
+            writeln!(out, "_exptl.entry_id")?;
+            writeln!(out, "_exptl.method")?;
+            for t in techniques {
+                writeln!(out, "{} {}", quote(&id), quote(&technique(t)))?;
+            }
+            writeln!(out, "#")?;
+        }
+        if let Some(authors) = metadata.and_then(|m| m.authors.as_ref()) {
+            writeln!(out, "loop_")?;
+            writeln!(out, "_audit_author.name")?;
+            writeln!(out, "_audit_author.pdbx_ordinal")?;
+            for (k, author) in authors.iter().enumerate() {
+                writeln!(out, "{} {}", quote(author), k + 1)?;
+            }
+            writeln!(out, "#")?;
+        }
+        if let Some(cryst1) = metadata.and_then(|m| m.cryst1.as_ref()) {
+            writeln!(out, "_cell.entry_id {}", quote(&id))?;
+            writeln!(out, "_cell.length_a {:.3}", cryst1.a)?;
+            writeln!(out, "_cell.length_b {:.3}", cryst1.b)?;
+            writeln!(out, "_cell.length_c {:.3}", cryst1.c)?;
+            writeln!(out, "_cell.angle_alpha {:.2}", cryst1.alpha)?;
+            writeln!(out, "_cell.angle_beta {:.2}", cryst1.beta)?;
+            writeln!(out, "_cell.angle_gamma {:.2}", cryst1.gamma)?;
+            writeln!(out, "_cell.Z_PDB {}", cryst1.z)?;
+            writeln!(out, "#")?;
+            writeln!(out, "_symmetry.entry_id {}", quote(&id))?;
+            writeln!(
+                out,
+                "_symmetry.space_group_name_H-M {}",
+                quote(&space_group(cryst1))
+            )?;
+            writeln!(out, "#")?;
+        }
+
+        writeln!(out, "loop_")?;
+        for tag in ATOM_SITE {
+            writeln!(out, "_atom_site.{}", tag)?;
+        }
+        for model in entry.hierarchy() {
+            // * polymers chain by chain, then the non-polymer residues in their original order,
+            // as in the PDB writer
+            for chain in &model.chains {
+                let entity = entry
+                    .title_section
+                    .compounds
+                    .iter()
+                    .find(|c| c.chains.contains(&chain.id))
+                    .map_or("?".to_owned(), |c| c.mol_id.to_string());
+                for (i, residue) in chain.polymer.iter().enumerate() {
+                    write_residue(out, entry, &model, residue, Some((&entity, i + 1)))?;
+                }
+            }
+            let mut non_polymer: Vec<&ResidueView> =
+                model.chains.iter().flat_map(|c| &c.non_polymer).collect();
+            non_polymer.sort_by_key(|r| r.offset);
+            for residue in non_polymer {
+                write_residue(out, entry, &model, residue, None)?;
+            }
+        }
+        writeln!(out, "#")
+    }
+
+    pub fn write_to_string(entry: &Entry) -> String {
+        let mut buf = Vec::new();
+        Self::write(entry, &mut buf).expect("writing to a Vec cannot fail");
+        String::from_utf8(buf).expect("the writer only writes UTF-8")
+    }
+}
+
+/// Splits the input into tokens: bare words, quoted strings and semicolon-delimited text fields.
+/// Comments are dropped. Quoted values are marked so that `.` and `?` in quotes stay values.
+fn tokenize(input: &str) -> Result<Vec<(String, bool)>, MmcifError> {
+    let mut tokens = Vec::new();
+    let bytes = input.as_bytes();
+    let mut i = 0;
+    let mut line_start = true;
+    while i < bytes.len() {
+        let c = bytes[i];
+        if c == b'\n' {
+            line_start = true;
+            i += 1;
+            continue;
+        }
+        if c.is_ascii_whitespace() {
+            line_start = false;
+            i += 1;
+            continue;
+        }
+        if c == b';' && line_start {
+            let end = input[i + 1..]
+                .find("\n;")
+                .ok_or_else(|| MmcifError("unterminated text field".to_owned()))?;
+            tokens.push((input[i + 1..i + 1 + end].trim_end().to_owned(), true));
+            i += end + 3;
+            line_start = false;
+            continue;
+        }
+        line_start = false;
+        if c == b'#' {
+            while i < bytes.len() && bytes[i] != b'\n' {
+                i += 1;
+            }
+            continue;
+        }
+        if c == b'\'' || c == b'"' {
+            // * a quote only closes the string if followed by whitespace
+            let mut j = i + 1;
+            while j < bytes.len()
+                && (bytes[j] != c
+                    || matches!(bytes.get(j + 1), Some(b) if !b.is_ascii_whitespace()))
+            {
+                if bytes[j] == b'\n' {
+                    return Err(MmcifError("unterminated quoted string".to_owned()));
+                }
+                j += 1;
+            }
+            tokens.push((input[i + 1..j.min(bytes.len())].to_owned(), true));
+            i = j + 1;
+            continue;
+        }
+        let start = i;
+        while i < bytes.len() && !bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        tokens.push((input[start..i].to_owned(), false));
+    }
+    Ok(tokens)
+}
+
+/// The items of one category: the tags, and the values row by row. `None` stands for `.` and `?`.
+#[derive(Debug, Default)]
+struct Table {
+    tags: Vec<String>,
+    values: Vec<Option<String>>,
+}
+
+impl Table {
+    fn rows(&self) -> impl Iterator<Item = &[Option<String>]> {
+        self.values.chunks(self.tags.len().max(1))
+    }
+
+    fn column(&self, tag: &str) -> Option<usize> {
+        self.tags.iter().position(|t| t == tag)
+    }
+}
+
+/// The tables of the first data block, by category.
+fn read_tables(input: &str) -> Result<HashMap<String, Table>, MmcifError> {
+    let tokens = tokenize(input)?;
+    let mut tables: HashMap<String, Table> = HashMap::new();
+    let value = |(token, quoted): &(String, bool)| {
+        if !quoted && (token == "." || token == "?") {
+            None
+        } else {
+            Some(token.clone())
+        }
+    };
+    let split = |tag: &str| -> Result<(String, String), MmcifError> {
+        let (category, item) = tag[1..]
+            .split_once('.')
+            .ok_or_else(|| MmcifError(format!("malformed tag `{}`", tag)))?;
+        Ok((category.to_owned(), item.to_owned()))
+    };
+    let mut blocks = 0;
+    let mut i = 0;
+    while i < tokens.len() {
+        let (token, quoted) = &tokens[i];
+        let lower = token.to_ascii_lowercase();
+        if *quoted {
+            return Err(MmcifError(format!("unexpected value `{}`", token)));
+        } else if lower.starts_with("data_") {
+            blocks += 1;
+            if blocks > 1 {
+                break;
+            }
+            i += 1;
+        } else if lower == "loop_" {
+            i += 1;
+            let mut category = None;
+            let mut tags = Vec::new();
+            while i < tokens.len() && !tokens[i].1 && tokens[i].0.starts_with('_') {
+                let (c, item) = split(&tokens[i].0)?;
+                category = Some(c);
+                tags.push(item);
+                i += 1;
+            }
+            let mut values = Vec::new();
+            while i < tokens.len() {
+                let (token, quoted) = &tokens[i];
+                let lower = token.to_ascii_lowercase();
+                if !quoted
+                    && (token.starts_with('_') || lower == "loop_" || lower.starts_with("data_"))
+                {
+                    break;
+                }
+                values.push(value(&tokens[i]));
+                i += 1;
+            }
+            let category = category.ok_or_else(|| MmcifError("loop without tags".to_owned()))?;
+            if values.len() % tags.len() != 0 {
+                return Err(MmcifError(format!(
+                    "the values of loop `{}` do not fill its rows",
+                    category
+                )));
+            }
+            tables.insert(category, Table { tags, values });
+        } else if token.starts_with('_') {
+            let (category, item) = split(token)?;
+            let v = tokens
+                .get(i + 1)
+                .ok_or_else(|| MmcifError(format!("`{}` has no value", token)))?;
+            let table = tables.entry(category).or_default();
+            table.tags.push(item);
+            table.values.push(value(v));
+            i += 2;
+        } else {
+            return Err(MmcifError(format!("unexpected `{}`", token)));
+        }
+    }
+    Ok(tables)
+}
+
+/// A TER record after the last polymer atom of each chain, polymer atoms being those with a
+/// `label_seq_id`.
+fn polymer_ters(atoms: &[Atom], polymer: &[bool]) -> Vec<(usize, Ter)> {
+    let mut ters: Vec<(usize, Ter)> = Vec::new();
+    for (k, atom) in atoms.iter().enumerate().filter(|(k, _)| polymer[*k]) {
+        let ter = Ter {
+            id: None,
+            residue_name: residue_name(&atom.residue),
+            chain: atom.chain,
+            sequence_number: Some(atom.sequence_number),
+            insertion_code: atom.insertion_code,
+        };
+        match ters.iter_mut().find(|(_, t)| t.chain == atom.chain) {
+            Some(last) => *last = (k + 1, ter),
+            None => ters.push((k + 1, ter)),
+        }
+    }
+    ters.sort_by_key(|(preceding, _)| *preceding);
+    ters
+}
+
+pub struct MmcifParser;
+
+impl MmcifParser {
+    /// Reads the coordinates and the metadata listed in the [module documentation](index.html)
+    /// from the first data block.
+    pub fn parse_entry(input: &[u8]) -> Result<Entry, MmcifError> {
+        Self::parse_entry_with(input, &ParseOptions::default())
+    }
+
+    /// Like [`parse_entry`](#method.parse_entry), with the atoms kept controlled by `options`.
+    pub fn parse_entry_with(input: &[u8], options: &ParseOptions) -> Result<Entry, MmcifError> {
+        let input =
+            std::str::from_utf8(input).map_err(|_| MmcifError("not valid UTF-8".to_owned()))?;
+        let tables = read_tables(input)?;
+        let mut entry = Entry::default();
+        let mut metadata = Metadata::default();
+
+        // * the first value of an item, and all the values of an item in a loop
+        let item = |category: &str, tag: &str| -> Option<String> {
+            let table = tables.get(category)?;
+            table.values[table.column(tag)?].clone()
+        };
+        let items = |category: &str, tag: &str| -> Vec<String> {
+            let table = tables.get(category);
+            let column = table.and_then(|t| t.column(tag));
+            match (table, column) {
+                (Some(table), Some(c)) => table.rows().filter_map(|row| row[c].clone()).collect(),
+                _ => Vec::new(),
+            }
+        };
+
+        let id_code = item("entry", "id");
+        let classification = item("struct_keywords", "pdbx_keywords");
+        let deposition_date = item("pdbx_database_status", "recvd_initial_deposition_date")
+            .and_then(|d| NaiveDate::parse_from_str(&d, "%Y-%m-%d").ok());
+        if id_code.is_some() || classification.is_some() || deposition_date.is_some() {
+            let default = Header::default();
+            metadata.header = Some(Header {
+                classification: classification.unwrap_or(default.classification),
+                deposition_date: deposition_date.unwrap_or(default.deposition_date),
+                id_code: id_code.unwrap_or(default.id_code),
+            });
+        }
+        metadata.title = item("struct", "title");
+        metadata.authors = Some(items("audit_author", "name")).filter(|a| !a.is_empty());
+        let techniques: Vec<ExperimentalTechnique> = items("exptl", "method")
+            .iter()
+            .filter_map(|m| m.to_ascii_uppercase().parse().ok())
+            .collect();
+        metadata.experimental_techniques = Some(techniques).filter(|t| !t.is_empty());
+        entry.title_section.keywords = item("struct_keywords", "text").map(|t| split_list(&t, ','));
+        if let Some(cell) = tables.get("cell") {
+            let get = |tag: &str| -> Option<f32> {
+                cell.values[cell.column(tag)?].as_ref()?.parse().ok()
+            };
+            if let (Some(a), Some(b), Some(c)) = (get("length_a"), get("length_b"), get("length_c"))
+            {
+                let (lattice_type, space_group) = item("symmetry", "space_group_name_H-M")
+                    .and_then(|s| parse_space_group(&s))
+                    .unwrap_or((
+                        LatticeType::Primitive,
+                        SpaceGroup(GroupAxis(1, 0), None, None),
+                    ));
+                metadata.cryst1 = Some(Cryst1 {
+                    a,
+                    b,
+                    c,
+                    alpha: get("angle_alpha").unwrap_or(90.0),
+                    beta: get("angle_beta").unwrap_or(90.0),
+                    gamma: get("angle_gamma").unwrap_or(90.0),
+                    z: get("Z_PDB").map_or(1, |z| z as u8),
+                    lattice_type,
+                    space_group,
+                });
+            }
+        }
+
+        let atom_site = tables
+            .get("atom_site")
+            .ok_or_else(|| MmcifError("no _atom_site category".to_owned()))?;
+        let column = |tags: &[&str]| tags.iter().find_map(|t| atom_site.column(t));
+        let required = |tags: &[&str]| {
+            column(tags).ok_or_else(|| MmcifError(format!("no _atom_site.{}", tags[0])))
+        };
+        let id = required(&["id"])?;
+        let symbol = column(&["type_symbol"]);
+        let atom_name = required(&["auth_atom_id", "label_atom_id"])?;
+        let alt_id = column(&["label_alt_id"]);
+        let comp = required(&["auth_comp_id", "label_comp_id"])?;
+        let chain = required(&["auth_asym_id", "label_asym_id"])?;
+        let seq = required(&["auth_seq_id", "label_seq_id"])?;
+        let ins_code = column(&["pdbx_PDB_ins_code"]);
+        let xyz = [
+            required(&["Cartn_x"])?,
+            required(&["Cartn_y"])?,
+            required(&["Cartn_z"])?,
+        ];
+        let occupancy = column(&["occupancy"]);
+        let b_factor = column(&["B_iso_or_equiv"]);
+        let charge = column(&["pdbx_formal_charge"]);
+        let model_num = column(&["pdbx_PDB_model_num"]);
+        let label_seq = column(&["label_seq_id"]);
+
+        let modified = (Default::default(), Default::default());
+        let mut model_serials: Vec<u32> = Vec::new();
+        // * whether each atom of the current model belongs to a polymer, to place TER records
+        let mut polymer: Vec<bool> = Vec::new();
+        for (r, row) in atom_site.rows().enumerate() {
+            let text = |c: Option<usize>| c.and_then(|c| row[c].as_deref());
+            let number = |c: Option<usize>| -> Result<Option<f32>, MmcifError> {
+                text(c)
+                    .map(|v| {
+                        v.parse::<f32>().map_err(|_| {
+                            MmcifError(format!("atom_site row {}: invalid number `{}`", r + 1, v))
+                        })
+                    })
+                    .transpose()
+            };
+            let first_char =
+                |c: Option<usize>| text(c).and_then(|v| v.chars().next()).unwrap_or(' ');
+
+            let serial = text(model_num).and_then(|v| v.parse().ok()).unwrap_or(1);
+            if model_serials.last() != Some(&serial) {
+                if let (Some(model), Some(info)) =
+                    (entry.structure.models.last(), entry.model_info.last_mut())
+                {
+                    info.ters = polymer_ters(&model.atoms, &polymer);
+                }
+                polymer.clear();
+                model_serials.push(serial);
+                entry.structure.models.push(Model::default());
+                entry.model_info.push(ModelInfo::default());
+            }
+            let element = text(symbol).and_then(ChemicalElement::from_symbol);
+            if options.skip_hydrogens && matches!(element, Some(e) if e.is_hydrogen()) {
+                continue;
+            }
+            let residue_name = text(Some(comp)).unwrap_or("UNK");
+            let residue = if residue_name.len() <= 3 {
+                let padded = format!("{:>3}", residue_name);
+                parse_residue(padded.as_bytes(), &modified.0, &modified.1)
+                    .map(|(_, residue)| residue)
+                    .unwrap_or_else(|_| Residue::Other(residue_name.to_owned()))
+            } else {
+                Residue::Other(residue_name.to_owned())
+            };
+            let name = text(Some(atom_name)).unwrap_or("");
+            let atom = Atom {
+                id: text(Some(id))
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(r as u32 + 1),
+                name: AtomName::from_str(name).map_err(|_| {
+                    MmcifError(format!(
+                        "atom_site row {}: invalid atom name `{}`",
+                        r + 1,
+                        name
+                    ))
+                })?,
+                id1: first_char(alt_id),
+                residue,
+                chain: first_char(Some(chain)),
+                sequence_number: text(Some(seq)).and_then(|v| v.parse().ok()).unwrap_or(0),
+                insertion_code: first_char(ins_code),
+                coord: [
+                    number(Some(xyz[0]))?.unwrap_or(0.0),
+                    number(Some(xyz[1]))?.unwrap_or(0.0),
+                    number(Some(xyz[2]))?.unwrap_or(0.0),
+                ],
+                occupancy: number(occupancy)?.unwrap_or(1.0),
+                temperature_factor: number(b_factor)?.unwrap_or(0.0),
+                element: element.map_or(Element::Unknown, Element::from),
+                charge: text(charge).and_then(|v| v.parse().ok()).unwrap_or(0),
+            };
+            entry.structure.models.last_mut().unwrap().atoms.push(atom);
+            entry.model_info.last_mut().unwrap().elements.push(element);
+            polymer.push(text(label_seq).is_some());
+        }
+        if let (Some(model), Some(info)) =
+            (entry.structure.models.last(), entry.model_info.last_mut())
+        {
+            info.ters = polymer_ters(&model.atoms, &polymer);
+        }
+        if model_serials.len() > 1 {
+            for (info, serial) in entry.model_info.iter_mut().zip(model_serials) {
+                info.serial = Some(serial);
+                info.ended = true;
+            }
+        }
+        entry.structure.metadata = Some(metadata);
+        Ok(entry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_tokenize() {
+        let tokens = tokenize("_a.b 'it''s x' # comment\n;line one\nline two\n;\n. '.'").unwrap();
+        let values: Vec<(&str, bool)> = tokens.iter().map(|(t, q)| (t.as_str(), *q)).collect();
+        assert_eq!(
+            values,
+            vec![
+                ("_a.b", false),
+                ("it''s x", true),
+                ("line one\nline two", true),
+                (".", false),
+                (".", true)
+            ]
+        );
+        assert_eq!(quote("O5'"), "O5'");
+        assert_eq!(quote("N A"), "'N A'");
+        assert_eq!(quote("'x y"), "\"'x y\"");
+    }
+}
@@ -0,0 +1,635 @@
+// Copyright (c) 2020 Tianyi Shi
+//
+// This software is released under the MIT License.
+// https://opensource.org/licenses/MIT
+
+//! Backbone torsions, sugar puckers and base pairs of nucleic acids.
+//!
+//! For nucleotide *i*, the backbone torsions are
+//!
+//! - α: O3'(*i*-1)-P-O5'-C5'
+//! - β: P-O5'-C5'-C4'
+//! - γ: O5'-C5'-C4'-C3'
+//! - δ: C5'-C4'-C3'-O3'
+//! - ε: C4'-C3'-O3'-P(*i*+1)
+//! - ζ: C3'-O3'-P(*i*+1)-O5'(*i*+1)
+//!
+//! and χ is O4'-C1'-N9-C4 for purines and O4'-C1'-N1-C2 for pyrimidines. Angles involving a
+//! neighbouring nucleotide are only computed if O3'(*i*-1) and P(*i*) are no further than 2 Å
+//! apart. The sugar pucker is described by the pseudorotation phase and amplitude of Altona &
+//! Sundaralingam (1972).
+//!
+//! Base pairs are detected from hydrogen bonds, taken as donor–acceptor pairs of base atoms (or
+//! O2') no more than 3.4 Å apart and offset by no more than 2 Å along the normal of the first
+//! base, which excludes stacked bases. The planes of the bases must be within 65° of each other,
+//! and at least two hydrogen bonds are required. Each pair is classified after Leontis & Westhof
+//! (2001) by the edge of each base that takes part, which is the edge with the most hydrogen
+//! bonds, and by the orientation of the glycosidic bonds relative to the axis between the edges.
+//! This is a simplification that agrees with dedicated tools such as FR3D or MC-Annotate for
+//! well-formed pairs, but may differ for distorted ones.
+//!
+//! The dot-bracket notation marks the canonical (Watson–Crick and G·U wobble) pairs, with `()`
+//! for nested pairs and `[]`, `{}` and `<>` for pseudoknots.
+
+use crate::entry::Entry;
+use crate::geometry::{centroid, cross, dihedral, distance, dot, norm, sub, Coord};
+use crate::hierarchy::{ChainView, ModelView, ResidueView};
+use crate::types::{Residue, ResidueSerial};
+
+/// The maximum length, in Å, of an O3'-P bond for two nucleotides to be considered adjacent.
+const PHOSPHODIESTER_BOND_CUTOFF: f32 = 2.0;
+const HYDROGEN_BOND_DISTANCE: f32 = 3.4;
+/// The largest angle, in degrees, between the planes of paired bases.
+const BASE_PAIR_MAX_ANGLE: f32 = 65.0;
+/// The largest distance, in Å, between the atoms of a hydrogen bond along the normal of a base.
+const BASE_PAIR_MAX_RISE: f32 = 2.0;
+const BRACKETS: [(char, char); 4] = [('(', ')'), ('[', ']'), ('{', '}'), ('<', '>')];
+
+/// The conformation of a ribose, named after the atom displaced from the plane of the others.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PuckerConformation {
+    C3Endo,
+    C4Exo,
+    O4Endo,
+    C1Exo,
+    C2Endo,
+    C3Exo,
+    C4Endo,
+    O4Exo,
+    C1Endo,
+    C2Exo,
+}
+
+impl PuckerConformation {
+    /// The conformation whose 36° sector of the pseudorotation cycle contains `phase`.
+    pub fn from_phase(phase: f32) -> Self {
+        use PuckerConformation::*;
+        const CYCLE: [PuckerConformation; 10] = [
+            C3Endo, C4Exo, O4Endo, C1Exo, C2Endo, C3Exo, C4Endo, O4Exo, C1Endo, C2Exo,
+        ];
+        CYCLE[(phase.rem_euclid(360.0) / 36.0) as usize % 10]
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SugarPucker {
+    /// Pseudorotation phase angle P in degrees, from 0 to 360
+    pub phase: f32,
+    /// Amplitude τm in degrees
+    pub amplitude: f32,
+    pub conformation: PuckerConformation,
+}
+
+impl SugarPucker {
+    /// The pucker from the endocyclic torsions ν0 - ν4 of the ribose, in degrees.
+    pub fn from_torsions(nu: [f32; 5]) -> Self {
+        let (sin36, sin72) = (36f32.to_radians().sin(), 72f32.to_radians().sin());
+        let y = (nu[4] + nu[1]) - (nu[3] + nu[0]);
+        let x = 2.0 * nu[2] * (sin36 + sin72);
+        let phase = y.atan2(x).to_degrees().rem_euclid(360.0);
+        Self {
+            phase,
+            amplitude: (x * x + y * y).sqrt() / (2.0 * (sin36 + sin72)),
+            conformation: PuckerConformation::from_phase(phase),
+        }
+    }
+}
+
+/// The torsion angles of a nucleotide, in degrees.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct NucleotideTorsions {
+    pub chain: char,
+    pub sequence_number: ResidueSerial,
+    pub insertion_code: char,
+    pub residue_name: String,
+    pub alpha: Option<f32>,
+    pub beta: Option<f32>,
+    pub gamma: Option<f32>,
+    pub delta: Option<f32>,
+    pub epsilon: Option<f32>,
+    pub zeta: Option<f32>,
+    pub chi: Option<f32>,
+    /// `None` if an atom of the ribose is missing
+    pub pucker: Option<SugarPucker>,
+}
+
+/// The edges of a base that can form hydrogen bonds (Leontis & Westhof, 2001).
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edge {
+    WatsonCrick,
+    Hoogsteen,
+    /// The shallow-groove edge, including O2'
+    Sugar,
+}
+
+impl Edge {
+    pub fn code(self) -> char {
+        match self {
+            Edge::WatsonCrick => 'W',
+            Edge::Hoogsteen => 'H',
+            Edge::Sugar => 'S',
+        }
+    }
+}
+
+/// Whether the glycosidic bonds of a base pair are on the same side of the axis between the
+/// interacting edges.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GlycosidicOrientation {
+    Cis,
+    Trans,
+}
+
+/// A nucleotide of a base pair.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct PairedBase {
+    pub chain: char,
+    pub sequence_number: ResidueSerial,
+    pub insertion_code: char,
+    pub residue_name: String,
+    /// One-letter code of the base, with modified nucleotides mapped to their parents
+    pub code: char,
+    pub edge: Edge,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct BasePair {
+    /// The nucleotide that comes first in the model
+    pub a: PairedBase,
+    pub b: PairedBase,
+    pub orientation: GlycosidicOrientation,
+    /// The atoms of `a` and `b` and their distance in Å
+    pub hydrogen_bonds: Vec<(String, String, f32)>,
+}
+
+impl BasePair {
+    /// The Leontis–Westhof family, e.g. `cWW` or `tHS`.
+    pub fn family(&self) -> String {
+        let orientation = match self.orientation {
+            GlycosidicOrientation::Cis => 'c',
+            GlycosidicOrientation::Trans => 't',
+        };
+        format!(
+            "{}{}{}",
+            orientation,
+            self.a.edge.code(),
+            self.b.edge.code()
+        )
+    }
+
+    fn is_cis_watson_crick(&self) -> bool {
+        self.orientation == GlycosidicOrientation::Cis
+            && self.a.edge == Edge::WatsonCrick
+            && self.b.edge == Edge::WatsonCrick
+    }
+
+    /// A-U, A-T or G-C in the cis Watson–Crick/Watson–Crick geometry.
+    pub fn is_watson_crick(&self) -> bool {
+        self.is_cis_watson_crick()
+            && matches!(
+                (self.a.code, self.b.code),
+                ('A', 'U') | ('U', 'A') | ('A', 'T') | ('T', 'A') | ('G', 'C') | ('C', 'G')
+            )
+    }
+
+    /// G-U in the cis Watson–Crick/Watson–Crick geometry.
+    pub fn is_wobble(&self) -> bool {
+        self.is_cis_watson_crick() && matches!((self.a.code, self.b.code), ('G', 'U') | ('U', 'G'))
+    }
+}
+
+impl std::fmt::Display for BasePair {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let base = |b: &PairedBase| {
+            format!(
+                "{} {}{}{}",
+                b.residue_name,
+                b.chain,
+                b.sequence_number,
+                b.insertion_code.to_string().trim()
+            )
+        };
+        write!(f, "{} - {} {}", base(&self.a), base(&self.b), self.family())
+    }
+}
+
+fn is_purine(code: char) -> bool {
+    matches!(code, 'A' | 'G')
+}
+
+/// The glycosidic nitrogen and the base atom that follows it in χ.
+fn glycosidic_atoms(code: char) -> (&'static str, &'static str) {
+    if is_purine(code) {
+        ("N9", "C4")
+    } else {
+        ("N1", "C2")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Role {
+    Donor,
+    Acceptor,
+    Both,
+}
+
+/// The base atoms of each nucleotide that can form hydrogen bonds, with the edges they belong
+/// to. O2' is added separately.
+fn polar_atoms(code: char) -> &'static [(&'static str, Role, &'static [Edge])] {
+    use Edge::*;
+    use Role::*;
+    match code {
+        'A' => &[
+            ("N1", Acceptor, &[WatsonCrick]),
+            ("N3", Acceptor, &[Sugar]),
+            ("N6", Donor, &[WatsonCrick, Hoogsteen]),
+            ("N7", Acceptor, &[Hoogsteen]),
+        ],
+        'G' => &[
+            ("N1", Donor, &[WatsonCrick]),
+            ("N2", Donor, &[WatsonCrick, Sugar]),
+            ("N3", Acceptor, &[Sugar]),
+            ("O6", Acceptor, &[WatsonCrick, Hoogsteen]),
+            ("N7", Acceptor, &[Hoogsteen]),
+        ],
+        'C' => &[
+            ("O2", Acceptor, &[WatsonCrick, Sugar]),
+            ("N3", Acceptor, &[WatsonCrick]),
+            ("N4", Donor, &[WatsonCrick, Hoogsteen]),
+        ],
+        'U' | 'T' => &[
+            ("O2", Acceptor, &[WatsonCrick, Sugar]),
+            ("N3", Donor, &[WatsonCrick]),
+            ("O4", Acceptor, &[WatsonCrick, Hoogsteen]),
+        ],
+        _ => &[],
+    }
+}
+
+/// A base with the coordinates needed to find its pairs.
+struct Base<'a> {
+    residue: &'a ResidueView<'a>,
+    code: char,
+    /// Hydrogen bonding atoms: name, coordinates, role and edges
+    polar: Vec<(&'static str, Coord, Role, &'static [Edge])>,
+    normal: Coord,
+    /// From the glycosidic nitrogen to C1'
+    glycosidic: Coord,
+}
+
+impl<'a> Base<'a> {
+    fn new(residue: &'a ResidueView<'a>, code: char) -> Option<Self> {
+        let coord = |name: &str| residue.atom(name).map(|a| a.coord);
+        let (c2, c4, c6) = (coord("C2")?, coord("C4")?, coord("C6")?);
+        let normal = cross(sub(c4, c2), sub(c6, c2));
+        let length = norm(normal);
+        let normal = [normal[0] / length, normal[1] / length, normal[2] / length];
+        let glycosidic = sub(coord("C1'")?, coord(glycosidic_atoms(code).0)?);
+        let mut polar: Vec<_> = polar_atoms(code)
+            .iter()
+            .filter_map(|&(name, role, edges)| Some((name, coord(name)?, role, edges)))
+            .collect();
+        if let Some(o2) = coord("O2'") {
+            polar.push(("O2'", o2, Role::Both, &[Edge::Sugar]));
+        }
+        Some(Self {
+            residue,
+            code,
+            polar,
+            normal,
+            glycosidic,
+        })
+    }
+
+    fn paired(&self, edge: Edge) -> PairedBase {
+        PairedBase {
+            chain: self.residue.chain(),
+            sequence_number: self.residue.sequence_number(),
+            insertion_code: self.residue.insertion_code(),
+            residue_name: self.residue.name(),
+            code: self.code,
+            edge,
+        }
+    }
+}
+
+/// The edge with the most hydrogen bonds, given the edges of the atom of each bond.
+fn dominant_edge(bonds: &[&[Edge]]) -> Edge {
+    let mut best = (Edge::WatsonCrick, 0);
+    for &edge in &[Edge::WatsonCrick, Edge::Hoogsteen, Edge::Sugar] {
+        let count = bonds.iter().filter(|edges| edges.contains(&edge)).count();
+        if count > best.1 {
+            best = (edge, count);
+        }
+    }
+    best.0
+}
+
+/// The component of `v` perpendicular to the unit vector `axis`.
+fn perpendicular(v: Coord, axis: Coord) -> Coord {
+    let d = dot(v, axis);
+    [v[0] - d * axis[0], v[1] - d * axis[1], v[2] - d * axis[2]]
+}
+
+fn base_pair(a: &Base, b: &Base) -> Option<BasePair> {
+    let cos = dot(a.normal, b.normal).abs().min(1.0);
+    if cos.acos().to_degrees() > BASE_PAIR_MAX_ANGLE {
+        return None;
+    }
+    let mut bonds = Vec::new();
+    for &(name_a, p, role_a, edges_a) in &a.polar {
+        for &(name_b, q, role_b, edges_b) in &b.polar {
+            let compatible = !matches!(
+                (role_a, role_b),
+                (Role::Donor, Role::Donor) | (Role::Acceptor, Role::Acceptor)
+            );
+            let d = distance(&p, &q);
+            // * stacked bases are held apart along their normals
+            let rise = dot(sub(q, p), a.normal).abs();
+            if compatible
+                && d <= HYDROGEN_BOND_DISTANCE
+                && rise <= BASE_PAIR_MAX_RISE
+                && !(name_a == "O2'" && name_b == "O2'")
+            {
+                bonds.push((name_a, p, edges_a, name_b, q, edges_b, d));
+            }
+        }
+    }
+    if bonds.len() < 2 {
+        return None;
+    }
+    let edge_a = dominant_edge(&bonds.iter().map(|b| b.2).collect::<Vec<_>>());
+    let edge_b = dominant_edge(&bonds.iter().map(|b| b.5).collect::<Vec<_>>());
+    let axis = sub(
+        centroid(&bonds.iter().map(|b| b.4).collect::<Vec<_>>()).unwrap(),
+        centroid(&bonds.iter().map(|b| b.1).collect::<Vec<_>>()).unwrap(),
+    );
+    let length = norm(axis);
+    let axis = [axis[0] / length, axis[1] / length, axis[2] / length];
+    let orientation = if dot(
+        perpendicular(a.glycosidic, axis),
+        perpendicular(b.glycosidic, axis),
+    ) > 0.0
+    {
+        GlycosidicOrientation::Cis
+    } else {
+        GlycosidicOrientation::Trans
+    };
+    Some(BasePair {
+        a: a.paired(edge_a),
+        b: b.paired(edge_b),
+        orientation,
+        hydrogen_bonds: bonds
+            .into_iter()
+            .map(|(a, _, _, b, _, _, d)| (a.to_owned(), b.to_owned(), d))
+            .collect(),
+    })
+}
+
+/// Whether `prev` is joined to `next` by a phosphodiester bond.
+fn phosphodiester_bonded(prev: &ResidueView, next: &ResidueView) -> bool {
+    match (prev.atom("O3'"), next.atom("P")) {
+        (Some(o3), Some(p)) => distance(o3, p) <= PHOSPHODIESTER_BOND_CUTOFF,
+        _ => false,
+    }
+}
+
+fn torsion(residues: [&ResidueView; 4], names: [&str; 4]) -> Option<f32> {
+    let a = residues[0].atom(names[0])?;
+    let b = residues[1].atom(names[1])?;
+    let c = residues[2].atom(names[2])?;
+    let d = residues[3].atom(names[3])?;
+    Some(dihedral(a, b, c, d))
+}
+
+/// Assigns brackets to pairs `(i, j)` with `i < j`, nesting each in the first level whose pairs
+/// it does not cross. Pairs that would need more levels than there are brackets are left out.
+fn bracket_levels(pairs: &[(usize, usize)]) -> Vec<Option<usize>> {
+    let mut order: Vec<usize> = (0..pairs.len()).collect();
+    order.sort_by_key(|&k| pairs[k]);
+    let mut levels: Vec<Vec<(usize, usize)>> = vec![Vec::new(); BRACKETS.len()];
+    let mut assigned = vec![None; pairs.len()];
+    for k in order {
+        let (i, j) = pairs[k];
+        let crosses =
+            |&(a, b): &(usize, usize)| (a < i && i < b && b < j) || (i < a && a < j && j < b);
+        if let Some(level) = levels.iter().position(|l| !l.iter().any(crosses)) {
+            levels[level].push((i, j));
+            assigned[k] = Some(level);
+        }
+    }
+    assigned
+}
+
+impl<'a> ChainView<'a> {
+    /// The torsion angles and sugar puckers of the nucleotides of the polymer.
+    pub fn nucleotide_torsions(&self) -> Vec<NucleotideTorsions> {
+        let residues: Vec<&ResidueView> = self
+            .polymer
+            .iter()
+            .filter(|r| matches!(r.residue(), Residue::Nucleotide(_)))
+            .collect();
+        residues
+            .iter()
+            .enumerate()
+            .map(|(i, &r)| {
+                let prev = i
+                    .checked_sub(1)
+                    .map(|j| residues[j])
+                    .filter(|p| phosphodiester_bonded(p, r));
+                let next = residues
+                    .get(i + 1)
+                    .copied()
+                    .filter(|n| phosphodiester_bonded(r, n));
+                // * purines are told apart by N9, which also works for modified nucleotides
+                let (n, c) = glycosidic_atoms(if r.atom("N9").is_some() { 'A' } else { 'C' });
+                let nu = [
+                    torsion([r, r, r, r], ["C4'", "O4'", "C1'", "C2'"]),
+                    torsion([r, r, r, r], ["O4'", "C1'", "C2'", "C3'"]),
+                    torsion([r, r, r, r], ["C1'", "C2'", "C3'", "C4'"]),
+                    torsion([r, r, r, r], ["C2'", "C3'", "C4'", "O4'"]),
+                    torsion([r, r, r, r], ["C3'", "C4'", "O4'", "C1'"]),
+                ];
+                let pucker = match nu {
+                    [Some(a), Some(b), Some(c), Some(d), Some(e)] => {
+                        Some(SugarPucker::from_torsions([a, b, c, d, e]))
+                    }
+                    _ => None,
+                };
+                NucleotideTorsions {
+                    chain: r.chain(),
+                    sequence_number: r.sequence_number(),
+                    insertion_code: r.insertion_code(),
+                    residue_name: r.name(),
+                    alpha: prev.and_then(|p| torsion([p, r, r, r], ["O3'", "P", "O5'", "C5'"])),
+                    beta: torsion([r, r, r, r], ["P", "O5'", "C5'", "C4'"]),
+                    gamma: torsion([r, r, r, r], ["O5'", "C5'", "C4'", "C3'"]),
+                    delta: torsion([r, r, r, r], ["C5'", "C4'", "C3'", "O3'"]),
+                    epsilon: next.and_then(|n| torsion([r, r, r, n], ["C4'", "C3'", "O3'", "P"])),
+                    zeta: next.and_then(|n| torsion([r, r, n, n], ["C3'", "O3'", "P", "O5'"])),
+                    chi: torsion([r, r, r, r], ["O4'", "C1'", n, c]),
+                    pucker,
+                }
+            })
+            .collect()
+    }
+}
+
+impl<'a> ModelView<'a> {
+    /// The torsion angles and sugar puckers of the nucleotides of all chains.
+    pub fn nucleotide_torsions(&self) -> Vec<NucleotideTorsions> {
+        self.chains
+            .iter()
+            .flat_map(ChainView::nucleotide_torsions)
+            .collect()
+    }
+}
+
+impl Entry {
+    /// The one-letter code of each nucleotide of the polymers of `view`, with its residue.
+    fn nucleotides<'a>(&self, view: &'a ModelView<'a>) -> Vec<(&'a ResidueView<'a>, char)> {
+        view.chains
+            .iter()
+            .flat_map(|c| &c.polymer)
+            .filter_map(|r| match r.residue() {
+                Residue::Nucleotide(n) => Some((r, self.nucleotide_code(n))),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The base pairs of the model at `model` (an index in `structure.models`), within and
+    /// between chains.
+    pub fn base_pairs(&self, model: usize) -> Vec<BasePair> {
+        let view = ModelView::new(
+            model,
+            &self.structure.models[model],
+            &self.model_info[model],
+        );
+        let bases: Vec<Base> = self
+            .nucleotides(&view)
+            .into_iter()
+            .filter_map(|(r, code)| Base::new(r, code))
+            .collect();
+        let mut pairs = Vec::new();
+        for (i, a) in bases.iter().enumerate() {
+            for b in &bases[i + 1..] {
+                if let Some(pair) = base_pair(a, b) {
+                    pairs.push(pair);
+                }
+            }
+        }
+        pairs
+    }
+
+    /// The secondary structure of the nucleic acid chains `chains` of the model at `model` in
+    /// dot-bracket notation, with the chains separated by `&`. Only canonical pairs are marked,
+    /// and a nucleotide in several of them keeps the one with the most hydrogen bonds.
+    pub fn dot_bracket(&self, model: usize, chains: &[char]) -> String {
+        let view = ModelView::new(
+            model,
+            &self.structure.models[model],
+            &self.model_info[model],
+        );
+        let mut keys = Vec::new();
+        let mut breaks = Vec::new();
+        for &chain in chains {
+            let residues = view
+                .chain(chain)
+                .into_iter()
+                .flat_map(|c| &c.polymer)
+                .filter(|r| matches!(r.residue(), Residue::Nucleotide(_)));
+            for r in residues {
+                keys.push((r.chain(), r.sequence_number(), r.insertion_code()));
+            }
+            breaks.push(keys.len());
+        }
+        let position = |b: &PairedBase| {
+            keys.iter()
+                .position(|&k| k == (b.chain, b.sequence_number, b.insertion_code))
+        };
+        let mut canonical: Vec<(usize, usize, usize)> = self
+            .base_pairs(model)
+            .iter()
+            .filter(|p| p.is_watson_crick() || p.is_wobble())
+            .filter_map(|p| Some((position(&p.a)?, position(&p.b)?, p.hydrogen_bonds.len())))
+            .collect();
+        canonical.sort_by_key(|&(i, j, bonds)| (std::cmp::Reverse(bonds), i, j));
+        let mut paired = vec![false; keys.len()];
+        let mut pairs = Vec::new();
+        for (i, j, _) in canonical {
+            if !paired[i] && !paired[j] {
+                paired[i] = true;
+                paired[j] = true;
+                pairs.push((i.min(j), i.max(j)));
+            }
+        }
+
+        let mut symbols = vec!['.'; keys.len()];
+        for (&(i, j), level) in pairs.iter().zip(bracket_levels(&pairs)) {
+            if let Some(level) = level {
+                symbols[i] = BRACKETS[level].0;
+                symbols[j] = BRACKETS[level].1;
+            }
+        }
+        let mut notation = String::new();
+        for (k, symbol) in symbols.into_iter().enumerate() {
+            if k > 0 && breaks.contains(&k) {
+                notation.push('&');
+            }
+            notation.push(symbol);
+        }
+        notation
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_pucker() {
+        // * ideal C3'-endo (A-form) and C2'-endo (B-form) riboses
+        let c3_endo = SugarPucker::from_torsions([3.9, -25.4, 37.0, -36.0, 20.6]);
+        assert_eq!(c3_endo.conformation, PuckerConformation::C3Endo);
+        assert!((c3_endo.phase - 13.0).abs() < 3.0, "{:?}", c3_endo);
+        assert!((c3_endo.amplitude - 38.0).abs() < 2.0, "{:?}", c3_endo);
+        let c2_endo = SugarPucker::from_torsions([-26.0, 36.0, -33.0, 19.0, 5.0]);
+        assert_eq!(c2_endo.conformation, PuckerConformation::C2Endo);
+        // * O4'-endo, where ν2 and cos P vanish
+        let o4_endo = SugarPucker::from_torsions([-36.1, 22.3, 0.0, -22.3, 36.1]);
+        assert_eq!(o4_endo.conformation, PuckerConformation::O4Endo);
+        assert!((o4_endo.phase - 90.0).abs() < 1.0, "{:?}", o4_endo);
+        assert!((o4_endo.amplitude - 38.0).abs() < 1.0, "{:?}", o4_endo);
+    }
+
+    #[test]
+    fn test_dominant_edge() {
+        use Edge::*;
+        // * A N6 and N7 in a Hoogsteen pair; G N2 and N3 in a sheared pair
+        assert_eq!(
+            dominant_edge(&[&[WatsonCrick, Hoogsteen], &[Hoogsteen]]),
+            Hoogsteen
+        );
+        assert_eq!(dominant_edge(&[&[WatsonCrick, Sugar], &[Sugar]]), Sugar);
+        assert_eq!(
+            dominant_edge(&[&[WatsonCrick, Hoogsteen], &[WatsonCrick]]),
+            WatsonCrick
+        );
+    }
+
+    #[test]
+    fn test_bracket_levels() {
+        // * a hairpin and a pseudoknot crossing it
+        let pairs = [(0, 9), (1, 8), (4, 12), (5, 11)];
+        assert_eq!(bracket_levels(&pairs), [Some(0), Some(0), Some(1), Some(1)]);
+        let nested = [(0, 5), (1, 4), (6, 8)];
+        assert_eq!(bracket_levels(&nested), [Some(0); 3]);
+    }
+}
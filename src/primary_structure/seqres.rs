@@ -33,18 +33,13 @@
 // //! | 68 - 70 | Residue name | resName  | Residue name.                                                                                                                     |
 
 use crate::{
-    common::parser::{jump_newline, parse_residue, parse_right},
+    common::parser::{jump_newline, parse_residue, parse_right, take_record_lines},
     types::{
         AminoAcid, Chain, ModifiedAminoAcidTable, ModifiedNucleotideTable, Nucleotide, ParseFw3,
         Residue,
     },
 };
-use nom::{
-    bytes::complete::take,
-    character::complete::{anychar, line_ending, not_line_ending},
-    combinator::map,
-    IResult,
-};
+use nom::{bytes::complete::take, character::complete::anychar, combinator::map, IResult};
 
 pub struct SeqResParser;
 impl SeqResParser {
@@ -66,15 +61,18 @@ impl SeqResParser {
         let mut chains_aa: Vec<Chain<AminoAcid>> = Vec::new();
         let mut chains_nuc: Vec<Chain<Nucleotide>> = Vec::new();
         while inp.len() > 0 {
-            let (new_inp, _) = Self::parse_chain(
+            match Self::parse_chain(
                 inp,
                 modified_aa,
                 modified_nuc,
                 &mut chains_aa,
                 &mut chains_nuc,
-            )
-            .unwrap(); // TODO: remove unwrap
-            inp = new_inp;
+            ) {
+                Ok((new_inp, _)) => inp = new_inp,
+                // * a chain whose records run out early, as in a truncated file, is left out
+                Err(nom::Err::Error((&[], _))) => break,
+                Err(e) => return Err(e.map_input(|_| ())),
+            }
         }
         Ok(((), (chains_aa, chains_nuc)))
     }
@@ -89,20 +87,31 @@ impl SeqResParser {
         chains_nuc: &mut Vec<Chain<Nucleotide>>,
     ) -> IResult<&'a [u8], ()> {
         // println!("{}", unsafe { std::str::from_utf8_unchecked(inp) });
-        let inp = &inp[5..]; // first line 7 - 11
+        let (inp, _) = take(5usize)(inp)?; // first line 7 - 11
         let (inp, chain) = anychar(inp)?; // first line 12
-        let inp = &inp[1..]; // first line 13
+        let (inp, _) = take(1usize)(inp)?; // first line 13
         let (inp, n) = parse_right::<u32>(inp, 4)?; // first line 14 - 17
-        let inp = &inp[2..]; // first line 18 - 19
+        let (inp, _) = take(2usize)(inp)?; // first line 18 - 19
         let lines = n / 13u32;
         let last_line_items = n % 13u32;
         let mut inp = inp;
 
-        let first_res = &inp[..3];
         //println!("{:?}", (chain as char, n, lines));
-        // try parse
-        match parse_residue(inp, modified_aa, modified_nuc)?.1 {
-            Residue::AminoAcid(_) => {
+        // * the chain is of the kind of its first residue known as an amino acid or a nucleotide;
+        // * modified residues are only known from MODRES records, which a truncated file may lack
+        let is_nucleotide = inp
+            .chunks(4)
+            .take(n.min(13) as usize)
+            .find_map(
+                |name| match parse_residue(name, modified_aa, modified_nuc) {
+                    Ok((_, Residue::AminoAcid(_))) => Some(false),
+                    Ok((_, Residue::Nucleotide(_))) => Some(true),
+                    _ => None,
+                },
+            )
+            .unwrap_or(false);
+        match is_nucleotide {
+            false => {
                 let mut aas: Vec<AminoAcid> = Vec::new();
                 for _i in 0..lines {
                     for _j in 0..13 {
@@ -127,7 +136,7 @@ impl SeqResParser {
                 });
                 return Ok((inp, ()));
             }
-            Residue::Nucleotide(_) => {
+            true => {
                 let mut nucs: Vec<Nucleotide> = Vec::new();
                 for _i in 0..lines {
                     for _j in 0..13 {
@@ -152,23 +161,16 @@ impl SeqResParser {
                 });
                 return Ok((inp, ()));
             }
-            _ => panic!(format!("Invalid residue in chain: {}", unsafe {
-                std::str::from_utf8_unchecked(first_res)
-            })),
         }
     }
+    /// Copies the SEQRES records, from column 7, into `buffer`. The last line of the input may
+    /// lack its line ending.
     pub fn buffer_seqres<'a>(inp: &'a [u8], buffer: &mut Vec<u8>) -> IResult<&'a [u8], ()> {
-        let (inp, first_line) = not_line_ending(inp)?;
-        let (mut inp, _) = line_ending(inp)?;
-        buffer.extend_from_slice(&first_line);
-        buffer.push(b'\n');
-        while inp[..6] == b"SEQRES"[..] {
-            let (new_inp, ln) = not_line_ending(inp)?;
-            let (new_inp, _) = line_ending(new_inp)?;
-            buffer.extend_from_slice(&ln[6..]);
+        let (lines_end, lines) = take_record_lines(inp, b"SEQRES")?;
+        for line in lines {
+            buffer.extend_from_slice(line);
             buffer.push(b'\n');
-            inp = new_inp;
         }
-        Ok((inp, ()))
+        Ok((lines_end, ()))
     }
 }
@@ -0,0 +1,373 @@
+// Copyright (c) 2020 Tianyi Shi
+//
+// This software is released under the MIT License.
+// https://opensource.org/licenses/MIT
+
+//! Solvent accessible surface area (SASA) by the method of Shrake & Rupley (1973).
+//!
+//! Each atom is represented by a sphere whose radius is its van der Waals radius plus that of the
+//! solvent probe. The area of the sphere is sampled by evenly distributed points, and the points
+//! that lie inside no other sphere are taken as accessible.
+//!
+//! Hydrogen atoms and waters are left out, as are alternate locations other than the first of
+//! each residue: they neither contribute area nor occlude other atoms. Their area is reported as
+//! zero.
+//!
+//! The relative accessibility of an amino acid residue is its area divided by the theoretical
+//! maximum of Tien et al. (2013).
+
+use crate::element::ChemicalElement;
+use crate::entry::Entry;
+use crate::geometry::{distance, Coord, NeighborSearch};
+use crate::hierarchy::{first_alt_locs, ModelView};
+use crate::types::{AminoAcid, Atom, Model, Residue, ResidueSerial, StandardAminoAcid};
+use std::f32::consts::PI;
+
+/// The radius of a water molecule in Å.
+pub const PROBE_RADIUS: f32 = 1.4;
+/// The radius assumed for atoms of unknown elements, in Å.
+const DEFAULT_RADIUS: f32 = 1.8;
+
+/// Parameters of the calculation.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShrakeRupley {
+    /// Radius of the solvent probe in Å
+    pub probe_radius: f32,
+    /// Number of points sampled on the sphere of each atom; 0 is taken as 1
+    pub points: usize,
+}
+
+impl Default for ShrakeRupley {
+    fn default() -> Self {
+        Self {
+            probe_radius: PROBE_RADIUS,
+            points: 100,
+        }
+    }
+}
+
+/// The accessible surface area of a residue.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResidueSasa {
+    pub chain: char,
+    pub sequence_number: ResidueSerial,
+    pub insertion_code: char,
+    pub residue_name: String,
+    /// Area in Å²
+    pub area: f32,
+    /// Area relative to the maximum for the residue type; `None` for residues other than
+    /// standard amino acids
+    pub relative: Option<f32>,
+}
+
+/// The accessible surface area of a model.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sasa {
+    /// Area of each atom in Å², in the order of `Model::atoms`
+    pub atoms: Vec<f32>,
+    pub residues: Vec<ResidueSasa>,
+    /// Area of each chain in Å², in the order in which the chains first appear
+    pub chains: Vec<(char, f32)>,
+    pub total: f32,
+}
+
+/// The area buried between two chains.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Interface {
+    pub chains: (char, char),
+    /// The sum of the areas of the chains on their own less the area of the pair, in Å²
+    pub buried_area: f32,
+}
+
+/// The maximum accessible surface area of each standard amino acid in Å² (theoretical values of
+/// Tien et al., 2013).
+pub fn max_asa(aa: &StandardAminoAcid) -> Option<f32> {
+    use StandardAminoAcid::*;
+    Some(match aa {
+        Ala => 129.0,
+        Arg => 274.0,
+        Asn => 195.0,
+        Asp => 193.0,
+        Cys | Sec => 167.0,
+        Gln => 225.0,
+        Glu => 223.0,
+        Gly => 104.0,
+        His => 224.0,
+        Ile => 197.0,
+        Leu => 201.0,
+        Lys => 236.0,
+        Met | Mse => 224.0,
+        Phe => 240.0,
+        Pro => 159.0,
+        Ser => 155.0,
+        Thr => 172.0,
+        Trp => 285.0,
+        Tyr => 263.0,
+        Val => 174.0,
+        Pyl => return None,
+    })
+}
+
+/// Points evenly distributed on the unit sphere, along a golden-section spiral.
+fn sphere_points(n: usize) -> Vec<Coord> {
+    let increment = PI * (3.0 - 5f32.sqrt());
+    let offset = 2.0 / n as f32;
+    (0..n)
+        .map(|k| {
+            let y = k as f32 * offset - 1.0 + offset / 2.0;
+            let r = (1.0 - y * y).sqrt();
+            let phi = k as f32 * increment;
+            [phi.cos() * r, y, phi.sin() * r]
+        })
+        .collect()
+}
+
+/// The radius of each atom that takes part in the calculation.
+fn radii(atoms: &[Atom], element: impl Fn(usize) -> Option<ChemicalElement>) -> Vec<Option<f32>> {
+    first_alt_locs(atoms)
+        .into_iter()
+        .enumerate()
+        .map(|(i, first)| {
+            let element = element(i);
+            if !first
+                || matches!(atoms[i].residue, Residue::Water)
+                || element.map(|e| e.is_hydrogen()) == Some(true)
+            {
+                None
+            } else {
+                Some(element.map_or(DEFAULT_RADIUS, |e| e.vdw_radius()))
+            }
+        })
+        .collect()
+}
+
+impl ShrakeRupley {
+    /// The accessible area of each atom in Å². Atoms whose radius is `None` are left out.
+    pub fn atom_areas(&self, coords: &[Coord], radii: &[Option<f32>]) -> Vec<f32> {
+        let points = sphere_points(self.points.max(1));
+        let expanded: Vec<Option<f32>> = radii
+            .iter()
+            .map(|r| r.map(|r| r + self.probe_radius))
+            .collect();
+        let max_radius = expanded.iter().filter_map(|&r| r).fold(0f32, f32::max);
+        if max_radius == 0.0 {
+            return vec![0.0; coords.len()];
+        }
+        let search = NeighborSearch::with_indices(
+            coords,
+            (0..coords.len()).filter(|&i| expanded[i].is_some()),
+            2.0 * max_radius,
+        );
+
+        coords
+            .iter()
+            .enumerate()
+            .map(|(i, c)| {
+                let ri = match expanded[i] {
+                    Some(r) => r,
+                    None => return 0.0,
+                };
+                let neighbours: Vec<(Coord, f32)> = search
+                    .within(c, ri + max_radius)
+                    .into_iter()
+                    .filter_map(|j| {
+                        let rj = expanded[j].unwrap();
+                        if j != i && distance(c, &coords[j]) < ri + rj {
+                            Some((coords[j], rj * rj))
+                        } else {
+                            None
+                        }
+                    })
+                    .collect();
+                // * the last occluding neighbour is tried first, as it is likely to occlude the
+                // * next point as well
+                let mut last = 0;
+                let accessible = points
+                    .iter()
+                    .filter(|p| {
+                        let q = [c[0] + ri * p[0], c[1] + ri * p[1], c[2] + ri * p[2]];
+                        let occludes = |&(n, r2): &(Coord, f32)| {
+                            let d = [q[0] - n[0], q[1] - n[1], q[2] - n[2]];
+                            d[0] * d[0] + d[1] * d[1] + d[2] * d[2] < r2
+                        };
+                        if neighbours.get(last).map(occludes) == Some(true) {
+                            return false;
+                        }
+                        match neighbours.iter().position(occludes) {
+                            Some(k) => {
+                                last = k;
+                                false
+                            }
+                            None => true,
+                        }
+                    })
+                    .count();
+                4.0 * PI * ri * ri * accessible as f32 / points.len() as f32
+            })
+            .collect()
+    }
+
+    /// The accessible area of each atom of a model in Å², with elements taken from
+    /// `Atom::element`, or inferred from the atom name where it is unknown.
+    pub fn model(&self, model: &Model) -> Vec<f32> {
+        let atoms = &model.atoms;
+        let radii = radii(atoms, |i| {
+            ChemicalElement::from_element(atoms[i].element)
+                .or_else(|| ChemicalElement::from_atom_name(&atoms[i].name.0, &atoms[i].residue))
+        });
+        let coords: Vec<Coord> = atoms.iter().map(|a| a.coord).collect();
+        self.atom_areas(&coords, &radii)
+    }
+}
+
+impl Entry {
+    /// The solvent accessible surface area of the model at `model` (an index in
+    /// `structure.models`), with the default probe radius of 1.4 Å.
+    pub fn sasa(&self, model: usize) -> Sasa {
+        self.sasa_with(model, &ShrakeRupley::default())
+    }
+
+    pub fn sasa_with(&self, model: usize, params: &ShrakeRupley) -> Sasa {
+        let atoms = self.model_radii(model);
+        let coords: Vec<Coord> = self.structure.models[model]
+            .atoms
+            .iter()
+            .map(|a| a.coord)
+            .collect();
+        let areas = params.atom_areas(&coords, &atoms);
+
+        let view = ModelView::new(
+            model,
+            &self.structure.models[model],
+            &self.model_info[model],
+        );
+        let mut residues = Vec::new();
+        let mut chains: Vec<(char, f32)> = Vec::new();
+        for chain in &view.chains {
+            let mut chain_area = 0.0;
+            for residue in chain.residues() {
+                let offset = residue.offset;
+                let area: f32 = areas[offset..offset + residue.atoms.len()].iter().sum();
+                chain_area += area;
+                let relative = match residue.residue() {
+                    Residue::AminoAcid(AminoAcid::Standard(aa)) => max_asa(aa).map(|m| area / m),
+                    _ => None,
+                };
+                residues.push(ResidueSasa {
+                    chain: residue.chain(),
+                    sequence_number: residue.sequence_number(),
+                    insertion_code: residue.insertion_code(),
+                    residue_name: residue.name(),
+                    area,
+                    relative,
+                });
+            }
+            chains.push((chain.id, chain_area));
+        }
+        Sasa {
+            total: areas.iter().sum(),
+            atoms: areas,
+            residues,
+            chains,
+        }
+    }
+
+    fn model_radii(&self, model: usize) -> Vec<Option<f32>> {
+        radii(&self.structure.models[model].atoms, |i| {
+            self.element(model, i)
+        })
+    }
+
+    /// The area buried between chains `a` and `b` of the model at `model`.
+    pub fn buried_surface_area(&self, model: usize, a: char, b: char) -> f32 {
+        self.buried_area_with(model, a, b, &ShrakeRupley::default())
+    }
+
+    fn buried_area_with(&self, model: usize, a: char, b: char, params: &ShrakeRupley) -> f32 {
+        let atoms = &self.structure.models[model].atoms;
+        let radii = self.model_radii(model);
+        let coords: Vec<Coord> = atoms.iter().map(|a| a.coord).collect();
+        let area_of = |chains: &[char]| -> f32 {
+            let radii: Vec<Option<f32>> = radii
+                .iter()
+                .zip(atoms)
+                .map(|(&r, atom)| r.filter(|_| chains.contains(&atom.chain)))
+                .collect();
+            params.atom_areas(&coords, &radii).iter().sum()
+        };
+        area_of(&[a]) + area_of(&[b]) - area_of(&[a, b])
+    }
+
+    /// The area buried between each pair of chains of the model at `model` that are in contact.
+    pub fn interfaces(&self, model: usize) -> Vec<Interface> {
+        let params = ShrakeRupley::default();
+        let atoms = &self.structure.models[model].atoms;
+        let radii = self.model_radii(model);
+        let mut chains: Vec<char> = Vec::new();
+        for atom in atoms {
+            if !chains.contains(&atom.chain) {
+                chains.push(atom.chain);
+            }
+        }
+        // * two atoms can only bury each other's surface if their expanded spheres overlap
+        let reach =
+            2.0 * (radii.iter().filter_map(|&r| r).fold(0f32, f32::max) + params.probe_radius);
+        let in_contact = |a: char, b: char| {
+            let of = |c: char| {
+                atoms
+                    .iter()
+                    .zip(&radii)
+                    .filter(move |(atom, r)| atom.chain == c && r.is_some())
+                    .map(|(atom, _)| atom.coord)
+            };
+            of(a).any(|p| {
+                of(b).any(|q| {
+                    let d = [p[0] - q[0], p[1] - q[1], p[2] - q[2]];
+                    d[0] * d[0] + d[1] * d[1] + d[2] * d[2] < reach * reach
+                })
+            })
+        };
+        let mut interfaces = Vec::new();
+        for (k, &a) in chains.iter().enumerate() {
+            for &b in &chains[k + 1..] {
+                if in_contact(a, b) {
+                    interfaces.push(Interface {
+                        chains: (a, b),
+                        buried_area: self.buried_area_with(model, a, b, &params),
+                    });
+                }
+            }
+        }
+        interfaces
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_atom_areas() {
+        let sr = ShrakeRupley {
+            probe_radius: 1.4,
+            points: 1000,
+        };
+        // * an isolated sphere
+        let area = sr.atom_areas(&[[0.0, 0.0, 0.0]], &[Some(1.6)])[0];
+        assert!((area - 4.0 * PI * 9.0).abs() < 1e-2);
+        // * two overlapping spheres of radius 3 whose centres are 3 Å apart each lose a cap of
+        // * height 1.5, i.e. a quarter of their area
+        let areas = sr.atom_areas(&[[0.0, 0.0, 0.0], [3.0, 0.0, 0.0]], &[Some(1.6), Some(1.6)]);
+        assert!((areas[0] - 0.75 * 4.0 * PI * 9.0).abs() < 2.0);
+        // * atoms left out neither contribute nor occlude
+        let areas = sr.atom_areas(&[[0.0, 0.0, 0.0], [3.0, 0.0, 0.0]], &[Some(1.6), None]);
+        assert!((areas[0] - 4.0 * PI * 9.0).abs() < 1e-2);
+        assert_eq!(areas[1], 0.0);
+        // * no points at all still give a finite area
+        let sr = ShrakeRupley { points: 0, ..sr };
+        assert!(sr.atom_areas(&[[0.0, 0.0, 0.0]], &[Some(1.6)])[0].is_finite());
+    }
+}
@@ -3,13 +3,52 @@
 // This software is released under the MIT License.
 // https://opensource.org/licenses/MIT
 
+use crate::common::hybrid36;
 use crate::common::parser::FieldParser;
-use crate::common::parser::{jump_newline, parse_right, take_trim_start_own};
+use crate::common::parser::{columns, jump_newline, parse_right, take_trim_start_own};
+use crate::dssp::{Dssp, SecondaryStructure};
+use crate::entry::Entry;
+use crate::hierarchy::{ChainView, ModelView, ResidueView};
 use crate::types::{
-    AtomName, Helix, HelixClass, ParseFw4, Registration, ResidueSerial, SecondaryStructureSerial,
-    Sense, Sheet, Ssbond, Strand,
+    AtomName, Helix, HelixClass, ParseFw4, Registration, Residue, ResidueSerial,
+    SecondaryStructureSerial, Sense, Sheet, Ssbond, Strand,
 };
-use nom::{bytes::complete::take, character::complete::anychar, combinator::map, IResult};
+use nom::{
+    bytes::complete::take,
+    character::complete::{anychar, line_ending, not_line_ending},
+    combinator::map,
+    IResult,
+};
+
+/// The name and insertion code of a residue that a HELIX or SHEET record refers to, which `Helix`,
+/// `Strand` and `Registration` leave out.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ResidueDetails {
+    pub name: String,
+    pub insertion_code: char,
+}
+
+/// The fields of a HELIX record that `Helix` has no place for.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct HelixDetails {
+    pub start: ResidueDetails,
+    pub end: ResidueDetails,
+    /// The length declared in columns 72 - 76
+    pub length: Option<u32>,
+}
+
+/// The fields of a SHEET record that `Strand` and `Registration` have no place for.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct StrandDetails {
+    pub start: ResidueDetails,
+    pub end: ResidueDetails,
+    /// The insertion codes of the residues in the current and the previous strand of the
+    /// registration. `None` for the first strand of a sheet.
+    pub registration_insertion_codes: Option<(char, char)>,
+}
 
 /// # Overview
 ///
@@ -63,21 +102,38 @@ pub struct HelixParser;
 impl FieldParser for HelixParser {
     type Output = Helix;
     fn parse(inp: &[u8]) -> IResult<&[u8], Self::Output> {
+        let (inp, (helix, _)) = Self::parse_with_details(inp)?;
+        Ok((inp, helix))
+    }
+}
+
+impl HelixParser {
+    /// Also returns the residue names, insertion codes and length, which `Helix` leaves out.
+    pub fn parse_with_details(inp: &[u8]) -> IResult<&[u8], (Helix, HelixDetails)> {
         let inp = &inp[5..]; // 7; 8 - 10; 11
         let (inp, id) = take(3usize)(inp)?; // 12 - 14
-        let inp = &inp[5..]; // 15; 16 - 18; 19
+        let inp = &inp[1..]; // 15
+        let (inp, start_name) = unsafe { take_trim_start_own(inp, 3usize)? }; // 16 - 18
+        let inp = &inp[1..]; // 19
         let (inp, start_chain) = anychar(inp)?; // 20
         let inp = &inp[1..]; // 21
         let (inp, start_serial) = parse_right::<ResidueSerial>(inp, 4)?; // 22 - 25
-        let (inp, _start_icode) = anychar(inp)?; // 26
-        let inp = &inp[5..]; // 27; 28 - 30; 31
+        let (inp, start_icode) = anychar(inp)?; // 26
+        let inp = &inp[1..]; // 27
+        let (inp, end_name) = unsafe { take_trim_start_own(inp, 3usize)? }; // 28 - 30
+        let inp = &inp[1..]; // 31
         let (inp, end_chain) = anychar(inp)?; // 32
         let inp = &inp[1..]; // 33
         let (inp, end_serial) = parse_right::<ResidueSerial>(inp, 4)?; // 34 - 37
-        let (inp, _end_icode) = anychar(inp)?; // 38
+        let (inp, end_icode) = anychar(inp)?; // 38
         let (inp, class) = Self::parse_helix_class(inp)?; // 39 - 40
         let (inp, comment) = take(30usize)(inp)?; // 41 - 70
-        let (inp, _) = jump_newline(inp)?;
+        let (inp, rest) = not_line_ending(inp)?; // 71 - 80, which may be left out
+        let (inp, _) = line_ending(inp)?;
+        let length = rest
+            .get(1..rest.len().min(6))
+            .and_then(|x| std::str::from_utf8(x).ok())
+            .and_then(|x| x.trim().parse().ok());
         let helix = Helix {
             id: unsafe { std::str::from_utf8_unchecked(id).trim().to_owned() },
             class,
@@ -85,17 +141,26 @@ impl FieldParser for HelixParser {
             end: (end_chain, end_serial),
             comment: unsafe { std::str::from_utf8_unchecked(comment).trim().to_owned() },
         };
-        Ok((inp, helix))
+        let details = HelixDetails {
+            start: ResidueDetails {
+                name: start_name,
+                insertion_code: start_icode,
+            },
+            end: ResidueDetails {
+                name: end_name,
+                insertion_code: end_icode,
+            },
+            length,
+        };
+        Ok((inp, (helix, details)))
     }
-}
 
-impl HelixParser {
     pub fn parse_helix_class(inp: &[u8]) -> IResult<&[u8], HelixClass> {
         use HelixClass::*;
         let (inp, code) = parse_right::<usize>(inp, 2)?;
 
-        let class = if code < 11 {
-            [
+        let class = match code {
+            1..=10 => [
                 RightHandedAlpha,
                 RightHandedOmega,
                 RightHandedPi,
@@ -106,9 +171,8 @@ impl HelixParser {
                 LeftHandedGamma,
                 TwoSevenRibbonHelix,
                 Polyproline,
-            ][code]
-        } else {
-            Unknown
+            ][code - 1],
+            _ => Unknown,
         };
         Ok((inp, class))
     }
@@ -169,72 +233,89 @@ pub struct SheetParser;
 impl FieldParser for SheetParser {
     type Output = Sheet;
     fn parse(inp: &[u8]) -> IResult<&[u8], Self::Output> {
-        Self::parse_sheet(inp)
+        let (inp, (sheet, _)) = Self::parse_with_details(inp)?;
+        Ok((inp, sheet))
     }
 }
 
 impl SheetParser {
-    fn parse_sheet(inp: &[u8]) -> IResult<&[u8], Sheet> {
+    /// Also returns the residue names and insertion codes of each strand, which `Strand` and
+    /// `Registration` leave out.
+    pub fn parse_with_details(inp: &[u8]) -> IResult<&[u8], (Sheet, Vec<StrandDetails>)> {
         let mut sheet = Sheet::default();
+        let mut details = Vec::new();
         // first line
         let inp = &inp[5..]; // 7 - 11
         let (inp, id) = unsafe { take_trim_start_own(inp, 3usize)? }; // 12 - 14
         sheet.id = id;
         let (inp, num_strands) = parse_right::<SecondaryStructureSerial>(inp, 2)?; // 15 - 16
         let inp = &inp[1..]; // 17
-        let (inp, first_strand) = Self::parse_first_line(inp)?;
+        let (inp, (first_strand, first_details)) = Self::parse_first_line(inp)?;
         sheet.strands.push(first_strand);
+        details.push(first_details);
         let mut i = 1 as SecondaryStructureSerial;
         let mut last_inp = inp;
-        while i < num_strands {
+        // * a sheet cut short by the end of the input keeps the strands read so far
+        while i < num_strands && last_inp.starts_with(b"SHEET ") {
             let (inp, _) = take(7usize)(last_inp)?; // 1 - 7
             let (inp, idx) = parse_right::<SecondaryStructureSerial>(inp, 3)?; // 8 - 10
             i = idx;
             let inp = &inp[7..]; // 11 - 17
-            let (inp, (strand, registration)) = Self::parse_line(inp)?;
+            let (inp, (strand, strand_details, registration)) = Self::parse_line(inp)?;
             sheet.strands.push(strand);
             sheet.registration.push(registration);
+            details.push(strand_details);
             last_inp = inp;
         }
-        Ok((last_inp, sheet))
+        Ok((last_inp, (sheet, details)))
     }
 
-    fn parse_first_line(inp: &[u8]) -> IResult<&[u8], Strand> {
+    fn parse_first_line(inp: &[u8]) -> IResult<&[u8], (Strand, StrandDetails)> {
         let (inp, res) = Self::parse_strand(inp)?;
         let (inp, _) = jump_newline(inp)?;
         Ok((inp, res))
     }
 
-    fn parse_line(inp: &[u8]) -> IResult<&[u8], (Strand, Registration)> {
-        let (inp, strand) = Self::parse_strand(inp)?;
+    fn parse_line(inp: &[u8]) -> IResult<&[u8], (Strand, StrandDetails, Registration)> {
+        let (inp, (strand, mut details)) = Self::parse_strand(inp)?;
         let inp = &inp[1..];
-        let (inp, registration) = Self::parse_registration(inp)?;
-        Ok((inp, (strand, registration)))
+        let (inp, (registration, icodes)) = Self::parse_registration(inp)?;
+        details.registration_insertion_codes = Some(icodes);
+        Ok((inp, (strand, details, registration)))
     }
 
-    fn parse_strand(inp: &[u8]) -> IResult<&[u8], Strand> {
-        // let (inp, _start_res) = map(take(3usize), parse_amino_acid)(inp)?;
-        let inp = &inp[3..]; // 18 - 20
+    fn parse_strand(inp: &[u8]) -> IResult<&[u8], (Strand, StrandDetails)> {
+        let (inp, start_name) = unsafe { take_trim_start_own(inp, 3usize)? }; // 18 - 20
         let inp = &inp[1..]; //           21
         let (inp, start_chain) = anychar(inp)?; // 22
         let (inp, start_serial) = parse_right::<ResidueSerial>(inp, 4)?; // 23 - 26
-        let (inp, _start_icode) = anychar(inp)?; // 27
+        let (inp, start_icode) = anychar(inp)?; // 27
         let inp = &inp[1..]; // 28
-                             // let (inp, _end_res) = map(take(3usize), parse_amino_acid)(inp)?;
-        let inp = &inp[3..]; // 29 - 31
+        let (inp, end_name) = unsafe { take_trim_start_own(inp, 3usize)? }; // 29 - 31
         let inp = &inp[1..]; //      32
         let (inp, end_chain) = anychar(inp)?; // 33
         let (inp, end_serial) = parse_right::<ResidueSerial>(inp, 4)?; // 34 - 37
-        let (inp, _end_icode) = anychar(inp)?; // 38
+        let (inp, end_icode) = anychar(inp)?; // 38
         let (inp, sense) = Self::parse_sense(inp)?;
         let strand = Strand {
             start: (start_chain, start_serial),
             end: (end_chain, end_serial),
             sense,
         };
-        Ok((inp, strand))
+        let details = StrandDetails {
+            start: ResidueDetails {
+                name: start_name,
+                insertion_code: start_icode,
+            },
+            end: ResidueDetails {
+                name: end_name,
+                insertion_code: end_icode,
+            },
+            registration_insertion_codes: None,
+        };
+        Ok((inp, (strand, details)))
     }
-    fn parse_registration(inp: &[u8]) -> IResult<&[u8], Registration> {
+    fn parse_registration(inp: &[u8]) -> IResult<&[u8], (Registration, (char, char))> {
         // | 42 - 45 | Atom         | curAtom     | Registration.  Atom name in current strand.       |
         // | 46 - 48 | Residue name | curResName  | Registration.  Residue name in current strand     |
         // | 50      | Character    | curChainId  | Registration. Chain identifier in                 |
@@ -255,17 +336,21 @@ impl SheetParser {
         let inp = &inp[4..]; // 46 - 48; 49
         let (inp, cur_chain) = anychar(inp)?; // 50
         let (inp, cur_serial) = parse_right::<ResidueSerial>(inp, 4)?; // 51 - 54
-        let inp = &inp[2..]; // 55; 56
+        let (inp, cur_icode) = anychar(inp)?; // 55
+        let inp = &inp[1..]; // 56
         let (inp, prev_atom) = map(take(4usize), AtomName::parse_fw4)(inp)?; // 57 - 60
         let inp = &inp[4..]; // 61 - 63; 64
         let (inp, prev_chain) = anychar(inp)?; // 65
         let (inp, prev_serial) = parse_right::<ResidueSerial>(inp, 4)?; // 66 - 69
-        let (inp, _) = jump_newline(inp)?;
+                                                                        // * column 70 onwards may be left out
+        let (inp, rest) = not_line_ending(inp)?;
+        let (inp, _) = line_ending(inp)?;
+        let prev_icode = rest.first().map_or(' ', |&c| c as char); // 70
         let registration = Registration {
             curr: (cur_atom, cur_chain, cur_serial),
             prev: (prev_atom, prev_chain, prev_serial),
         };
-        Ok((inp, registration))
+        Ok((inp, (registration, (cur_icode, prev_icode))))
     }
 
     fn parse_sense(inp: &[u8]) -> IResult<&[u8], Sense> {
@@ -355,3 +440,431 @@ impl FieldParser for SsbondParser {
         ))
     }
 }
+
+/// One end of a LINK record.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkAtom {
+    /// The atom name, without leading and trailing spaces
+    pub name: String,
+    pub alt_loc: char,
+    pub residue_name: String,
+    pub chain: char,
+    pub sequence_number: ResidueSerial,
+    pub insertion_code: char,
+    /// The symmetry operator applied to the residue, such as `1555`
+    pub symmetry: String,
+}
+
+/// # LINK
+///
+/// The [LINK](http://www.wwpdb.org/documentation/file-format-content/format33/sect6.html#LINK)
+/// records specify connectivity between residues that is not implied by the primary structure.
+/// Connectivity is expressed in terms of the atom names. They also include the distance
+/// associated with each linkage following the symmetry operations at the end of each record.
+///
+/// ## Record Format
+///
+/// | COLUMNS | DATA  TYPE  | FIELD    | DEFINITION                        |
+/// | ------- | ----------- | -------- | --------------------------------- |
+/// | 1 -  6  | Record name | "LINK  " |                                   |
+/// | 13 - 16 | Atom        | name1    | Atom name.                        |
+/// | 17      | Character   | altLoc1  | Alternate location indicator.     |
+/// | 18 - 20 | Residue name| resName1 | Residue  name.                    |
+/// | 22      | Character   | chainID1 | Chain identifier.                 |
+/// | 23 - 26 | Integer     | resSeq1  | Residue sequence number.          |
+/// | 27      | AChar       | iCode1   | Insertion code.                   |
+/// | 43 - 46 | Atom        | name2    | Atom name.                        |
+/// | 47      | Character   | altLoc2  | Alternate location indicator.     |
+/// | 48 - 50 | Residue name| resName2 | Residue name.                     |
+/// | 52      | Character   | chainID2 | Chain identifier.                 |
+/// | 53 - 56 | Integer     | resSeq2  | Residue sequence number.          |
+/// | 57      | AChar       | iCode2   | Insertion code.                   |
+/// | 60 - 65 | SymOP       | sym1     | Symmetry operator atom 1.         |
+/// | 67 - 72 | SymOP       | sym2     | Symmetry operator atom 2.         |
+/// | 74 – 78 | Real(5.2)   | Length   | Link distance                     |
+///
+/// ## Details
+///
+/// - The atoms involved in bonds between HET groups or between a HET group and standard residue
+///   are listed.
+/// - Interresidue linkages not implied by the primary structure are listed.
+/// - Symmetry operators are given even when the identity operator is to be applied.
+///
+/// Example
+///
+/// ```ignore
+///          1         2         3         4         5         6         7         8
+/// 12345678901234567890123456789012345678901234567890123456789012345678901234567890
+/// LINK         O   GLY A  49                NA    NA A6001     1555   1555  2.98
+/// LINK         ND2 ASN A  17                C1  NAG A 701     1555   1555  1.45
+/// ```
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Link {
+    pub a: LinkAtom,
+    pub b: LinkAtom,
+    /// The link distance in Å, if given
+    pub length: Option<f32>,
+}
+
+pub struct LinkParser;
+
+impl FieldParser for LinkParser {
+    type Output = Link;
+    fn parse(inp: &[u8]) -> IResult<&[u8], Link> {
+        let (rest, line) = not_line_ending(inp)?;
+        let (rest, _) = line_ending(rest)?;
+        let character = |column: usize| line.get(column - 7).map_or(' ', |&c| c as char);
+        let end = |offset: usize, symmetry: usize| {
+            Some(LinkAtom {
+                name: columns(line, 13 + offset, 16 + offset).to_owned(),
+                alt_loc: character(17 + offset),
+                residue_name: columns(line, 18 + offset, 20 + offset).to_owned(),
+                chain: character(22 + offset),
+                sequence_number: hybrid36::decode(columns(line, 23 + offset, 26 + offset), 4)?,
+                insertion_code: character(27 + offset),
+                symmetry: columns(line, symmetry, symmetry + 5).to_owned(),
+            })
+        };
+        // * a link to a residue whose number cannot be read would attach to the wrong residue
+        match (end(0, 60), end(30, 67)) {
+            (Some(a), Some(b)) => Ok((
+                rest,
+                Link {
+                    a,
+                    b,
+                    length: columns(line, 74, 78).parse().ok(),
+                },
+            )),
+            _ => Err(nom::Err::Error((inp, nom::error::ErrorKind::Digit))),
+        }
+    }
+}
+
+/// A residue at one end of a helix or strand.
+struct SpanEnd<'a> {
+    chain: char,
+    sequence_number: ResidueSerial,
+    details: Option<&'a ResidueDetails>,
+}
+
+impl<'a> SpanEnd<'a> {
+    fn insertion_code(&self) -> char {
+        self.details.map_or(' ', |d| d.insertion_code)
+    }
+
+    fn find(&self, residues: &[&ResidueView]) -> Option<usize> {
+        residues.iter().position(|r| {
+            r.chain() == self.chain
+                && r.sequence_number() == self.sequence_number
+                && r.insertion_code() == self.insertion_code()
+        })
+    }
+}
+
+/// A helix or strand as declared by a HELIX or SHEET record.
+struct Span<'a> {
+    record: &'static str,
+    id: String,
+    state: SecondaryStructure,
+    start: SpanEnd<'a>,
+    end: SpanEnd<'a>,
+    length: Option<u32>,
+}
+
+/// The secondary structure of the amino acid residues of a chain.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainSecondaryStructure {
+    pub chain: char,
+    /// The sequence number and insertion code of each residue
+    pub residues: Vec<(ResidueSerial, char)>,
+    pub states: Vec<SecondaryStructure>,
+}
+
+impl ChainSecondaryStructure {
+    /// The DSSP code of each residue, with `-` for coil.
+    pub fn eight_state(&self) -> String {
+        self.states
+            .iter()
+            .map(|s| match s.code() {
+                ' ' => '-',
+                c => c,
+            })
+            .collect()
+    }
+
+    /// `H` for helices (H, G and I), `E` for strands and bridges (E and B), and `C` for the rest.
+    pub fn three_state(&self) -> String {
+        self.states.iter().map(|s| s.three_state()).collect()
+    }
+}
+
+impl Dssp {
+    /// The assignment of each chain.
+    pub fn chains(&self) -> Vec<ChainSecondaryStructure> {
+        let mut chains: Vec<ChainSecondaryStructure> = Vec::new();
+        for r in &self.residues {
+            let chain = match chains.last_mut() {
+                Some(chain) if chain.chain == r.chain => chain,
+                _ => {
+                    chains.push(ChainSecondaryStructure {
+                        chain: r.chain,
+                        residues: Vec::new(),
+                        states: Vec::new(),
+                    });
+                    chains.last_mut().unwrap()
+                }
+            };
+            chain.residues.push((r.sequence_number, r.insertion_code));
+            chain.states.push(r.structure);
+        }
+        chains
+    }
+}
+
+/// A disagreement between a HELIX or SHEET record and the coordinates.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SecondaryStructureIssue {
+    /// A residue at an end of a helix or strand has no coordinates.
+    MissingResidue {
+        record: String,
+        id: String,
+        chain: char,
+        sequence_number: ResidueSerial,
+        insertion_code: char,
+    },
+    /// The residue name on the record differs from that of the coordinates.
+    ResidueNameMismatch {
+        record: String,
+        id: String,
+        chain: char,
+        sequence_number: ResidueSerial,
+        insertion_code: char,
+        declared: String,
+        found: String,
+    },
+    /// The length on a HELIX record differs from the number of residues from its first to its
+    /// last.
+    HelixLength {
+        id: String,
+        declared: u32,
+        found: u32,
+    },
+}
+
+impl std::fmt::Display for SecondaryStructureIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::MissingResidue {
+                record,
+                id,
+                chain,
+                sequence_number,
+                insertion_code,
+            } => write!(
+                f,
+                "{} {} refers to residue {}{}{} which has no coordinates",
+                record,
+                id,
+                chain,
+                sequence_number,
+                insertion_code.to_string().trim()
+            ),
+            Self::ResidueNameMismatch {
+                record,
+                id,
+                chain,
+                sequence_number,
+                insertion_code,
+                declared,
+                found,
+            } => write!(
+                f,
+                "{} {} names residue {}{}{} {} but the coordinates have {}",
+                record,
+                id,
+                chain,
+                sequence_number,
+                insertion_code.to_string().trim(),
+                declared,
+                found
+            ),
+            Self::HelixLength {
+                id,
+                declared,
+                found,
+            } => write!(
+                f,
+                "HELIX {} declares a length of {} but spans {} residues",
+                id, declared, found
+            ),
+        }
+    }
+}
+
+/// The residue state that a helix class corresponds to. Classes without a DSSP code count as α
+/// helices.
+fn helix_state(class: HelixClass) -> SecondaryStructure {
+    match class {
+        HelixClass::RightHanded310 => SecondaryStructure::Helix310,
+        HelixClass::RightHandedPi => SecondaryStructure::PiHelix,
+        _ => SecondaryStructure::AlphaHelix,
+    }
+}
+
+fn amino_acids<'a, 'b>(chain: &'b ChainView<'a>) -> Vec<&'b ResidueView<'a>> {
+    chain
+        .polymer
+        .iter()
+        .filter(|r| matches!(r.residue(), Residue::AminoAcid(_)))
+        .collect()
+}
+
+impl Entry {
+    fn spans(&self) -> Vec<Span<'_>> {
+        let mut spans = Vec::new();
+        for (sheet, details) in self.structure.sheets.iter().zip(
+            self.sheet_details
+                .iter()
+                .map(Some)
+                .chain(std::iter::repeat(None)),
+        ) {
+            for (k, strand) in sheet.strands.iter().enumerate() {
+                let details = details.and_then(|d| d.get(k));
+                spans.push(Span {
+                    record: "SHEET",
+                    id: format!("{} strand {}", sheet.id, k + 1),
+                    state: SecondaryStructure::Strand,
+                    start: SpanEnd {
+                        chain: strand.start.0,
+                        sequence_number: strand.start.1,
+                        details: details.map(|d| &d.start),
+                    },
+                    end: SpanEnd {
+                        chain: strand.end.0,
+                        sequence_number: strand.end.1,
+                        details: details.map(|d| &d.end),
+                    },
+                    length: None,
+                });
+            }
+        }
+        for (k, helix) in self.structure.helices.iter().enumerate() {
+            let details = self.helix_details.get(k);
+            spans.push(Span {
+                record: "HELIX",
+                id: helix.id.clone(),
+                state: helix_state(helix.class),
+                start: SpanEnd {
+                    chain: helix.start.0,
+                    sequence_number: helix.start.1,
+                    details: details.map(|d| &d.start),
+                },
+                end: SpanEnd {
+                    chain: helix.end.0,
+                    sequence_number: helix.end.1,
+                    details: details.map(|d| &d.end),
+                },
+                length: details.and_then(|d| d.length),
+            });
+        }
+        spans
+    }
+
+    /// The secondary structure of the amino acid residues of each chain of the model at `model`
+    /// (an index in `structure.models`), as declared by the HELIX and SHEET records. Helices take
+    /// precedence over strands where the two overlap.
+    pub fn secondary_structure(&self, model: usize) -> Vec<ChainSecondaryStructure> {
+        let view = ModelView::new(
+            model,
+            &self.structure.models[model],
+            &self.model_info[model],
+        );
+        let spans = self.spans();
+        view.chains
+            .iter()
+            .map(amino_acids)
+            .filter(|residues| !residues.is_empty())
+            .map(|residues| {
+                let mut states = vec![SecondaryStructure::Coil; residues.len()];
+                for span in &spans {
+                    if let (Some(start), Some(end)) =
+                        (span.start.find(&residues), span.end.find(&residues))
+                    {
+                        for state in states.iter_mut().take(end + 1).skip(start) {
+                            *state = span.state;
+                        }
+                    }
+                }
+                ChainSecondaryStructure {
+                    chain: residues[0].chain(),
+                    residues: residues
+                        .iter()
+                        .map(|r| (r.sequence_number(), r.insertion_code()))
+                        .collect(),
+                    states,
+                }
+            })
+            .collect()
+    }
+
+    /// Compares the HELIX and SHEET records with the coordinates of the first model: that the
+    /// residues at the ends of each helix and strand exist and bear the declared names, and that
+    /// each helix spans the declared number of residues.
+    pub fn check_secondary_structure(&self) -> Vec<SecondaryStructureIssue> {
+        let mut issues = Vec::new();
+        let view = match self.hierarchy().into_iter().next() {
+            Some(view) => view,
+            None => return issues,
+        };
+        for span in self.spans() {
+            let residues = view
+                .chain(span.start.chain)
+                .map(amino_acids)
+                .unwrap_or_default();
+            let mut found = Vec::new();
+            for end in &[&span.start, &span.end] {
+                let index = end.find(&residues);
+                match index {
+                    None => issues.push(SecondaryStructureIssue::MissingResidue {
+                        record: span.record.to_owned(),
+                        id: span.id.clone(),
+                        chain: end.chain,
+                        sequence_number: end.sequence_number,
+                        insertion_code: end.insertion_code(),
+                    }),
+                    Some(i) => {
+                        let declared = end.details.map_or("", |d| d.name.trim());
+                        let name = residues[i].name();
+                        if !declared.is_empty() && declared != name.trim() {
+                            issues.push(SecondaryStructureIssue::ResidueNameMismatch {
+                                record: span.record.to_owned(),
+                                id: span.id.clone(),
+                                chain: end.chain,
+                                sequence_number: end.sequence_number,
+                                insertion_code: end.insertion_code(),
+                                declared: declared.to_owned(),
+                                found: name,
+                            });
+                        }
+                    }
+                }
+                found.push(index);
+            }
+            if let (Some(declared), [Some(start), Some(end)]) = (span.length, &found[..]) {
+                let found = (end + 1).saturating_sub(*start) as u32;
+                if found != declared {
+                    issues.push(SecondaryStructureIssue::HelixLength {
+                        id: span.id.clone(),
+                        declared,
+                        found,
+                    });
+                }
+            }
+        }
+        issues
+    }
+}
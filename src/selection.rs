@@ -0,0 +1,365 @@
+// Copyright (c) 2020 Tianyi Shi
+//
+// This software is released under the MIT License.
+// https://opensource.org/licenses/MIT
+
+//! Atom selections in a small language similar to those of PyMOL and VMD.
+//!
+//! A selection combines the following terms with `and`, `or`, `not` and parentheses; `and` binds
+//! more tightly than `or`. Terms that take values accept several, separated by spaces.
+//!
+//! | Term                 | Selects                                                          |
+//! | -------------------- | ---------------------------------------------------------------- |
+//! | `all`, `none`        | every atom, no atom                                              |
+//! | `chain A B`          | atoms of chains A and B                                          |
+//! | `resn ALA GLY`       | atoms of residues by name                                        |
+//! | `resi 10-20 25 52A`  | atoms of residues by sequence number, with optional insertion code |
+//! | `name CA CB`         | atoms by name                                                    |
+//! | `element C N`        | atoms by element                                                 |
+//! | `altloc A`           | atoms by alternate location indicator                            |
+//! | `protein`, `nucleic` | atoms of amino acids or nucleotides, standard or modified        |
+//! | `water`              | atoms of waters                                                  |
+//! | `ligand`             | atoms of any other residue, including ions                       |
+//! | `hydrogen`           | hydrogen and deuterium atoms                                     |
+//! | `backbone`           | main chain atoms of amino acids and nucleotides                  |
+//! | `sidechain`          | the other atoms of amino acids                                   |
+//!
+//! For example, `chain A and not (water or hydrogen)` or `backbone and resi 1-50`.
+
+use crate::element::ChemicalElement;
+use crate::entry::Entry;
+use crate::types::{Atom, Residue, ResidueSerial};
+use crate::writer::residue_name;
+use std::str::FromStr;
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Selection {
+    All,
+    None,
+    Chain(Vec<char>),
+    ResidueName(Vec<String>),
+    ResidueNumber(Vec<ResidueRange>),
+    AtomName(Vec<String>),
+    Element(Vec<ChemicalElement>),
+    AltLoc(Vec<char>),
+    Protein,
+    NucleicAcid,
+    Water,
+    Ligand,
+    Hydrogen,
+    Backbone,
+    SideChain,
+    Not(Box<Selection>),
+    And(Box<Selection>, Box<Selection>),
+    Or(Box<Selection>, Box<Selection>),
+}
+
+/// Residues from `start` to `end`, both included. An insertion code, if given, must match.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResidueRange {
+    pub start: ResidueSerial,
+    pub end: ResidueSerial,
+    pub insertion_code: Option<char>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectionError(pub String);
+
+impl std::fmt::Display for SelectionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "invalid selection: {}", self.0)
+    }
+}
+
+impl std::error::Error for SelectionError {}
+
+const PROTEIN_BACKBONE: &[&str] = &["N", "CA", "C", "O", "OXT", "H", "HA", "HA2", "HA3"];
+const NUCLEIC_BACKBONE: &[&str] = &[
+    "P", "OP1", "OP2", "OP3", "O1P", "O2P", "O5'", "C5'", "C4'", "C3'", "O3'",
+];
+
+fn is_operator(token: &str) -> bool {
+    matches!(token, "and" | "or" | "not" | "(" | ")")
+}
+
+fn tokenize(input: &str) -> Vec<String> {
+    input
+        .replace('(', " ( ")
+        .replace(')', " ) ")
+        .split_whitespace()
+        .map(str::to_owned)
+        .collect()
+}
+
+struct SelectionParser {
+    tokens: Vec<String>,
+    position: usize,
+}
+
+impl SelectionParser {
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.position).map(String::as_str)
+    }
+
+    fn next(&mut self) -> Option<String> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn or(&mut self) -> Result<Selection, SelectionError> {
+        let mut selection = self.and()?;
+        while self.peek() == Some("or") {
+            self.position += 1;
+            selection = Selection::Or(Box::new(selection), Box::new(self.and()?));
+        }
+        Ok(selection)
+    }
+
+    fn and(&mut self) -> Result<Selection, SelectionError> {
+        let mut selection = self.not()?;
+        while self.peek() == Some("and") {
+            self.position += 1;
+            selection = Selection::And(Box::new(selection), Box::new(self.not()?));
+        }
+        Ok(selection)
+    }
+
+    fn not(&mut self) -> Result<Selection, SelectionError> {
+        if self.peek() == Some("not") {
+            self.position += 1;
+            return Ok(Selection::Not(Box::new(self.not()?)));
+        }
+        self.term()
+    }
+
+    /// The values following a keyword, up to the next operator or parenthesis.
+    fn values(&mut self, keyword: &str) -> Result<Vec<String>, SelectionError> {
+        let mut values = Vec::new();
+        while let Some(token) = self.peek() {
+            if is_operator(token) {
+                break;
+            }
+            values.push(token.to_owned());
+            self.position += 1;
+        }
+        if values.is_empty() {
+            return Err(SelectionError(format!("`{}` needs a value", keyword)));
+        }
+        Ok(values)
+    }
+
+    fn term(&mut self) -> Result<Selection, SelectionError> {
+        let token = self
+            .next()
+            .ok_or_else(|| SelectionError("unexpected end".to_owned()))?;
+        let char_values = |values: Vec<String>, keyword: &str| {
+            values
+                .iter()
+                .map(|v| {
+                    let mut chars = v.chars();
+                    match (chars.next(), chars.next()) {
+                        (Some(c), None) => Ok(c),
+                        _ => Err(SelectionError(format!(
+                            "`{}` takes single characters, not `{}`",
+                            keyword, v
+                        ))),
+                    }
+                })
+                .collect::<Result<Vec<char>, _>>()
+        };
+        Ok(match token.as_str() {
+            "(" => {
+                let selection = self.or()?;
+                if self.next().as_deref() != Some(")") {
+                    return Err(SelectionError("unbalanced parenthesis".to_owned()));
+                }
+                selection
+            }
+            "all" => Selection::All,
+            "none" => Selection::None,
+            "protein" => Selection::Protein,
+            "nucleic" => Selection::NucleicAcid,
+            "water" => Selection::Water,
+            "ligand" => Selection::Ligand,
+            "hydrogen" => Selection::Hydrogen,
+            "backbone" => Selection::Backbone,
+            "sidechain" => Selection::SideChain,
+            "chain" => Selection::Chain(char_values(self.values(&token)?, &token)?),
+            "altloc" => Selection::AltLoc(char_values(self.values(&token)?, &token)?),
+            "resn" => Selection::ResidueName(
+                self.values(&token)?
+                    .iter()
+                    .map(|v| v.to_uppercase())
+                    .collect(),
+            ),
+            "name" => Selection::AtomName(self.values(&token)?),
+            "element" => Selection::Element(
+                self.values(&token)?
+                    .iter()
+                    .map(|v| {
+                        ChemicalElement::from_symbol(v)
+                            .ok_or_else(|| SelectionError(format!("unknown element `{}`", v)))
+                    })
+                    .collect::<Result<_, _>>()?,
+            ),
+            "resi" => Selection::ResidueNumber(
+                self.values(&token)?
+                    .iter()
+                    .map(|v| parse_range(v))
+                    .collect::<Result<_, _>>()?,
+            ),
+            _ => return Err(SelectionError(format!("unexpected `{}`", token))),
+        })
+    }
+}
+
+fn parse_range(value: &str) -> Result<ResidueRange, SelectionError> {
+    let error = || SelectionError(format!("invalid residue number `{}`", value));
+    let number = |s: &str| s.parse::<ResidueSerial>().map_err(|_| error());
+    if let Some((start, end)) = value.split_once('-') {
+        return Ok(ResidueRange {
+            start: number(start)?,
+            end: number(end)?,
+            insertion_code: None,
+        });
+    }
+    let (digits, insertion_code) = match value.chars().last() {
+        Some(c) if c.is_ascii_alphabetic() => (&value[..value.len() - 1], Some(c)),
+        _ => (value, None),
+    };
+    let n = number(digits)?;
+    Ok(ResidueRange {
+        start: n,
+        end: n,
+        insertion_code,
+    })
+}
+
+impl FromStr for Selection {
+    type Err = SelectionError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = SelectionParser {
+            tokens: tokenize(s),
+            position: 0,
+        };
+        let selection = parser.or()?;
+        match parser.peek() {
+            None => Ok(selection),
+            Some(token) => Err(SelectionError(format!("unexpected `{}`", token))),
+        }
+    }
+}
+
+impl Selection {
+    /// Whether `atom`, whose element is `element`, is selected.
+    pub fn matches(&self, atom: &Atom, element: Option<ChemicalElement>) -> bool {
+        let name = || std::str::from_utf8(&atom.name.0).unwrap_or("").trim();
+        let protein = matches!(atom.residue, Residue::AminoAcid(_));
+        let nucleic = matches!(atom.residue, Residue::Nucleotide(_));
+        match self {
+            Self::All => true,
+            Self::None => false,
+            Self::Chain(chains) => chains.contains(&atom.chain),
+            Self::ResidueName(names) => names.contains(&residue_name(&atom.residue)),
+            Self::ResidueNumber(ranges) => ranges.iter().any(|r| {
+                (r.start..=r.end).contains(&atom.sequence_number)
+                    && !matches!(r.insertion_code, Some(c) if c != atom.insertion_code)
+            }),
+            Self::AtomName(names) => names.iter().any(|n| n == name()),
+            Self::Element(elements) => matches!(element, Some(e) if elements.contains(&e)),
+            Self::AltLoc(alt_locs) => alt_locs.contains(&atom.id1),
+            Self::Protein => protein,
+            Self::NucleicAcid => nucleic,
+            Self::Water => matches!(atom.residue, Residue::Water),
+            Self::Ligand => !protein && !nucleic && !matches!(atom.residue, Residue::Water),
+            Self::Hydrogen => matches!(element, Some(e) if e.is_hydrogen()),
+            Self::Backbone => {
+                (protein && PROTEIN_BACKBONE.contains(&name()))
+                    || (nucleic && NUCLEIC_BACKBONE.contains(&name()))
+            }
+            Self::SideChain => protein && !PROTEIN_BACKBONE.contains(&name()),
+            Self::Not(s) => !s.matches(atom, element),
+            Self::And(a, b) => a.matches(atom, element) && b.matches(atom, element),
+            Self::Or(a, b) => a.matches(atom, element) || b.matches(atom, element),
+        }
+    }
+}
+
+impl Entry {
+    /// The indices in `Model::atoms` of the atoms of the model at `model` that `selection`
+    /// matches.
+    pub fn select_atoms(&self, model: usize, selection: &Selection) -> Vec<usize> {
+        self.structure.models[model]
+            .atoms
+            .iter()
+            .enumerate()
+            .filter(|&(i, atom)| selection.matches(atom, self.element(model, i)))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// A copy of the entry with only the atoms that `selection` matches; see
+    /// [`retain_atoms`](../entry/struct.Entry.html#method.retain_atoms).
+    pub fn select(&self, selection: &Selection) -> Entry {
+        let mut entry = self.clone();
+        let elements: Vec<Vec<Option<ChemicalElement>>> = (0..self.structure.models.len())
+            .map(|m| {
+                (0..self.structure.models[m].atoms.len())
+                    .map(|i| self.element(m, i))
+                    .collect()
+            })
+            .collect();
+        entry.retain_atoms(|m, i, atom| selection.matches(atom, elements[m][i]));
+        entry
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_parse() {
+        let s: Selection = "chain A and not (water or resi 10-20 52A)".parse().unwrap();
+        let expected = Selection::And(
+            Box::new(Selection::Chain(vec!['A'])),
+            Box::new(Selection::Not(Box::new(Selection::Or(
+                Box::new(Selection::Water),
+                Box::new(Selection::ResidueNumber(vec![
+                    ResidueRange {
+                        start: 10,
+                        end: 20,
+                        insertion_code: None,
+                    },
+                    ResidueRange {
+                        start: 52,
+                        end: 52,
+                        insertion_code: Some('A'),
+                    },
+                ])),
+            )))),
+        );
+        assert_eq!(s, expected);
+        // * `and` binds more tightly than `or`
+        assert_eq!(
+            "water or protein and backbone"
+                .parse::<Selection>()
+                .unwrap(),
+            Selection::Or(
+                Box::new(Selection::Water),
+                Box::new(Selection::And(
+                    Box::new(Selection::Protein),
+                    Box::new(Selection::Backbone)
+                ))
+            )
+        );
+        assert!("chain".parse::<Selection>().is_err());
+        assert!("(protein".parse::<Selection>().is_err());
+        assert!("protein water".parse::<Selection>().is_err());
+        assert!("element Xx".parse::<Selection>().is_err());
+        assert!("chain AB".parse::<Selection>().is_err());
+    }
+}
@@ -0,0 +1,260 @@
+// Copyright (c) 2020 Tianyi Shi
+//
+// This software is released under the MIT License.
+// https://opensource.org/licenses/MIT
+
+//! One-letter sequences of the chains of an [`Entry`] and their export as FASTA.
+//!
+//! Sequences come either from SEQRES, i.e. the molecule that was studied, or from the residues
+//! observed in the coordinates of the first model, which lack disordered residues. Modified
+//! residues take the letter of their standard parent from MODRES; residues without one become
+//! `X` in peptides and `N` in nucleic acids.
+//!
+//! [`Entry`]: ../entry/struct.Entry.html
+
+use crate::entry::Entry;
+use crate::hierarchy::ModelView;
+use crate::types::{AminoAcid, Nucleotide, Residue, StandardAminoAcid, StandardNucleotide};
+
+pub fn amino_acid_code(aa: &StandardAminoAcid) -> char {
+    use StandardAminoAcid::*;
+    match aa {
+        Ala => 'A',
+        Arg => 'R',
+        Asn => 'N',
+        Asp => 'D',
+        Cys => 'C',
+        Gln => 'Q',
+        Glu => 'E',
+        Gly => 'G',
+        His => 'H',
+        Ile => 'I',
+        Leu => 'L',
+        Lys => 'K',
+        Met | Mse => 'M',
+        Phe => 'F',
+        Pro => 'P',
+        Ser => 'S',
+        Thr => 'T',
+        Trp => 'W',
+        Tyr => 'Y',
+        Val => 'V',
+        Pyl => 'O',
+        Sec => 'U',
+    }
+}
+
+pub fn nucleotide_code(nucleotide: &StandardNucleotide) -> char {
+    use StandardNucleotide::*;
+    match nucleotide {
+        A | DA => 'A',
+        C | DC => 'C',
+        G | DG => 'G',
+        U => 'U',
+        DT => 'T',
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SequenceSource {
+    /// The SEQRES records
+    Seqres,
+    /// The polymer residues of the first model
+    Coordinates,
+}
+
+/// The sequence of a chain.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainSequence {
+    pub chain: char,
+    /// The `MOL_ID` of the entity the chain belongs to
+    pub entity: Option<u32>,
+    pub nucleic_acid: bool,
+    /// One-letter codes
+    pub sequence: String,
+}
+
+/// The fields of the header line of each FASTA record, which are joined by `|`, e.g.
+/// `>1A8O|Chain A|Entity 1|HIV CAPSID`.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FastaOptions {
+    /// The ID code from HEADER
+    pub id: bool,
+    pub chain: bool,
+    /// The `MOL_ID` of the entity
+    pub entity: bool,
+    /// The `MOLECULE` of the entity
+    pub description: bool,
+    /// The maximum number of letters per line, or 0 for the whole sequence on one line
+    pub line_width: usize,
+}
+
+impl Default for FastaOptions {
+    fn default() -> Self {
+        Self {
+            id: true,
+            chain: true,
+            entity: true,
+            description: true,
+            line_width: 80,
+        }
+    }
+}
+
+impl Entry {
+    /// The one-letter code of an amino acid, with modified residues mapped to their parents.
+    pub fn amino_acid_code(&self, aa: &AminoAcid) -> char {
+        match aa {
+            AminoAcid::Standard(aa) => amino_acid_code(aa),
+            AminoAcid::Modified(name) => self
+                .structure
+                .modified_aa
+                .get(name)
+                .map_or('X', |m| amino_acid_code(&m.standard)),
+            AminoAcid::Unknown => 'X',
+        }
+    }
+
+    /// The one-letter code of a nucleotide, with modified residues mapped to their parents.
+    pub fn nucleotide_code(&self, nucleotide: &Nucleotide) -> char {
+        match nucleotide {
+            Nucleotide::Standard(n) => nucleotide_code(n),
+            Nucleotide::Modified(name) => self
+                .structure
+                .modified_nuc
+                .get(name)
+                .map_or('N', |m| nucleotide_code(&m.standard)),
+            Nucleotide::Unknown => 'N',
+        }
+    }
+
+    /// The sequence of each polymer chain, peptides first.
+    pub fn sequences(&self, source: SequenceSource) -> Vec<ChainSequence> {
+        let entity = |chain: char| {
+            self.title_section
+                .compounds
+                .iter()
+                .find(|c| c.chains.contains(&chain))
+                .map(|c| c.mol_id)
+        };
+        let mut sequences = Vec::new();
+        match source {
+            SequenceSource::Seqres => {
+                for chain in &self.structure.chains_aa {
+                    sequences.push(ChainSequence {
+                        chain: chain.id,
+                        entity: entity(chain.id),
+                        nucleic_acid: false,
+                        sequence: chain
+                            .seq
+                            .iter()
+                            .map(|aa| self.amino_acid_code(aa))
+                            .collect(),
+                    });
+                }
+                for chain in &self.structure.chains_nuc {
+                    sequences.push(ChainSequence {
+                        chain: chain.id,
+                        entity: entity(chain.id),
+                        nucleic_acid: true,
+                        sequence: chain.seq.iter().map(|n| self.nucleotide_code(n)).collect(),
+                    });
+                }
+            }
+            SequenceSource::Coordinates => {
+                let (models, info) = (&self.structure.models, &self.model_info);
+                let view = match models.first() {
+                    Some(model) => ModelView::new(0, model, &info[0]),
+                    None => return sequences,
+                };
+                let mut nucleic_acids = Vec::new();
+                for chain in &view.chains {
+                    let mut peptide = String::new();
+                    let mut nucleic_acid = String::new();
+                    for residue in &chain.polymer {
+                        match residue.residue() {
+                            Residue::AminoAcid(aa) => peptide.push(self.amino_acid_code(aa)),
+                            Residue::Nucleotide(n) => nucleic_acid.push(self.nucleotide_code(n)),
+                            _ => {}
+                        }
+                    }
+                    if !peptide.is_empty() {
+                        sequences.push(ChainSequence {
+                            chain: chain.id,
+                            entity: entity(chain.id),
+                            nucleic_acid: false,
+                            sequence: peptide,
+                        });
+                    }
+                    if !nucleic_acid.is_empty() {
+                        nucleic_acids.push(ChainSequence {
+                            chain: chain.id,
+                            entity: entity(chain.id),
+                            nucleic_acid: true,
+                            sequence: nucleic_acid,
+                        });
+                    }
+                }
+                sequences.extend(nucleic_acids);
+            }
+        }
+        sequences
+    }
+
+    /// The sequence of `chain`, peptide or nucleic acid.
+    pub fn sequence(&self, chain: char, source: SequenceSource) -> Option<String> {
+        self.sequences(source)
+            .into_iter()
+            .find(|s| s.chain == chain)
+            .map(|s| s.sequence)
+    }
+
+    /// The sequences of all polymer chains in FASTA format.
+    pub fn fasta(&self, source: SequenceSource, options: &FastaOptions) -> String {
+        let id = self
+            .structure
+            .metadata
+            .as_ref()
+            .and_then(|m| m.header.as_ref())
+            .map(|h| h.id_code.clone())
+            .unwrap_or_default();
+        let mut fasta = String::new();
+        for sequence in self.sequences(source) {
+            let mut fields = Vec::new();
+            if options.id && !id.is_empty() {
+                fields.push(id.clone());
+            }
+            if options.chain {
+                fields.push(format!("Chain {}", sequence.chain));
+            }
+            if let (true, Some(entity)) = (options.entity, sequence.entity) {
+                fields.push(format!("Entity {}", entity));
+            }
+            let description = self
+                .title_section
+                .compounds
+                .iter()
+                .find(|c| Some(c.mol_id) == sequence.entity)
+                .and_then(|c| c.molecule.clone());
+            if let (true, Some(description)) = (options.description, description) {
+                fields.push(description);
+            }
+            fasta.push('>');
+            fasta.push_str(&fields.join("|"));
+            fasta.push('\n');
+            let letters: Vec<char> = sequence.sequence.chars().collect();
+            let width = match options.line_width {
+                0 => letters.len().max(1),
+                w => w,
+            };
+            for line in letters.chunks(width) {
+                fasta.extend(line);
+                fasta.push('\n');
+            }
+        }
+        fasta
+    }
+}
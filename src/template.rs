@@ -0,0 +1,512 @@
+// Copyright (c) 2020 Tianyi Shi
+//
+// This software is released under the MIT License.
+// https://opensource.org/licenses/MIT
+
+//! Templates of the standard residues, and the residues of a model that do not match them.
+//!
+//! A template lists the heavy atoms of a residue as named in the remediated (version 3) PDB
+//! format, the covalent bonds between them and the configuration of its chiral centres. Atoms
+//! that only occur at the end of a chain (OXT of amino acids, OP3 of nucleotides) are listed
+//! apart, and are never reported missing; neither are the phosphate atoms of the first residue of
+//! a nucleic acid chain, which is usually not phosphorylated. Hydrogens are not part of the
+//! templates and are ignored.
+//!
+//! This complements REMARK 465 and 470, which list missing residues and atoms but are often
+//! absent or out of date.
+
+use crate::entry::Entry;
+use crate::hierarchy::{ModelView, ResidueView};
+use crate::types::{
+    AminoAcid, Nucleotide, Residue, ResidueSerial, StandardAminoAcid, StandardNucleotide,
+};
+use std::collections::HashSet;
+
+/// A chiral centre and three of its substituents, in the order for which
+/// `(a - centre) · ((b - centre) × (c - centre))` is positive in the correct configuration
+/// (e.g. N, C and CB around the CA of an L-amino acid).
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChiralCentre {
+    pub centre: &'static str,
+    pub substituents: [&'static str; 3],
+}
+
+/// The heavy atoms, bonds and chiral centres of a standard residue.
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResidueTemplate {
+    pub name: &'static str,
+    pub atoms: Vec<&'static str>,
+    /// Atoms only present at the end of a chain
+    pub terminal: Vec<&'static str>,
+    pub bonds: Vec<(&'static str, &'static str)>,
+    /// The atom of this residue bonded to the `.1` atom of the next residue in a polymer
+    pub link: (&'static str, &'static str),
+    pub chiral: Vec<ChiralCentre>,
+}
+
+impl ResidueTemplate {
+    /// Whether `name` is an atom of the template, terminal atoms included.
+    pub fn contains(&self, name: &str) -> bool {
+        self.atoms.contains(&name) || self.terminal.contains(&name)
+    }
+}
+
+const fn chiral(centre: &'static str, substituents: [&'static str; 3]) -> ChiralCentre {
+    ChiralCentre {
+        centre,
+        substituents,
+    }
+}
+
+/// The template of a standard amino acid.
+pub fn amino_acid_template(aa: &StandardAminoAcid) -> ResidueTemplate {
+    use StandardAminoAcid::*;
+    let (name, side_chain, bonds): (_, &[&str], &[(&str, &str)]) = match aa {
+        Ala => ("ALA", &["CB"], &[]),
+        Arg => (
+            "ARG",
+            &["CB", "CG", "CD", "NE", "CZ", "NH1", "NH2"],
+            &[
+                ("CB", "CG"),
+                ("CG", "CD"),
+                ("CD", "NE"),
+                ("NE", "CZ"),
+                ("CZ", "NH1"),
+                ("CZ", "NH2"),
+            ],
+        ),
+        Asn => (
+            "ASN",
+            &["CB", "CG", "OD1", "ND2"],
+            &[("CB", "CG"), ("CG", "OD1"), ("CG", "ND2")],
+        ),
+        Asp => (
+            "ASP",
+            &["CB", "CG", "OD1", "OD2"],
+            &[("CB", "CG"), ("CG", "OD1"), ("CG", "OD2")],
+        ),
+        Cys => ("CYS", &["CB", "SG"], &[("CB", "SG")]),
+        Gln => (
+            "GLN",
+            &["CB", "CG", "CD", "OE1", "NE2"],
+            &[("CB", "CG"), ("CG", "CD"), ("CD", "OE1"), ("CD", "NE2")],
+        ),
+        Glu => (
+            "GLU",
+            &["CB", "CG", "CD", "OE1", "OE2"],
+            &[("CB", "CG"), ("CG", "CD"), ("CD", "OE1"), ("CD", "OE2")],
+        ),
+        Gly => ("GLY", &[], &[]),
+        His => (
+            "HIS",
+            &["CB", "CG", "ND1", "CD2", "CE1", "NE2"],
+            &[
+                ("CB", "CG"),
+                ("CG", "ND1"),
+                ("CG", "CD2"),
+                ("ND1", "CE1"),
+                ("CD2", "NE2"),
+                ("CE1", "NE2"),
+            ],
+        ),
+        Ile => (
+            "ILE",
+            &["CB", "CG1", "CG2", "CD1"],
+            &[("CB", "CG1"), ("CB", "CG2"), ("CG1", "CD1")],
+        ),
+        Leu => (
+            "LEU",
+            &["CB", "CG", "CD1", "CD2"],
+            &[("CB", "CG"), ("CG", "CD1"), ("CG", "CD2")],
+        ),
+        Lys => (
+            "LYS",
+            &["CB", "CG", "CD", "CE", "NZ"],
+            &[("CB", "CG"), ("CG", "CD"), ("CD", "CE"), ("CE", "NZ")],
+        ),
+        Met => (
+            "MET",
+            &["CB", "CG", "SD", "CE"],
+            &[("CB", "CG"), ("CG", "SD"), ("SD", "CE")],
+        ),
+        Mse => (
+            "MSE",
+            &["CB", "CG", "SE", "CE"],
+            &[("CB", "CG"), ("CG", "SE"), ("SE", "CE")],
+        ),
+        Phe => (
+            "PHE",
+            &["CB", "CG", "CD1", "CD2", "CE1", "CE2", "CZ"],
+            &[
+                ("CB", "CG"),
+                ("CG", "CD1"),
+                ("CG", "CD2"),
+                ("CD1", "CE1"),
+                ("CD2", "CE2"),
+                ("CE1", "CZ"),
+                ("CE2", "CZ"),
+            ],
+        ),
+        Pro => (
+            "PRO",
+            &["CB", "CG", "CD"],
+            &[("CB", "CG"), ("CG", "CD"), ("CD", "N")],
+        ),
+        Ser => ("SER", &["CB", "OG"], &[("CB", "OG")]),
+        Thr => (
+            "THR",
+            &["CB", "OG1", "CG2"],
+            &[("CB", "OG1"), ("CB", "CG2")],
+        ),
+        Trp => (
+            "TRP",
+            &[
+                "CB", "CG", "CD1", "CD2", "NE1", "CE2", "CE3", "CZ2", "CZ3", "CH2",
+            ],
+            &[
+                ("CB", "CG"),
+                ("CG", "CD1"),
+                ("CG", "CD2"),
+                ("CD1", "NE1"),
+                ("NE1", "CE2"),
+                ("CD2", "CE2"),
+                ("CD2", "CE3"),
+                ("CE2", "CZ2"),
+                ("CE3", "CZ3"),
+                ("CZ2", "CH2"),
+                ("CZ3", "CH2"),
+            ],
+        ),
+        Tyr => (
+            "TYR",
+            &["CB", "CG", "CD1", "CD2", "CE1", "CE2", "CZ", "OH"],
+            &[
+                ("CB", "CG"),
+                ("CG", "CD1"),
+                ("CG", "CD2"),
+                ("CD1", "CE1"),
+                ("CD2", "CE2"),
+                ("CE1", "CZ"),
+                ("CE2", "CZ"),
+                ("CZ", "OH"),
+            ],
+        ),
+        Val => (
+            "VAL",
+            &["CB", "CG1", "CG2"],
+            &[("CB", "CG1"), ("CB", "CG2")],
+        ),
+        Sec => ("SEC", &["CB", "SE"], &[("CB", "SE")]),
+        Pyl => (
+            "PYL",
+            &[
+                "CB", "CG", "CD", "CE", "NZ", "C2", "O2", "CA2", "CB2", "CG2", "CD2", "CE2", "N2",
+            ],
+            &[
+                ("CB", "CG"),
+                ("CG", "CD"),
+                ("CD", "CE"),
+                ("CE", "NZ"),
+                ("NZ", "C2"),
+                ("C2", "O2"),
+                ("C2", "CA2"),
+                ("CA2", "CB2"),
+                ("CB2", "CG2"),
+                ("CB2", "CD2"),
+                ("CD2", "CE2"),
+                ("CE2", "N2"),
+                ("N2", "CA2"),
+            ],
+        ),
+    };
+    let mut atoms = vec!["N", "CA", "C", "O"];
+    atoms.extend(side_chain);
+    let mut all_bonds = vec![("N", "CA"), ("CA", "C"), ("C", "O"), ("C", "OXT")];
+    if !side_chain.is_empty() {
+        all_bonds.push(("CA", "CB"));
+    }
+    all_bonds.extend(bonds);
+    let mut centres = Vec::new();
+    if !side_chain.is_empty() {
+        centres.push(chiral("CA", ["N", "C", "CB"]));
+    }
+    match aa {
+        Ile => centres.push(chiral("CB", ["CA", "CG1", "CG2"])),
+        Thr => centres.push(chiral("CB", ["CA", "OG1", "CG2"])),
+        _ => {}
+    }
+    ResidueTemplate {
+        name,
+        atoms,
+        terminal: vec!["OXT"],
+        bonds: all_bonds,
+        link: ("C", "N"),
+        chiral: centres,
+    }
+}
+
+/// The template of a standard nucleotide.
+pub fn nucleotide_template(nuc: &StandardNucleotide) -> ResidueTemplate {
+    use StandardNucleotide::*;
+    const PURINE: &[(&str, &str)] = &[
+        ("C1'", "N9"),
+        ("N9", "C8"),
+        ("C8", "N7"),
+        ("N7", "C5"),
+        ("C5", "C6"),
+        ("C6", "N1"),
+        ("N1", "C2"),
+        ("C2", "N3"),
+        ("N3", "C4"),
+        ("C4", "C5"),
+        ("C4", "N9"),
+    ];
+    const PYRIMIDINE: &[(&str, &str)] = &[
+        ("C1'", "N1"),
+        ("N1", "C2"),
+        ("C2", "O2"),
+        ("C2", "N3"),
+        ("N3", "C4"),
+        ("C4", "C5"),
+        ("C5", "C6"),
+        ("C6", "N1"),
+    ];
+    let name = match nuc {
+        A => "A",
+        C => "C",
+        G => "G",
+        U => "U",
+        DA => "DA",
+        DC => "DC",
+        DG => "DG",
+        DT => "DT",
+    };
+    let (base, base_bonds, exocyclic): (&[&str], _, &[(&str, &str)]) = match nuc {
+        A | DA => (
+            &["N9", "C8", "N7", "C5", "C6", "N6", "N1", "C2", "N3", "C4"],
+            PURINE,
+            &[("C6", "N6")],
+        ),
+        G | DG => (
+            &[
+                "N9", "C8", "N7", "C5", "C6", "O6", "N1", "C2", "N2", "N3", "C4",
+            ],
+            PURINE,
+            &[("C6", "O6"), ("C2", "N2")],
+        ),
+        C | DC => (
+            &["N1", "C2", "O2", "N3", "C4", "N4", "C5", "C6"],
+            PYRIMIDINE,
+            &[("C4", "N4")],
+        ),
+        U => (
+            &["N1", "C2", "O2", "N3", "C4", "O4", "C5", "C6"],
+            PYRIMIDINE,
+            &[("C4", "O4")],
+        ),
+        DT => (
+            &["N1", "C2", "O2", "N3", "C4", "O4", "C5", "C7", "C6"],
+            PYRIMIDINE,
+            &[("C4", "O4"), ("C5", "C7")],
+        ),
+    };
+    let dna = matches!(nuc, DA | DC | DG | DT);
+    let mut atoms = vec![
+        "P", "OP1", "OP2", "O5'", "C5'", "C4'", "O4'", "C3'", "O3'", "C2'",
+    ];
+    if !dna {
+        atoms.push("O2'");
+    }
+    atoms.push("C1'");
+    atoms.extend(base);
+    let mut bonds = vec![
+        ("P", "OP1"),
+        ("P", "OP2"),
+        ("P", "OP3"),
+        ("P", "O5'"),
+        ("O5'", "C5'"),
+        ("C5'", "C4'"),
+        ("C4'", "O4'"),
+        ("C4'", "C3'"),
+        ("C3'", "O3'"),
+        ("C3'", "C2'"),
+        ("C2'", "C1'"),
+        ("C1'", "O4'"),
+    ];
+    if !dna {
+        bonds.push(("C2'", "O2'"));
+    }
+    bonds.extend(base_bonds);
+    bonds.extend(exocyclic);
+    let glycosidic = if base.contains(&"N9") { "N9" } else { "N1" };
+    let mut centres = vec![
+        chiral("C1'", ["O4'", "C2'", glycosidic]),
+        chiral("C3'", ["C4'", "C2'", "O3'"]),
+        chiral("C4'", ["O4'", "C5'", "C3'"]),
+    ];
+    if !dna {
+        centres.push(chiral("C2'", ["C3'", "C1'", "O2'"]));
+    }
+    ResidueTemplate {
+        name,
+        atoms,
+        terminal: vec!["OP3"],
+        bonds,
+        link: ("O3'", "P"),
+        chiral: centres,
+    }
+}
+
+/// The template of `residue`, if it is a standard amino acid or nucleotide.
+pub fn template(residue: &Residue) -> Option<ResidueTemplate> {
+    match residue {
+        Residue::AminoAcid(AminoAcid::Standard(aa)) => Some(amino_acid_template(aa)),
+        Residue::Nucleotide(Nucleotide::Standard(nuc)) => Some(nucleotide_template(nuc)),
+        _ => None,
+    }
+}
+
+/// How a residue departs from its template.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub enum TemplateProblem {
+    /// Heavy atoms of the template absent from the residue
+    MissingAtoms(Vec<String>),
+    /// Heavy atoms of the residue absent from the template
+    UnknownAtoms(Vec<String>),
+    /// A chiral centre in the wrong configuration, e.g. the CA of a D-amino acid
+    WrongChirality(String),
+}
+
+/// A residue that does not match its template.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemplateIssue {
+    pub chain: char,
+    pub sequence_number: ResidueSerial,
+    pub insertion_code: char,
+    pub residue_name: String,
+    pub problem: TemplateProblem,
+}
+
+impl std::fmt::Display for TemplateIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {}{}{} ",
+            self.residue_name,
+            self.chain,
+            self.sequence_number,
+            self.insertion_code.to_string().trim()
+        )?;
+        match &self.problem {
+            TemplateProblem::MissingAtoms(atoms) => write!(f, "is missing {}", atoms.join(", ")),
+            TemplateProblem::UnknownAtoms(atoms) => {
+                write!(f, "has unknown atoms {}", atoms.join(", "))
+            }
+            TemplateProblem::WrongChirality(centre) => {
+                write!(f, "has the wrong chirality at {}", centre)
+            }
+        }
+    }
+}
+
+/// The signed volume of the tetrahedron formed by `centre` and three substituents.
+fn chiral_volume(centre: [f32; 3], a: [f32; 3], b: [f32; 3], c: [f32; 3]) -> f32 {
+    use crate::geometry::{cross, dot, sub};
+    dot(sub(a, centre), cross(sub(b, centre), sub(c, centre)))
+}
+
+impl Entry {
+    /// Compares the standard residues of the model at `model` (index in `structure.models`) with
+    /// their templates. Only the first alternate location of each atom is considered.
+    pub fn check_residues(&self, model: usize) -> Vec<TemplateIssue> {
+        let view = match self.model_info.get(model) {
+            Some(info) => ModelView::new(model, &self.structure.models[model], info),
+            None => return Vec::new(),
+        };
+        let mut issues = Vec::new();
+        for chain in &view.chains {
+            let residues = chain
+                .polymer
+                .iter()
+                .enumerate()
+                .map(|(i, r)| (r, i == 0))
+                .chain(chain.non_polymer.iter().map(|r| (r, true)));
+            for (residue, first) in residues {
+                let template = match template(residue.residue()) {
+                    Some(template) => template,
+                    None => continue,
+                };
+                for problem in self.compare(model, residue, &template, first) {
+                    issues.push(TemplateIssue {
+                        chain: residue.chain(),
+                        sequence_number: residue.sequence_number(),
+                        insertion_code: residue.insertion_code(),
+                        residue_name: residue.name(),
+                        problem,
+                    });
+                }
+            }
+        }
+        issues
+    }
+
+    fn compare(
+        &self,
+        model: usize,
+        residue: &ResidueView,
+        template: &ResidueTemplate,
+        first: bool,
+    ) -> Vec<TemplateProblem> {
+        let mut names: Vec<String> = Vec::new();
+        for (k, atom) in residue.atoms.iter().enumerate() {
+            if self.is_hydrogen(model, residue.offset + k) {
+                continue;
+            }
+            let name = String::from_utf8_lossy(&atom.name.0).trim().to_owned();
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        let present: HashSet<&str> = names.iter().map(String::as_str).collect();
+        // * the 5' end of a nucleic acid chain is usually not phosphorylated
+        let optional: &[&str] = if first && template.link.0 == "O3'" {
+            &["P", "OP1", "OP2"]
+        } else {
+            &[]
+        };
+        let mut problems = Vec::new();
+        let missing: Vec<String> = template
+            .atoms
+            .iter()
+            .filter(|a| !present.contains(*a) && !optional.contains(a))
+            .map(|a| a.to_string())
+            .collect();
+        if !missing.is_empty() {
+            problems.push(TemplateProblem::MissingAtoms(missing));
+        }
+        let unknown: Vec<String> = names
+            .iter()
+            .filter(|n| !template.contains(n))
+            .cloned()
+            .collect();
+        if !unknown.is_empty() {
+            problems.push(TemplateProblem::UnknownAtoms(unknown));
+        }
+        for centre in &template.chiral {
+            let coord = |name: &str| residue.atom(name).map(|a| a.coord);
+            let [a, b, c] = centre.substituents;
+            if let (Some(x), Some(a), Some(b), Some(c)) =
+                (coord(centre.centre), coord(a), coord(b), coord(c))
+            {
+                if chiral_volume(x, a, b, c) < 0.0 {
+                    problems.push(TemplateProblem::WrongChirality(centre.centre.to_owned()));
+                }
+            }
+        }
+        problems
+    }
+}
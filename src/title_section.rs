@@ -4,12 +4,57 @@
 // https://opensource.org/licenses/MIT
 
 pub mod author;
+pub mod caveat;
+pub mod compnd;
 pub mod expdta;
 pub mod header;
+pub mod jrnl;
 pub mod keywords;
+pub mod mdltyp;
+pub mod nummdl;
 pub mod obslte;
+pub mod revdat;
+pub mod source;
+pub mod split;
+pub mod sprsde;
 pub mod title;
 pub use author::AuthorsParser;
+pub use caveat::{Caveat, CaveatParser};
+pub use compnd::{CompndParser, Compound};
 pub use expdta::ExperimentalTechniquesParser;
 pub use header::HeaderParser;
+pub use jrnl::{Journal, JournalParser, JournalReference, SerialNumber};
+pub use keywords::KeywordsParser;
+pub use mdltyp::{MdltypParser, ModelType};
+pub use nummdl::NummdlParser;
+pub use obslte::{Obslte, ObslteParser};
+pub use revdat::{ModificationType, Revdat, RevdatParser};
+pub use source::{Source, SourceParser};
+pub use split::{Split, SplitParser};
+pub use sprsde::{Sprsde, SprsdeParser};
 pub use title::TitleParser;
+
+use crate::types::Keywords;
+
+/// Records of the title section that have no counterpart in [`protein_core::metadata::Metadata`].
+///
+/// HEADER, TITLE, AUTHOR and EXPDTA are parsed into the `Metadata` of the [`Structure`]; all the
+/// other title section records are collected here.
+///
+/// [`Structure`]: ../../protein_core/structure/struct.Structure.html
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Default)]
+pub struct TitleSection {
+    pub obsolete: Option<Obslte>,
+    pub split: Option<Split>,
+    pub caveat: Option<Caveat>,
+    pub compounds: Vec<Compound>,
+    pub sources: Vec<Source>,
+    pub keywords: Option<Keywords>,
+    /// Total number of models, from NUMMDL.
+    pub model_count: Option<u32>,
+    pub model_type: Option<ModelType>,
+    pub revisions: Vec<Revdat>,
+    pub superseded: Option<Sprsde>,
+    pub journal: Option<Journal>,
+}
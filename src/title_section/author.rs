@@ -21,7 +21,7 @@ pub struct AuthorsParser;
 impl FieldParser for AuthorsParser {
     type Output = Authors;
     fn parse(inp: &[u8]) -> nom::IResult<&[u8], Authors> {
-        let (inp, names) = parse_multiline_list(inp)?;
+        let (inp, names) = parse_multiline_list(inp, b"AUTHOR", ',')?;
         Ok((inp, names))
     }
 }
@@ -0,0 +1,59 @@
+// Copyright (c) 2020 Tianyi Shi
+//
+// This software is released under the MIT License.
+// https://opensource.org/licenses/MIT
+
+//! CAVEAT warns of errors and unresolved issues in the entry. Use caution when using an entry
+//! containing this record.
+//!
+//! # Record Format
+//!
+//! | COLUMNS | DATA  TYPE   | FIELD        | DEFINITION                                |
+//! |---------|--------------|--------------|-------------------------------------------|
+//! | 1 -  6  | Record name  | "CAVEAT"     |                                           |
+//! | 9 - 10  | Continuation | continuation | Allows concatenation of multiple records. |
+//! | 12 - 15 | IDcode       | idCode       | PDB ID code of this entry.                |
+//! | 20 - 79 | String       | comment      | Free text giving the reason for the       |
+//! |         |              |              | CAVEAT.                                   |
+
+use crate::common::parser::{columns, join_continued, take_record_lines, FieldParser};
+use nom::IResult;
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Caveat {
+    pub id_code: String,
+    pub comment: String,
+}
+
+pub struct CaveatParser;
+
+impl FieldParser for CaveatParser {
+    type Output = Caveat;
+    fn parse(inp: &[u8]) -> IResult<&[u8], Caveat> {
+        let (inp, lines) = take_record_lines(inp, b"CAVEAT")?;
+        let id_code = columns(lines[0], 12, 15).to_owned();
+        let comment = join_continued(lines.iter().map(|line| columns(line, 20, 79)));
+        Ok((inp, Caveat { id_code, comment }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_caveat() {
+        const CAVEAT: &[u8] =
+            b"     1ABC    INCORRECT CHIRALITY AT RESIDUES A 12 AND                     
+CAVEAT   2 1ABC    A 14                                                         
+COMPND    MOL_ID: 1;                                                            
+";
+        let (inp, caveat) = CaveatParser::parse(CAVEAT).unwrap();
+        assert_eq!(&inp[..6], b"COMPND");
+        assert_eq!(caveat.id_code, "1ABC");
+        assert_eq!(
+            caveat.comment,
+            "INCORRECT CHIRALITY AT RESIDUES A 12 AND A 14"
+        );
+    }
+}
@@ -0,0 +1,146 @@
+// Copyright (c) 2020 Tianyi Shi
+//
+// This software is released under the MIT License.
+// https://opensource.org/licenses/MIT
+
+//! The COMPND record describes the macromolecular contents of an entry. Each macromolecule found
+//! in the entry is described by a set of token: value pairs, and is referred to as a COMPND record
+//! component. Since the concept of a molecule is difficult to specify exactly, staff may exercise
+//! editorial judgment in consultation with depositors in assigning these names.
+//!
+//! # Record Format
+//!
+//! | COLUMNS | DATA  TYPE         | FIELD        | DEFINITION                              |
+//! |---------|--------------------|--------------|-----------------------------------------|
+//! | 1 -  6  | Record name        | "COMPND"     |                                         |
+//! | 8 - 10  | Continuation       | continuation | Allows concatenation of multiple        |
+//! |         |                    |              | records.                                |
+//! | 11 - 80 | Specification list | compound     | Description of the molecular components.|
+//!
+//! # Tokens
+//!
+//! | TOKEN          | VALUE DEFINITION                                                     |
+//! |----------------|----------------------------------------------------------------------|
+//! | MOL_ID         | Numbers each component; also used in SOURCE to associate the         |
+//! |                | information.                                                         |
+//! | MOLECULE       | Name of the macromolecule.                                           |
+//! | CHAIN          | Comma-separated list of chain identifier(s).                         |
+//! | FRAGMENT       | Specifies a domain or region of the molecule.                        |
+//! | SYNONYM        | Comma-separated list of synonyms for the MOLECULE.                   |
+//! | EC             | The Enzyme Commission number associated with the molecule. If there  |
+//! |                | is more than one EC number, they are presented as a comma-separated  |
+//! |                | list.                                                                |
+//! | ENGINEERED     | Indicates that the molecule was produced using recombinant           |
+//! |                | technology or by purely chemical synthesis.                          |
+//! | MUTATION       | Indicates if there is a mutation.                                    |
+//! | OTHER_DETAILS  | Additional comments.                                                 |
+
+use crate::common::parser::{
+    columns, join_continued, split_list, split_specification_list, take_record_lines, FieldParser,
+};
+use nom::IResult;
+
+/// A macromolecule described by one `MOL_ID` group of the COMPND record.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Compound {
+    pub mol_id: u32,
+    pub molecule: Option<String>,
+    pub chains: Vec<char>,
+    pub fragment: Option<String>,
+    pub synonyms: Vec<String>,
+    pub ec: Vec<String>,
+    pub engineered: bool,
+    pub mutation: bool,
+    pub other_details: Option<String>,
+}
+
+pub struct CompndParser;
+
+impl FieldParser for CompndParser {
+    type Output = Vec<Compound>;
+    fn parse(inp: &[u8]) -> IResult<&[u8], Vec<Compound>> {
+        let (inp, lines) = take_record_lines(inp, b"COMPND")?;
+        let specification = join_continued(lines.iter().map(|line| columns(line, 11, 80)));
+        let mut compounds: Vec<Compound> = Vec::new();
+        for (token, value) in split_specification_list(&specification) {
+            if token == "MOL_ID" {
+                compounds.push(Compound {
+                    mol_id: value.parse().unwrap_or(compounds.len() as u32 + 1),
+                    ..Default::default()
+                });
+                continue;
+            }
+            if compounds.is_empty() {
+                // * entries predating format 3.0 may omit MOL_ID
+                compounds.push(Compound {
+                    mol_id: 1,
+                    ..Default::default()
+                });
+            }
+            let compound = compounds.last_mut().unwrap();
+            match token.as_str() {
+                "MOLECULE" => compound.molecule = Some(value),
+                "CHAIN" => compound.chains = parse_chain_list(&value),
+                "FRAGMENT" => compound.fragment = Some(value),
+                "SYNONYM" => compound.synonyms = split_list(&value, ','),
+                "EC" => compound.ec = split_list(&value, ','),
+                "ENGINEERED" => compound.engineered = parse_yes_no(&value),
+                "MUTATION" => compound.mutation = parse_yes_no(&value),
+                "OTHER_DETAILS" => compound.other_details = Some(value),
+                _ => {}
+            }
+        }
+        Ok((inp, compounds))
+    }
+}
+
+pub(crate) fn parse_yes_no(value: &str) -> bool {
+    value == "YES"
+}
+
+fn parse_chain_list(value: &str) -> Vec<char> {
+    split_list(value, ',')
+        .iter()
+        .filter_map(|chain| chain.chars().next())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_compnd() {
+        const COMPND: &[u8] =
+            b"    MOL_ID: 1;                                                            
+COMPND   2 MOLECULE: POU DOMAIN, CLASS 5, TRANSCRIPTION FACTOR 1;               
+COMPND   3 CHAIN: A, B;                                                         
+COMPND   4 SYNONYM: OCTAMER-BINDING TRANSCRIPTION FACTOR 3, OCT-3;              
+COMPND   5 ENGINEERED: YES;                                                     
+COMPND   6 MOL_ID: 2;                                                           
+COMPND   7 MOLECULE: DNA (5'-                                                   
+COMPND   8 D(*TP*CP*CP*AP*CP*AP*TP*TP*TP*GP*AP*AP*AP*GP*GP*CP*AP*AP*AP*TP*GP*GP*
+COMPND   9 A)-3');                                                              
+COMPND  10 CHAIN: N;                                                            
+COMPND  11 EC: 1.1.1.85                                                         
+SOURCE    MOL_ID: 1;                                                            
+";
+        let (inp, compounds) = CompndParser::parse(COMPND).unwrap();
+        assert_eq!(&inp[..6], b"SOURCE");
+        assert_eq!(compounds.len(), 2);
+        assert_eq!(
+            compounds[0].molecule.as_deref(),
+            Some("POU DOMAIN, CLASS 5, TRANSCRIPTION FACTOR 1")
+        );
+        assert_eq!(compounds[0].chains, vec!['A', 'B']);
+        assert_eq!(compounds[0].synonyms.len(), 2);
+        assert!(compounds[0].engineered);
+        assert!(!compounds[0].mutation);
+        assert_eq!(compounds[1].mol_id, 2);
+        assert_eq!(
+            compounds[1].molecule.as_deref(),
+            Some("DNA (5'-D(*TP*CP*CP*AP*CP*AP*TP*TP*TP*GP*AP*AP*AP*GP*GP*CP*AP*AP*AP*TP*GP*GP*A)-3')")
+        );
+        assert_eq!(compounds[1].ec, vec!["1.1.1.85".to_owned()]);
+    }
+}
@@ -23,14 +23,10 @@ pub struct ExperimentalTechniquesParser;
 impl FieldParser for ExperimentalTechniquesParser {
     type Output = Vec<ExperimentalTechnique>;
     fn parse(inp: &[u8]) -> nom::IResult<&[u8], Vec<ExperimentalTechnique>> {
-        let (inp, techniques_as_str) = parse_multiline_list(inp)?;
-        let techniques: Vec<ExperimentalTechnique> = techniques_as_str
-            .into_iter()
-            .map(|s| {
-                s.parse::<ExperimentalTechnique>()
-                    .expect("Failed to parse experimental techniques")
-            })
-            .collect();
-        Ok((inp, techniques))
+        let (i, techniques) = parse_multiline_list(inp, b"EXPDTA", ';')?;
+        match techniques.iter().map(|s| s.parse().ok()).collect() {
+            Some(techniques) => Ok((i, techniques)),
+            None => Err(nom::Err::Error((inp, nom::error::ErrorKind::Verify))),
+        }
     }
 }
@@ -27,7 +27,11 @@ impl FieldParser for HeaderParser {
     fn parse(inp: &[u8]) -> IResult<&[u8], Self::Output> {
         let inp = &inp[4..];
         let (inp, classification) = unsafe { take_trim_own(inp, 40usize)? };
-        let (inp, deposition_date) = parse_date(inp)?;
+        // * a blank date, as written for entries read without one, stands for the default
+        let (inp, deposition_date) = match take::<_, _, (&[u8], _)>(9usize)(inp) {
+            Ok((rest, b"         ")) => (rest, Header::default().deposition_date),
+            _ => parse_date(inp)?,
+        };
         let inp = &inp[3..];
         let (inp, id_code) = take(4usize)(inp)?;
         let (inp, _) = multispace1(inp)?;
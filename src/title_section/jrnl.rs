@@ -0,0 +1,162 @@
+// Copyright (c) 2020 Tianyi Shi
+//
+// This software is released under the MIT License.
+// https://opensource.org/licenses/MIT
+
+//! The JRNL record contains the primary literature citation that describes the experiment which
+//! resulted in the deposited coordinate set. There is at most one JRNL reference per entry. If
+//! there is no primary reference, then there is no JRNL reference. Other references are given in
+//! REMARK 1.
+//!
+//! # Record Format
+//!
+//! | COLUMNS | DATA  TYPE   | FIELD        | DEFINITION                                   |
+//! |---------|--------------|--------------|----------------------------------------------|
+//! | 1 -  6  | Record name  | "JRNL  "     |                                              |
+//! | 13 - 79 | LString      | text         | See Details below.                           |
+//!
+//! # Details
+//!
+//! The JRNL record is made of the following sub-records, identified in columns 13 - 16, each of
+//! which may be continued (columns 17 - 18):
+//!
+//! | SUB-RECORD | CONTENT (COLUMNS)                                                        |
+//! |------------|--------------------------------------------------------------------------|
+//! | AUTH       | List of author names (20 - 79).                                          |
+//! | TITL       | Title of the article (20 - 79).                                          |
+//! | EDIT       | List of editor names (20 - 79).                                          |
+//! | REF        | Publication name (20 - 47), volume (52 - 55), first page (57 - 61) and   |
+//! |            | year (63 - 66); `TO BE PUBLISHED` if not yet published.                  |
+//! | PUBL       | Publisher (20 - 79).                                                     |
+//! | REFN       | `ISSN` or `ESSN` (36 - 39) and the serial number (41 - 65).              |
+//! | PMID       | PubMed ID (20 - 79).                                                     |
+//! | DOI        | Digital Object Identifier (20 - 79).                                     |
+
+use crate::common::parser::{columns, join_continued, split_list, take_record_lines, FieldParser};
+use nom::IResult;
+
+/// The primary citation of the entry.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Journal {
+    pub authors: Vec<String>,
+    pub title: Option<String>,
+    pub editors: Vec<String>,
+    pub reference: Option<JournalReference>,
+    pub publisher: Option<String>,
+    pub serial: Option<(SerialNumber, String)>,
+    pub pmid: Option<u32>,
+    pub doi: Option<String>,
+}
+
+/// journal reference
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct JournalReference {
+    /// `TO BE PUBLISHED` for unpublished articles.
+    pub publication_name: String,
+    pub volume: Option<String>,
+    pub page: Option<String>,
+    pub year: Option<u32>,
+}
+
+/// Serial Number Type of a JRNL REFN record
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SerialNumber {
+    Issn,
+    Essn,
+}
+
+pub struct JournalParser;
+
+impl FieldParser for JournalParser {
+    type Output = Journal;
+    fn parse(inp: &[u8]) -> IResult<&[u8], Journal> {
+        let (inp, lines) = take_record_lines(inp, b"JRNL  ")?;
+        let sub_record = |name: &'static str| sub_record_lines(&lines, name);
+        let text = |name| join_continued(sub_record(name).map(|line| columns(line, 20, 79)));
+        let non_empty = |s: String| if s.is_empty() { None } else { Some(s) };
+
+        let reference = sub_record("REF").next().map(|first| JournalReference {
+            publication_name: join_continued(sub_record("REF").map(|line| columns(line, 20, 47))),
+            volume: non_empty(columns(first, 52, 55).to_owned()),
+            page: non_empty(columns(first, 57, 61).to_owned()),
+            year: columns(first, 63, 66).parse().ok(),
+        });
+        let serial = sub_record("REFN").next().and_then(|line| {
+            let kind = match columns(line, 36, 39) {
+                "ISSN" => SerialNumber::Issn,
+                "ESSN" => SerialNumber::Essn,
+                _ => return None,
+            };
+            Some((kind, columns(line, 41, 65).to_owned()))
+        });
+        Ok((
+            inp,
+            Journal {
+                authors: split_list(&text("AUTH"), ','),
+                title: non_empty(text("TITL")),
+                editors: split_list(&text("EDIT"), ','),
+                reference,
+                publisher: non_empty(text("PUBL")),
+                serial,
+                pmid: text("PMID").parse().ok(),
+                doi: non_empty(text("DOI")),
+            },
+        ))
+    }
+}
+
+fn sub_record_lines<'a>(
+    lines: &'a [&'a [u8]],
+    name: &'static str,
+) -> impl Iterator<Item = &'a [u8]> + 'a {
+    lines
+        .iter()
+        .filter(move |line| columns(line, 13, 16) == name)
+        .copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_jrnl() {
+        const JRNL: &[u8] =
+            b"      AUTH   T.R.GAMBLE,S.YOO,F.F.VAJDOS,U.K.VON SCHWEDLER,               
+JRNL        AUTH 2 D.K.WORTHYLAKE,H.WANG,J.P.MCCUTCHEON,W.I.SUNDQUIST,          
+JRNL        AUTH 3 C.P.HILL                                                     
+JRNL        TITL   STRUCTURE OF THE CARBOXYL-TERMINAL DIMERIZATION              
+JRNL        TITL 2 DOMAIN OF THE HIV-1 CAPSID PROTEIN.                          
+JRNL        REF    SCIENCE                       V. 278   849 1997              
+JRNL        REFN                   ISSN 0036-8075                               
+JRNL        PMID   9346481                                                      
+JRNL        DOI    10.1126/SCIENCE.278.5339.849                                 
+REMARK   1                                                                      
+";
+        let (inp, journal) = JournalParser::parse(JRNL).unwrap();
+        assert_eq!(&inp[..6], b"REMARK");
+        assert_eq!(journal.authors.len(), 9);
+        assert_eq!(journal.authors[3], "U.K.VON SCHWEDLER");
+        assert_eq!(
+            journal.title.as_deref(),
+            Some("STRUCTURE OF THE CARBOXYL-TERMINAL DIMERIZATION DOMAIN OF THE HIV-1 CAPSID PROTEIN.")
+        );
+        assert_eq!(
+            journal.reference,
+            Some(JournalReference {
+                publication_name: "SCIENCE".to_owned(),
+                volume: Some("278".to_owned()),
+                page: Some("849".to_owned()),
+                year: Some(1997),
+            })
+        );
+        assert_eq!(
+            journal.serial,
+            Some((SerialNumber::Issn, "0036-8075".to_owned()))
+        );
+        assert_eq!(journal.pmid, Some(9346481));
+        assert_eq!(journal.doi.as_deref(), Some("10.1126/SCIENCE.278.5339.849"));
+    }
+}
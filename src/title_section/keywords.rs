@@ -4,8 +4,7 @@
 // https://opensource.org/licenses/MIT
 
 //! Parses KEYWDS record which is a multiline continuation record. Contains comma-seperated list of
-//! keywords relevant to pdb entry.If successfull returns [Record](../ast/types/enum.Record.html)
-//! variant containing [KEYWDS](../ast/types/struct.Keywds.html) instance.
+//! keywords relevant to pdb entry.
 //!
 //! # Record structure
 //!
@@ -15,9 +14,35 @@
 //! | 9 - 10  | Continuation | continuation | Allows concatenation of records if necessary.|
 //! | 11 - 79 | List         | keywds       | Comma-separated list of keywords relevant    |
 //! |         |              |              | to the entry.                                |
-use crate::common::parser::parse_multiline_list;
+use crate::common::parser::{columns, join_continued, split_list, take_record_lines, FieldParser};
+use crate::types::Keywords;
 
-type Keywords = Vec<String>;
-pub fn parse_kaywords(inp: &[u8]) -> nom::IResult<&[u8], Keywords> {
-    parse_multiline_list(inp)
+pub struct KeywordsParser;
+
+impl FieldParser for KeywordsParser {
+    type Output = Keywords;
+    fn parse(inp: &[u8]) -> nom::IResult<&[u8], Keywords> {
+        let (inp, lines) = take_record_lines(inp, b"KEYWDS")?;
+        let keywords = join_continued(lines.iter().map(|line| columns(line, 11, 79)));
+        Ok((inp, split_list(&keywords, ',')))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_keywords() {
+        const KEYWDS: &[u8] =
+            b"    LYASE(OXO-ACID), CARBONIC ANHYDRASE, ZINC                             
+KEYWDS   2ENZYME                                                                
+EXPDTA    X-RAY DIFFRACTION                                                     
+";
+        let (inp, keywords) = KeywordsParser::parse(KEYWDS).unwrap();
+        assert_eq!(&inp[..6], b"EXPDTA");
+        assert_eq!(
+            keywords,
+            vec!["LYASE(OXO-ACID)", "CARBONIC ANHYDRASE", "ZINC ENZYME"]
+        );
+    }
 }
@@ -0,0 +1,51 @@
+// Copyright (c) 2020 Tianyi Shi
+//
+// This software is released under the MIT License.
+// https://opensource.org/licenses/MIT
+
+//! The MDLTYP record contains additional annotation pertinent to the coordinates presented in the
+//! entry, e.g. `MINIMIZED AVERAGE` or `CA ATOMS ONLY, CHAIN A, B`.
+//!
+//! # Record Format
+//!
+//! | COLUMNS | DATA  TYPE   | FIELD        | DEFINITION                                |
+//! |---------|--------------|--------------|-------------------------------------------|
+//! | 1 -  6  | Record name  | "MDLTYP"     |                                           |
+//! | 9 - 10  | Continuation | continuation | Allows concatenation of multiple records. |
+//! | 11 - 80 | SList        | comment      | Free Text providing  additional           |
+//! |         |              |              | structural annotation.                    |
+
+use crate::common::parser::{columns, join_continued, split_list, take_record_lines, FieldParser};
+use nom::IResult;
+
+pub type ModelType = Vec<String>;
+
+pub struct MdltypParser;
+
+impl FieldParser for MdltypParser {
+    type Output = ModelType;
+    fn parse(inp: &[u8]) -> IResult<&[u8], ModelType> {
+        let (inp, lines) = take_record_lines(inp, b"MDLTYP")?;
+        let comment = join_continued(lines.iter().map(|line| columns(line, 11, 80)));
+        Ok((inp, split_list(&comment, ';')))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_mdltyp() {
+        const MDLTYP: &[u8] =
+            b"    CA ATOMS ONLY, CHAIN A, B ; P ATOMS ONLY,                             
+MDLTYP   2CHAIN X, Y                                                            
+AUTHOR    A.BAX                                                                 
+";
+        let (inp, model_type) = MdltypParser::parse(MDLTYP).unwrap();
+        assert_eq!(&inp[..6], b"AUTHOR");
+        assert_eq!(
+            model_type,
+            vec!["CA ATOMS ONLY, CHAIN A, B", "P ATOMS ONLY, CHAIN X, Y"]
+        );
+    }
+}
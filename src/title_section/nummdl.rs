@@ -0,0 +1,45 @@
+// Copyright (c) 2020 Tianyi Shi
+//
+// This software is released under the MIT License.
+// https://opensource.org/licenses/MIT
+
+//! The NUMMDL record indicates total number of models in a PDB entry.
+//!
+//! # Record Format
+//!
+//! | COLUMNS | DATA  TYPE  | FIELD       | DEFINITION        |
+//! |---------|-------------|-------------|-------------------|
+//! | 1 -  6  | Record name | "NUMMDL"    |                   |
+//! | 11 - 14 | Integer     | modelNumber | Number of models. |
+
+use crate::common::parser::{columns, take_record_lines, FieldParser};
+use nom::IResult;
+
+pub struct NummdlParser;
+
+impl FieldParser for NummdlParser {
+    type Output = u32;
+    fn parse(inp: &[u8]) -> IResult<&[u8], u32> {
+        let (i, lines) = take_record_lines(inp, b"NUMMDL")?;
+        match columns(lines[0], 11, 14).parse() {
+            Ok(n) => Ok((i, n)),
+            Err(_) => Err(nom::Err::Error((inp, nom::error::ErrorKind::Digit))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_nummdl() {
+        const NUMMDL: &[u8] =
+            b"    20                                                                    
+AUTHOR    A.BAX                                                                 
+";
+        let (inp, model_count) = NummdlParser::parse(NUMMDL).unwrap();
+        assert_eq!(&inp[..6], b"AUTHOR");
+        assert_eq!(model_count, 20);
+        assert!(NummdlParser::parse(b"    XX\n").is_err());
+    }
+}
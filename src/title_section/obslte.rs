@@ -28,11 +28,67 @@
 //! |67 - 70   |   IDcode       | rIdCode     | ID code of entry that replaced this one. |
 //! |72 - 75   |   IDcode       | rIdCode     | ID code of entry that replaced this one. |
 
+use crate::common::parser::{columns, parse_date, take_record_lines, FieldParser};
 use chrono::NaiveDate;
+use nom::IResult;
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct Header<'a> {
-    pub date_replaced: NaiveDate,
-    pub id_code: &'a [u8],
-    pub id_code_replaced: Vec<&'a [u8]>,
+pub struct Obslte {
+    pub replacement_date: NaiveDate,
+    pub id_code: String,
+    pub replaced_by: Vec<String>,
+}
+
+pub struct ObslteParser;
+
+impl FieldParser for ObslteParser {
+    type Output = Obslte;
+    fn parse(inp: &[u8]) -> IResult<&[u8], Obslte> {
+        let (inp, lines) = take_record_lines(inp, b"OBSLTE")?;
+        let (_, replacement_date) = parse_date(columns(lines[0], 12, 20).as_bytes())?;
+        let id_code = columns(lines[0], 22, 25).to_owned();
+        let replaced_by = parse_id_code_list(&lines);
+        Ok((
+            inp,
+            Obslte {
+                replacement_date,
+                id_code,
+                replaced_by,
+            },
+        ))
+    }
+}
+
+/// Collects the ID codes in columns 32 - 75 of OBSLTE and SPRSDE records.
+pub(crate) fn parse_id_code_list(lines: &[&[u8]]) -> Vec<String> {
+    lines
+        .iter()
+        .flat_map(|line| {
+            (32..=72)
+                .step_by(5)
+                .map(move |start| columns(line, start, start + 3))
+        })
+        .filter(|id| !id.is_empty())
+        .map(|id| id.to_owned())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_obslte() {
+        const OBSLTE: &[u8] =
+            b"     31-JAN-94 1MBP      2MBP 3MBP                                        
+OBSLTE   2                     4MBP                                             
+TITLE     MALTODEXTRIN-BINDING PROTEIN                                          
+";
+        let (inp, obsolete) = ObslteParser::parse(OBSLTE).unwrap();
+        assert_eq!(&inp[..6], b"TITLE ");
+        assert_eq!(obsolete.replacement_date, NaiveDate::from_ymd(1994, 1, 31));
+        assert_eq!(obsolete.id_code, "1MBP");
+        assert_eq!(obsolete.replaced_by, vec!["2MBP", "3MBP", "4MBP"]);
+        assert!(ObslteParser::parse(b"     31-XXX-94 1MBP      2MBP\n").is_err());
+    }
 }
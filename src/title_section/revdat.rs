@@ -0,0 +1,130 @@
+// Copyright (c) 2020 Tianyi Shi
+//
+// This software is released under the MIT License.
+// https://opensource.org/licenses/MIT
+
+//! REVDAT records contain a history of the modifications made to an entry since its release.
+//!
+//! # Record Format
+//!
+//! | COLUMNS | DATA  TYPE    | FIELD        | DEFINITION                                   |
+//! |---------|---------------|--------------|----------------------------------------------|
+//! | 1 -  6  | Record name   | "REVDAT"     |                                              |
+//! | 8 - 10  | Integer       | modNum       | Modification number.                         |
+//! | 11 - 12 | Continuation  | continuation | Allows concatenation of multiple records.    |
+//! | 14 - 22 | Date          | modDate      | Date of modification (or release  for new    |
+//! |         |               |              | entries)  in DD-MMM-YY format. This is not   |
+//! |         |               |              | repeated on continued lines.                 |
+//! | 24 - 27 | IDCode        | modId        | ID code of this entry. This is not repeated  |
+//! |         |               |              | on continuation lines.                       |
+//! | 32      | Integer       | modType      | An integer identifying the type of           |
+//! |         |               |              | modification. For all  revisions, the        |
+//! |         |               |              | modification type is listed as 1             |
+//! | 40 - 45 | LString(6)    | record       | Modification detail.                         |
+//! | 47 - 52 | LString(6)    | record       | Modification detail.                         |
+//! | 54 - 59 | LString(6)    | record       | Modification detail.                         |
+//! | 61 - 66 | LString(6)    | record       | Modification detail.                         |
+
+use crate::common::parser::{columns, parse_date, take_record_lines, FieldParser};
+use chrono::NaiveDate;
+use nom::IResult;
+
+/// Represents a modification made to this pdb entry.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Revdat {
+    pub modification_number: u32,
+    pub modification_date: NaiveDate,
+    pub id_code: String,
+    pub modification_type: ModificationType,
+    /// Names of the records that were modified.
+    pub records: Vec<String>,
+}
+
+/// modification type of REVDAT record
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModificationType {
+    /// initial release of the entry. Indicated as 0 in a REVDAT record
+    InitialRelease,
+    /// modifications other than initial release. Indicated with 1 in a REVDAT record.
+    OtherModification,
+    /// modification type other than 0 or 1
+    UnknownModification,
+}
+
+pub struct RevdatParser;
+
+impl FieldParser for RevdatParser {
+    type Output = Vec<Revdat>;
+    fn parse(inp: &[u8]) -> IResult<&[u8], Vec<Revdat>> {
+        let (inp, lines) = take_record_lines(inp, b"REVDAT")?;
+        let mut revisions: Vec<Revdat> = Vec::new();
+        for line in lines {
+            let records = [40, 47, 54, 61]
+                .iter()
+                .map(|&start| columns(line, start, start + 5))
+                .filter(|record| !record.is_empty())
+                .map(|record| record.to_owned());
+            if !columns(line, 11, 12).is_empty() {
+                // continuation of the previous modification
+                if let Some(revision) = revisions.last_mut() {
+                    revision.records.extend(records);
+                }
+                continue;
+            }
+            let modification_number = columns(line, 8, 10).parse().unwrap_or(0);
+            let (_, modification_date) = parse_date(columns(line, 14, 22).as_bytes())?;
+            let modification_type = match columns(line, 32, 32) {
+                "0" => ModificationType::InitialRelease,
+                "1" => ModificationType::OtherModification,
+                _ => ModificationType::UnknownModification,
+            };
+            revisions.push(Revdat {
+                modification_number,
+                modification_date,
+                id_code: columns(line, 24, 27).to_owned(),
+                modification_type,
+                records: records.collect(),
+            });
+        }
+        Ok((inp, revisions))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_revdat() {
+        const REVDAT: &[u8] =
+            b"   3   01-APR-03 1A8O    1       JRNL                                     
+REVDAT   2   28-OCT-98 1A8O    1       REMARK SOURCE ATOM   HETATM              
+REVDAT   2 1                           CONECT                                   
+REVDAT   1   14-OCT-98 1A8O    0                                                
+SPRSDE     14-OCT-98 1A8O      1AM3                                             
+";
+        let (inp, revisions) = RevdatParser::parse(REVDAT).unwrap();
+        assert_eq!(&inp[..6], b"SPRSDE");
+        assert_eq!(revisions.len(), 3);
+        assert_eq!(revisions[0].modification_number, 3);
+        assert_eq!(
+            revisions[0].modification_date,
+            NaiveDate::from_ymd(2003, 4, 1)
+        );
+        assert_eq!(revisions[0].id_code, "1A8O");
+        assert_eq!(
+            revisions[0].modification_type,
+            ModificationType::OtherModification
+        );
+        assert_eq!(
+            revisions[1].records,
+            vec!["REMARK", "SOURCE", "ATOM", "HETATM", "CONECT"]
+        );
+        assert_eq!(
+            revisions[2].modification_type,
+            ModificationType::InitialRelease
+        );
+        assert!(revisions[2].records.is_empty());
+    }
+}
@@ -0,0 +1,148 @@
+// Copyright (c) 2020 Tianyi Shi
+//
+// This software is released under the MIT License.
+// https://opensource.org/licenses/MIT
+
+//! The SOURCE record specifies the biological and/or chemical source of each biological molecule
+//! in the entry. Sources are described by both the common name and the scientific name, e.g.,
+//! genus and species. Strain and/or cell-line for immortalized cells are given when they help to
+//! uniquely identify the biological entity studied.
+//!
+//! # Record Format
+//!
+//! | COLUMNS | DATA  TYPE         | FIELD        | DEFINITION                                |
+//! |---------|--------------------|--------------|-------------------------------------------|
+//! | 1 -  6  | Record name        | "SOURCE"     |                                           |
+//! | 8 - 10  | Continuation       | continuation | Allows concatenation of multiple records. |
+//! | 11 - 79 | Specification List | srcName      | Identifies the source of the              |
+//! |         |                    |              | macromolecule in a token: value format.   |
+//!
+//! # Details
+//!
+//! - The `MOL_ID` of each group refers to the corresponding [`Compound`] in the COMPND record.
+//! - Tokens prefixed with `EXPRESSION_SYSTEM` describe the host organism used to produce the
+//!   molecule; the others describe the organism the molecule originates from.
+//!
+//! [`Compound`]: ../compnd/struct.Compound.html
+
+use super::compnd::parse_yes_no;
+use crate::common::parser::{
+    columns, join_continued, split_list, split_specification_list, take_record_lines, FieldParser,
+};
+use nom::IResult;
+
+/// The source of the macromolecule described by one `MOL_ID` group of the SOURCE record.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Source {
+    pub mol_id: u32,
+    pub synthetic: bool,
+    pub fragment: Option<String>,
+    pub organism_scientific: Option<String>,
+    pub organism_common: Vec<String>,
+    pub organism_taxid: Vec<u32>,
+    pub strain: Option<String>,
+    pub variant: Option<String>,
+    pub cell_line: Option<String>,
+    pub atcc: Option<String>,
+    pub organ: Option<String>,
+    pub tissue: Option<String>,
+    pub cell: Option<String>,
+    pub organelle: Option<String>,
+    pub secretion: Option<String>,
+    pub cellular_location: Option<String>,
+    pub plasmid: Option<String>,
+    pub gene: Vec<String>,
+    pub expression_system: Option<String>,
+    pub expression_system_common: Vec<String>,
+    pub expression_system_taxid: Vec<u32>,
+    pub expression_system_strain: Option<String>,
+    pub expression_system_variant: Option<String>,
+    pub expression_system_cell_line: Option<String>,
+    pub expression_system_atcc_number: Option<String>,
+    pub expression_system_organ: Option<String>,
+    pub expression_system_tissue: Option<String>,
+    pub expression_system_cell: Option<String>,
+    pub expression_system_organelle: Option<String>,
+    pub expression_system_cellular_location: Option<String>,
+    pub expression_system_vector_type: Option<String>,
+    pub expression_system_vector: Option<String>,
+    pub expression_system_plasmid: Option<String>,
+    pub expression_system_gene: Option<String>,
+    pub other_details: Option<String>,
+}
+
+pub struct SourceParser;
+
+impl FieldParser for SourceParser {
+    type Output = Vec<Source>;
+    fn parse(inp: &[u8]) -> IResult<&[u8], Vec<Source>> {
+        let (inp, lines) = take_record_lines(inp, b"SOURCE")?;
+        let specification = join_continued(lines.iter().map(|line| columns(line, 11, 79)));
+        let mut sources: Vec<Source> = Vec::new();
+        for (token, value) in split_specification_list(&specification) {
+            if token == "MOL_ID" {
+                sources.push(Source {
+                    mol_id: value.parse().unwrap_or(sources.len() as u32 + 1),
+                    ..Default::default()
+                });
+                continue;
+            }
+            if sources.is_empty() {
+                // * entries predating format 3.0 may omit MOL_ID
+                sources.push(Source {
+                    mol_id: 1,
+                    ..Default::default()
+                });
+            }
+            let s = sources.last_mut().unwrap();
+            match token.as_str() {
+                "SYNTHETIC" => s.synthetic = parse_yes_no(&value),
+                "FRAGMENT" => s.fragment = Some(value),
+                "ORGANISM_SCIENTIFIC" => s.organism_scientific = Some(value),
+                "ORGANISM_COMMON" => s.organism_common = split_list(&value, ','),
+                "ORGANISM_TAXID" => s.organism_taxid = parse_taxid_list(&value),
+                "STRAIN" => s.strain = Some(value),
+                "VARIANT" => s.variant = Some(value),
+                "CELL_LINE" => s.cell_line = Some(value),
+                "ATCC" => s.atcc = Some(value),
+                "ORGAN" => s.organ = Some(value),
+                "TISSUE" => s.tissue = Some(value),
+                "CELL" => s.cell = Some(value),
+                "ORGANELLE" => s.organelle = Some(value),
+                "SECRETION" => s.secretion = Some(value),
+                "CELLULAR_LOCATION" => s.cellular_location = Some(value),
+                "PLASMID" => s.plasmid = Some(value),
+                "GENE" => s.gene = split_list(&value, ','),
+                "EXPRESSION_SYSTEM" => s.expression_system = Some(value),
+                "EXPRESSION_SYSTEM_COMMON" => s.expression_system_common = split_list(&value, ','),
+                "EXPRESSION_SYSTEM_TAXID" => s.expression_system_taxid = parse_taxid_list(&value),
+                "EXPRESSION_SYSTEM_STRAIN" => s.expression_system_strain = Some(value),
+                "EXPRESSION_SYSTEM_VARIANT" => s.expression_system_variant = Some(value),
+                "EXPRESSION_SYSTEM_CELL_LINE" => s.expression_system_cell_line = Some(value),
+                "EXPRESSION_SYSTEM_ATCC_NUMBER" => s.expression_system_atcc_number = Some(value),
+                "EXPRESSION_SYSTEM_ORGAN" => s.expression_system_organ = Some(value),
+                "EXPRESSION_SYSTEM_TISSUE" => s.expression_system_tissue = Some(value),
+                "EXPRESSION_SYSTEM_CELL" => s.expression_system_cell = Some(value),
+                "EXPRESSION_SYSTEM_ORGANELLE" => s.expression_system_organelle = Some(value),
+                "EXPRESSION_SYSTEM_CELLULAR_LOCATION" => {
+                    s.expression_system_cellular_location = Some(value)
+                }
+                "EXPRESSION_SYSTEM_VECTOR_TYPE" => s.expression_system_vector_type = Some(value),
+                "EXPRESSION_SYSTEM_VECTOR" => s.expression_system_vector = Some(value),
+                "EXPRESSION_SYSTEM_PLASMID" => s.expression_system_plasmid = Some(value),
+                "EXPRESSION_SYSTEM_GENE" => s.expression_system_gene = Some(value),
+                "OTHER_DETAILS" => s.other_details = Some(value),
+                _ => {}
+            }
+        }
+        Ok((inp, sources))
+    }
+}
+
+fn parse_taxid_list(value: &str) -> Vec<u32> {
+    split_list(value, ',')
+        .iter()
+        .filter_map(|id| id.parse().ok())
+        .collect()
+}
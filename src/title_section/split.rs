@@ -0,0 +1,61 @@
+// Copyright (c) 2020 Tianyi Shi
+//
+// This software is released under the MIT License.
+// https://opensource.org/licenses/MIT
+
+//! The SPLIT record is used in instances where a specific entry composes part of a large
+//! macromolecular complex. It will identify the PDB entries that are required to reconstitute a
+//! complete complex.
+//!
+//! # Record Format
+//!
+//! | COLUMNS | DATA  TYPE   | FIELD        | DEFINITION                                |
+//! |---------|--------------|--------------|-------------------------------------------|
+//! | 1 -  6  | Record name  | "SPLIT "     |                                           |
+//! | 9 - 10  | Continuation | continuation | Allows concatenation of multiple records. |
+//! | 12 - 15 | IDcode       | idCode       | ID code of related entry.                 |
+//! | 17 - 20 | IDcode       | idCode       | ID code of related entry.                 |
+//! | ...     | ...          | ...          | ...                                       |
+//! | 77 - 80 | IDcode       | idCode       | ID code of related entry.                 |
+
+use crate::common::parser::{columns, take_record_lines, FieldParser};
+use nom::IResult;
+
+/// ID codes of the entries that together make up the complete complex.
+pub type Split = Vec<String>;
+
+pub struct SplitParser;
+
+impl FieldParser for SplitParser {
+    type Output = Split;
+    fn parse(inp: &[u8]) -> IResult<&[u8], Split> {
+        let (inp, lines) = take_record_lines(inp, b"SPLIT ")?;
+        let id_codes = lines
+            .iter()
+            .flat_map(|line| {
+                (12..=77)
+                    .step_by(5)
+                    .map(move |start| columns(line, start, start + 3))
+            })
+            .filter(|id| !id.is_empty())
+            .map(|id| id.to_owned())
+            .collect();
+        Ok((inp, id_codes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_split() {
+        const SPLIT: &[u8] =
+            b"     1VOQ 1VOR 1VOS                                                       
+SPLIT    2 1VOU                                                                 
+CAVEAT     1VOQ    SEE REMARK 5                                                 
+";
+        let (inp, split) = SplitParser::parse(SPLIT).unwrap();
+        assert_eq!(&inp[..6], b"CAVEAT");
+        assert_eq!(split, vec!["1VOQ", "1VOR", "1VOS", "1VOU"]);
+    }
+}
@@ -0,0 +1,80 @@
+// Copyright (c) 2020 Tianyi Shi
+//
+// This software is released under the MIT License.
+// https://opensource.org/licenses/MIT
+
+//! The SPRSDE records contain a list of the ID codes of entries that were made obsolete by the
+//! given coordinate entry and withdrawn from the PDB release set. One entry may replace many. It
+//! is PDB policy that only the principal investigator of a structure has the authority to
+//! withdraw it.
+//!
+//! # Record Format
+//!
+//! | COLUMNS | DATA  TYPE   | FIELD        | DEFINITION                                 |
+//! |---------|--------------|--------------|--------------------------------------------|
+//! | 1 -  6  | Record name  | "SPRSDE"     |                                            |
+//! | 9 - 10  | Continuation | continuation | Allows for multiple ID codes.              |
+//! | 12 - 20 | Date         | sprsdeDate   | Date this entry superseded the listed      |
+//! |         |              |              | entries. This field is not copied on       |
+//! |         |              |              | continuations.                             |
+//! | 22 - 25 | IDcode       | idCode       | ID code of this entry. This field is not   |
+//! |         |              |              | copied on continuations.                   |
+//! | 32 - 35 | IDcode       | sIdCode      | ID code of a superseded entry.             |
+//! | 37 - 40 | IDcode       | sIdCode      | ID code of a superseded entry.             |
+//! | 42 - 45 | IDcode       | sIdCode      | ID code of a superseded entry.             |
+//! | 47 - 50 | IDcode       | sIdCode      | ID code of a superseded entry.             |
+//! | 52 - 55 | IDcode       | sIdCode      | ID code of a superseded entry.             |
+//! | 57 - 60 | IDcode       | sIdCode      | ID code of a superseded entry.             |
+//! | 62 - 65 | IDcode       | sIdCode      | ID code of a superseded entry.             |
+//! | 67 - 70 | IDcode       | sIdCode      | ID code of a superseded entry.             |
+//! | 72 - 75 | IDcode       | sIdCode      | ID code of a superseded entry.             |
+
+use super::obslte::parse_id_code_list;
+use crate::common::parser::{columns, parse_date, take_record_lines, FieldParser};
+use chrono::NaiveDate;
+use nom::IResult;
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sprsde {
+    pub date: NaiveDate,
+    pub id_code: String,
+    pub superseded: Vec<String>,
+}
+
+pub struct SprsdeParser;
+
+impl FieldParser for SprsdeParser {
+    type Output = Sprsde;
+    fn parse(inp: &[u8]) -> IResult<&[u8], Sprsde> {
+        let (inp, lines) = take_record_lines(inp, b"SPRSDE")?;
+        let (_, date) = parse_date(columns(lines[0], 12, 20).as_bytes())?;
+        let id_code = columns(lines[0], 22, 25).to_owned();
+        let superseded = parse_id_code_list(&lines);
+        Ok((
+            inp,
+            Sprsde {
+                date,
+                id_code,
+                superseded,
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_sprsde() {
+        const SPRSDE: &[u8] =
+            b"     17-JUL-84 4HHB      1HHB 2HHB                                        
+JRNL        AUTH   G.FERMI,M.F.PERUTZ                                           
+";
+        let (inp, superseded) = SprsdeParser::parse(SPRSDE).unwrap();
+        assert_eq!(&inp[..6], b"JRNL  ");
+        assert_eq!(superseded.date, NaiveDate::from_ymd(1984, 7, 17));
+        assert_eq!(superseded.id_code, "4HHB");
+        assert_eq!(superseded.superseded, vec!["1HHB", "2HHB"]);
+    }
+}
@@ -0,0 +1,549 @@
+// Copyright (c) 2020 Tianyi Shi
+//
+// This software is released under the MIT License.
+// https://opensource.org/licenses/MIT
+
+//! Backbone and side-chain torsion angles of protein residues, with Ramachandran classification.
+//!
+//! For residue *i*, φ is C(*i*-1)-N-CA-C, ψ is N-CA-C-N(*i*+1) and ω is CA(*i*-1)-C(*i*-1)-N-CA,
+//! i.e. the torsion about the peptide bond preceding the residue. Angles involving a neighbouring
+//! residue are only computed if the two residues are joined by a peptide bond, which is assumed
+//! when C(*i*-1) and N(*i*) are no further than 2 Å apart. A peptide bond is *cis* when |ω| < 30°.
+//!
+//! The side-chain angles χ1 - χ4 follow the IUPAC-IUB definitions. Where the choice of atom is
+//! arbitrary, the one with the lower number is used (e.g. CD1 of Leu).
+//!
+//! The Ramachandran regions are polygons in (φ, ψ) traced from the contours of Lovell et al. (2003)
+//! for the general case, glycine, proline and residues preceding proline: the favored regions
+//! enclose 98% of the residues of their reference set, and the allowed regions 99.95%. The traced
+//! polygons follow the contours to within a few degrees, so residues close to a boundary may be
+//! classified differently from MolProbity.
+
+use crate::geometry::{dihedral, distance};
+use crate::hierarchy::{ChainView, ModelView, ResidueView};
+use crate::types::{AminoAcid, Residue, ResidueSerial, StandardAminoAcid};
+
+/// The maximum length, in Å, of a C-N bond for two residues to be considered adjacent.
+const PEPTIDE_BOND_CUTOFF: f32 = 2.0;
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RamachandranCategory {
+    General,
+    Glycine,
+    Proline,
+    /// Residues preceding a proline, glycine and proline excluded
+    PrePro,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RamachandranRegion {
+    Favored,
+    Allowed,
+    Outlier,
+}
+
+/// The torsion angles of a protein residue, in degrees.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResidueTorsions {
+    pub chain: char,
+    pub sequence_number: ResidueSerial,
+    pub insertion_code: char,
+    pub residue_name: String,
+    pub phi: Option<f32>,
+    pub psi: Option<f32>,
+    pub omega: Option<f32>,
+    /// χ1 - χ4; `None` if not defined for the residue or if an atom is missing
+    pub chi: [Option<f32>; 4],
+    pub category: RamachandranCategory,
+    /// `None` if φ or ψ is not defined
+    pub ramachandran: Option<RamachandranRegion>,
+}
+
+impl ResidueTorsions {
+    /// Whether the peptide bond preceding the residue is *cis*.
+    pub fn is_cis(&self) -> bool {
+        matches!(self.omega, Some(omega) if omega.abs() < 30.0)
+    }
+}
+
+/// The four atoms defining each χ angle of a standard amino acid.
+pub fn chi_atoms(aa: &StandardAminoAcid) -> &'static [[&'static str; 4]] {
+    use StandardAminoAcid::*;
+    const N_CA_CB_CG: [&str; 4] = ["N", "CA", "CB", "CG"];
+    match aa {
+        Ala | Gly => &[],
+        Arg => &[
+            N_CA_CB_CG,
+            ["CA", "CB", "CG", "CD"],
+            ["CB", "CG", "CD", "NE"],
+            ["CG", "CD", "NE", "CZ"],
+        ],
+        Asn => &[N_CA_CB_CG, ["CA", "CB", "CG", "OD1"]],
+        Asp => &[N_CA_CB_CG, ["CA", "CB", "CG", "OD1"]],
+        Cys => &[["N", "CA", "CB", "SG"]],
+        Gln => &[
+            N_CA_CB_CG,
+            ["CA", "CB", "CG", "CD"],
+            ["CB", "CG", "CD", "OE1"],
+        ],
+        Glu => &[
+            N_CA_CB_CG,
+            ["CA", "CB", "CG", "CD"],
+            ["CB", "CG", "CD", "OE1"],
+        ],
+        His => &[N_CA_CB_CG, ["CA", "CB", "CG", "ND1"]],
+        Ile => &[["N", "CA", "CB", "CG1"], ["CA", "CB", "CG1", "CD1"]],
+        Leu => &[N_CA_CB_CG, ["CA", "CB", "CG", "CD1"]],
+        Lys | Pyl => &[
+            N_CA_CB_CG,
+            ["CA", "CB", "CG", "CD"],
+            ["CB", "CG", "CD", "CE"],
+            ["CG", "CD", "CE", "NZ"],
+        ],
+        Met => &[
+            N_CA_CB_CG,
+            ["CA", "CB", "CG", "SD"],
+            ["CB", "CG", "SD", "CE"],
+        ],
+        Mse => &[
+            N_CA_CB_CG,
+            ["CA", "CB", "CG", "SE"],
+            ["CB", "CG", "SE", "CE"],
+        ],
+        Phe | Trp | Tyr => &[N_CA_CB_CG, ["CA", "CB", "CG", "CD1"]],
+        Pro => &[N_CA_CB_CG, ["CA", "CB", "CG", "CD"]],
+        Ser => &[["N", "CA", "CB", "OG"]],
+        Thr => &[["N", "CA", "CB", "OG1"]],
+        Val => &[["N", "CA", "CB", "CG1"]],
+        Sec => &[["N", "CA", "CB", "SE"]],
+    }
+}
+
+/// A polygon in (φ, ψ), as a list of vertices. Vertices may lie outside [-180°, 180°] so that a
+/// region can extend across the edge of the plot.
+type Region = &'static [(f32, f32)];
+
+const GENERAL_FAVORED: &[Region] = &[
+    // * β strand, polyproline II, the bridge region and right-handed α helix
+    &[
+        (-180.0, 190.0),
+        (-55.0, 190.0),
+        (-45.0, 175.0),
+        (-42.0, 140.0),
+        (-50.0, 115.0),
+        (-65.0, 100.0),
+        (-75.0, 80.0),
+        (-70.0, 60.0),
+        (-65.0, 35.0),
+        (-55.0, 20.0),
+        (-40.0, 0.0),
+        (-32.0, -25.0),
+        (-35.0, -50.0),
+        (-45.0, -72.0),
+        (-60.0, -77.0),
+        (-90.0, -72.0),
+        (-115.0, -68.0),
+        (-150.0, -58.0),
+        (-160.0, -40.0),
+        (-155.0, -10.0),
+        (-145.0, 20.0),
+        (-150.0, 50.0),
+        (-165.0, 75.0),
+        (-180.0, 80.0),
+    ],
+    // * left-handed α helix
+    &[
+        (42.0, 25.0),
+        (55.0, 10.0),
+        (70.0, 20.0),
+        (78.0, 50.0),
+        (70.0, 80.0),
+        (55.0, 80.0),
+        (42.0, 55.0),
+    ],
+];
+const GENERAL_ALLOWED: &[Region] = &[
+    &[
+        (-180.0, -180.0),
+        (-180.0, 180.0),
+        (-45.0, 180.0),
+        (-35.0, 135.0),
+        (-45.0, 90.0),
+        (-45.0, 40.0),
+        (-25.0, 10.0),
+        (-20.0, -40.0),
+        (-30.0, -65.0),
+        (-60.0, -100.0),
+        (-75.0, -130.0),
+        (-60.0, -160.0),
+        (-45.0, -180.0),
+    ],
+    // * extended, across φ = ±180°
+    &[
+        (-180.0, 100.0),
+        (-200.0, 110.0),
+        (-210.0, 150.0),
+        (-205.0, 200.0),
+        (-180.0, 200.0),
+    ],
+    &[
+        (40.0, -15.0),
+        (60.0, -30.0),
+        (85.0, -10.0),
+        (100.0, 30.0),
+        (100.0, 75.0),
+        (80.0, 105.0),
+        (55.0, 105.0),
+        (38.0, 70.0),
+        (33.0, 30.0),
+    ],
+];
+const GLYCINE_FAVORED: &[Region] = &[
+    // * right-handed α helix and the bridge region
+    &[
+        (-140.0, -25.0),
+        (-115.0, -55.0),
+        (-85.0, -78.0),
+        (-60.0, -80.0),
+        (-42.0, -60.0),
+        (-40.0, -30.0),
+        (-50.0, 0.0),
+        (-65.0, 20.0),
+        (-85.0, 30.0),
+        (-110.0, 20.0),
+        (-135.0, 5.0),
+    ],
+    // * left-handed α helix and the bridge region
+    &[
+        (140.0, 25.0),
+        (115.0, 55.0),
+        (85.0, 78.0),
+        (60.0, 80.0),
+        (42.0, 60.0),
+        (40.0, 30.0),
+        (50.0, 0.0),
+        (65.0, -20.0),
+        (85.0, -30.0),
+        (110.0, -20.0),
+        (135.0, -5.0),
+    ],
+    // * polyproline II and extended, on either side of φ = ±180°
+    &[
+        (-180.0, 145.0),
+        (-110.0, 140.0),
+        (-100.0, 110.0),
+        (-75.0, 105.0),
+        (-55.0, 125.0),
+        (-50.0, 175.0),
+        (-65.0, 200.0),
+        (-75.0, 220.0),
+        (-95.0, 225.0),
+        (-180.0, 220.0),
+    ],
+    &[
+        (180.0, -145.0),
+        (110.0, -140.0),
+        (100.0, -110.0),
+        (75.0, -105.0),
+        (55.0, -125.0),
+        (50.0, -175.0),
+        (65.0, -200.0),
+        (75.0, -220.0),
+        (95.0, -225.0),
+        (180.0, -220.0),
+    ],
+];
+const GLYCINE_ALLOWED: &[Region] = &[
+    &[
+        (-180.0, -180.0),
+        (-180.0, 180.0),
+        (-45.0, 180.0),
+        (-40.0, 120.0),
+        (-60.0, 80.0),
+        (-45.0, 45.0),
+        (-30.0, 0.0),
+        (-30.0, -45.0),
+        (-50.0, -80.0),
+        (-70.0, -120.0),
+        (-60.0, -160.0),
+        (-45.0, -180.0),
+    ],
+    &[
+        (180.0, 180.0),
+        (180.0, -180.0),
+        (45.0, -180.0),
+        (40.0, -120.0),
+        (60.0, -80.0),
+        (45.0, -45.0),
+        (30.0, 0.0),
+        (30.0, 45.0),
+        (50.0, 80.0),
+        (70.0, 120.0),
+        (60.0, 160.0),
+        (45.0, 180.0),
+    ],
+];
+const PROLINE_FAVORED: &[Region] = &[
+    // * right-handed α helix
+    &[
+        (-100.0, -45.0),
+        (-75.0, -60.0),
+        (-55.0, -62.0),
+        (-42.0, -50.0),
+        (-42.0, -25.0),
+        (-50.0, 0.0),
+        (-65.0, 18.0),
+        (-85.0, 18.0),
+        (-100.0, -5.0),
+    ],
+    // * polyproline II
+    &[
+        (-95.0, 115.0),
+        (-75.0, 105.0),
+        (-55.0, 115.0),
+        (-45.0, 140.0),
+        (-48.0, 170.0),
+        (-60.0, 190.0),
+        (-90.0, 190.0),
+        (-95.0, 160.0),
+    ],
+];
+const PROLINE_ALLOWED: &[Region] = &[&[
+    (-105.0, -65.0),
+    (-70.0, -75.0),
+    (-45.0, -70.0),
+    (-35.0, -35.0),
+    (-40.0, 10.0),
+    (-50.0, 50.0),
+    (-35.0, 110.0),
+    (-35.0, 160.0),
+    (-50.0, 200.0),
+    (-100.0, 200.0),
+    (-110.0, 150.0),
+    (-105.0, 60.0),
+    (-110.0, -20.0),
+]];
+const PRE_PRO_FAVORED: &[Region] = &[
+    // * β strand, polyproline II and the ζ region
+    &[
+        (-180.0, 110.0),
+        (-160.0, 95.0),
+        (-155.0, 55.0),
+        (-140.0, 40.0),
+        (-125.0, 45.0),
+        (-110.0, 80.0),
+        (-80.0, 105.0),
+        (-55.0, 115.0),
+        (-45.0, 140.0),
+        (-48.0, 170.0),
+        (-60.0, 190.0),
+        (-180.0, 190.0),
+    ],
+    // * right-handed α helix
+    &[
+        (-110.0, -45.0),
+        (-80.0, -72.0),
+        (-55.0, -72.0),
+        (-40.0, -55.0),
+        (-40.0, -30.0),
+        (-50.0, -5.0),
+        (-65.0, 5.0),
+        (-90.0, 5.0),
+        (-105.0, -20.0),
+    ],
+];
+const PRE_PRO_ALLOWED: &[Region] = &[
+    &[
+        (-180.0, -180.0),
+        (-180.0, 180.0),
+        (-45.0, 180.0),
+        (-35.0, 135.0),
+        (-45.0, 90.0),
+        (-45.0, 40.0),
+        (-30.0, 0.0),
+        (-25.0, -40.0),
+        (-35.0, -75.0),
+        (-70.0, -95.0),
+        (-130.0, -85.0),
+        (-150.0, -120.0),
+        (-150.0, -150.0),
+        (-60.0, -160.0),
+        (-50.0, -180.0),
+    ],
+    &[
+        (42.0, 15.0),
+        (60.0, 5.0),
+        (75.0, 30.0),
+        (75.0, 65.0),
+        (60.0, 85.0),
+        (38.0, 65.0),
+    ],
+];
+
+/// Whether (φ, ψ) lies inside the polygon, by ray casting.
+fn in_polygon(polygon: Region, phi: f32, psi: f32) -> bool {
+    let mut inside = false;
+    let mut j = polygon.len() - 1;
+    for (i, &(phi_i, psi_i)) in polygon.iter().enumerate() {
+        let (phi_j, psi_j) = polygon[j];
+        if (psi_i > psi) != (psi_j > psi)
+            && phi < phi_i + (psi - psi_i) * (phi_j - phi_i) / (psi_j - psi_i)
+        {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
+
+fn in_regions(regions: &[Region], phi: f32, psi: f32) -> bool {
+    // * the plot is periodic, so try the images of the point in the neighbouring cells as well
+    const SHIFTS: [f32; 3] = [0.0, -360.0, 360.0];
+    regions.iter().any(|polygon| {
+        SHIFTS.iter().any(|&dphi| {
+            SHIFTS
+                .iter()
+                .any(|&dpsi| in_polygon(polygon, phi + dphi, psi + dpsi))
+        })
+    })
+}
+
+/// Classifies (φ, ψ) for a residue of the given category.
+pub fn ramachandran_region(
+    category: RamachandranCategory,
+    phi: f32,
+    psi: f32,
+) -> RamachandranRegion {
+    let (favored, allowed) = match category {
+        RamachandranCategory::General => (GENERAL_FAVORED, GENERAL_ALLOWED),
+        RamachandranCategory::Glycine => (GLYCINE_FAVORED, GLYCINE_ALLOWED),
+        RamachandranCategory::Proline => (PROLINE_FAVORED, PROLINE_ALLOWED),
+        RamachandranCategory::PrePro => (PRE_PRO_FAVORED, PRE_PRO_ALLOWED),
+    };
+    if in_regions(favored, phi, psi) {
+        RamachandranRegion::Favored
+    } else if in_regions(allowed, phi, psi) {
+        RamachandranRegion::Allowed
+    } else {
+        RamachandranRegion::Outlier
+    }
+}
+
+fn standard_amino_acid(residue: &Residue) -> Option<&StandardAminoAcid> {
+    match residue {
+        Residue::AminoAcid(AminoAcid::Standard(aa)) => Some(aa),
+        _ => None,
+    }
+}
+
+/// Whether `prev` is joined to `next` by a peptide bond.
+pub(crate) fn peptide_bonded(prev: &ResidueView, next: &ResidueView) -> bool {
+    match (prev.atom("C"), next.atom("N")) {
+        (Some(c), Some(n)) => distance(c, n) <= PEPTIDE_BOND_CUTOFF,
+        _ => false,
+    }
+}
+
+fn torsion(residues: [&ResidueView; 4], names: [&str; 4]) -> Option<f32> {
+    let a = residues[0].atom(names[0])?;
+    let b = residues[1].atom(names[1])?;
+    let c = residues[2].atom(names[2])?;
+    let d = residues[3].atom(names[3])?;
+    Some(dihedral(a, b, c, d))
+}
+
+impl<'a> ChainView<'a> {
+    /// The torsion angles of the amino acid residues of the polymer.
+    pub fn torsions(&self) -> Vec<ResidueTorsions> {
+        let residues: Vec<&ResidueView> = self
+            .polymer
+            .iter()
+            .filter(|r| matches!(r.residue(), Residue::AminoAcid(_)))
+            .collect();
+        residues
+            .iter()
+            .enumerate()
+            .map(|(i, &r)| {
+                let prev = i
+                    .checked_sub(1)
+                    .map(|j| residues[j])
+                    .filter(|p| peptide_bonded(p, r));
+                let next = residues
+                    .get(i + 1)
+                    .copied()
+                    .filter(|n| peptide_bonded(r, n));
+                let phi = prev.and_then(|p| torsion([p, r, r, r], ["C", "N", "CA", "C"]));
+                let psi = next.and_then(|n| torsion([r, r, r, n], ["N", "CA", "C", "N"]));
+                let omega = prev.and_then(|p| torsion([p, p, r, r], ["CA", "C", "N", "CA"]));
+                let mut chi = [None; 4];
+                if let Some(aa) = standard_amino_acid(r.residue()) {
+                    for (angle, names) in chi.iter_mut().zip(chi_atoms(aa)) {
+                        *angle = torsion([r, r, r, r], *names);
+                    }
+                }
+                let category = match standard_amino_acid(r.residue()) {
+                    Some(StandardAminoAcid::Gly) => RamachandranCategory::Glycine,
+                    Some(StandardAminoAcid::Pro) => RamachandranCategory::Proline,
+                    _ if next.map(|n| standard_amino_acid(n.residue()))
+                        == Some(Some(&StandardAminoAcid::Pro)) =>
+                    {
+                        RamachandranCategory::PrePro
+                    }
+                    _ => RamachandranCategory::General,
+                };
+                let ramachandran = match (phi, psi) {
+                    (Some(phi), Some(psi)) => Some(ramachandran_region(category, phi, psi)),
+                    _ => None,
+                };
+                ResidueTorsions {
+                    chain: r.chain(),
+                    sequence_number: r.sequence_number(),
+                    insertion_code: r.insertion_code(),
+                    residue_name: r.name(),
+                    phi,
+                    psi,
+                    omega,
+                    chi,
+                    category,
+                    ramachandran,
+                }
+            })
+            .collect()
+    }
+}
+
+impl<'a> ModelView<'a> {
+    /// The torsion angles of the amino acid residues of all chains.
+    pub fn torsions(&self) -> Vec<ResidueTorsions> {
+        self.chains.iter().flat_map(ChainView::torsions).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_ramachandran_region() {
+        use RamachandranCategory::*;
+        use RamachandranRegion::*;
+        // * α helix and β strand
+        assert_eq!(ramachandran_region(General, -63.0, -43.0), Favored);
+        assert_eq!(ramachandran_region(General, -120.0, 130.0), Favored);
+        // * β strand across ψ = ±180°
+        assert_eq!(ramachandran_region(General, -150.0, -175.0), Favored);
+        // * left-handed helix
+        assert_eq!(ramachandran_region(General, 60.0, 45.0), Favored);
+        assert_eq!(ramachandran_region(General, 80.0, 0.0), Allowed);
+        assert_eq!(ramachandran_region(Glycine, 60.0, 45.0), Favored);
+        assert_eq!(ramachandran_region(General, 60.0, -150.0), Outlier);
+        assert_eq!(ramachandran_region(Glycine, 80.0, -170.0), Favored);
+        assert_eq!(ramachandran_region(Glycine, 0.0, 180.0), Outlier);
+        assert_eq!(ramachandran_region(Proline, -120.0, 130.0), Outlier);
+        assert_eq!(ramachandran_region(Proline, -65.0, 145.0), Favored);
+        assert_eq!(ramachandran_region(PrePro, 60.0, 45.0), Allowed);
+        assert_eq!(ramachandran_region(PrePro, 80.0, 0.0), Outlier);
+    }
+}
@@ -10,10 +10,6 @@ use std::collections::HashMap;
 pub(crate) type ModifiedAminoAcidTable = HashMap<String, ModifiedAminoAcid>;
 pub(crate) type ModifiedNucleotideTable = HashMap<String, ModifiedNucleotide>;
 
-pub(crate) trait ParseFw2 {
-    fn parse_fw2(inp: &[u8]) -> Self;
-}
-
 pub(crate) trait ParseFw3 {
     fn parse_fw3(inp: &[u8]) -> Self;
 }
@@ -43,40 +39,6 @@ where
     fn try_parse_fw4(inp: &[u8]) -> Option<Self>;
 }
 
-impl ParseFw2 for Element {
-    fn parse_fw2(inp: &[u8]) -> Self {
-        match inp {
-            b" H" => Self::H,
-            b" C" => Self::C,
-            b" O" => Self::O,
-            b" N" => Self::N,
-            b" P" => Self::P,
-            b" S" => Self::S,
-            b"SE" => Self::Se,
-            b"NA" => Self::Na,
-            b"MG" => Self::Mg,
-            b"CL" => Self::Cl,
-            b" K" => Self::K,
-            b"CA" => Self::Ca,
-            b"FE" => Self::Fe,
-            b"MN" => Self::Mn,
-            b"CO" => Self::Co,
-            b"CR" => Self::Cr,
-            b" I" => Self::I,
-            b"ZN" => Self::Zn,
-            b"CU" => Self::Cu,
-            b" F" => Self::F,
-            b"AL" => Self::Al,
-            b" V" => Self::V,
-            b"X1" => Self::Unknown,
-            _ => panic!(format!(
-                "fail to parse element: {}",
-                std::str::from_utf8(inp).unwrap()
-            )),
-        }
-    }
-}
-
 impl TryParseFw3 for StandardAminoAcid {
     fn try_parse_fw3(inp: &[u8]) -> Option<Self> {
         match inp {
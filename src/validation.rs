@@ -0,0 +1,865 @@
+// Copyright (c) 2020 Tianyi Shi
+//
+// This software is released under the MIT License.
+// https://opensource.org/licenses/MIT
+
+//! A validation report on a model, in the spirit of MolProbity and the wwPDB validation reports.
+//!
+//! - Clashes: pairs of atoms whose van der Waals spheres overlap by 0.4 Å or more. Atoms within
+//!   three covalent bonds of each other are exempt, with bonds inferred from the covalent radii,
+//!   as are pairs involving a metal and pairs of nitrogen and oxygen atoms at least 2.5 Å apart,
+//!   which may be hydrogen bonded. The clashscore is the number of clashes per 1000 atoms.
+//! - Bond lengths and angles of amino acids, compared with the ideals of Engh & Huber (1991). A
+//!   deviation of more than 4σ is an outlier. Side-chain angles are only checked up to the γ
+//!   atom.
+//! - Ramachandran outliers, as classified in [`torsion`](../torsion/index.html).
+//! - Rotamer outliers: side chains with a χ angle about a bond between two sp³ atoms more than 30°
+//!   from the nearest staggered value (60°, 180° or -60°). Like the Ramachandran regions, this
+//!   approximates the distributions used by MolProbity.
+//! - Occupancies of the alternate locations of an atom that sum to zero or to more than one.
+//! - B-factors that are not positive, or more than five robust standard deviations (scaled median
+//!   absolute deviations) above the median. B-factors are not checked if they are all the same,
+//!   as in many NMR structures.
+//!
+//! Hydrogens, and alternate locations other than the first of each residue, are ignored except
+//! in the occupancy check. Since MolProbity counts clashes of hydrogens, its clashscore is
+//! usually higher than the one reported here.
+//!
+//! The summary score is the MolProbity score of Chen et al. (2010), which combines the
+//! clashscore, rotamer outliers and residues outside the favored Ramachandran regions into a
+//! number on the scale of the crystallographic resolution at which such a model would be
+//! typical. Lower is better. With the `serde` feature, the report can be serialized.
+
+use crate::element::ChemicalElement;
+use crate::entry::Entry;
+use crate::geometry::{angle, distance, Coord, NeighborSearch};
+use crate::hierarchy::{first_alt_locs, ModelView, ResidueView};
+use crate::interaction::Partner;
+use crate::torsion::{peptide_bonded, RamachandranCategory, RamachandranRegion};
+use crate::types::{AminoAcid, Atom, Residue, ResidueSerial, StandardAminoAcid};
+use std::collections::HashSet;
+
+/// The radius assumed for atoms of unknown elements, in Å.
+const DEFAULT_VDW_RADIUS: f32 = 1.8;
+/// The radius of oxygen used by Probe, smaller than that of Bondi (1964), in Å.
+const OXYGEN_VDW_RADIUS: f32 = 1.4;
+const DEFAULT_COVALENT_RADIUS: f32 = 0.77;
+/// The tolerance, in Å, over the sum of the covalent radii of two bonded atoms.
+const BOND_TOLERANCE: f32 = 0.4;
+/// The distance below which two nitrogen or oxygen atoms clash even if hydrogen bonded, in Å.
+const HYDROGEN_BOND_MIN_DISTANCE: f32 = 2.5;
+/// The tolerance, in units, over one of the sum of the occupancies of an atom.
+const OCCUPANCY_TOLERANCE: f32 = 0.01;
+/// The ratio of the standard deviation to the median absolute deviation of a normal distribution.
+const MAD_SCALE: f32 = 1.4826;
+const STAGGERED: [f32; 3] = [60.0, 180.0, -60.0];
+
+/// The limits beyond which something is reported.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Thresholds {
+    /// Overlap of van der Waals spheres, in Å
+    pub clash_overlap: f32,
+    /// Deviation of a bond length or angle from its ideal, in standard deviations
+    pub geometry_sigma: f32,
+    /// Deviation of a χ angle from the nearest staggered value, in degrees
+    pub rotamer_tolerance: f32,
+    /// Distance of a B-factor above the median, in robust standard deviations
+    pub b_factor_sigma: f32,
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        Self {
+            clash_overlap: 0.4,
+            geometry_sigma: 4.0,
+            rotamer_tolerance: 30.0,
+            b_factor_sigma: 5.0,
+        }
+    }
+}
+
+/// Something wrong with a residue. Atoms of the preceding residue are prefixed with `-`, e.g.
+/// `-C` for the carbonyl carbon of a peptide bond.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outlier {
+    Clash {
+        atom: String,
+        /// The other atom, which may be in the same residue
+        other: Partner,
+        /// In Å
+        overlap: f32,
+    },
+    /// In Å
+    BondLength {
+        atoms: [String; 2],
+        value: f32,
+        ideal: f32,
+        z: f32,
+    },
+    /// In degrees
+    BondAngle {
+        atoms: [String; 3],
+        value: f32,
+        ideal: f32,
+        z: f32,
+    },
+    Ramachandran {
+        category: RamachandranCategory,
+        phi: f32,
+        psi: f32,
+    },
+    Rotamer {
+        /// 1 for χ1 and so on
+        chi: usize,
+        value: f32,
+    },
+    Occupancy {
+        atom: String,
+        /// The sum over the alternate locations of the atom
+        total: f32,
+    },
+    BFactor {
+        atom: String,
+        value: f32,
+    },
+}
+
+impl std::fmt::Display for Outlier {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Outlier::Clash {
+                atom,
+                other,
+                overlap,
+            } => write!(
+                f,
+                "{} clashes with {} {}{}{} {} ({:.2} Å overlap)",
+                atom,
+                other.residue_name,
+                other.chain,
+                other.sequence_number,
+                other.insertion_code.to_string().trim(),
+                other.atoms[0],
+                overlap
+            ),
+            Outlier::BondLength {
+                atoms,
+                value,
+                ideal,
+                z,
+            } => write!(
+                f,
+                "bond {} has length {:.3} Å (ideal {:.3}, z = {:.1})",
+                atoms.join("-"),
+                value,
+                ideal,
+                z
+            ),
+            Outlier::BondAngle {
+                atoms,
+                value,
+                ideal,
+                z,
+            } => write!(
+                f,
+                "angle {} is {:.1}° (ideal {:.1}, z = {:.1})",
+                atoms.join("-"),
+                value,
+                ideal,
+                z
+            ),
+            Outlier::Ramachandran { phi, psi, .. } => {
+                write!(f, "Ramachandran outlier (φ = {:.1}, ψ = {:.1})", phi, psi)
+            }
+            Outlier::Rotamer { chi, value } => {
+                write!(f, "rotamer outlier (χ{} = {:.1})", chi, value)
+            }
+            Outlier::Occupancy { atom, total } => {
+                write!(f, "occupancy of {} sums to {:.2}", atom, total)
+            }
+            Outlier::BFactor { atom, value } => {
+                write!(f, "B-factor of {} is {:.2}", atom, value)
+            }
+        }
+    }
+}
+
+/// The outliers of a residue.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResidueValidation {
+    pub chain: char,
+    pub sequence_number: ResidueSerial,
+    pub insertion_code: char,
+    pub residue_name: String,
+    /// `None` if not an amino acid, or if φ or ψ is not defined
+    pub ramachandran: Option<RamachandranRegion>,
+    pub outliers: Vec<Outlier>,
+}
+
+/// Statistics over a model. Percentages are 0 if there is nothing to count.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationSummary {
+    /// Number of atoms considered for clashes
+    pub atoms: usize,
+    pub clashes: usize,
+    /// Clashes per 1000 atoms
+    pub clashscore: f32,
+    pub bonds: usize,
+    pub bond_outliers: usize,
+    /// Root-mean-square of the z-scores of the bond lengths
+    pub bond_rmsz: f32,
+    pub angles: usize,
+    pub angle_outliers: usize,
+    /// Root-mean-square of the z-scores of the bond angles
+    pub angle_rmsz: f32,
+    /// Number of residues with φ and ψ
+    pub ramachandran_residues: usize,
+    /// Percentage of those in the favored regions
+    pub ramachandran_favored: f32,
+    /// Percentage of those in no allowed region
+    pub ramachandran_outliers: f32,
+    /// Number of residues with a checked χ angle
+    pub rotamer_residues: usize,
+    /// Percentage of those with an outlier
+    pub rotamer_outliers: f32,
+    pub occupancy_outliers: usize,
+    pub b_factor_outliers: usize,
+    /// The MolProbity score
+    pub score: f32,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationReport {
+    /// Every residue of the model, in the order of the hierarchy, including those without
+    /// outliers
+    pub residues: Vec<ResidueValidation>,
+    pub summary: ValidationSummary,
+}
+
+impl ValidationReport {
+    /// The residues with at least one outlier.
+    pub fn outliers(&self) -> impl Iterator<Item = &ResidueValidation> {
+        self.residues.iter().filter(|r| !r.outliers.is_empty())
+    }
+}
+
+/// The ideal length and standard deviation of the bonds of a standard amino acid, in Å, from
+/// Engh & Huber (1991). Bonds without an established ideal, such as those of selenium, are left
+/// out.
+pub fn ideal_bonds(aa: &StandardAminoAcid) -> Vec<(&'static str, &'static str, f32, f32)> {
+    use StandardAminoAcid::*;
+    let mut bonds = match aa {
+        Gly => vec![("N", "CA", 1.451, 0.016), ("CA", "C", 1.516, 0.018)],
+        Pro => vec![("N", "CA", 1.466, 0.015), ("CA", "C", 1.525, 0.021)],
+        _ => vec![("N", "CA", 1.458, 0.019), ("CA", "C", 1.525, 0.021)],
+    };
+    bonds.push(("C", "O", 1.231, 0.020));
+    bonds.push(("C", "OXT", 1.231, 0.020));
+    match aa {
+        Gly => {}
+        Ala => bonds.push(("CA", "CB", 1.520, 0.021)),
+        Ile | Thr | Val => bonds.push(("CA", "CB", 1.540, 0.027)),
+        _ => bonds.push(("CA", "CB", 1.530, 0.020)),
+    }
+    let side_chain: &[(&str, &str, f32, f32)] = match aa {
+        Arg => &[
+            ("CB", "CG", 1.520, 0.030),
+            ("CG", "CD", 1.520, 0.030),
+            ("CD", "NE", 1.460, 0.018),
+            ("NE", "CZ", 1.329, 0.014),
+            ("CZ", "NH1", 1.326, 0.018),
+            ("CZ", "NH2", 1.326, 0.018),
+        ],
+        Asn => &[
+            ("CB", "CG", 1.516, 0.025),
+            ("CG", "OD1", 1.231, 0.020),
+            ("CG", "ND2", 1.328, 0.021),
+        ],
+        Asp => &[
+            ("CB", "CG", 1.516, 0.025),
+            ("CG", "OD1", 1.249, 0.019),
+            ("CG", "OD2", 1.249, 0.019),
+        ],
+        Cys => &[("CB", "SG", 1.808, 0.033)],
+        Gln => &[
+            ("CB", "CG", 1.520, 0.030),
+            ("CG", "CD", 1.516, 0.025),
+            ("CD", "OE1", 1.231, 0.020),
+            ("CD", "NE2", 1.328, 0.021),
+        ],
+        Glu => &[
+            ("CB", "CG", 1.520, 0.030),
+            ("CG", "CD", 1.516, 0.025),
+            ("CD", "OE1", 1.249, 0.019),
+            ("CD", "OE2", 1.249, 0.019),
+        ],
+        His => &[
+            ("CB", "CG", 1.497, 0.014),
+            ("CG", "ND1", 1.371, 0.017),
+            ("CG", "CD2", 1.356, 0.011),
+            ("ND1", "CE1", 1.319, 0.013),
+            ("CD2", "NE2", 1.374, 0.021),
+            ("CE1", "NE2", 1.345, 0.020),
+        ],
+        Ile => &[
+            ("CB", "CG1", 1.530, 0.020),
+            ("CB", "CG2", 1.521, 0.033),
+            ("CG1", "CD1", 1.513, 0.039),
+        ],
+        Leu => &[
+            ("CB", "CG", 1.530, 0.020),
+            ("CG", "CD1", 1.521, 0.033),
+            ("CG", "CD2", 1.521, 0.033),
+        ],
+        Lys => &[
+            ("CB", "CG", 1.520, 0.030),
+            ("CG", "CD", 1.520, 0.030),
+            ("CD", "CE", 1.520, 0.030),
+            ("CE", "NZ", 1.489, 0.030),
+        ],
+        Met => &[
+            ("CB", "CG", 1.520, 0.030),
+            ("CG", "SD", 1.803, 0.034),
+            ("SD", "CE", 1.791, 0.059),
+        ],
+        Mse => &[("CB", "CG", 1.520, 0.030)],
+        Phe => &[
+            ("CB", "CG", 1.502, 0.023),
+            ("CG", "CD1", 1.384, 0.021),
+            ("CG", "CD2", 1.384, 0.021),
+            ("CD1", "CE1", 1.382, 0.030),
+            ("CD2", "CE2", 1.382, 0.030),
+            ("CE1", "CZ", 1.382, 0.030),
+            ("CE2", "CZ", 1.382, 0.030),
+        ],
+        Pro => &[
+            ("CB", "CG", 1.492, 0.050),
+            ("CG", "CD", 1.503, 0.034),
+            ("N", "CD", 1.473, 0.014),
+        ],
+        Ser => &[("CB", "OG", 1.417, 0.020)],
+        Thr => &[("CB", "OG1", 1.433, 0.016), ("CB", "CG2", 1.521, 0.033)],
+        Trp => &[
+            ("CB", "CG", 1.498, 0.031),
+            ("CG", "CD1", 1.365, 0.025),
+            ("CG", "CD2", 1.433, 0.018),
+            ("CD1", "NE1", 1.374, 0.021),
+            ("NE1", "CE2", 1.370, 0.011),
+            ("CD2", "CE2", 1.409, 0.017),
+            ("CD2", "CE3", 1.398, 0.016),
+            ("CE2", "CZ2", 1.394, 0.021),
+            ("CE3", "CZ3", 1.382, 0.030),
+            ("CZ2", "CH2", 1.368, 0.019),
+            ("CZ3", "CH2", 1.400, 0.025),
+        ],
+        Tyr => &[
+            ("CB", "CG", 1.512, 0.015),
+            ("CG", "CD1", 1.389, 0.015),
+            ("CG", "CD2", 1.389, 0.015),
+            ("CD1", "CE1", 1.382, 0.030),
+            ("CD2", "CE2", 1.382, 0.030),
+            ("CE1", "CZ", 1.378, 0.013),
+            ("CE2", "CZ", 1.378, 0.013),
+            ("CZ", "OH", 1.376, 0.016),
+        ],
+        Val => &[("CB", "CG1", 1.521, 0.033), ("CB", "CG2", 1.521, 0.033)],
+        Ala | Gly | Sec | Pyl => &[],
+    };
+    bonds.extend_from_slice(side_chain);
+    bonds
+}
+
+/// The ideal value and standard deviation of the bond angles of a standard amino acid, in
+/// degrees, from Engh & Huber (1991), up to the γ atoms of the side chain.
+pub fn ideal_angles(
+    aa: &StandardAminoAcid,
+) -> Vec<(&'static str, &'static str, &'static str, f32, f32)> {
+    use StandardAminoAcid::*;
+    let mut angles = match aa {
+        Gly => vec![("N", "CA", "C", 112.5, 2.9), ("CA", "C", "O", 120.8, 2.1)],
+        Pro => vec![("N", "CA", "C", 111.8, 2.5), ("CA", "C", "O", 120.1, 2.1)],
+        _ => vec![("N", "CA", "C", 111.2, 2.8), ("CA", "C", "O", 120.1, 2.1)],
+    };
+    match aa {
+        Gly => {}
+        Pro => angles
+            .extend_from_slice(&[("N", "CA", "CB", 103.0, 1.1), ("C", "CA", "CB", 110.1, 1.9)]),
+        Ala => angles
+            .extend_from_slice(&[("N", "CA", "CB", 110.4, 1.5), ("C", "CA", "CB", 110.5, 1.5)]),
+        Ile | Thr | Val => angles
+            .extend_from_slice(&[("N", "CA", "CB", 111.5, 1.7), ("C", "CA", "CB", 109.1, 2.2)]),
+        _ => angles
+            .extend_from_slice(&[("N", "CA", "CB", 110.5, 1.7), ("C", "CA", "CB", 110.1, 1.9)]),
+    }
+    let side_chain: &[(&str, &str, &str, f32, f32)] = match aa {
+        Arg | Gln | Glu | Lys | Met => &[("CA", "CB", "CG", 114.1, 2.0)],
+        Asn | Asp => &[("CA", "CB", "CG", 112.6, 1.0)],
+        Cys => &[("CA", "CB", "SG", 114.4, 2.3)],
+        His => &[("CA", "CB", "CG", 113.6, 1.7)],
+        Ile => &[
+            ("CA", "CB", "CG1", 110.4, 1.7),
+            ("CA", "CB", "CG2", 110.5, 1.7),
+        ],
+        Leu => &[("CA", "CB", "CG", 116.3, 3.5)],
+        Phe => &[("CA", "CB", "CG", 113.8, 1.0)],
+        Pro => &[("CA", "CB", "CG", 104.5, 1.9)],
+        Ser => &[("CA", "CB", "OG", 111.1, 2.0)],
+        Thr => &[
+            ("CA", "CB", "OG1", 109.6, 1.5),
+            ("CA", "CB", "CG2", 110.5, 1.7),
+        ],
+        Trp => &[("CA", "CB", "CG", 113.6, 1.9)],
+        Tyr => &[("CA", "CB", "CG", 113.9, 1.8)],
+        Val => &[
+            ("CA", "CB", "CG1", 110.5, 1.7),
+            ("CA", "CB", "CG2", 110.5, 1.7),
+        ],
+        Ala | Gly | Mse | Sec | Pyl => &[],
+    };
+    angles.extend_from_slice(side_chain);
+    angles
+}
+
+/// The ideal length and standard deviation of the peptide bond preceding `aa`.
+fn ideal_peptide_bond(aa: &StandardAminoAcid) -> (f32, f32) {
+    match aa {
+        StandardAminoAcid::Pro => (1.341, 0.016),
+        _ => (1.329, 0.014),
+    }
+}
+
+/// The ideal bond angles about the peptide bond preceding `aa`.
+fn ideal_peptide_angles(
+    aa: &StandardAminoAcid,
+) -> [(&'static str, &'static str, &'static str, f32, f32); 3] {
+    use StandardAminoAcid::*;
+    match aa {
+        Gly => [
+            ("-CA", "-C", "N", 116.2, 2.0),
+            ("-O", "-C", "N", 123.0, 1.6),
+            ("-C", "N", "CA", 120.6, 1.7),
+        ],
+        Pro => [
+            ("-CA", "-C", "N", 118.2, 2.1),
+            ("-O", "-C", "N", 121.9, 1.4),
+            ("-C", "N", "CA", 122.6, 5.0),
+        ],
+        _ => [
+            ("-CA", "-C", "N", 116.2, 2.0),
+            ("-O", "-C", "N", 123.0, 1.6),
+            ("-C", "N", "CA", 121.7, 1.8),
+        ],
+    }
+}
+
+/// The (zero-based) χ angles of `aa` about a bond between two sp³ atoms, which should be
+/// staggered.
+fn staggered_chis(aa: &StandardAminoAcid) -> &'static [usize] {
+    use StandardAminoAcid::*;
+    match aa {
+        Ala | Gly | Pro => &[],
+        Gln | Glu | Ile | Leu => &[0, 1],
+        Arg | Met | Mse => &[0, 1, 2],
+        Lys | Pyl => &[0, 1, 2, 3],
+        _ => &[0],
+    }
+}
+
+/// The smallest difference in degrees between `angle` and one of `targets`.
+fn angular_deviation(angle: f32, targets: &[f32]) -> f32 {
+    targets
+        .iter()
+        .map(|t| {
+            let d = (angle - t).rem_euclid(360.0);
+            d.min(360.0 - d)
+        })
+        .fold(f32::INFINITY, f32::min)
+}
+
+fn median(values: &mut [f32]) -> f32 {
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let n = values.len();
+    if n % 2 == 1 {
+        values[n / 2]
+    } else {
+        (values[n / 2 - 1] + values[n / 2]) / 2.0
+    }
+}
+
+fn atom_name(atom: &Atom) -> String {
+    String::from_utf8_lossy(&atom.name.0).trim().to_owned()
+}
+
+/// Pairs of atoms among `indices` that clash, with their overlap.
+fn clashes(
+    coords: &[Coord],
+    elements: &[Option<ChemicalElement>],
+    indices: &[usize],
+    min_overlap: f32,
+) -> Vec<(usize, usize, f32)> {
+    let vdw = |i: usize| match elements[i] {
+        Some(e) if e.symbol() == "O" => OXYGEN_VDW_RADIUS,
+        Some(e) => e.vdw_radius(),
+        None => DEFAULT_VDW_RADIUS,
+    };
+    let covalent = |i: usize| elements[i].map_or(DEFAULT_COVALENT_RADIUS, |e| e.covalent_radius());
+    let max_radius = indices.iter().map(|&i| vdw(i)).fold(0f32, f32::max);
+    let search = NeighborSearch::with_indices(coords, indices.iter().copied(), 2.0 * max_radius);
+    let pairs = search.pairs(2.0 * max_radius);
+
+    let mut bonded: Vec<Vec<usize>> = vec![Vec::new(); coords.len()];
+    for &(i, j) in &pairs {
+        if distance(&coords[i], &coords[j]) <= covalent(i) + covalent(j) + BOND_TOLERANCE {
+            bonded[i].push(j);
+            bonded[j].push(i);
+        }
+    }
+    // * whether `j` is within three bonds of `i`
+    let near = |i: usize, j: usize| {
+        let mut frontier = vec![i];
+        let mut seen: HashSet<usize> = frontier.iter().copied().collect();
+        for _ in 0..3 {
+            frontier = frontier
+                .iter()
+                .flat_map(|&k| bonded[k].iter().copied())
+                .filter(|&k| seen.insert(k))
+                .collect();
+            if frontier.contains(&j) {
+                return true;
+            }
+        }
+        false
+    };
+    let polar = |i: usize| matches!(elements[i], Some(e) if matches!(e.symbol(), "N" | "O"));
+
+    pairs
+        .into_iter()
+        .filter_map(|(i, j)| {
+            let d = distance(&coords[i], &coords[j]);
+            let overlap = vdw(i) + vdw(j) - d;
+            if overlap < min_overlap
+                || (polar(i) && polar(j) && d >= HYDROGEN_BOND_MIN_DISTANCE)
+                || near(i, j)
+            {
+                None
+            } else {
+                Some((i, j, overlap))
+            }
+        })
+        .collect()
+}
+
+/// Running sums for the root-mean-square z-score of bond lengths or angles.
+#[derive(Default)]
+struct Deviations {
+    count: usize,
+    outliers: usize,
+    sum_of_squares: f32,
+}
+
+impl Deviations {
+    fn rmsz(&self) -> f32 {
+        if self.count == 0 {
+            0.0
+        } else {
+            (self.sum_of_squares / self.count as f32).sqrt()
+        }
+    }
+}
+
+fn percentage(part: usize, whole: usize) -> f32 {
+    if whole == 0 {
+        0.0
+    } else {
+        100.0 * part as f32 / whole as f32
+    }
+}
+
+/// The bond length and angle outliers of an amino acid and the peptide bond preceding it.
+fn geometry(
+    aa: &StandardAminoAcid,
+    residue: &ResidueView,
+    prev: Option<&ResidueView>,
+    sigma: f32,
+    bonds: &mut Deviations,
+    angles: &mut Deviations,
+) -> Vec<Outlier> {
+    let atom = |name: &str| match name.strip_prefix('-') {
+        Some(name) => prev.and_then(|p| p.atom(name)),
+        None => residue.atom(name),
+    };
+    let mut outliers = Vec::new();
+    let mut bond_terms = ideal_bonds(aa);
+    let mut angle_terms = ideal_angles(aa);
+    if prev.is_some() {
+        let (ideal, sd) = ideal_peptide_bond(aa);
+        bond_terms.push(("-C", "N", ideal, sd));
+        angle_terms.extend_from_slice(&ideal_peptide_angles(aa));
+    }
+    for (a, b, ideal, sd) in bond_terms {
+        if let (Some(x), Some(y)) = (atom(a), atom(b)) {
+            let value = distance(x, y);
+            let z = (value - ideal) / sd;
+            bonds.count += 1;
+            bonds.sum_of_squares += z * z;
+            if z.abs() > sigma {
+                bonds.outliers += 1;
+                outliers.push(Outlier::BondLength {
+                    atoms: [a.to_owned(), b.to_owned()],
+                    value,
+                    ideal,
+                    z,
+                });
+            }
+        }
+    }
+    for (a, b, c, ideal, sd) in angle_terms {
+        if let (Some(x), Some(y), Some(w)) = (atom(a), atom(b), atom(c)) {
+            let value = angle(x, y, w);
+            let z = (value - ideal) / sd;
+            angles.count += 1;
+            angles.sum_of_squares += z * z;
+            if z.abs() > sigma {
+                angles.outliers += 1;
+                outliers.push(Outlier::BondAngle {
+                    atoms: [a.to_owned(), b.to_owned(), c.to_owned()],
+                    value,
+                    ideal,
+                    z,
+                });
+            }
+        }
+    }
+    outliers
+}
+
+impl Entry {
+    /// The validation report of the model at `model` (an index in `structure.models`), with the
+    /// default thresholds.
+    pub fn validation_report(&self, model: usize) -> ValidationReport {
+        self.validation_report_with(model, &Thresholds::default())
+    }
+
+    pub fn validation_report_with(
+        &self,
+        model: usize,
+        thresholds: &Thresholds,
+    ) -> ValidationReport {
+        let view = ModelView::new(
+            model,
+            &self.structure.models[model],
+            &self.model_info[model],
+        );
+        let atoms = view.atoms;
+        let first = first_alt_locs(atoms);
+        let elements: Vec<Option<ChemicalElement>> =
+            (0..atoms.len()).map(|i| self.element(model, i)).collect();
+        let heavy: Vec<bool> = first
+            .iter()
+            .zip(&elements)
+            .map(|(&first, e)| first && !matches!(e, Some(e) if e.is_hydrogen()))
+            .collect();
+        let coords: Vec<Coord> = atoms.iter().map(|a| a.coord).collect();
+
+        let residues: Vec<&ResidueView> = view.chains.iter().flat_map(|c| c.residues()).collect();
+        let mut residue_of = vec![0; atoms.len()];
+        for (r, residue) in residues.iter().enumerate() {
+            for k in &mut residue_of[residue.offset..residue.offset + residue.atoms.len()] {
+                *k = r;
+            }
+        }
+        let mut reports: Vec<ResidueValidation> = residues
+            .iter()
+            .map(|r| ResidueValidation {
+                chain: r.chain(),
+                sequence_number: r.sequence_number(),
+                insertion_code: r.insertion_code(),
+                residue_name: r.name(),
+                ramachandran: None,
+                outliers: Vec::new(),
+            })
+            .collect();
+        let partner = |i: usize| {
+            let residue = residues[residue_of[i]];
+            Partner {
+                chain: residue.chain(),
+                sequence_number: residue.sequence_number(),
+                insertion_code: residue.insertion_code(),
+                residue_name: residue.name(),
+                atoms: vec![atom_name(&atoms[i])],
+                indices: vec![i],
+            }
+        };
+
+        // * clashes, reported on both residues if they differ
+        let candidates: Vec<usize> = (0..atoms.len())
+            .filter(|&i| heavy[i] && !matches!(elements[i], Some(e) if e.is_metal()))
+            .collect();
+        let clashes = clashes(&coords, &elements, &candidates, thresholds.clash_overlap);
+        for &(i, j, overlap) in &clashes {
+            let both = residue_of[i] != residue_of[j];
+            for &(a, b) in [(i, j), (j, i)].iter().take(1 + both as usize) {
+                reports[residue_of[a]].outliers.push(Outlier::Clash {
+                    atom: atom_name(&atoms[a]),
+                    other: partner(b),
+                    overlap,
+                });
+            }
+        }
+
+        // * geometry and torsions of amino acids
+        let mut bonds = Deviations::default();
+        let mut angles = Deviations::default();
+        let mut rotamer_residues = 0;
+        let mut rotamer_outliers = 0;
+        let mut offset = 0;
+        for chain in &view.chains {
+            let amino_acids: Vec<usize> = (0..chain.polymer.len())
+                .filter(|&i| matches!(chain.polymer[i].residue(), Residue::AminoAcid(_)))
+                .collect();
+            for (k, (&i, torsions)) in amino_acids.iter().zip(chain.torsions()).enumerate() {
+                let residue = &chain.polymer[i];
+                let report = &mut reports[offset + i];
+                report.ramachandran = torsions.ramachandran;
+                if let (Some(RamachandranRegion::Outlier), Some(phi), Some(psi)) =
+                    (torsions.ramachandran, torsions.phi, torsions.psi)
+                {
+                    report.outliers.push(Outlier::Ramachandran {
+                        category: torsions.category,
+                        phi,
+                        psi,
+                    });
+                }
+                let aa = match residue.residue() {
+                    Residue::AminoAcid(AminoAcid::Standard(aa)) => aa,
+                    _ => continue,
+                };
+                let prev = k
+                    .checked_sub(1)
+                    .map(|j| &chain.polymer[amino_acids[j]])
+                    .filter(|p| peptide_bonded(p, residue));
+                report.outliers.extend(geometry(
+                    aa,
+                    residue,
+                    prev,
+                    thresholds.geometry_sigma,
+                    &mut bonds,
+                    &mut angles,
+                ));
+                let mut checked = false;
+                let mut outlier = false;
+                for &c in staggered_chis(aa) {
+                    if let Some(value) = torsions.chi[c] {
+                        checked = true;
+                        if angular_deviation(value, &STAGGERED) > thresholds.rotamer_tolerance {
+                            outlier = true;
+                            report.outliers.push(Outlier::Rotamer { chi: c + 1, value });
+                        }
+                    }
+                }
+                rotamer_residues += checked as usize;
+                rotamer_outliers += outlier as usize;
+            }
+            offset += chain.polymer.len() + chain.non_polymer.len();
+        }
+
+        // * occupancies summed over the alternate locations of each atom
+        let mut occupancy_outliers = 0;
+        for (r, residue) in residues.iter().enumerate() {
+            let mut totals: Vec<(String, f32)> = Vec::new();
+            for atom in residue.atoms {
+                let name = atom_name(atom);
+                match totals.iter_mut().find(|(n, _)| *n == name) {
+                    Some((_, total)) => *total += atom.occupancy,
+                    None => totals.push((name, atom.occupancy)),
+                }
+            }
+            for (atom, total) in totals {
+                if total <= 0.0 || total > 1.0 + OCCUPANCY_TOLERANCE {
+                    occupancy_outliers += 1;
+                    reports[r].outliers.push(Outlier::Occupancy { atom, total });
+                }
+            }
+        }
+
+        // * B-factors, unless they are all the same
+        let mut b_factor_outliers = 0;
+        let mut b_factors: Vec<f32> = (0..atoms.len())
+            .filter(|&i| heavy[i])
+            .map(|i| atoms[i].temperature_factor)
+            .collect();
+        let varies = b_factors.windows(2).any(|w| w[0] != w[1]);
+        if varies {
+            let centre = median(&mut b_factors);
+            let mut deviations: Vec<f32> = b_factors.iter().map(|b| (b - centre).abs()).collect();
+            let spread = MAD_SCALE * median(&mut deviations);
+            for i in (0..atoms.len()).filter(|&i| heavy[i]) {
+                let value = atoms[i].temperature_factor;
+                if value <= 0.0
+                    || (spread > 0.0 && value - centre > thresholds.b_factor_sigma * spread)
+                {
+                    b_factor_outliers += 1;
+                    reports[residue_of[i]].outliers.push(Outlier::BFactor {
+                        atom: atom_name(&atoms[i]),
+                        value,
+                    });
+                }
+            }
+        }
+
+        let (ramachandran_residues, favored, outliers) = reports
+            .iter()
+            .filter_map(|r| r.ramachandran)
+            .fold((0, 0, 0), |(n, f, o), region| {
+                (
+                    n + 1,
+                    f + (region == RamachandranRegion::Favored) as usize,
+                    o + (region == RamachandranRegion::Outlier) as usize,
+                )
+            });
+        let atom_count = candidates.len();
+        let clashscore = if atom_count == 0 {
+            0.0
+        } else {
+            1000.0 * clashes.len() as f32 / atom_count as f32
+        };
+        let ramachandran_favored = percentage(favored, ramachandran_residues);
+        let rotamer_percentage = percentage(rotamer_outliers, rotamer_residues);
+        let not_favored = if ramachandran_residues == 0 {
+            0.0
+        } else {
+            100.0 - ramachandran_favored
+        };
+        let score = 0.426 * (1.0 + clashscore).ln()
+            + 0.33 * (1.0 + (rotamer_percentage - 1.0).max(0.0)).ln()
+            + 0.25 * (1.0 + (not_favored - 2.0).max(0.0)).ln()
+            + 0.5;
+        ValidationReport {
+            residues: reports,
+            summary: ValidationSummary {
+                atoms: atom_count,
+                clashes: clashes.len(),
+                clashscore,
+                bonds: bonds.count,
+                bond_outliers: bonds.outliers,
+                bond_rmsz: bonds.rmsz(),
+                angles: angles.count,
+                angle_outliers: angles.outliers,
+                angle_rmsz: angles.rmsz(),
+                ramachandran_residues,
+                ramachandran_favored,
+                ramachandran_outliers: percentage(outliers, ramachandran_residues),
+                rotamer_residues,
+                rotamer_outliers: rotamer_percentage,
+                occupancy_outliers,
+                b_factor_outliers,
+                score,
+            },
+        }
+    }
+}
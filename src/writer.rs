@@ -0,0 +1,658 @@
+// Copyright (c) 2020 Tianyi Shi
+//
+// This software is released under the MIT License.
+// https://opensource.org/licenses/MIT
+
+//! Writes an [`Entry`] back into the PDB format.
+//!
+//! The output starts with the records of the title section, from HEADER to JRNL, and the
+//! annotations that refer to residues: SEQRES, MODRES (one for each modified residue in the
+//! coordinates), HELIX, SHEET, SSBOND and LINK, followed by CRYST1. REMARK records are not
+//! written. A lattice other than P, C, I or F, which is read as `LatticeType::Unknown`, is written
+//! as R, the rhombohedral lattice that is the only other one found in macromolecular structures.
+//!
+//! Each model is written chain by chain as seen through the [hierarchy view](../hierarchy/index.html):
+//! the polymer part of every chain followed by a TER record, then the non-polymer residues of all
+//! chains in their original order. SIGATM, ANISOU and SIGUIJ records follow the ATOM/HETATM
+//! record of their atom. Atom serial numbers are kept, so that CONECT records stay valid; the
+//! serial number of each TER record is one greater than that of the atom preceding it. Serial
+//! numbers and residue numbers too large for their columns are written in the
+//! [hybrid-36](../common/hybrid36/index.html) encoding. The output ends with CONECT, MASTER and
+//! END records.
+//!
+//! [`Entry`]: ../entry/struct.Entry.html
+
+use crate::bookkeeping::RecordCounts;
+use crate::common::hybrid36;
+use crate::coordinate::Sigatm;
+use crate::element::ChemicalElement;
+use crate::entry::Entry;
+use crate::hierarchy::ResidueView;
+use crate::secondary_structure::{LinkAtom, ResidueDetails};
+use crate::types::{
+    AminoAcid, Anisou, Atom, AtomName, AtomSerial, Cryst1, ExperimentalTechnique, GroupAxis,
+    HelixClass, LatticeType, Nucleotide, Residue, ResidueSerial, Sense, SpaceGroup,
+};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::{self, Write};
+
+mod title_section;
+
+pub struct Writer;
+
+impl Writer {
+    pub fn write<W: Write>(entry: &Entry, out: &mut W) -> io::Result<()> {
+        let mut counts = RecordCounts::default();
+        write_annotations(out, entry, &mut counts)?;
+        let multiple_models = entry.structure.models.len() > 1;
+        for model in entry.hierarchy() {
+            let model_record = multiple_models || entry.model_info[model.index].serial.is_some();
+            if model_record {
+                write_line(out, &format!("MODEL     {:>4}", model.serial))?;
+            }
+            let info = &entry.model_info[model.index];
+            let extra = ExtraRecords {
+                anisou: by_serial(&entry.structure.models[model.index].anisou, |a| a.id),
+                sigatm: by_serial(&info.sigatm, |s| s.id),
+                siguij: by_serial(&info.siguij, |s| s.id),
+            };
+            for chain in &model.chains {
+                for residue in &chain.polymer {
+                    write_residue(out, entry, model.index, residue, &extra, &mut counts)?;
+                }
+                if let Some(last) = chain.polymer.last().and_then(|r| r.atoms.last()) {
+                    write_line(
+                        out,
+                        &format!(
+                            "TER   {}      {:>3} {}{}{}",
+                            hybrid36_field(last.id + 1, 5)?,
+                            residue_name(&last.residue),
+                            last.chain,
+                            hybrid36_field(last.sequence_number, 4)?,
+                            last.insertion_code
+                        ),
+                    )?;
+                    counts.ter += 1;
+                }
+            }
+            let mut non_polymer: Vec<&ResidueView> =
+                model.chains.iter().flat_map(|c| &c.non_polymer).collect();
+            non_polymer.sort_by_key(|r| r.offset);
+            for residue in non_polymer {
+                write_residue(out, entry, model.index, residue, &extra, &mut counts)?;
+            }
+            if model_record {
+                write_line(out, "ENDMDL")?;
+            }
+        }
+
+        let mut bonds: BTreeMap<AtomSerial, Vec<AtomSerial>> = BTreeMap::new();
+        for &[a, b] in &entry.structure.connect {
+            bonds.entry(a).or_default().push(b);
+            bonds.entry(b).or_default().push(a);
+        }
+        for (atom, mut bonded) in bonds {
+            bonded.sort_unstable();
+            for chunk in bonded.chunks(4) {
+                let mut line = format!("CONECT{}", hybrid36_field(atom, 5)?);
+                for &serial in chunk {
+                    line += &hybrid36_field(serial, 5)?;
+                }
+                write_line(out, &line)?;
+                counts.conect += 1;
+            }
+        }
+
+        write_line(
+            out,
+            &format!(
+                "MASTER    {:>5}{:>5}{:>5}{:>5}{:>5}{:>5}{:>5}{:>5}{:>5}{:>5}{:>5}{:>5}",
+                counts.remark,
+                0,
+                counts.het,
+                counts.helix,
+                counts.sheet,
+                counts.turn,
+                counts.site,
+                counts.transform,
+                counts.coordinate,
+                counts.ter,
+                counts.conect,
+                counts.seqres
+            ),
+        )?;
+        write_line(out, "END")
+    }
+
+    pub fn write_to_string(entry: &Entry) -> String {
+        let mut buf = Vec::new();
+        Self::write(entry, &mut buf).expect("writing to a Vec cannot fail");
+        String::from_utf8(buf).expect("the PDB format is ASCII")
+    }
+}
+
+/// The records that precede the coordinates.
+fn write_annotations<W: Write>(
+    out: &mut W,
+    entry: &Entry,
+    counts: &mut RecordCounts,
+) -> io::Result<()> {
+    for line in title_section::title_section_lines(entry) {
+        write_line(out, &line)?;
+    }
+    let metadata = entry.structure.metadata.as_ref();
+    let header = metadata.and_then(|m| m.header.as_ref());
+    let residue_names = first_residue_names(entry);
+    let seqres = seqres_lines(entry);
+    let helices = helix_lines(entry, &residue_names);
+    let sheets = sheet_lines(entry, &residue_names);
+    counts.seqres += seqres.len() as u32;
+    counts.helix += helices.len() as u32;
+    counts.sheet += sheets.len() as u32;
+    let id_code = header.map_or("", |h| h.id_code.as_str());
+    for line in seqres
+        .iter()
+        .chain(&modres_lines(entry, id_code))
+        .chain(&helices)
+        .chain(&sheets)
+        .chain(&ssbond_lines(entry))
+        .chain(&link_lines(entry)?)
+    {
+        write_line(out, line)?;
+    }
+    if let Some(cryst1) = metadata.and_then(|m| m.cryst1.as_ref()) {
+        write_line(out, &cryst1_line(cryst1))?;
+    }
+    Ok(())
+}
+
+fn cryst1_line(cryst1: &Cryst1) -> String {
+    format!(
+        "CRYST1{:>9.3}{:>9.3}{:>9.3}{:>7.2}{:>7.2}{:>7.2} {:<11}{:>4}",
+        cryst1.a,
+        cryst1.b,
+        cryst1.c,
+        cryst1.alpha,
+        cryst1.beta,
+        cryst1.gamma,
+        space_group(cryst1),
+        cryst1.z
+    )
+}
+
+/// The space group symbol, such as `P 1 21 1`, as in columns 56 - 66 of CRYST1.
+pub(crate) fn space_group(cryst1: &Cryst1) -> String {
+    let lattice = match cryst1.lattice_type {
+        LatticeType::Primitive => "P",
+        LatticeType::SideCentered => "C",
+        LatticeType::BodyCentered => "I",
+        LatticeType::FaceCentered => "F",
+        LatticeType::Unknown => "R",
+    };
+    let SpaceGroup(first, second, third) = &cryst1.space_group;
+    let mut symbol = lattice.to_owned();
+    for GroupAxis(rotation, screw) in Some(first).into_iter().chain(second).chain(third) {
+        symbol += &match screw {
+            0 => format!(" {}", rotation),
+            _ => format!(" {}{}", rotation, screw),
+        };
+    }
+    symbol
+}
+
+/// The name of an experimental technique, as in EXPDTA records and `_exptl.method`.
+pub(crate) fn technique(technique: &ExperimentalTechnique) -> String {
+    use ExperimentalTechnique::*;
+    match technique {
+        XRayDiffraction => "X-RAY DIFFRACTION",
+        ElectronMicroscopy => "ELECTRON MICROSCOPY",
+        SolidStateNmr => "SOLID-STATE NMR",
+        SolutionNmr => "SOLUTION NMR",
+        NeutronDiffraction => "NEUTRON DIFFRACTION",
+        ElectronCrystallography => "ELECTRON CRYSTALLOGRAPHY",
+        SolutionScattering => "SOLUTION SCATTERING",
+        FiberDiffraction => "FIBER DIFFRACTION",
+    }
+    .to_owned()
+}
+
+/// SEQRES records, with the chains in the order in which they appear in the coordinates.
+fn seqres_lines(entry: &Entry) -> Vec<String> {
+    let structure = &entry.structure;
+    let aa = structure.chains_aa.iter().map(|c| {
+        let names = c.seq.iter().map(|r| Residue::AminoAcid(r.clone()));
+        (c.id, names.map(|r| residue_name(&r)).collect::<Vec<_>>())
+    });
+    let nuc = structure.chains_nuc.iter().map(|c| {
+        let names = c.seq.iter().map(|r| Residue::Nucleotide(r.clone()));
+        (c.id, names.map(|r| residue_name(&r)).collect::<Vec<_>>())
+    });
+    let mut chains: Vec<(char, Vec<String>)> = aa.chain(nuc).collect();
+    let atoms = structure.models.first().map_or(&[][..], |m| &m.atoms[..]);
+    chains.sort_by_key(|(id, _)| {
+        atoms
+            .iter()
+            .position(|a| a.chain == *id)
+            .unwrap_or(atoms.len())
+    });
+    let mut lines = Vec::new();
+    for (id, residues) in &chains {
+        for (serial, chunk) in residues.chunks(13).enumerate() {
+            let names: Vec<String> = chunk.iter().map(|n| format!("{:>3}", n)).collect();
+            lines.push(format!(
+                "SEQRES {:>3} {} {:>4}  {}",
+                serial + 1,
+                id,
+                residues.len(),
+                names.join(" ")
+            ));
+        }
+    }
+    lines
+}
+
+/// A MODRES record for each modified residue in the coordinates of the first model.
+fn modres_lines(entry: &Entry, id_code: &str) -> Vec<String> {
+    let structure = &entry.structure;
+    let mut seen = HashSet::new();
+    let mut lines = Vec::new();
+    for atom in structure.models.iter().take(1).flat_map(|m| &m.atoms) {
+        let (name, standard, description) = match &atom.residue {
+            Residue::AminoAcid(AminoAcid::Modified(name)) => {
+                match structure.modified_aa.get(name) {
+                    Some(m) => (
+                        name,
+                        Residue::AminoAcid(AminoAcid::Standard(m.standard.clone())),
+                        &m.description,
+                    ),
+                    None => continue,
+                }
+            }
+            Residue::Nucleotide(Nucleotide::Modified(name)) => {
+                match structure.modified_nuc.get(name) {
+                    Some(m) => (
+                        name,
+                        Residue::Nucleotide(Nucleotide::Standard(m.standard.clone())),
+                        &m.description,
+                    ),
+                    None => continue,
+                }
+            }
+            _ => continue,
+        };
+        if seen.insert((atom.chain, atom.sequence_number, atom.insertion_code)) {
+            lines.push(format!(
+                "MODRES {:<4} {:>3} {} {:>4}{} {:>3}  {}",
+                id_code,
+                name,
+                atom.chain,
+                atom.sequence_number,
+                atom.insertion_code,
+                residue_name(&standard),
+                description
+            ));
+        }
+    }
+    lines
+}
+
+/// The residue name and insertion code at one end of a helix or strand, from its details or else
+/// from the coordinates.
+fn span_end(
+    details: Option<&ResidueDetails>,
+    residue: (char, ResidueSerial),
+    residue_names: &HashMap<(char, ResidueSerial), String>,
+) -> (String, char) {
+    match details {
+        Some(d) => (d.name.clone(), d.insertion_code),
+        None => (
+            residue_names.get(&residue).cloned().unwrap_or_default(),
+            ' ',
+        ),
+    }
+}
+
+fn helix_lines(
+    entry: &Entry,
+    residue_names: &HashMap<(char, ResidueSerial), String>,
+) -> Vec<String> {
+    let mut lines = Vec::new();
+    for (i, helix) in entry.structure.helices.iter().enumerate() {
+        let details = entry.helix_details.get(i);
+        let start = span_end(details.map(|d| &d.start), helix.start, residue_names);
+        let end = span_end(details.map(|d| &d.end), helix.end, residue_names);
+        let length = details
+            .and_then(|d| d.length)
+            .map_or(String::new(), |l| format!("{:>5}", l));
+        lines.push(format!(
+            "HELIX  {:>3} {:>3} {:>3} {} {:>4}{} {:>3} {} {:>4}{}{:>2}{:<30} {}",
+            i + 1,
+            helix.id,
+            start.0,
+            helix.start.0,
+            helix.start.1,
+            start.1,
+            end.0,
+            helix.end.0,
+            helix.end.1,
+            end.1,
+            helix_class(helix.class),
+            helix.comment,
+            length
+        ));
+    }
+    lines
+}
+
+fn sheet_lines(
+    entry: &Entry,
+    residue_names: &HashMap<(char, ResidueSerial), String>,
+) -> Vec<String> {
+    // * columns 42 - 55 or 57 - 70: an atom of the registration
+    let registration = |(atom, chain, serial): &(AtomName, char, ResidueSerial), code: char| {
+        let atom = std::str::from_utf8(&atom.0).unwrap_or("").trim_end();
+        format!(
+            "{}{:>3} {}{:>4}{}",
+            if atom.len() < 4 {
+                format!(" {:<3}", atom)
+            } else {
+                atom.to_owned()
+            },
+            residue_names
+                .get(&(*chain, *serial))
+                .map_or("", String::as_str),
+            chain,
+            serial,
+            code
+        )
+    };
+    let mut lines = Vec::new();
+    for (s, sheet) in entry.structure.sheets.iter().enumerate() {
+        for (i, strand) in sheet.strands.iter().enumerate() {
+            let details = entry.sheet_details.get(s).and_then(|d| d.get(i));
+            let start = span_end(details.map(|d| &d.start), strand.start, residue_names);
+            let end = span_end(details.map(|d| &d.end), strand.end, residue_names);
+            let mut line = format!(
+                "SHEET  {:>3} {:>3}{:>2} {:>3} {}{:>4}{} {:>3} {}{:>4}{}{:>2}",
+                i + 1,
+                sheet.id,
+                sheet.strands.len(),
+                start.0,
+                strand.start.0,
+                strand.start.1,
+                start.1,
+                end.0,
+                strand.end.0,
+                strand.end.1,
+                end.1,
+                match strand.sense {
+                    Sense::Parallel => " 1",
+                    Sense::Antiparallel => "-1",
+                    Sense::Unknown => " 0",
+                }
+            );
+            // * the first strand of a sheet has no registration
+            if let Some(r) = i.checked_sub(1).and_then(|r| sheet.registration.get(r)) {
+                let (curr, prev) = details
+                    .and_then(|d| d.registration_insertion_codes)
+                    .unwrap_or((' ', ' '));
+                line += &format!(
+                    " {} {}",
+                    registration(&r.curr, curr),
+                    registration(&r.prev, prev)
+                );
+            }
+            lines.push(line);
+        }
+    }
+    lines
+}
+
+fn ssbond_lines(entry: &Entry) -> Vec<String> {
+    entry
+        .structure
+        .ssbonds
+        .iter()
+        .enumerate()
+        .map(|(i, ssbond)| {
+            format!(
+                "SSBOND {:>3} CYS {} {:>4}    CYS {} {:>4}",
+                i + 1,
+                ssbond.a.0,
+                ssbond.a.1,
+                ssbond.b.0,
+                ssbond.b.1
+            )
+        })
+        .collect()
+}
+
+fn link_lines(entry: &Entry) -> io::Result<Vec<String>> {
+    let atoms = entry
+        .structure
+        .models
+        .first()
+        .map_or(&[][..], |m| &m.atoms[..]);
+    // * columns 13 - 27 or 43 - 57, with the name aligned as in the ATOM record of the atom
+    let end = |a: &LinkAtom| -> io::Result<String> {
+        let atom = atoms.iter().position(|atom| {
+            (atom.chain, atom.sequence_number, atom.insertion_code)
+                == (a.chain, a.sequence_number, a.insertion_code)
+                && std::str::from_utf8(&atom.name.0).map(str::trim) == Ok(a.name.as_str())
+        });
+        let name = match atom {
+            Some(i) => atom_name(&atoms[i], entry.element(0, i)),
+            None if a.name.len() < 4 => format!(" {:<3}", a.name),
+            None => a.name.clone(),
+        };
+        Ok(format!(
+            "{}{}{:>3} {}{}{}",
+            name,
+            a.alt_loc,
+            a.residue_name,
+            a.chain,
+            hybrid36_field(a.sequence_number, 4)?,
+            a.insertion_code
+        ))
+    };
+    entry
+        .links
+        .iter()
+        .map(|link| {
+            Ok(format!(
+                "LINK        {}               {}  {:>6} {:>6} {}",
+                end(&link.a)?,
+                end(&link.b)?,
+                link.a.symmetry,
+                link.b.symmetry,
+                link.length.map_or(String::new(), |l| format!("{:>5.2}", l))
+            ))
+        })
+        .collect()
+}
+
+/// The name of each residue in the first model, by chain and residue number.
+fn first_residue_names(entry: &Entry) -> HashMap<(char, ResidueSerial), String> {
+    let mut names = HashMap::new();
+    for atom in entry.structure.models.iter().take(1).flat_map(|m| &m.atoms) {
+        names
+            .entry((atom.chain, atom.sequence_number))
+            .or_insert_with(|| residue_name(&atom.residue));
+    }
+    names
+}
+
+/// The class code in columns 39 - 40 of a HELIX record; 0 for an unknown class.
+fn helix_class(class: HelixClass) -> u8 {
+    use HelixClass::*;
+    match class {
+        RightHandedAlpha => 1,
+        RightHandedOmega => 2,
+        RightHandedPi => 3,
+        RightHandedGamma => 4,
+        RightHanded310 => 5,
+        LeftHandedAlpha => 6,
+        LeftHandedOmega => 7,
+        LeftHandedGamma => 8,
+        TwoSevenRibbonHelix => 9,
+        Polyproline => 10,
+        Unknown => 0,
+    }
+}
+
+/// The records that follow the ATOM/HETATM record of an atom, by atom serial number.
+struct ExtraRecords<'a> {
+    anisou: HashMap<AtomSerial, &'a Anisou>,
+    sigatm: HashMap<AtomSerial, &'a Sigatm>,
+    siguij: HashMap<AtomSerial, &'a Anisou>,
+}
+
+fn by_serial<T>(records: &[T], id: impl Fn(&T) -> AtomSerial) -> HashMap<AtomSerial, &T> {
+    records.iter().map(|r| (id(r), r)).collect()
+}
+
+/// Writes a line padded to 80 columns.
+fn write_line<W: Write>(out: &mut W, line: &str) -> io::Result<()> {
+    writeln!(out, "{:<80}", line)
+}
+
+fn write_residue<W: Write>(
+    out: &mut W,
+    entry: &Entry,
+    model: usize,
+    residue: &ResidueView,
+    extra: &ExtraRecords,
+    counts: &mut RecordCounts,
+) -> io::Result<()> {
+    for (i, atom) in residue.atoms.iter().enumerate() {
+        let element = entry.element(model, residue.offset + i);
+        let record = if is_standard(&atom.residue) {
+            "ATOM  "
+        } else {
+            "HETATM"
+        };
+        let fields = atom_fields(atom, element)?;
+        write_line(out, &format!("{}{}", record, fields))?;
+        if atom.id1 == ' ' || atom.id1 == 'A' {
+            counts.coordinate += 1;
+        }
+        if let Some(sig) = extra.sigatm.get(&atom.id) {
+            write_line(
+                out,
+                &format!(
+                    "SIGATM{}   {:>8.3}{:>8.3}{:>8.3}{:>6.2}{:>6.2}{}",
+                    &fields[..21],
+                    sig.coord[0],
+                    sig.coord[1],
+                    sig.coord[2],
+                    sig.occupancy,
+                    sig.temperature_factor,
+                    &fields[60..]
+                ),
+            )?;
+        }
+        if let Some(u) = extra.anisou.get(&atom.id) {
+            write_line(out, &anisou_line("ANISOU", &fields, u))?;
+        }
+        if let Some(u) = extra.siguij.get(&atom.id) {
+            write_line(out, &anisou_line("SIGUIJ", &fields, u))?;
+        }
+    }
+    Ok(())
+}
+
+/// An ANISOU or SIGUIJ record, which copy columns 7 - 27 and 73 - 80 of the ATOM record.
+fn anisou_line(record: &str, fields: &str, u: &Anisou) -> String {
+    format!(
+        "{}{} {:>7}{:>7}{:>7}{:>7}{:>7}{:>7}      {}",
+        record,
+        &fields[..21],
+        u.u11,
+        u.u22,
+        u.u33,
+        u.u12,
+        u.u13,
+        u.u23,
+        &fields[70..]
+    )
+}
+
+/// A serial number or residue number in a field of `width` columns.
+fn hybrid36_field(value: u32, width: u32) -> io::Result<String> {
+    hybrid36::encode(value, width).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} does not fit in {} columns", value, width),
+        )
+    })
+}
+
+/// Columns 7 - 80 of an ATOM/HETATM record.
+fn atom_fields(atom: &Atom, element: Option<ChemicalElement>) -> io::Result<String> {
+    Ok(format!(
+        "{} {}{}{:>3} {}{}{}   {:>8.3}{:>8.3}{:>8.3}{:>6.2}{:>6.2}          {:>2}{:>2}",
+        hybrid36_field(atom.id, 5)?,
+        atom_name(atom, element),
+        atom.id1,
+        residue_name(&atom.residue),
+        atom.chain,
+        hybrid36_field(atom.sequence_number, 4)?,
+        atom.insertion_code,
+        atom.coord[0],
+        atom.coord[1],
+        atom.coord[2],
+        atom.occupancy,
+        atom.temperature_factor,
+        element_symbol(element),
+        charge(atom.charge)
+    ))
+}
+
+/// Standard residues are written as ATOM, everything else as HETATM.
+pub(crate) fn is_standard(residue: &Residue) -> bool {
+    matches!(
+        residue,
+        Residue::AminoAcid(AminoAcid::Standard(_))
+            | Residue::AminoAcid(AminoAcid::Unknown)
+            | Residue::Nucleotide(Nucleotide::Standard(_))
+            | Residue::Nucleotide(Nucleotide::Unknown)
+    )
+}
+
+/// The atom name in columns 13 - 16. Names of atoms of one-letter elements start at column 14.
+fn atom_name(atom: &Atom, element: Option<ChemicalElement>) -> String {
+    let name = std::str::from_utf8(&atom.name.0).unwrap_or("").trim_end();
+    if name.len() < 4 && element_symbol(element).len() < 2 {
+        format!(" {:<3}", name)
+    } else {
+        format!("{:<4}", name)
+    }
+}
+
+pub(crate) fn residue_name(residue: &Residue) -> String {
+    match residue {
+        Residue::AminoAcid(AminoAcid::Standard(aa)) => format!("{:?}", aa).to_uppercase(),
+        Residue::AminoAcid(AminoAcid::Modified(name)) => name.clone(),
+        Residue::AminoAcid(AminoAcid::Unknown) => "UNK".to_owned(),
+        Residue::Nucleotide(Nucleotide::Standard(nuc)) => format!("{:?}", nuc),
+        Residue::Nucleotide(Nucleotide::Modified(name)) => name.clone(),
+        Residue::Nucleotide(Nucleotide::Unknown) => "N".to_owned(),
+        Residue::Water => "HOH".to_owned(),
+        Residue::Metal(element) => element_symbol(ChemicalElement::from_element(*element)),
+        Residue::Other(name) => name.clone(),
+        Residue::UnknownAtomOrIon => "UNX".to_owned(),
+        Residue::Molecule(_) | Residue::UnknownLigand => "UNL".to_owned(),
+    }
+}
+
+fn element_symbol(element: Option<ChemicalElement>) -> String {
+    element.map_or(String::new(), |e| e.symbol().to_uppercase())
+}
+
+fn charge(charge: i8) -> String {
+    match charge {
+        0 => String::new(),
+        c if c > 0 => format!("{}+", c),
+        c => format!("{}-", -c),
+    }
+}
@@ -0,0 +1,503 @@
+// Copyright (c) 2020 Tianyi Shi
+//
+// This software is released under the MIT License.
+// https://opensource.org/licenses/MIT
+
+//! Records of the title section, in the order of the format specification: HEADER, OBSLTE, TITLE,
+//! SPLIT, CAVEAT, COMPND, SOURCE, KEYWDS, EXPDTA, NUMMDL, MDLTYP, AUTHOR, REVDAT, SPRSDE and JRNL.
+//!
+//! Text continued over several lines is broken where the parsers put it back together unchanged:
+//! at a blank, or after a hyphen or an asterisk (see `join_continued`), or, in lists, after a
+//! delimiter.
+
+use super::technique;
+use crate::entry::Entry;
+use crate::title_section::{Compound, Journal, ModificationType, Revdat, SerialNumber, Source};
+use crate::types::Header;
+use chrono::NaiveDate;
+
+pub(super) fn title_section_lines(entry: &Entry) -> Vec<String> {
+    let metadata = entry.structure.metadata.as_ref();
+    let title_section = &entry.title_section;
+    let mut lines = Vec::new();
+    if let Some(header) = metadata.and_then(|m| m.header.as_ref()) {
+        lines.push(format!(
+            "HEADER    {:<40}{:9}   {:<4}",
+            header.classification,
+            // * a default date stands for an unknown one, as in entries read from mmCIF files
+            if header.deposition_date == Header::default().deposition_date {
+                String::new()
+            } else {
+                date(header.deposition_date)
+            },
+            header.id_code
+        ));
+    }
+    if let Some(obsolete) = &title_section.obsolete {
+        lines.extend(id_code_lines(
+            "OBSLTE",
+            obsolete.replacement_date,
+            &obsolete.id_code,
+            &obsolete.replaced_by,
+        ));
+    }
+    if let Some(title) = metadata.and_then(|m| m.title.as_ref()) {
+        lines.extend(title_lines(title));
+    }
+    if let Some(split) = &title_section.split {
+        for (k, chunk) in split.chunks(14).enumerate() {
+            lines.push(format!(
+                "SPLIT   {:>2} {}",
+                continuation(k),
+                id_codes(chunk)
+            ));
+        }
+    }
+    if let Some(caveat) = &title_section.caveat {
+        for (k, piece) in wrap(&caveat.comment, 60, 60, None).iter().enumerate() {
+            lines.push(format!(
+                "CAVEAT  {:>2} {:<4}    {}",
+                continuation(k),
+                caveat.id_code,
+                piece
+            ));
+        }
+    }
+    if !title_section.compounds.is_empty() {
+        let specifications = title_section.compounds.iter().flat_map(compound);
+        lines.extend(specification_lines("COMPND", 80, specifications));
+    }
+    if !title_section.sources.is_empty() {
+        let specifications = title_section.sources.iter().flat_map(source);
+        lines.extend(specification_lines("SOURCE", 79, specifications));
+    }
+    if let Some(keywords) = &title_section.keywords {
+        lines.extend(continued(
+            "KEYWDS",
+            79,
+            &[list(keywords, ',', ", ")],
+            Some(','),
+        ));
+    }
+    if let Some(techniques) = metadata.and_then(|m| m.experimental_techniques.as_ref()) {
+        let techniques: Vec<String> = techniques.iter().map(technique).collect();
+        lines.extend(continued("EXPDTA", 79, &[techniques.join("; ")], Some(';')));
+    }
+    if let Some(model_count) = title_section.model_count {
+        lines.push(format!("NUMMDL    {:<4}", model_count));
+    }
+    if let Some(model_type) = &title_section.model_type {
+        lines.extend(continued(
+            "MDLTYP",
+            80,
+            &[list(model_type, ';', "; ")],
+            Some(';'),
+        ));
+    }
+    if let Some(authors) = metadata.and_then(|m| m.authors.as_ref()) {
+        lines.extend(continued(
+            "AUTHOR",
+            79,
+            &[list(authors, ',', ",")],
+            Some(','),
+        ));
+    }
+    for revision in &title_section.revisions {
+        lines.extend(revision_lines(revision));
+    }
+    if let Some(superseded) = &title_section.superseded {
+        lines.extend(id_code_lines(
+            "SPRSDE",
+            superseded.date,
+            &superseded.id_code,
+            &superseded.superseded,
+        ));
+    }
+    if let Some(journal) = &title_section.journal {
+        lines.extend(journal_lines(journal));
+    }
+    lines
+}
+
+/// A date in the DD-MMM-YY format.
+fn date(date: NaiveDate) -> String {
+    date.format("%d-%b-%y").to_string().to_uppercase()
+}
+
+/// The continuation field of the `k`th line of a record: blank on the first line, then 2, 3, ...
+fn continuation(k: usize) -> String {
+    if k == 0 {
+        String::new()
+    } else {
+        (k + 1).to_string()
+    }
+}
+
+/// ID codes in fields of four columns separated by a blank.
+fn id_codes(id_codes: &[String]) -> String {
+    let fields: Vec<String> = id_codes.iter().map(|id| format!("{:<4}", id)).collect();
+    fields.join(" ")
+}
+
+/// The items of a List or an SList, with `delimiter` escaped within items.
+fn list(items: &[String], delimiter: char, separator: &str) -> String {
+    let escaped = format!("\\{}", delimiter);
+    let items: Vec<String> = items
+        .iter()
+        .map(|item| item.replace(delimiter, &escaped))
+        .collect();
+    items.join(separator)
+}
+
+/// Breaks `text` into pieces of at most `first` characters for the first line and `width` for the
+/// others, which `join_continued` (and `split_list` with `delimiter`, if any) turn back into the
+/// original text. Lists are broken after a delimiter where possible, so that items are kept whole.
+fn wrap(text: &str, first: usize, width: usize, delimiter: Option<char>) -> Vec<String> {
+    let chars: Vec<char> = text.trim().chars().collect();
+    let mut rest = &chars[..];
+    let mut limit = first;
+    let mut pieces = Vec::new();
+    while rest.len() > limit {
+        let delimited = |k: usize| Some(rest[k - 1]) == delimiter && (k < 2 || rest[k - 2] != '\\');
+        // * (preference, end of the piece, start of the rest) for a break after `k` characters
+        let split = |k: usize| match (rest[k - 1], rest[k]) {
+            (' ', _) | ('-', ' ') | ('*', ' ') => None,
+            (_, ' ') if delimited(k) => Some((2, k, k + 1)),
+            (_, ' ') => Some((1, k, k + 1)),
+            _ if delimited(k) => Some((2, k, k)),
+            ('-', _) | ('*', _) => Some((1, k, k)),
+            _ => None,
+        };
+        let (_, end, start) = (1..=limit)
+            .filter_map(split)
+            .max()
+            .unwrap_or((0, limit, limit));
+        pieces.push(rest[..end].iter().collect());
+        rest = &rest[start..];
+        limit = width;
+    }
+    if !rest.is_empty() {
+        pieces.push(rest.iter().collect());
+    }
+    pieces
+}
+
+/// Lines of a record whose text starts in column 11 and ends by `last_column`. Each item of
+/// `texts` starts a new line; lines after the first are numbered in columns 8 - 10 and their text
+/// starts in column 12.
+fn continued(
+    record: &str,
+    last_column: usize,
+    texts: &[String],
+    delimiter: Option<char>,
+) -> Vec<String> {
+    let width = last_column - 10;
+    let mut lines = Vec::new();
+    for text in texts {
+        let first = if lines.is_empty() { width } else { width - 1 };
+        for piece in wrap(text, first, width - 1, delimiter) {
+            lines.push(match lines.len() {
+                0 => format!("{}    {}", record, piece),
+                k => format!("{}{:>4} {}", record, k + 1, piece),
+            });
+        }
+    }
+    lines
+}
+
+/// COMPND or SOURCE records, with each specification on a new line.
+fn specification_lines(
+    record: &str,
+    last_column: usize,
+    specifications: impl Iterator<Item = String>,
+) -> Vec<String> {
+    let mut texts: Vec<String> = specifications.collect();
+    let last = texts.len() - 1;
+    for text in &mut texts[..last] {
+        text.push(';');
+    }
+    continued(record, last_column, &texts, Some(';'))
+}
+
+/// The `TOKEN: value` pairs of a Specification List.
+#[derive(Default)]
+struct Specification(Vec<String>);
+
+impl Specification {
+    fn value(&mut self, token: &str, value: &Option<String>) {
+        if let Some(value) = value {
+            self.0
+                .push(format!("{}: {}", token, value.replace(';', "\\;")));
+        }
+    }
+
+    fn list<T: ToString>(&mut self, token: &str, items: &[T]) {
+        if !items.is_empty() {
+            let items: Vec<String> = items.iter().map(T::to_string).collect();
+            self.value(token, &Some(list(&items, ',', ", ")));
+        }
+    }
+
+    fn flag(&mut self, token: &str, set: bool) {
+        if set {
+            self.value(token, &Some("YES".to_owned()));
+        }
+    }
+}
+
+fn compound(compound: &Compound) -> Vec<String> {
+    let mut s = Specification::default();
+    s.value("MOL_ID", &Some(compound.mol_id.to_string()));
+    s.value("MOLECULE", &compound.molecule);
+    s.list("CHAIN", &compound.chains);
+    s.value("FRAGMENT", &compound.fragment);
+    s.list("SYNONYM", &compound.synonyms);
+    s.list("EC", &compound.ec);
+    s.flag("ENGINEERED", compound.engineered);
+    s.flag("MUTATION", compound.mutation);
+    s.value("OTHER_DETAILS", &compound.other_details);
+    s.0
+}
+
+fn source(source: &Source) -> Vec<String> {
+    let mut s = Specification::default();
+    s.value("MOL_ID", &Some(source.mol_id.to_string()));
+    s.flag("SYNTHETIC", source.synthetic);
+    s.value("FRAGMENT", &source.fragment);
+    s.value("ORGANISM_SCIENTIFIC", &source.organism_scientific);
+    s.list("ORGANISM_COMMON", &source.organism_common);
+    s.list("ORGANISM_TAXID", &source.organism_taxid);
+    s.value("STRAIN", &source.strain);
+    s.value("VARIANT", &source.variant);
+    s.value("CELL_LINE", &source.cell_line);
+    s.value("ATCC", &source.atcc);
+    s.value("ORGAN", &source.organ);
+    s.value("TISSUE", &source.tissue);
+    s.value("CELL", &source.cell);
+    s.value("ORGANELLE", &source.organelle);
+    s.value("SECRETION", &source.secretion);
+    s.value("CELLULAR_LOCATION", &source.cellular_location);
+    s.value("PLASMID", &source.plasmid);
+    s.list("GENE", &source.gene);
+    s.value("EXPRESSION_SYSTEM", &source.expression_system);
+    s.list("EXPRESSION_SYSTEM_COMMON", &source.expression_system_common);
+    s.list("EXPRESSION_SYSTEM_TAXID", &source.expression_system_taxid);
+    s.value("EXPRESSION_SYSTEM_STRAIN", &source.expression_system_strain);
+    s.value(
+        "EXPRESSION_SYSTEM_VARIANT",
+        &source.expression_system_variant,
+    );
+    s.value(
+        "EXPRESSION_SYSTEM_CELL_LINE",
+        &source.expression_system_cell_line,
+    );
+    s.value(
+        "EXPRESSION_SYSTEM_ATCC_NUMBER",
+        &source.expression_system_atcc_number,
+    );
+    s.value("EXPRESSION_SYSTEM_ORGAN", &source.expression_system_organ);
+    s.value("EXPRESSION_SYSTEM_TISSUE", &source.expression_system_tissue);
+    s.value("EXPRESSION_SYSTEM_CELL", &source.expression_system_cell);
+    s.value(
+        "EXPRESSION_SYSTEM_ORGANELLE",
+        &source.expression_system_organelle,
+    );
+    s.value(
+        "EXPRESSION_SYSTEM_CELLULAR_LOCATION",
+        &source.expression_system_cellular_location,
+    );
+    s.value(
+        "EXPRESSION_SYSTEM_VECTOR_TYPE",
+        &source.expression_system_vector_type,
+    );
+    s.value("EXPRESSION_SYSTEM_VECTOR", &source.expression_system_vector);
+    s.value(
+        "EXPRESSION_SYSTEM_PLASMID",
+        &source.expression_system_plasmid,
+    );
+    s.value("EXPRESSION_SYSTEM_GENE", &source.expression_system_gene);
+    s.value("OTHER_DETAILS", &source.other_details);
+    s.0
+}
+
+/// TITLE records. Lines after the first start with the blank, if any, that separates their text
+/// from that of the line before.
+fn title_lines(title: &str) -> Vec<String> {
+    let chars: Vec<char> = title.trim().chars().collect();
+    let mut rest = &chars[..];
+    let mut lines = Vec::new();
+    while !rest.is_empty() {
+        let mut end = rest.len().min(70);
+        if end < rest.len() {
+            // * break before a blank, or else anywhere the piece does not end with a blank
+            let breaks = (1..=end).rev();
+            end = breaks
+                .clone()
+                .find(|&k| rest[k] == ' ' && rest[k - 1] != ' ')
+                .or_else(|| breaks.clone().find(|&k| rest[k - 1] != ' '))
+                .unwrap_or(end);
+        }
+        let piece: String = rest[..end].iter().collect();
+        lines.push(match lines.len() {
+            0 => format!("TITLE     {}", piece),
+            k => format!("TITLE   {:>2}{}", k + 1, piece),
+        });
+        rest = &rest[end..];
+    }
+    lines
+}
+
+/// OBSLTE or SPRSDE records, with nine ID codes per line.
+fn id_code_lines(record: &str, date: NaiveDate, id_code: &str, id_codes: &[String]) -> Vec<String> {
+    let mut chunks = id_codes.chunks(9);
+    let mut lines = vec![format!(
+        "{}     {} {:<4}      {}",
+        record,
+        self::date(date),
+        id_code,
+        self::id_codes(chunks.next().unwrap_or_default())
+    )];
+    for (k, chunk) in chunks.enumerate() {
+        lines.push(format!(
+            "{}  {:>2}{:21}{}",
+            record,
+            k + 2,
+            "",
+            self::id_codes(chunk)
+        ));
+    }
+    lines
+}
+
+fn revision_lines(revision: &Revdat) -> Vec<String> {
+    let modification_type = match revision.modification_type {
+        ModificationType::InitialRelease => "0",
+        ModificationType::OtherModification => "1",
+        ModificationType::UnknownModification => " ",
+    };
+    let records: Vec<String> = revision
+        .records
+        .iter()
+        .map(|r| format!("{:<6}", r))
+        .collect();
+    let mut chunks = records.chunks(4);
+    let mut lines = vec![format!(
+        "REVDAT {:>3}   {} {:<4}    {}       {}",
+        revision.modification_number,
+        date(revision.modification_date),
+        revision.id_code,
+        modification_type,
+        chunks.next().unwrap_or_default().join(" ")
+    )];
+    for (k, chunk) in chunks.enumerate() {
+        lines.push(format!(
+            "REVDAT {:>3}{:>2}{:27}{}",
+            revision.modification_number,
+            k + 2,
+            "",
+            chunk.join(" ")
+        ));
+    }
+    lines
+}
+
+fn journal_lines(journal: &Journal) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut sub_record = |name: &str, pieces: Vec<String>| {
+        for (k, piece) in pieces.iter().enumerate() {
+            lines.push(format!(
+                "JRNL        {:<4}{:>2} {}",
+                name,
+                continuation(k),
+                piece
+            ));
+        }
+    };
+    let text = |text: &str, delimiter| wrap(text, 60, 60, delimiter);
+    if !journal.authors.is_empty() {
+        sub_record("AUTH", text(&list(&journal.authors, ',', ","), Some(',')));
+    }
+    if let Some(title) = &journal.title {
+        sub_record("TITL", text(title, None));
+    }
+    if !journal.editors.is_empty() {
+        sub_record("EDIT", text(&list(&journal.editors, ',', ","), Some(',')));
+    }
+    if let Some(reference) = &journal.reference {
+        let mut pieces = wrap(&reference.publication_name, 28, 28, None);
+        if pieces.is_empty() {
+            pieces.push(String::new());
+        }
+        pieces[0] = format!(
+            "{:<28}  {}{:>4} {:>5} {:>4}",
+            pieces[0],
+            if reference.volume.is_some() {
+                "V."
+            } else {
+                "  "
+            },
+            reference.volume.as_deref().unwrap_or(""),
+            reference.page.as_deref().unwrap_or(""),
+            reference.year.map_or(String::new(), |y| y.to_string())
+        );
+        sub_record("REF", pieces);
+    }
+    if let Some(publisher) = &journal.publisher {
+        sub_record("PUBL", text(publisher, None));
+    }
+    if let Some((kind, serial)) = &journal.serial {
+        let kind = match kind {
+            SerialNumber::Issn => "ISSN",
+            SerialNumber::Essn => "ESSN",
+        };
+        sub_record("REFN", vec![format!("{:16}{} {}", "", kind, serial)]);
+    }
+    if let Some(pmid) = journal.pmid {
+        sub_record("PMID", vec![pmid.to_string()]);
+    }
+    if let Some(doi) = &journal.doi {
+        sub_record("DOI", text(doi, None));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::parser::{join_continued, split_list};
+    #[test]
+    fn test_wrap() {
+        let molecule = "DNA (5'-D(*TP*CP*CP*AP*CP*AP*TP*TP*TP*GP*AP*AP*AP*GP*GP*CP*AP*AP*A)-3')";
+        let pieces = wrap(molecule, 10, 30, None);
+        assert!(pieces.iter().all(|p| p.len() <= 30));
+        assert_eq!(pieces[0], "DNA (5'-");
+        assert_eq!(join_continued(pieces.iter().map(String::as_str)), molecule);
+
+        let authors = vec![
+            "H.VOM BRUCH".to_owned(),
+            "D.HAN,JR".to_owned(),
+            "C.K.L.NG".to_owned(),
+        ];
+        let text = list(&authors, ',', ",");
+        let pieces = wrap(&text, 12, 12, Some(','));
+        assert_eq!(pieces, vec!["H.VOM BRUCH,", "D.HAN\\,JR,", "C.K.L.NG"]);
+        let joined = join_continued(pieces.iter().map(String::as_str));
+        assert_eq!(
+            split_list(&joined, ','),
+            vec!["H.VOM BRUCH", "D.HAN,JR", "C.K.L.NG"]
+        );
+    }
+
+    #[test]
+    fn test_title_lines() {
+        let title = "STRUCTURE OF ISOPROPYLMALATE DEHYDROGENASE FROM THERMUS THERMOPHILUS \
+                     IN COMPLEX WITH IPM, MN AND NADH";
+        assert_eq!(
+            title_lines(title),
+            vec![
+                "TITLE     STRUCTURE OF ISOPROPYLMALATE DEHYDROGENASE FROM THERMUS THERMOPHILUS",
+                "TITLE    2 IN COMPLEX WITH IPM, MN AND NADH"
+            ]
+        );
+    }
+}
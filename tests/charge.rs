@@ -0,0 +1,36 @@
+use nom_pdb::Parser;
+
+#[test]
+fn charges() {
+    let data = "\
+ATOM      1  NZ  LYS A   1      11.104   6.134  -6.504  1.00  0.00           N1+
+ATOM      2  OD1 ASP A   2      11.104   6.134  -6.504  1.00  0.00           O1-
+ATOM      3  OD2 ASP A   2      11.104   6.134  -6.504  1.00  0.00           O  
+TER       4      ASP A   2                                                      
+HETATM    5 ZN    ZN A 101      11.104   6.134  -6.504  1.00  0.00          ZN+2
+HETATM    6 CL    CL A 102      11.104   6.134  -6.504  1.00  0.00          CL-
+HETATM    7  O   HOH A 201      11.104   6.134  -6.504  1.00  0.00           O
+END
+";
+    let entry = Parser::parse_entry(data.as_bytes()).unwrap();
+    let charges: Vec<i8> = entry.structure.models[0]
+        .atoms
+        .iter()
+        .map(|a| a.charge)
+        .collect();
+    assert_eq!(charges, vec![1, -1, 0, 2, -1, 0]);
+
+    let summary = entry.hierarchy()[0].charge_summary();
+    assert_eq!(summary.residues.len(), 2);
+    assert_eq!(summary.residues[1].residue_name, "ASP");
+    assert_eq!(summary.residues[1].net_charge, -1);
+    assert_eq!(
+        summary
+            .ligands
+            .iter()
+            .map(|c| (c.residue_name.as_str(), c.net_charge))
+            .collect::<Vec<_>>(),
+        vec![(" ZN", 2), (" CL", -1)]
+    );
+    assert_eq!(summary.total, 1);
+}
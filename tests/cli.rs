@@ -0,0 +1,145 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+fn run(args: &[&str], stdin: Option<&[u8]>) -> (i32, String) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_nom-pdb"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    let input = stdin.unwrap_or_default().to_vec();
+    child.stdin.take().unwrap().write_all(&input).unwrap();
+    let output = child.wait_with_output().unwrap();
+    (
+        output.status.code().unwrap(),
+        String::from_utf8(output.stdout).unwrap(),
+    )
+}
+
+#[test]
+fn commands() {
+    let (code, info) = run(&["info", "assets/1a8o.pdb"], None);
+    assert_eq!(code, 0);
+    assert!(info.contains("ID: 1A8O"));
+    assert!(info.contains("Atoms: 644"));
+
+    let (_, fasta) = run(
+        &[
+            "fasta",
+            "--width",
+            "0",
+            "--no-description",
+            "assets/1a8o.pdb",
+        ],
+        None,
+    );
+    assert!(fasta.starts_with(">1A8O|Chain A|Entity 1\nMDIRQGP"));
+
+    let (code, selected) = run(&["select", "name CA", "assets/1a8o.pdb"], None);
+    assert_eq!(code, 0);
+    assert_eq!(
+        selected
+            .lines()
+            .filter(|l| l.starts_with("ATOM") || l.starts_with("HETATM"))
+            .count(),
+        70
+    );
+
+    let (code, _) = run(&["validate", "assets/1a8o.pdb"], None);
+    assert_eq!(code, 0);
+
+    // * from standard input, through mmCIF and back
+    let pdb = std::fs::read("assets/1a8o.pdb").unwrap();
+    let (_, cif) = run(&["convert", "-"], Some(&pdb));
+    assert!(cif.starts_with("data_1A8O"));
+    let (_, back) = run(&["convert", "--to", "pdb"], Some(cif.as_bytes()));
+    let (_, direct) = run(&["select", "all"], Some(&pdb));
+    let atoms = |s: &str| s.lines().filter(|l| l.starts_with("ATOM")).count();
+    assert_eq!(atoms(&back), atoms(&direct));
+
+    let (_, renumbered) = run(&["renumber", "--start", "1", "assets/1a8o.pdb"], None);
+    let first_atom = renumbered
+        .lines()
+        .find(|l| l.starts_with("HETATM"))
+        .unwrap();
+    assert!(first_atom.contains("MSE A   1"));
+    // * the annotations are written with the new numbers
+    assert!(renumbered.contains("SSBOND   1 CYS A   48    CYS A   68"));
+
+    let (code, stats) = run(&["stats", "assets/1a8o.pdb"], None);
+    assert_eq!(code, 0);
+    assert!(stats.contains("waters: 88"));
+
+    let (code, json) = run(&["json", "assets/1a8o.pdb"], None);
+    if cfg!(feature = "serde") {
+        assert_eq!(code, 0);
+        assert!(json.starts_with("{\"schema_version\":"));
+    } else {
+        assert_ne!(code, 0);
+    }
+
+    let (_, stats) = run(&["stats", "--no-hydrogens", "assets/7znf.pdb"], None);
+    assert!(stats.contains("atoms: 254"));
+    let (code, protonated) = run(&["add-hydrogens", "--his", "hid", "assets/1a8o.pdb"], None);
+    assert_eq!(code, 0);
+    assert!(protonated
+        .lines()
+        .any(|l| l.starts_with("ATOM") && l[12..16].trim() == "HD1"));
+
+    assert_eq!(run(&["select", "chain", "assets/1a8o.pdb"], None).0, 2);
+    assert_eq!(run(&["frobnicate"], None).0, 2);
+}
+
+#[test]
+fn help() {
+    for flag in &["--help", "-h", "help"] {
+        let (code, usage) = run(&[flag], None);
+        assert_eq!(code, 0);
+        assert!(usage.starts_with("Usage: nom-pdb"));
+    }
+}
+
+#[test]
+fn annotations_survive_commands() {
+    let cryst1 = "CRYST1   41.980   41.980   88.920  90.00  90.00  90.00 P 43 21 2     8";
+    for args in &[
+        &["select", "chain A"][..],
+        &["renumber", "--offset", "10"],
+        &["add-hydrogens"],
+    ] {
+        let args: Vec<&str> = args
+            .iter()
+            .copied()
+            .chain(Some("assets/1a8o.pdb"))
+            .collect();
+        let (code, pdb) = run(&args, None);
+        assert_eq!(code, 0);
+        assert!(pdb.contains(cryst1), "{:?}", args);
+        assert!(
+            pdb.contains("TITLE     HIV CAPSID C-TERMINAL DOMAIN"),
+            "{:?}",
+            args
+        );
+        assert!(pdb.contains("JRNL        PMID   9346481"), "{:?}", args);
+    }
+
+    let prefix = std::env::temp_dir().join(format!("nom-pdb-{}-model", std::process::id()));
+    let prefix = prefix.to_str().unwrap();
+    let (code, _) = run(
+        &["split-models", "--prefix", prefix, "assets/7znf.pdb"],
+        None,
+    );
+    assert_eq!(code, 0);
+    let path = format!("{}_1.pdb", prefix);
+    let model = std::fs::read_to_string(&path).unwrap();
+    for k in 1..=12 {
+        std::fs::remove_file(format!("{}_{}.pdb", prefix, k)).unwrap();
+    }
+    assert!(
+        model.contains("CRYST1    1.000    1.000    1.000  90.00  90.00  90.00 P 1           1")
+    );
+    assert!(model.contains("EXPDTA    SOLUTION NMR"));
+    assert!(!model.contains("NUMMDL"));
+}
@@ -0,0 +1,8 @@
+use nom_pdb::{Entry, Parser};
+use std::fs;
+
+/// Parses the entry `assets/{id}.pdb`.
+pub fn read(id: &str) -> Entry {
+    let data = fs::read(format!("assets/{}.pdb", id)).unwrap();
+    Parser::parse_entry(&data).unwrap()
+}
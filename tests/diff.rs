@@ -0,0 +1,86 @@
+mod common;
+
+use common::read;
+use nom_pdb::diff::{AtomChange, ChainChange, DiffOptions, ResidueChange, ResidueDifference};
+use nom_pdb::dssp::SecondaryStructure;
+
+#[test]
+fn identical() {
+    let entry = read("1a8o");
+    let diff = entry.diff(&entry.clone(), &DiffOptions::default());
+    assert!(diff.is_empty());
+    assert_eq!(diff.to_string(), "No differences\n");
+}
+
+#[test]
+fn differences() {
+    let a = read("1a8o");
+    let mut b = a.clone();
+    b.structure.metadata.as_mut().unwrap().title = Some("SOMETHING ELSE".to_owned());
+    b.remove_waters();
+    b.structure.helices.remove(0);
+    let gly = b.hierarchy()[0].chains[0]
+        .polymer
+        .iter()
+        .find(|r| r.name() == "GLY")
+        .unwrap()
+        .residue()
+        .clone();
+    let atoms = &mut b.structure.models[0].atoms;
+    // * ASP 152 becomes a glycine, and the CA of ILE 153 moves
+    for atom in atoms.iter_mut().filter(|a| a.sequence_number == 152) {
+        atom.residue = gly.clone();
+    }
+    let ca = atoms.iter().position(|a| a.sequence_number == 153).unwrap() + 1;
+    atoms[ca].coord[0] += 0.5;
+    b.rename_chain('A', 'B').unwrap();
+
+    let diff = a.diff(&b, &DiffOptions::default());
+    assert_eq!(diff.models, None);
+    assert_eq!(diff.metadata.len(), 1, "{:?}", diff.metadata);
+    assert!(diff.metadata.iter().any(|m| m.field == "title"));
+    assert_eq!(
+        diff.chains,
+        vec![ChainChange::Renamed { from: 'A', to: 'B' }]
+    );
+    let removed = diff
+        .residues
+        .iter()
+        .filter(|r| r.change == ResidueChange::Removed("HOH".to_owned()))
+        .count();
+    assert_eq!(removed, 88);
+    assert!(diff.residues.contains(&ResidueDifference {
+        chain: 'A',
+        sequence_number: 152,
+        insertion_code: ' ',
+        change: ResidueChange::Mutated {
+            from: "ASP".to_owned(),
+            to: "GLY".to_owned()
+        }
+    }));
+    assert_eq!(diff.atoms.len(), 1);
+    assert_eq!(diff.atoms[0].atom, "CA");
+    assert!(matches!(diff.atoms[0].change, AtomChange::Moved(d) if (d - 0.5).abs() < 1e-3));
+    assert!(!diff.secondary_structure.is_empty());
+    assert!(diff
+        .secondary_structure
+        .iter()
+        .all(|s| s.a == SecondaryStructure::AlphaHelix && s.b == SecondaryStructure::Coil));
+
+    let text = diff.to_string();
+    assert!(text.contains("  A renamed to B\n"));
+    assert!(text.contains("  A 153 ILE CA moved by 0.500 Å\n"));
+    assert!(text.contains("Secondary structure:\n  A "));
+}
+
+#[test]
+fn chains() {
+    let a = read("3l1p");
+    let mut b = a.clone();
+    b.retain_atoms(|_, _, atom| atom.chain != 'N');
+    let diff = a.diff(&b, &DiffOptions::default());
+    assert_eq!(diff.chains, vec![ChainChange::Removed('N')]);
+    assert!(diff.residues.is_empty());
+    let diff = b.diff(&a, &DiffOptions::default());
+    assert_eq!(diff.chains, vec![ChainChange::Added('N')]);
+}
@@ -0,0 +1,33 @@
+use nom_pdb::{Parser, Writer};
+
+const DATA: &str = "\
+ATOM    107  N   GLY A  13      12.681  37.302 -25.211  1.00 15.56           N  
+SIGATM  107  N   GLY A  13       0.003   0.003   0.003  0.00  0.05           N  
+ANISOU  107  N   GLY A  13     2406   1892   1614    198    519   -328       N  
+SIGUIJ  107  N   GLY A  13       10     10     10     10     10     10       N  
+ATOM    108  CA  GLY A  13      11.982  37.996 -26.241  1.00 16.92           C  
+END                                                                             
+";
+
+#[test]
+fn displacement() {
+    let entry = Parser::parse_entry(DATA.as_bytes()).unwrap();
+    let adp = entry.displacements(0);
+    assert_eq!(adp.len(), 2);
+    assert!(adp[1].is_none());
+    let u = adp[0].unwrap();
+    assert!((u.u[0][0] - 0.2406).abs() < 1e-6);
+    assert!((u.u[2][1] - -0.0328).abs() < 1e-6);
+    assert!((u.b_eq() - 15.56).abs() < 0.01);
+    assert!(u.is_positive_definite());
+
+    let sig = entry.displacement_uncertainties(0);
+    assert!((sig[0].unwrap().u[1][2] - 0.001).abs() < 1e-6);
+    let sigatm = entry.atom_uncertainties(0);
+    assert_eq!(sigatm[0].unwrap().temperature_factor, 0.05);
+    assert!(sigatm[1].is_none());
+
+    let written = Writer::write_to_string(&entry);
+    let lines: Vec<&str> = written.lines().take(5).collect();
+    assert_eq!(lines, DATA.lines().take(5).collect::<Vec<_>>());
+}
@@ -0,0 +1,48 @@
+use nom_pdb::dssp::SecondaryStructure;
+use nom_pdb::Parser;
+use std::collections::HashMap;
+use std::fs;
+
+#[test]
+fn dssp() {
+    let data = fs::read("assets/4f7i.pdb").unwrap();
+    let entry = Parser::parse_entry(&data).unwrap();
+    let dssp = entry.hierarchy()[0].dssp();
+
+    let mut author = HashMap::new();
+    for helix in &entry.structure.helices {
+        for seq in helix.start.1..=helix.end.1 {
+            author.insert((helix.start.0, seq), 'H');
+        }
+    }
+    for sheet in &entry.structure.sheets {
+        for strand in &sheet.strands {
+            for seq in strand.start.1..=strand.end.1 {
+                author.insert((strand.start.0, seq), 'E');
+            }
+        }
+    }
+    let agree = dssp
+        .residues
+        .iter()
+        .filter(|r| {
+            let assigned = match r.structure {
+                SecondaryStructure::AlphaHelix
+                | SecondaryStructure::Helix310
+                | SecondaryStructure::PiHelix => 'H',
+                SecondaryStructure::Strand => 'E',
+                _ => '-',
+            };
+            author.get(&(r.chain, r.sequence_number)).unwrap_or(&'-') == &assigned
+        })
+        .count();
+    assert!(agree as f32 / dssp.residues.len() as f32 > 0.85);
+
+    assert_eq!(dssp.sheets.len(), entry.structure.sheets.len());
+    for sheet in &dssp.sheets {
+        assert_eq!(sheet.registration.len(), sheet.strands.len() - 1);
+    }
+    for r in &dssp.residues {
+        assert_eq!(r.sheet.is_some(), r.structure == SecondaryStructure::Strand);
+    }
+}
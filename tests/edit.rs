@@ -0,0 +1,151 @@
+mod common;
+
+use common::read;
+use nom_pdb::edit::Renumbering;
+use nom_pdb::{Entry, Parser, Writer};
+
+/// The edited entry, once written and read back, passes the checks.
+fn assert_consistent(entry: &Entry) {
+    assert!(
+        entry.check_integrity().is_empty(),
+        "{:?}",
+        entry.check_integrity()
+    );
+    assert!(entry.check_secondary_structure().is_empty());
+    let written = Writer::write_to_string(entry);
+    let read = Parser::parse_entry(written.as_bytes()).unwrap();
+    assert!(
+        read.check_integrity().is_empty(),
+        "{:?}",
+        read.check_integrity()
+    );
+    assert!(read.check_secondary_structure().is_empty());
+}
+
+#[test]
+fn renumber_residues() {
+    let mut entry = read("1a8o");
+    entry
+        .renumber_residues(None, Renumbering::Offset(-150))
+        .unwrap();
+    assert_eq!(entry.structure.models[0].atoms[0].sequence_number, 1);
+    assert_eq!(entry.structure.ssbonds[0].a, ('A', 48));
+    assert_consistent(&entry);
+    assert!(entry
+        .renumber_residues(None, Renumbering::Offset(-10))
+        .is_err());
+
+    let mut entry = read("3l1p");
+    entry
+        .renumber_residues(Some('A'), Renumbering::FromSeqres)
+        .unwrap();
+    // * the first three residues of SEQRES (GAM) are not observed
+    let first = &entry.structure.models[0].atoms[0];
+    assert_eq!((first.chain, first.sequence_number), ('A', 4));
+    assert_consistent(&entry);
+
+    let mut entry = read("4f7i");
+    entry
+        .renumber_residues(None, Renumbering::Sequential(1))
+        .unwrap();
+    assert_consistent(&entry);
+}
+
+#[test]
+fn chains() {
+    let mut entry = read("3l1p");
+    assert!(entry.rename_chain('A', 'B').is_err());
+    entry.rename_chain('A', 'C').unwrap();
+    assert!(entry.structure.chains_aa.iter().any(|c| c.id == 'C'));
+    assert!(entry
+        .title_section
+        .compounds
+        .iter()
+        .any(|c| c.chains.contains(&'C')));
+    assert!(entry.structure.models[0]
+        .atoms
+        .iter()
+        .all(|a| a.chain != 'A'));
+    assert_consistent(&entry);
+
+    let mut entry = read("3l1p");
+    let atoms = entry.structure.models[0].atoms.len();
+    // * the two DNA strands share residue numbers
+    assert!(entry.merge_chains('N', 'M').is_err());
+    entry
+        .renumber_residues(Some('N'), Renumbering::Offset(100))
+        .unwrap();
+    entry.merge_chains('N', 'M').unwrap();
+    assert_eq!(entry.structure.models[0].atoms.len(), atoms);
+    let view = &entry.hierarchy()[0];
+    assert!(view.chain('N').is_none());
+    assert_eq!(view.chain('M').unwrap().ter.map(|t| t.chain), Some('M'));
+    assert!(entry.structure.chains_nuc.iter().all(|c| c.id != 'N'));
+    assert_consistent(&entry);
+    assert!(entry.merge_chains('A', 'B').is_err());
+}
+
+#[test]
+fn remove() {
+    let mut entry = read("1a8o");
+    entry.remove_waters();
+    assert_eq!(entry.structure.models[0].atoms.len(), 644 - 88);
+    assert_consistent(&entry);
+
+    let mut entry = read("4f7i");
+    let before = entry.structure.models[0].atoms.len();
+    entry.strip_ligands();
+    let view = &entry.hierarchy()[0];
+    assert!(view
+        .chains
+        .iter()
+        .flat_map(|c| &c.non_polymer)
+        .all(|r| r.name() == "HOH"));
+    assert!(entry.structure.models[0].atoms.len() < before);
+    entry.remove_alt_locs();
+    assert!(entry.structure.models[0].atoms.iter().all(|a| a.id1 == ' '));
+    entry.renumber_atoms(1);
+    let model = &entry.structure.models[0];
+    assert_eq!(model.atoms[0].id, 1);
+    assert!(model.atoms.windows(2).all(|w| w[0].id < w[1].id));
+    assert_consistent(&entry);
+
+    let mut entry = read("7znf");
+    // * including one pushed directly, whose element only `Atom::element` holds
+    let hydrogen = (0..entry.structure.models[0].atoms.len())
+        .find(|&i| entry.is_hydrogen(0, i))
+        .unwrap();
+    let atom = entry.structure.models[0].atoms[hydrogen].clone();
+    entry.structure.models[0].atoms.push(atom);
+    entry.remove_hydrogens();
+    assert!((0..entry.structure.models[0].atoms.len())
+        .all(|i| !matches!(entry.element(0, i), Some(e) if e.is_hydrogen())));
+    entry.renumber_atoms(1);
+    assert_consistent(&entry);
+}
+
+#[test]
+fn insert_atom() {
+    let mut entry = read("1a8o");
+    let model = &entry.structure.models[0];
+    let ter = entry.model_info[0].ters[0].0;
+    let mut atom = model.atoms[ter - 1].clone();
+    atom.name = "OXT".parse().unwrap();
+    atom.id = 10000;
+    let element = entry.element(0, ter - 1);
+    let index = entry.insert_atom(0, atom, element);
+    assert_eq!(index, ter);
+    assert_eq!(entry.model_info[0].ters[0].0, ter + 1);
+    entry.renumber_atoms(1);
+    assert_consistent(&entry);
+
+    // * an atom pushed directly is taken into account by the next edit
+    let mut atom = entry.structure.models[0].atoms[0].clone();
+    atom.id = 10001;
+    entry.structure.models[0].atoms.push(atom.clone());
+    entry.insert_atom(0, atom, element);
+    let atoms = entry.structure.models[0].atoms.len();
+    assert_eq!(entry.model_info[0].elements().len(), atoms);
+    assert_eq!(entry.element(0, atoms - 2), entry.element(0, 0));
+    assert_eq!(entry.element(0, atoms - 1), element);
+}
@@ -0,0 +1,23 @@
+use nom_pdb::{entity::Polymer, Parser};
+use std::fs;
+
+#[test]
+fn entities() {
+    let data = fs::read("assets/3l1p.pdb").unwrap();
+    let entry = Parser::parse_entry(&data).unwrap();
+    let entities = entry.entities();
+    assert_eq!(entities.len(), 3);
+    assert_eq!(entities[0].chains, vec!['A', 'B']);
+    assert_eq!(entities[0].organism_taxid, vec![10090]);
+    assert!(entities[0].mutation);
+    assert!(matches!(entities[0].polymer, Some(Polymer::Peptide(_))));
+    assert!(entities[1].synthetic);
+    assert!(matches!(entities[1].polymer, Some(Polymer::NucleicAcid(_))));
+    assert_eq!(entry.entity_of_chain('M').unwrap().id, 3);
+
+    let data = fs::read("assets/4f7i.pdb").unwrap();
+    let entities = Parser::parse_entry(&data).unwrap().entities();
+    assert_eq!(entities.len(), 1);
+    assert_eq!(entities[0].representative_chain(), Some('A'));
+    assert_eq!(entities[0].ec, vec!["1.1.1.85".to_owned()]);
+}
@@ -0,0 +1,37 @@
+use nom_pdb::geometry::{self, distance};
+use nom_pdb::Parser;
+use std::fs;
+
+#[test]
+fn superpose_models() {
+    let data = fs::read("assets/7znf.pdb").unwrap();
+    let entry = Parser::parse_entry(&data).unwrap();
+    let first = &entry.structure.models[0].atoms;
+    let second = &entry.structure.models[1].atoms;
+
+    let itself = geometry::superpose_atoms(first, first).unwrap();
+    assert_eq!(itself.pairs, first.len());
+    assert!(itself.rmsd < 1e-3);
+
+    let s = geometry::superpose_atoms(second, first).unwrap();
+    assert_eq!(s.pairs, first.len());
+    let (mobile, target) = geometry::pair_atoms(second, first);
+    let unmoved = geometry::rmsd(&mobile, &target).unwrap();
+    assert!(s.rmsd > 0.1 && s.rmsd <= unmoved);
+    let moved: Vec<_> = mobile.iter().map(|a| s.apply(a)).collect();
+    assert!((geometry::rmsd(&moved, &target).unwrap() - s.rmsd).abs() < 1e-3);
+}
+
+#[test]
+fn shape() {
+    let data = fs::read("assets/1a8o.pdb").unwrap();
+    let entry = Parser::parse_entry(&data).unwrap();
+    let atoms = &entry.structure.models[0].atoms;
+    let centroid = geometry::centroid(atoms).unwrap();
+    let (min, max) = geometry::bounding_box(atoms).unwrap();
+    for k in 0..3 {
+        assert!(min[k] <= centroid[k] && centroid[k] <= max[k]);
+    }
+    let rg = geometry::radius_of_gyration(atoms).unwrap();
+    assert!(rg > 5.0 && rg < distance(&min, &max));
+}
@@ -0,0 +1,105 @@
+use nom_pdb::glycan::Anomer;
+use nom_pdb::{Entry, Parser};
+use std::fs;
+
+// * an asparagine with the core of an N-glycan: GlcNAc₂Man₃ with a core fucose
+const GLYCOPROTEIN: &str = "\
+LINK         ND2 ASN A  17                 C1  NAG A 701     1555   1555  1.45
+LINK         O6  NAG A 701                 C1  FUC A 706     1555   1555  1.45
+ATOM      1  N   ASN A  17      -5.000   0.000   0.000  1.00 20.00           N
+ATOM      2  CA  ASN A  17      -4.000   1.000   0.000  1.00 20.00           C
+ATOM      3  C   ASN A  17      -4.000   2.500   0.000  1.00 20.00           C
+ATOM      4  O   ASN A  17      -5.000   3.200   0.000  1.00 20.00           O
+ATOM      5  CB  ASN A  17      -2.600   0.400   0.000  1.00 20.00           C
+ATOM      6  CG  ASN A  17      -1.300   0.000   0.000  1.00 20.00           C
+ATOM      7  OD1 ASN A  17      -1.300  -1.200   0.000  1.00 20.00           O
+ATOM      8  ND2 ASN A  17       0.000   0.000   0.000  1.00 20.00           N
+TER       9      ASN A  17
+HETATM   10  C1  NAG A 701       1.450   0.000   0.000  1.00 20.00           C
+HETATM   11  O4  NAG A 701       5.000   0.000   0.000  1.00 20.00           O
+HETATM   12  O6  NAG A 701       3.000   3.000   0.000  1.00 20.00           O
+HETATM   13  C1  NAG A 702       6.450   0.000   0.000  1.00 20.00           C
+HETATM   14  O4  NAG A 702      10.000   0.000   0.000  1.00 20.00           O
+HETATM   15  C1  BMA A 703      11.450   0.000   0.000  1.00 20.00           C
+HETATM   16  O3  BMA A 703      14.000   3.000   0.000  1.00 20.00           O
+HETATM   17  O6  BMA A 703      14.000  -3.000   0.000  1.00 20.00           O
+HETATM   18  C1  MAN A 704      14.000   4.450   0.000  1.00 20.00           C
+HETATM   19  C1  MAN A 705      14.000  -4.450   0.000  1.00 20.00           C
+HETATM   20  C1  FUC A 706       3.000   4.450   0.000  1.00 20.00           C
+HETATM   21  O   HOH A 801      20.000  20.000  20.000  1.00 20.00           O
+END
+";
+
+#[test]
+fn links() {
+    let data = fs::read("assets/4f7i.pdb").unwrap();
+    let entry = Parser::parse_entry(&data).unwrap();
+    assert_eq!(entry.links.len(), 56);
+    let link = &entry.links[0];
+    assert_eq!(
+        (link.a.name.as_str(), link.a.residue_name.as_str()),
+        ("OD1", "ASP")
+    );
+    assert_eq!((link.a.chain, link.a.sequence_number), ('B', 241));
+    assert_eq!(
+        (link.b.name.as_str(), link.b.residue_name.as_str()),
+        ("MN", "MN")
+    );
+    assert_eq!((link.b.chain, link.b.sequence_number), ('B', 999));
+    assert_eq!(
+        (link.a.symmetry.as_str(), link.b.symmetry.as_str()),
+        ("1555", "1555")
+    );
+    assert_eq!(link.length, Some(2.0));
+
+    let mut entry = Parser::parse_entry(GLYCOPROTEIN.as_bytes()).unwrap();
+    entry.rename_chain('A', 'G').unwrap();
+    assert!(entry
+        .links
+        .iter()
+        .all(|l| l.a.chain == 'G' && l.b.chain == 'G'));
+
+    // * a residue number that cannot be read is an error rather than residue 0
+    let broken = GLYCOPROTEIN.replacen("ASN A  17", "ASN A  1?", 1);
+    assert!(Parser::parse_entry(broken.as_bytes()).is_err());
+}
+
+#[test]
+fn glycans() {
+    let entry = Parser::parse_entry(GLYCOPROTEIN.as_bytes()).unwrap();
+    let glycans = entry.glycans(0);
+    assert_eq!(glycans.len(), 1);
+    let glycan = &glycans[0];
+    let site = glycan.site.as_ref().unwrap();
+    assert_eq!((site.sequence_number, site.atoms[0].as_str()), (17, "ND2"));
+    assert_eq!(glycan.residues.len(), 6);
+    assert_eq!(glycan.residues[0].sequence_number, 701);
+    assert_eq!(glycan.residues[0].monosaccharide.anomer, Anomer::Beta);
+    let from_link: Vec<u32> = glycan
+        .residues
+        .iter()
+        .filter(|r| r.from_link)
+        .map(|r| r.sequence_number)
+        .collect();
+    assert_eq!(from_link, [701, 706]);
+    assert_eq!(
+        glycan.to_string(),
+        "Man(a1-6)[Man(a1-3)]Man(b1-4)GlcNAc(b1-4)[Fuc(a1-6)]GlcNAc(b1-N)Asn"
+    );
+
+    // * without its site, a glycan is rooted at its reducing end
+    let mut entry = entry;
+    entry.links.clear();
+    entry.structure.models[0].atoms[7].coord = [-3.0, -3.0, 0.0];
+    let glycan = &entry.glycans(0)[0];
+    assert!(glycan.site.is_none());
+    assert!(glycan.residues.iter().all(|r| !r.from_link));
+    assert!(glycan.to_string().ends_with("[Fuc(a1-6)]GlcNAc"));
+}
+
+#[test]
+fn free_sugars() {
+    let data = fs::read("assets/1a8o.pdb").unwrap();
+    let entry: Entry = Parser::parse_entry(&data).unwrap();
+    assert!(entry.glycans(0).is_empty());
+}
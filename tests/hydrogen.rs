@@ -0,0 +1,148 @@
+mod common;
+
+use common::read;
+use nom_pdb::geometry::distance;
+use nom_pdb::hydrogen::{HistidineState, Protonation};
+use nom_pdb::{Entry, ParseOptions, Parser, Writer};
+use std::fs;
+
+fn hydrogen_count(entry: &Entry, model: usize) -> usize {
+    (0..entry.structure.models[model].atoms.len())
+        .filter(|&i| entry.is_hydrogen(model, i))
+        .count()
+}
+
+/// The number of atoms named `name` in the model at `model`.
+fn named(entry: &Entry, model: usize, name: &str) -> usize {
+    entry.structure.models[model]
+        .atoms
+        .iter()
+        .filter(|a| std::str::from_utf8(&a.name.0).map(str::trim) == Ok(name))
+        .count()
+}
+
+#[test]
+fn skip_at_parse_time() {
+    let data = fs::read("assets/7znf.pdb").unwrap();
+    let options = ParseOptions {
+        skip_hydrogens: true,
+    };
+    let entry = Parser::parse_entry_with(&data, &options).unwrap();
+    assert_eq!(entry.structure.models.len(), 12);
+    for model in 0..12 {
+        assert_eq!(hydrogen_count(&entry, model), 0);
+        assert_eq!(entry.structure.models[model].atoms.len(), 254);
+    }
+    assert!(
+        entry.check_integrity().is_empty(),
+        "{:?}",
+        entry.check_integrity()
+    );
+
+    let mut stripped = read("7znf");
+    assert_eq!(hydrogen_count(&stripped, 0), 256);
+    stripped.remove_hydrogens();
+    assert_eq!(
+        Writer::write_to_string(&stripped),
+        Writer::write_to_string(&entry)
+    );
+}
+
+#[test]
+fn add_hydrogens() {
+    // * the hydrogens of an NMR structure are put back where they were, apart from those whose
+    // * position depends on a free rotation
+    let original = read("7znf");
+    let mut entry = original.clone();
+    entry.remove_hydrogens();
+    // * the zinc is coordinated by two Cys and by His NE2, which leaves ND1 protonated
+    let protonation = Protonation {
+        histidine: HistidineState::Delta,
+        ..Default::default()
+    };
+    assert_eq!(entry.add_hydrogens(&protonation), 256 * 12);
+    assert_eq!(entry.add_hydrogens(&protonation), 0);
+    for model in 0..12 {
+        assert_eq!(
+            entry.structure.models[model].atoms.len(),
+            original.structure.models[model].atoms.len()
+        );
+    }
+    let rotatable = ["H1", "H2", "H3", "HZ1", "HZ2", "HZ3", "HG", "HG1", "HH"];
+    let atoms = &entry.structure.models[0].atoms;
+    for a in &original.structure.models[0].atoms {
+        let name = std::str::from_utf8(&a.name.0).unwrap().trim();
+        let b = atoms
+            .iter()
+            .find(|b| {
+                b.name == a.name && b.chain == a.chain && b.sequence_number == a.sequence_number
+            })
+            .unwrap();
+        if !rotatable.contains(&name) {
+            assert!(distance(a, b) < 0.3, "{} {}", name, a.sequence_number);
+        }
+    }
+
+    let written = Writer::write_to_string(&entry);
+    let read = Parser::parse_entry(written.as_bytes()).unwrap();
+    assert!(
+        read.check_integrity().is_empty(),
+        "{:?}",
+        read.check_integrity()
+    );
+}
+
+#[test]
+fn bond_lengths() {
+    let mut entry = read("1a8o");
+    assert_eq!(hydrogen_count(&entry, 0), 0);
+    let added = entry.add_hydrogens(&Protonation::default());
+    assert_eq!(hydrogen_count(&entry, 0), added);
+    assert!(entry.check_integrity().is_empty());
+    let atoms = &entry.structure.models[0].atoms;
+    for (i, h) in atoms.iter().enumerate() {
+        if !entry.is_hydrogen(0, i) {
+            continue;
+        }
+        // * the nearest heavy atom is the one the hydrogen is bonded to
+        let nearest = atoms
+            .iter()
+            .enumerate()
+            .filter(|&(k, _)| !entry.is_hydrogen(0, k))
+            .map(|(_, a)| distance(a, h))
+            .fold(f32::INFINITY, f32::min);
+        assert!(nearest > 0.9 && nearest < 1.4, "{}", nearest);
+    }
+}
+
+#[test]
+fn protonation() {
+    let count = |protonation: Protonation, name: &str| {
+        let mut entry = read("7znf");
+        entry.remove_hydrogens();
+        entry.add_hydrogens(&protonation);
+        named(&entry, 0, name)
+    };
+    let default = Protonation::default();
+    // * Lys, Phe, Tyr and His
+    assert_eq!(count(default, "HE2"), 7 + 1 + 2 + 2);
+    // * Phe and Tyr
+    assert_eq!(count(default, "HD1"), 1 + 2);
+    let hip = Protonation {
+        histidine: HistidineState::Protonated,
+        ..default
+    };
+    assert_eq!(count(hip, "HD1"), 1 + 2 + 2);
+    let lyn = Protonation {
+        protonated_lys: false,
+        ..default
+    };
+    assert_eq!(count(lyn, "HZ3"), 0);
+    assert_eq!(count(lyn, "HZ2"), 7);
+    let acids = Protonation {
+        protonated_asp: true,
+        protonated_glu: true,
+        ..default
+    };
+    assert_eq!(count(acids, "HD2"), count(default, "HD2") + 1);
+}
@@ -0,0 +1,112 @@
+use nom_pdb::{bookkeeping::IntegrityIssue, Parser};
+use std::fs;
+
+#[test]
+fn complete_files() {
+    for f in &["3l1p", "4f7i", "7znf", "1a8o", "3SE5"] {
+        let data = fs::read(format!("assets/{}.pdb", f)).unwrap();
+        let entry = Parser::parse_entry(&data).unwrap();
+        assert!(entry.check_integrity().is_empty(), "{}", f);
+    }
+}
+
+#[test]
+fn truncated_file() {
+    let data = fs::read("assets/1a8o.pdb").unwrap();
+    let entry = Parser::parse_entry(&data[..data.len() / 2]).unwrap();
+    let issues = entry.check_integrity();
+    assert!(issues.contains(&IntegrityIssue::MissingEnd));
+    assert!(issues.contains(&IntegrityIssue::MissingMaster));
+    assert!(matches!(issues[0], IntegrityIssue::UnterminatedLine(_)));
+}
+
+#[test]
+fn truncated_records() {
+    // * cuts in the middle of records that span several lines, and at the end of the line before
+    for (id, record) in &[
+        ("1a8o", "COMPND   3"),
+        ("1a8o", "SOURCE   2"),
+        ("1a8o", "AUTHOR   2"),
+        ("1a8o", "REVDAT   3"),
+        ("1a8o", "JRNL        TITL 2"),
+        ("1a8o", "SEQRES   2"),
+        ("1a8o", "MODRES"),
+        ("4f7i", "TITLE    2"),
+        ("4f7i", "SHEET    2"),
+    ] {
+        let data = fs::read(format!("assets/{}.pdb", id)).unwrap();
+        let start = data
+            .windows(record.len())
+            .position(|w| w == record.as_bytes())
+            .unwrap();
+
+        let entry = Parser::parse_entry(&data[..start + 20]).unwrap();
+        let issues = entry.check_integrity();
+        assert!(
+            matches!(issues[0], IntegrityIssue::UnterminatedLine(_)),
+            "{} {}",
+            id,
+            record
+        );
+        assert!(issues.contains(&IntegrityIssue::MissingEnd));
+
+        let entry = Parser::parse_entry(&data[..start]).unwrap();
+        let issues = entry.check_integrity();
+        assert!(
+            issues.contains(&IntegrityIssue::MissingEnd),
+            "{} {}",
+            id,
+            record
+        );
+        assert!(!matches!(issues[0], IntegrityIssue::UnterminatedLine(_)));
+    }
+
+    // * what precedes the cut is kept
+    let data = fs::read("assets/4f7i.pdb").unwrap();
+    let cut = |record: &str| {
+        let start = data
+            .windows(record.len())
+            .position(|w| w == record.as_bytes())
+            .unwrap();
+        Parser::parse_entry(&data[..start + 20]).unwrap()
+    };
+    let entry = cut("SHEET    2");
+    assert_eq!(entry.structure.sheets[0].strands.len(), 1);
+    assert_eq!(entry.sheet_details[0].len(), 1);
+    let entry = cut("SEQRES   2 B");
+    assert_eq!(entry.structure.chains_aa.len(), 1);
+    assert_eq!(entry.structure.chains_aa[0].seq.len(), 359);
+    let entry = cut("TITLE    2");
+    assert!(entry.structure.metadata.unwrap().title.is_some());
+}
+
+#[test]
+fn models() {
+    let data = fs::read("assets/7znf.pdb").unwrap();
+    let entry = Parser::parse_entry(&data).unwrap();
+    assert_eq!(entry.structure.models.len(), 12);
+    assert_eq!(entry.model(12).unwrap().atoms.len(), 510);
+    assert_eq!(
+        entry
+            .model_view(12)
+            .unwrap()
+            .chain('A')
+            .unwrap()
+            .polymer
+            .len(),
+        30
+    );
+    assert!(entry.model(13).is_none());
+
+    let atom = "ATOM      1  N   ALA A   1      11.104   6.134  -6.504  1.00  0.00           N  ";
+    let data = format!(
+        "MODEL        1\n{0}\nENDMDL\nENDMDL\nMODEL        1\n{0}\nEND\n",
+        atom
+    );
+    let issues = Parser::parse_entry(data.as_bytes())
+        .unwrap()
+        .check_integrity();
+    assert!(issues.contains(&IntegrityIssue::UnmatchedEndmdl(1)));
+    assert!(issues.contains(&IntegrityIssue::DuplicateModelSerial(1)));
+    assert!(issues.contains(&IntegrityIssue::MissingEndmdl(1)));
+}
@@ -0,0 +1,60 @@
+use nom_pdb::interaction::{InteractionKind, Moiety};
+use nom_pdb::Parser;
+use std::fs;
+
+#[test]
+fn interactions() {
+    let data = fs::read("assets/1a8o.pdb").unwrap();
+    let entry = Parser::parse_entry(&data).unwrap();
+    let interactions = entry.interactions(0);
+    let count =
+        |f: &dyn Fn(&InteractionKind) -> bool| interactions.iter().filter(|i| f(&i.kind)).count();
+    // * a helical domain has a backbone hydrogen bond for most residues
+    let backbone = count(&|k| {
+        *k == InteractionKind::HydrogenBond {
+            donor: Moiety::Backbone,
+            acceptor: Moiety::Backbone,
+        }
+    });
+    assert!(backbone > 40, "{}", backbone);
+    assert!(count(&|k| matches!(k, InteractionKind::SaltBridge)) > 0);
+    let disulfides: Vec<_> = interactions
+        .iter()
+        .filter(|i| i.kind == InteractionKind::Disulfide)
+        .collect();
+    assert_eq!(disulfides.len(), 1);
+    assert_eq!(disulfides[0].a.sequence_number, 198);
+    assert_eq!(disulfides[0].b.sequence_number, 218);
+    assert!(entry.check_disulfides(0).is_empty());
+    for i in &interactions {
+        assert_ne!(
+            (i.a.chain, i.a.sequence_number, i.a.insertion_code),
+            (i.b.chain, i.b.sequence_number, i.b.insertion_code)
+        );
+        assert!(i.distance > 0.0 && i.distance <= 6.0, "{:?}", i);
+    }
+}
+
+#[test]
+fn metal_coordination() {
+    let data = fs::read("assets/7znf.pdb").unwrap();
+    let entry = Parser::parse_entry(&data).unwrap();
+    let ligands: Vec<String> = entry
+        .interactions(0)
+        .into_iter()
+        .filter(|i| i.kind == InteractionKind::MetalCoordination)
+        .map(|i| format!("{}{}", i.b.residue_name, i.b.atoms[0]))
+        .collect();
+    // * a zinc finger binds its zinc with two cysteines and two histidines
+    assert_eq!(ligands.iter().filter(|l| l.starts_with("CYSSG")).count(), 2);
+    assert_eq!(ligands.iter().filter(|l| l.starts_with("HIS")).count(), 2);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serialize_interactions() {
+    let data = fs::read("assets/1a8o.pdb").unwrap();
+    let entry = Parser::parse_entry(&data).unwrap();
+    let json = serde_json::to_string(&entry.interactions(0)).unwrap();
+    assert!(json.contains("\"Disulfide\""));
+}
@@ -0,0 +1,98 @@
+#![cfg(feature = "serde")]
+
+mod common;
+
+use common::read;
+use nom_pdb::json::{self, SCHEMA_VERSION};
+use nom_pdb::secondary_structure::SecondaryStructureIssue;
+use nom_pdb::validation::ValidationReport;
+use nom_pdb::Writer;
+
+#[test]
+fn round_trip() {
+    for id in &["1a8o", "3l1p", "4f7i", "7znf"] {
+        let entry = read(id);
+        let written = json::to_string(&entry);
+        let read_back = json::from_str(&written).unwrap();
+        assert_eq!(json::to_string(&read_back), written, "{}", id);
+        assert_eq!(
+            Writer::write_to_string(&read_back),
+            Writer::write_to_string(&entry),
+            "{}",
+            id
+        );
+        assert_eq!(read_back.check_integrity(), entry.check_integrity());
+    }
+}
+
+#[test]
+fn schema() {
+    let entry = read("1a8o");
+    let value: serde_json::Value = serde_json::from_str(&json::to_string_pretty(&entry)).unwrap();
+    assert_eq!(value["schema_version"], SCHEMA_VERSION);
+    let atom = &value["entry"]["structure"]["models"][0]["atoms"][0];
+    assert_eq!(atom["residue"]["AminoAcid"]["Modified"], "MSE");
+    assert_eq!(atom["chain"], "A");
+    assert_eq!(value["entry"]["model_info"][0]["elements"][0], "N");
+    assert_eq!(
+        value["entry"]["structure"]["metadata"]["header"]["deposition_date"],
+        "1998-03-27"
+    );
+
+    let mut value = value;
+    value["schema_version"] = (SCHEMA_VERSION + 1).into();
+    let error = json::from_str(&value.to_string()).unwrap_err();
+    assert!(error.to_string().contains("schema version"), "{}", error);
+    assert!(json::from_str("{\"entry\": {}}").is_err());
+}
+
+#[test]
+fn analyses() {
+    let entry = read("1a8o");
+    let report = entry.validation_report(0);
+    let written = serde_json::to_string(&report).unwrap();
+    let read_back: ValidationReport = serde_json::from_str(&written).unwrap();
+    assert_eq!(read_back, report);
+    let issues = entry.check_secondary_structure();
+    let written = serde_json::to_string(&issues).unwrap();
+    assert_eq!(
+        serde_json::from_str::<Vec<SecondaryStructureIssue>>(&written).unwrap(),
+        issues
+    );
+}
+
+#[test]
+fn invariants() {
+    let entry = read("4f7i");
+    let value: serde_json::Value = serde_json::from_str(&json::to_string(&entry)).unwrap();
+    let broken = |path: &[&str], replacement: serde_json::Value| {
+        let mut value = value.clone();
+        let mut field = &mut value["entry"];
+        for member in path {
+            field = &mut field[*member];
+        }
+        *field = replacement;
+        json::from_str(&value.to_string()).unwrap_err()
+    };
+    let error = broken(&["model_info"], serde_json::json!([]));
+    assert!(error.to_string().contains("model_info"), "{}", error);
+    let error = broken(&["helix_details"], serde_json::json!([]));
+    assert!(error.to_string().contains("helix_details"), "{}", error);
+    let error = broken(&["sheet_details"], serde_json::json!([]));
+    assert!(error.to_string().contains("sheet_details"), "{}", error);
+
+    let mut info = value["entry"]["model_info"][0].clone();
+    info["elements"] = serde_json::json!(["C"]);
+    let error = broken(&["model_info"], serde_json::json!([info]));
+    assert!(error.to_string().contains("elements"), "{}", error);
+    let mut info = value["entry"]["model_info"][0].clone();
+    info["ters"][0][0] = (entry.structure.models[0].atoms.len() + 1).into();
+    let error = broken(&["model_info"], serde_json::json!([info]));
+    assert!(error.to_string().contains("TER"), "{}", error);
+
+    let mut info = value["entry"]["model_info"][0].clone();
+    info["elements"] = serde_json::json!([]);
+    let mut value = value.clone();
+    value["entry"]["model_info"] = serde_json::json!([info]);
+    assert!(json::from_str(&value.to_string()).is_ok());
+}
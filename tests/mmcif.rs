@@ -0,0 +1,123 @@
+use nom_pdb::mmcif::{MmcifParser, MmcifWriter};
+use nom_pdb::{Parser, Writer};
+use std::fs;
+
+#[test]
+fn round_trip() {
+    for id in &["1a8o", "3l1p", "7znf"] {
+        let data = fs::read(format!("assets/{}.pdb", id)).unwrap();
+        let entry = Parser::parse_entry(&data).unwrap();
+        let cif = MmcifWriter::write_to_string(&entry);
+        assert!(cif.starts_with(&format!("data_{}\n", id.to_uppercase())));
+        let read = MmcifParser::parse_entry(cif.as_bytes()).unwrap();
+        assert_eq!(read.structure.models.len(), entry.structure.models.len());
+        // * the coordinates are written in the same layout, with TERs after each polymer
+        let coordinates = |pdb: String| -> Vec<String> {
+            pdb.lines()
+                .filter(|l| {
+                    l.starts_with("ATOM") || l.starts_with("HETATM") || l.starts_with("TER")
+                })
+                .map(str::to_owned)
+                .collect()
+        };
+        assert_eq!(
+            coordinates(Writer::write_to_string(&read)),
+            coordinates(Writer::write_to_string(&entry))
+        );
+        // * HEADER, TITLE, EXPDTA, AUTHOR and CRYST1, with the space group
+        assert_eq!(
+            format!("{:?}", read.structure.metadata),
+            format!("{:?}", entry.structure.metadata),
+            "{}",
+            id
+        );
+        assert_eq!(read.title_section.keywords, entry.title_section.keywords);
+    }
+}
+
+#[test]
+fn metadata_without_date() {
+    let cif = "\
+data_TEST
+_entry.id TEST
+_struct.title
+;A TITLE WITH 'SINGLE' AND \"DOUBLE\" QUOTES
+;
+_struct_keywords.pdbx_keywords 'DE NOVO PROTEIN'
+_exptl.method 'x-ray diffraction'
+loop_
+_atom_site.id
+_atom_site.label_atom_id
+_atom_site.label_comp_id
+_atom_site.auth_asym_id
+_atom_site.auth_seq_id
+_atom_site.Cartn_x
+_atom_site.Cartn_y
+_atom_site.Cartn_z
+1 CA ALA A 1 1.0 2.0 3.0
+";
+    let entry = MmcifParser::parse_entry(cif.as_bytes()).unwrap();
+    let metadata = entry.structure.metadata.as_ref().unwrap();
+    let header = metadata.header.as_ref().unwrap();
+    assert_eq!(header.id_code, "TEST");
+    assert_eq!(header.classification, "DE NOVO PROTEIN");
+    assert_eq!(
+        metadata.title.as_deref(),
+        Some("A TITLE WITH 'SINGLE' AND \"DOUBLE\" QUOTES")
+    );
+    assert_eq!(
+        metadata.experimental_techniques.as_ref().map(Vec::len),
+        Some(1)
+    );
+
+    // * the unknown date is left blank rather than written as the default one
+    let pdb = Writer::write_to_string(&entry);
+    assert!(pdb.starts_with(&format!("HEADER    {:<40}{:12}TEST", "DE NOVO PROTEIN", "")));
+    let reread = Parser::parse_entry(pdb.as_bytes()).unwrap();
+    assert_eq!(
+        format!("{:?}", reread.structure.metadata),
+        format!("{:?}", entry.structure.metadata)
+    );
+    let cif = MmcifWriter::write_to_string(&entry);
+    assert!(!cif.contains("deposition_date"));
+    let reread = MmcifParser::parse_entry(cif.as_bytes()).unwrap();
+    assert_eq!(
+        format!("{:?}", reread.structure.metadata),
+        format!("{:?}", entry.structure.metadata)
+    );
+}
+
+#[test]
+fn parse() {
+    let cif = "\
+data_TEST
+# a comment
+_entry.id TEST
+loop_
+_atom_site.group_PDB
+_atom_site.id
+_atom_site.type_symbol
+_atom_site.label_atom_id
+_atom_site.label_comp_id
+_atom_site.label_asym_id
+_atom_site.label_seq_id
+_atom_site.Cartn_x
+_atom_site.Cartn_y
+_atom_site.Cartn_z
+_atom_site.auth_seq_id
+_atom_site.auth_asym_id
+ATOM 1 C \"C5'\" DA A 1 1.0 2.0 3.0 10 B
+ATOM 2 ZN ZN ZN B . 4.0 5.0 6.0 ? B
+";
+    let entry = MmcifParser::parse_entry(cif.as_bytes()).unwrap();
+    let atoms = &entry.structure.models[0].atoms;
+    assert_eq!(atoms.len(), 2);
+    assert_eq!(&atoms[0].name.0, b"C5' ");
+    assert_eq!((atoms[0].chain, atoms[0].sequence_number), ('B', 10));
+    assert_eq!(atoms[1].coord, [4.0, 5.0, 6.0]);
+    assert_eq!(entry.element(0, 1).map(|e| e.symbol()), Some("Zn"));
+    assert_eq!(entry.model_info[0].ters.len(), 1);
+
+    assert!(MmcifParser::parse_entry(b"data_X\n_entry.id X\n").is_err());
+    assert!(MmcifParser::parse_entry(b"data_X\nloop_\n_atom_site.id\n_atom_site.x\n1\n").is_err());
+}
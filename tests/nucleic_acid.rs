@@ -0,0 +1,64 @@
+mod common;
+
+use common::read;
+use nom_pdb::nucleic_acid::{GlycosidicOrientation, PuckerConformation};
+
+#[test]
+fn torsions() {
+    let entry = read("3l1p");
+    let view = &entry.hierarchy()[0];
+    let torsions = view.chain('N').unwrap().nucleotide_torsions();
+    assert_eq!(torsions.len(), 23);
+    assert!(view.chain('A').unwrap().nucleotide_torsions().is_empty());
+    assert_eq!(torsions[0].alpha, None);
+    assert_eq!(torsions[22].epsilon, None);
+    assert_eq!(torsions[22].zeta, None);
+    assert!(torsions
+        .iter()
+        .all(|t| t.chi.is_some() && t.pucker.is_some()));
+    // * B-DNA: anti bases, and C2'-endo or neighbouring puckers away from the ends
+    let inner = &torsions[3..20];
+    assert!(inner
+        .iter()
+        .all(|t| matches!(t.chi, Some(chi) if chi < -80.0 && chi > -140.0)));
+    let b_form = inner
+        .iter()
+        .filter(|t| {
+            matches!(
+                t.pucker.unwrap().conformation,
+                PuckerConformation::C2Endo | PuckerConformation::C1Exo
+            )
+        })
+        .count();
+    assert!(b_form >= 15, "{}", b_form);
+    assert!(inner
+        .iter()
+        .all(|t| matches!(t.delta, Some(delta) if delta > 100.0 && delta < 160.0)));
+}
+
+#[test]
+fn base_pairs() {
+    let entry = read("3l1p");
+    let pairs = entry.base_pairs(0);
+    assert_eq!(pairs.len(), 22);
+    for pair in &pairs {
+        assert!(pair.is_watson_crick(), "{}", pair);
+        assert_eq!(pair.family(), "cWW");
+        assert_eq!(pair.orientation, GlycosidicOrientation::Cis);
+        assert_eq!((pair.a.chain, pair.b.chain), ('N', 'M'));
+        // * the strands are antiparallel
+        assert_eq!(pair.a.sequence_number + pair.b.sequence_number, 25);
+        // * G-C pairs have three hydrogen bonds, except at the frayed end of the duplex
+        let gc = matches!(pair.a.code, 'G' | 'C') && pair.a.sequence_number > 2;
+        assert!(pair.hydrogen_bonds.len() >= 2 + gc as usize, "{}", pair);
+    }
+    assert_eq!(pairs[0].to_string(), "DC N2 - DG M23 cWW");
+
+    assert_eq!(
+        entry.dot_bracket(0, &['M', 'N']),
+        format!(".{}&.{}", "(".repeat(22), ")".repeat(22))
+    );
+    assert_eq!(entry.dot_bracket(0, &['N']), ".".repeat(23));
+    assert_eq!(entry.dot_bracket(0, &['A']), "");
+    assert!(read("1a8o").base_pairs(0).is_empty());
+}
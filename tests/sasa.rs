@@ -0,0 +1,40 @@
+use nom_pdb::sasa::ShrakeRupley;
+use nom_pdb::Parser;
+use std::fs;
+
+#[test]
+fn sasa() {
+    let data = fs::read("assets/1a8o.pdb").unwrap();
+    let entry = Parser::parse_entry(&data).unwrap();
+    let sasa = entry.sasa(0);
+    assert_eq!(sasa.atoms.len(), entry.structure.models[0].atoms.len());
+    let residues: f32 = sasa.residues.iter().map(|r| r.area).sum();
+    assert!((residues - sasa.total).abs() < 1.0);
+    assert_eq!(sasa.chains.len(), 1);
+    // * a 70-residue helical domain exposes a few thousand Å²
+    assert!(sasa.total > 4000.0 && sasa.total < 8000.0, "{}", sasa.total);
+    for r in &sasa.residues {
+        if let Some(relative) = r.relative {
+            assert!((0.0..1.3).contains(&relative), "{:?}", r);
+        }
+    }
+    assert!(sasa.residues.iter().any(|r| r.relative == Some(0.0)));
+    // * elements from the ATOM records give nearly the same result
+    let model = ShrakeRupley::default().model(&entry.structure.models[0]);
+    let total: f32 = model.iter().sum();
+    assert!((total - sasa.total).abs() / sasa.total < 0.01);
+}
+
+#[test]
+fn interfaces() {
+    let data = fs::read("assets/3l1p.pdb").unwrap();
+    let entry = Parser::parse_entry(&data).unwrap();
+    let interfaces = entry.interfaces(0);
+    assert!(!interfaces.is_empty());
+    for interface in &interfaces {
+        assert!(interface.buried_area >= 0.0, "{:?}", interface);
+    }
+    let (a, b) = interfaces[0].chains;
+    let buried = entry.buried_surface_area(0, a, b);
+    assert!((buried - interfaces[0].buried_area).abs() < 1e-3);
+}
@@ -0,0 +1,64 @@
+use nom_pdb::secondary_structure::SecondaryStructureIssue;
+use nom_pdb::Parser;
+
+#[test]
+fn annotation() {
+    let data = "\
+HELIX    1   1 ALA A   10A GLY A   12  1                                   4
+SHEET    1   A 2 SER A  13  VAL A  14  0
+SHEET    2   A 2 LEU A  16  THR A  17 -1  N  LEU A  16   O  VAL A  14
+ATOM      1  CA  GLY A   9       1.000   2.000   3.000  1.00  0.00           C
+ATOM      2  CA  ALA A  10A      2.000   2.000   3.000  1.00  0.00           C
+ATOM      3  CA  LYS A  11       3.000   2.000   3.000  1.00  0.00           C
+ATOM      4  CA  GLY A  12       4.000   2.000   3.000  1.00  0.00           C
+ATOM      5  CA  SER A  13       5.000   2.000   3.000  1.00  0.00           C
+ATOM      6  CA  VAL A  14       6.000   2.000   3.000  1.00  0.00           C
+ATOM      7  CA  GLY A  15       7.000   2.000   3.000  1.00  0.00           C
+ATOM      8  CA  LEU A  16       8.000   2.000   3.000  1.00  0.00           C
+ATOM      9  CA  SER A  17       9.000   2.000   3.000  1.00  0.00           C
+END
+";
+    let entry = Parser::parse_entry(data.as_bytes()).unwrap();
+    let helix = &entry.helix_details[0];
+    assert_eq!(helix.start.insertion_code, 'A');
+    assert_eq!(helix.end.name, "GLY");
+    assert_eq!(helix.length, Some(4));
+    let strands = &entry.sheet_details[0];
+    assert_eq!(strands[1].end.name, "THR");
+    assert_eq!(strands[1].registration_insertion_codes, Some((' ', ' ')));
+
+    let chains = entry.secondary_structure(0);
+    assert_eq!(chains[0].residues[1], (10, 'A'));
+    assert_eq!(chains[0].eight_state(), "-HHHEE-EE");
+    assert_eq!(chains[0].three_state(), "CHHHEECEE");
+
+    assert_eq!(
+        entry.check_secondary_structure(),
+        vec![
+            SecondaryStructureIssue::ResidueNameMismatch {
+                record: "SHEET".to_owned(),
+                id: "A strand 2".to_owned(),
+                chain: 'A',
+                sequence_number: 17,
+                insertion_code: ' ',
+                declared: "THR".to_owned(),
+                found: "SER".to_owned(),
+            },
+            SecondaryStructureIssue::HelixLength {
+                id: "1".to_owned(),
+                declared: 4,
+                found: 3,
+            },
+        ]
+    );
+}
+
+#[test]
+fn complete_files() {
+    for f in &["3l1p", "4f7i", "7znf", "1a8o", "3SE5"] {
+        let data = std::fs::read(format!("assets/{}.pdb", f)).unwrap();
+        let entry = Parser::parse_entry(&data).unwrap();
+        assert_eq!(entry.helix_details.len(), entry.structure.helices.len());
+        assert!(entry.check_secondary_structure().is_empty(), "{}", f);
+    }
+}
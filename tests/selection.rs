@@ -0,0 +1,35 @@
+use nom_pdb::selection::Selection;
+use nom_pdb::Parser;
+use std::fs;
+
+#[test]
+fn select() {
+    let data = fs::read("assets/1a8o.pdb").unwrap();
+    let entry = Parser::parse_entry(&data).unwrap();
+    let count = |s: &str| {
+        entry
+            .select_atoms(0, &s.parse::<Selection>().unwrap())
+            .len()
+    };
+    let atoms = entry.structure.models[0].atoms.len();
+    assert_eq!(count("all"), atoms);
+    assert_eq!(count("none"), 0);
+    assert_eq!(count("water"), 88);
+    assert_eq!(count("protein") + count("water"), atoms);
+    assert_eq!(count("protein and name CA"), 70);
+    assert_eq!(count("backbone") + count("sidechain"), count("protein"));
+    assert_eq!(count("resi 151-152 and backbone"), 8);
+    assert_eq!(count("element SE"), 4);
+    assert_eq!(count("not (water or element SE)"), atoms - 92);
+
+    let selected = entry.select(&"chain A and resn ASP".parse().unwrap());
+    let model = &selected.structure.models[0];
+    assert!(!model.atoms.is_empty());
+    assert!(model.atoms.iter().all(|a| a.sequence_number > 150));
+    assert_eq!(selected.model_info[0].elements().len(), model.atoms.len());
+    assert!(selected.check_integrity().is_empty());
+
+    assert!("chain A and".parse::<Selection>().is_err());
+    assert!("resi 10-".parse::<Selection>().is_err());
+    assert!("colour red".parse::<Selection>().is_err());
+}
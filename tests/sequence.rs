@@ -0,0 +1,49 @@
+use nom_pdb::alignment::Aligner;
+use nom_pdb::sequence::{FastaOptions, SequenceSource};
+use nom_pdb::Parser;
+use std::fs;
+
+#[test]
+fn fasta() {
+    let data = fs::read("assets/1a8o.pdb").unwrap();
+    let entry = Parser::parse_entry(&data).unwrap();
+    let seqres = entry.sequence('A', SequenceSource::Seqres).unwrap();
+    let observed = entry.sequence('A', SequenceSource::Coordinates).unwrap();
+    // * selenomethionines (MSE) are written as M
+    assert!(seqres.starts_with("MDIRQGPKEPFRDYVDRFYKTLRAEQASQEVKNWMTETL"));
+    assert_eq!(seqres.len(), 70);
+    assert!(!seqres.contains('X'));
+    let alignment = Aligner::default().local().align(&seqres, &observed);
+    assert_eq!(alignment.a.len(), observed.len());
+    assert_eq!(alignment.identities(), observed.len());
+
+    let fasta = entry.fasta(SequenceSource::Seqres, &FastaOptions::default());
+    let mut lines = fasta.lines();
+    assert_eq!(lines.next(), Some(">1A8O|Chain A|Entity 1|HIV CAPSID"));
+    assert!(lines.all(|l| l.len() <= 80 && !l.starts_with('>')));
+    let options = FastaOptions {
+        id: false,
+        entity: false,
+        description: false,
+        line_width: 0,
+        ..Default::default()
+    };
+    assert_eq!(
+        entry.fasta(SequenceSource::Seqres, &options),
+        format!(">Chain A\n{}\n", seqres)
+    );
+}
+
+#[test]
+fn nucleic_acids() {
+    let data = fs::read("assets/3l1p.pdb").unwrap();
+    let entry = Parser::parse_entry(&data).unwrap();
+    let sequences = entry.sequences(SequenceSource::Seqres);
+    let dna: Vec<_> = sequences.iter().filter(|s| s.nucleic_acid).collect();
+    assert_eq!(dna.len(), 2);
+    assert!(dna
+        .iter()
+        .all(|s| s.sequence.chars().all(|c| "ACGT".contains(c))));
+    let alignment = Aligner::nucleotide().align(&dna[0].sequence, &dna[1].sequence);
+    assert_eq!(alignment.a.replace('-', ""), dna[0].sequence);
+}
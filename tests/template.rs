@@ -0,0 +1,131 @@
+mod common;
+
+use common::read;
+use nom_pdb::template::{template, TemplateProblem};
+use std::collections::HashSet;
+use std::fs;
+
+#[test]
+fn templates() {
+    let mut names = HashSet::new();
+    for id in &["1a8o", "3l1p", "4f7i"] {
+        for atom in &read(id).structure.models[0].atoms {
+            let template = match template(&atom.residue) {
+                Some(template) => template,
+                None => continue,
+            };
+            if !names.insert(template.name) {
+                continue;
+            }
+            for (a, b) in &template.bonds {
+                assert!(
+                    template.contains(a) && template.contains(b),
+                    "{}",
+                    template.name
+                );
+            }
+            // * every atom is bonded to another
+            for atom in &template.atoms {
+                assert!(
+                    template.bonds.iter().any(|(a, b)| a == atom || b == atom),
+                    "{} {}",
+                    template.name,
+                    atom
+                );
+            }
+            for centre in &template.chiral {
+                assert!(template.atoms.contains(&centre.centre));
+                for s in &centre.substituents {
+                    assert!(template.atoms.contains(s));
+                }
+            }
+        }
+    }
+    // * the 20 amino acids and the four deoxynucleotides
+    assert_eq!(names.len(), 24);
+}
+
+#[test]
+fn missing_atoms() {
+    for id in &["1a8o", "4f7i", "7znf"] {
+        assert!(read(id).check_residues(0).is_empty(), "{}", id);
+    }
+    // * the same as REMARK 470
+    for id in &["3l1p", "3SE5"] {
+        let text = fs::read_to_string(format!("assets/{}.pdb", id)).unwrap();
+        let expected: Vec<(char, u32, Vec<String>)> = text
+            .lines()
+            .filter(|l| l.starts_with("REMARK 470     ") && l.len() > 28 && &l[15..18] != "   ")
+            .map(|l| {
+                let chain = l.chars().nth(19).unwrap();
+                let number = l[20..24].trim().parse().unwrap();
+                let atoms = l[28..].split_whitespace().map(String::from).collect();
+                (chain, number, atoms)
+            })
+            .collect();
+        let found: Vec<(char, u32, Vec<String>)> = read(id)
+            .check_residues(0)
+            .into_iter()
+            .map(|i| match i.problem {
+                TemplateProblem::MissingAtoms(atoms) => (i.chain, i.sequence_number, atoms),
+                problem => panic!("{:?}", problem),
+            })
+            .collect();
+        assert_eq!(found, expected);
+    }
+}
+
+#[test]
+fn unknown_atoms_and_chirality() {
+    let mut entry = read("1a8o");
+    let atoms = &mut entry.structure.models[0].atoms;
+    let cb = atoms
+        .iter()
+        .position(|a| {
+            template(&a.residue).is_some()
+                && std::str::from_utf8(&a.name.0).map(str::trim) == Ok("CB")
+        })
+        .unwrap();
+    atoms[cb].name = "CX".parse().unwrap();
+    let issues = entry.check_residues(0);
+    assert_eq!(issues.len(), 2);
+    assert_eq!(
+        issues[0].problem,
+        TemplateProblem::MissingAtoms(vec!["CB".to_owned()])
+    );
+    assert_eq!(
+        issues[1].problem,
+        TemplateProblem::UnknownAtoms(vec!["CX".to_owned()])
+    );
+    assert_eq!(issues[1].to_string(), "ASP A152 has unknown atoms CX");
+
+    // * the mirror image has D-amino acids
+    let mut entry = read("1a8o");
+    for atom in &mut entry.structure.models[0].atoms {
+        atom.coord[0] = -atom.coord[0];
+    }
+    let issues = entry.check_residues(0);
+    let at = |centre: &str| {
+        issues
+            .iter()
+            .filter(|i| i.problem == TemplateProblem::WrongChirality(centre.to_owned()))
+            .count()
+    };
+    let view = &entry.hierarchy()[0];
+    let residues: Vec<String> = view
+        .chains
+        .iter()
+        .flat_map(|c| c.polymer.iter())
+        .filter(|r| template(r.residue()).is_some())
+        .map(|r| r.name())
+        .collect();
+    let count = |names: &[&str]| {
+        residues
+            .iter()
+            .filter(|r| names.contains(&r.as_str()))
+            .count()
+    };
+    assert_eq!(at("CA"), residues.len() - count(&["GLY"]));
+    assert_eq!(at("CB"), count(&["ILE", "THR"]));
+    assert_eq!(issues.len(), at("CA") + at("CB"));
+}
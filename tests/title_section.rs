@@ -0,0 +1,72 @@
+mod common;
+
+use chrono::NaiveDate;
+use common::read;
+use nom_pdb::title_section::ModificationType;
+use nom_pdb::Parser;
+use std::fs;
+
+#[test]
+fn title_section() {
+    let title_section = read("1a8o").title_section;
+
+    let revisions = &title_section.revisions;
+    assert_eq!(revisions.len(), 5);
+    assert_eq!(revisions[0].modification_number, 5);
+    assert_eq!(
+        revisions[0].modification_date,
+        NaiveDate::from_ymd(2009, 11, 3)
+    );
+    assert_eq!(revisions[0].records, vec!["SEQADV"]);
+    assert_eq!(
+        revisions[4].modification_type,
+        ModificationType::InitialRelease
+    );
+    assert_eq!(
+        revisions[4].modification_date,
+        NaiveDate::from_ymd(1998, 10, 14)
+    );
+
+    let sources = &title_section.sources;
+    assert_eq!(sources.len(), 1);
+    assert_eq!(
+        sources[0].organism_scientific.as_deref(),
+        Some("HUMAN IMMUNODEFICIENCY VIRUS 1")
+    );
+    assert_eq!(sources[0].organism_taxid, vec![11676]);
+    assert_eq!(sources[0].expression_system_taxid, vec![469008]);
+    assert_eq!(
+        sources[0].expression_system_plasmid.as_deref(),
+        Some("WISP97-7")
+    );
+
+    assert_eq!(
+        title_section.keywords.unwrap(),
+        vec![
+            "CAPSID",
+            "CORE PROTEIN",
+            "HIV",
+            "C-TERMINAL DOMAIN",
+            "VIRAL PROTEIN"
+        ]
+    );
+    let superseded = title_section.superseded.unwrap();
+    assert_eq!(superseded.superseded, vec!["1AM3"]);
+    assert!(title_section.obsolete.is_none());
+    assert!(title_section.model_count.is_none());
+}
+
+#[test]
+fn malformed_records() {
+    let data = fs::read_to_string("assets/1a8o.pdb").unwrap();
+    for (from, to) in &[
+        ("REVDAT   3   01-APR-03", "REVDAT   3   01-XYZ-03"),
+        ("REVDAT   3   01-APR-03", "REVDAT   3   31-FEB-03"),
+        ("SPRSDE     14-OCT-98", "SPRSDE     14-OCT-9X"),
+    ] {
+        assert!(Parser::parse_entry(data.replacen(from, to, 1).as_bytes()).is_err());
+    }
+    let nummdl = "NUMMDL    XX                                                                \n";
+    let data = data.replacen("AUTHOR", &format!("{}AUTHOR", nummdl), 1);
+    assert!(Parser::parse_entry(data.as_bytes()).is_err());
+}
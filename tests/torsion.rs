@@ -0,0 +1,58 @@
+use nom_pdb::torsion::{RamachandranCategory, RamachandranRegion};
+use nom_pdb::Parser;
+use std::fs;
+
+#[test]
+fn torsions() {
+    let data = fs::read("assets/1a8o.pdb").unwrap();
+    let entry = Parser::parse_entry(&data).unwrap();
+    let torsions = entry.hierarchy()[0].chain('A').unwrap().torsions();
+    assert_eq!(torsions.len(), 70);
+    assert_eq!(torsions[0].phi, None);
+    assert!(torsions[0].psi.is_some());
+    assert_eq!(torsions[69].psi, None);
+    assert!(torsions.iter().all(|t| !t.is_cis()));
+    let favored = torsions
+        .iter()
+        .filter(|t| t.ramachandran == Some(RamachandranRegion::Favored))
+        .count();
+    assert!(favored > 55, "{}", favored);
+    for t in &torsions {
+        match t.residue_name.as_str() {
+            "GLY" => assert_eq!(t.category, RamachandranCategory::Glycine),
+            "PRO" => assert_eq!(t.category, RamachandranCategory::Proline),
+            "ALA" => assert_eq!(t.chi, [None; 4]),
+            "LYS" if t.chi[3].is_some() => assert!(t.chi.iter().all(Option::is_some)),
+            _ => {}
+        }
+        if let Some(omega) = t.omega {
+            assert!(omega.abs() > 150.0);
+        }
+    }
+}
+
+#[test]
+fn ramachandran_favored() {
+    // * high-resolution crystal structures have at least 95% of their residues in favored regions
+    for id in &["1a8o", "3SE5", "4f7i"] {
+        let data = fs::read(format!("assets/{}.pdb", id)).unwrap();
+        let entry = Parser::parse_entry(&data).unwrap();
+        let regions: Vec<RamachandranRegion> = entry.hierarchy()[0]
+            .torsions()
+            .iter()
+            .filter_map(|t| t.ramachandran)
+            .collect();
+        let favored = regions
+            .iter()
+            .filter(|&&r| r == RamachandranRegion::Favored)
+            .count();
+        assert!(
+            favored as f32 >= 0.95 * regions.len() as f32,
+            "{}: {} of {}",
+            id,
+            favored,
+            regions.len()
+        );
+        assert!(!regions.contains(&RamachandranRegion::Outlier), "{}", id);
+    }
+}
@@ -0,0 +1,127 @@
+mod common;
+
+use common::read;
+use nom_pdb::geometry::distance;
+use nom_pdb::validation::Outlier;
+use nom_pdb::Entry;
+
+fn index(entry: &Entry, sequence_number: u32, name: &str) -> usize {
+    entry.structure.models[0]
+        .atoms
+        .iter()
+        .position(|a| {
+            a.sequence_number == sequence_number
+                && std::str::from_utf8(&a.name.0).map(str::trim) == Ok(name)
+        })
+        .unwrap()
+}
+
+#[test]
+fn report() {
+    let entry = read("1a8o");
+    let report = entry.validation_report(0);
+    let residues: usize = entry
+        .hierarchy()
+        .iter()
+        .take(1)
+        .flat_map(|m| &m.chains)
+        .map(|c| c.polymer.len() + c.non_polymer.len())
+        .sum();
+    assert_eq!(report.residues.len(), residues);
+    let summary = &report.summary;
+    assert_eq!(summary.clashes, 0);
+    assert_eq!(summary.bond_outliers, 0);
+    assert_eq!(summary.angle_outliers, 0);
+    assert!(summary.bond_rmsz < 1.0 && summary.angle_rmsz < 1.0);
+    assert_eq!(summary.ramachandran_outliers, 0.0);
+    assert!(summary.ramachandran_favored > 85.0);
+    assert!(
+        summary.score > 0.5 && summary.score < 2.5,
+        "{}",
+        summary.score
+    );
+    // * the end of the side chain of Lys 203 has zero occupancy
+    let lys = report
+        .residues
+        .iter()
+        .find(|r| r.sequence_number == 203)
+        .unwrap();
+    let atoms: Vec<String> = lys
+        .outliers
+        .iter()
+        .filter_map(|o| match o {
+            Outlier::Occupancy { atom, total } if *total == 0.0 => Some(atom.clone()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(atoms, ["CD", "CE", "NZ"]);
+    assert_eq!(summary.occupancy_outliers, 3);
+    assert!(report.outliers().all(|r| !r.outliers.is_empty()));
+
+    // * all atoms of an NMR structure share the same B-factor
+    let nmr = read("7znf").validation_report(0);
+    assert_eq!(nmr.summary.b_factor_outliers, 0);
+    assert_eq!(nmr.summary.occupancy_outliers, 0);
+}
+
+#[test]
+fn outliers() {
+    let mut entry = read("1a8o");
+    let ca = index(&entry, 152, "CA");
+    let cb = index(&entry, 152, "CB");
+    let ile = index(&entry, 153, "CA");
+    let atoms = &mut entry.structure.models[0].atoms;
+    let water = atoms.iter().position(|a| a.sequence_number > 1000).unwrap();
+    let water_number = atoms[water].sequence_number;
+    // * stretch the CA-CB bond of Asp 152 by 0.2 Å
+    let (a, b) = (atoms[ca].coord, atoms[cb].coord);
+    let length = distance(&a, &b);
+    for k in 0..3 {
+        atoms[cb].coord[k] = a[k] + (b[k] - a[k]) * (length + 0.2) / length;
+    }
+    // * put a water 2 Å from the CA of Ile 153
+    let c = atoms[ile].coord;
+    atoms[water].coord = [c[0] + 2.0, c[1], c[2]];
+
+    let report = entry.validation_report(0);
+    let residue = |n| {
+        report
+            .residues
+            .iter()
+            .find(|r| r.sequence_number == n)
+            .unwrap()
+    };
+    let bond = residue(152)
+        .outliers
+        .iter()
+        .find_map(|o| match o {
+            Outlier::BondLength { atoms, z, .. } if atoms == &["CA", "CB"] => Some(*z),
+            _ => None,
+        })
+        .unwrap();
+    assert!(bond > 4.0);
+    let clash = residue(153)
+        .outliers
+        .iter()
+        .find(|o| matches!(o, Outlier::Clash { atom, .. } if atom == "CA"))
+        .unwrap();
+    assert_eq!(
+        clash.to_string(),
+        format!("CA clashes with HOH A{} O (1.10 Å overlap)", water_number)
+    );
+    assert!(residue(water_number)
+        .outliers
+        .iter()
+        .any(|o| matches!(o, Outlier::Clash { other, .. } if other.sequence_number == 153)));
+    assert!(report.summary.clashes >= 1);
+    assert_eq!(report.summary.bond_outliers, 1);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serialize_report() {
+    let report = read("1a8o").validation_report(0);
+    let json = serde_json::to_string(&report).unwrap();
+    assert!(json.contains("\"Occupancy\""));
+    assert!(json.contains("\"clashscore\""));
+}
@@ -0,0 +1,130 @@
+use nom_pdb::diff::DiffOptions;
+use nom_pdb::{Parser, Writer};
+use std::fs;
+
+fn records<'a>(s: &'a str, names: &[&str]) -> Vec<&'a str> {
+    s.lines()
+        .filter(|l| names.iter().any(|r| l.starts_with(r)))
+        .map(str::trim_end)
+        .collect()
+}
+
+fn coordinates(s: &str) -> Vec<&str> {
+    records(s, &["ATOM  ", "HETATM", "ANISOU", "TER   "])
+}
+
+#[test]
+fn polymer_and_non_polymer() {
+    let data = fs::read("assets/1a8o.pdb").unwrap();
+    let entry = Parser::parse_entry(&data).unwrap();
+    let models = entry.hierarchy();
+    let chain = models[0].chain('A').unwrap();
+    assert_eq!(chain.ter.unwrap().id, Some(557));
+    assert_eq!(chain.polymer.len(), 70);
+    assert_eq!(chain.non_polymer.len(), 88);
+}
+
+#[test]
+fn round_trip() {
+    for f in &["3l1p", "4f7i", "7znf", "1a8o", "3SE5"] {
+        let data = fs::read(format!("assets/{}.pdb", f)).unwrap();
+        let entry = Parser::parse_entry(&data).unwrap();
+        let written = Writer::write_to_string(&entry);
+        let reread = Parser::parse_entry(written.as_bytes()).unwrap();
+        assert!(reread.check_integrity().is_empty(), "{}", f);
+        let original = String::from_utf8_lossy(&data);
+        assert_eq!(coordinates(&original), coordinates(&written), "{}", f);
+        let annotations = ["HEADER", "SEQRES", "HELIX ", "SHEET ", "LINK  "];
+        assert_eq!(
+            records(&original, &annotations),
+            records(&written, &annotations),
+            "{}",
+            f
+        );
+        let ssbonds = |e: &nom_pdb::Entry| format!("{:?}", e.structure.ssbonds);
+        assert_eq!(ssbonds(&reread), ssbonds(&entry), "{}", f);
+    }
+}
+
+#[test]
+fn title_section_round_trip() {
+    for f in &["3l1p", "4f7i", "7znf", "1a8o", "3SE5"] {
+        let data = fs::read(format!("assets/{}.pdb", f)).unwrap();
+        let entry = Parser::parse_entry(&data).unwrap();
+        let written = Writer::write_to_string(&entry);
+        let reread = Parser::parse_entry(written.as_bytes()).unwrap();
+        assert!(
+            entry.diff(&reread, &DiffOptions::default()).is_empty(),
+            "{}",
+            f
+        );
+        assert_eq!(
+            format!("{:?}", reread.structure.metadata),
+            format!("{:?}", entry.structure.metadata),
+            "{}",
+            f
+        );
+        assert_eq!(
+            format!("{:?}", reread.title_section),
+            format!("{:?}", entry.title_section),
+            "{}",
+            f
+        );
+        let original = String::from_utf8_lossy(&data);
+        let records_kept = ["CRYST1", "EXPDTA", "NUMMDL", "REVDAT", "SPRSDE"];
+        assert_eq!(
+            records(&original, &records_kept),
+            records(&written, &records_kept),
+            "{}",
+            f
+        );
+    }
+}
+
+#[test]
+fn hybrid36() {
+    let atom = |serial: &str, residue: &str| {
+        format!(
+            "ATOM  {}  N   ALA A{}      11.104   6.134  -6.504  1.00  0.00           N  \n",
+            serial, residue
+        )
+    };
+    let data = format!(
+        "{}{}TER   A0001      ALA AA000\nCONECT99999A0000\nEND\n",
+        atom("99999", "9999"),
+        atom("A0000", "A000")
+    );
+    let entry = Parser::parse_entry(data.as_bytes()).unwrap();
+    let atoms = &entry.structure.models[0].atoms;
+    assert_eq!((atoms[1].id, atoms[1].sequence_number), (100000, 10000));
+    assert_eq!(entry.model_info[0].ters[0].1.id, Some(100001));
+    assert_eq!(entry.structure.connect, vec![[99999, 100000]]);
+    assert_eq!(
+        coordinates(&Writer::write_to_string(&entry)),
+        coordinates(&data)
+    );
+}
+
+#[test]
+fn elements() {
+    let data = "\
+HETATM    1 BR   BR  A 101      11.104   6.134  -6.504  1.00  0.00          BR  
+HETATM    2 NI   NI  A 102      11.104   6.134  -6.504  1.00  0.00          Ni  
+ATOM      3  D   ALA A   1      11.104   6.134  -6.504  1.00  0.00           D  
+ATOM      4  CA  ALA A   1      11.104   6.134  -6.504  1.00  0.00              
+ATOM      5 HG21 THR A   2      11.104   6.134  -6.504  1.00  0.00
+END
+";
+    let entry = Parser::parse_entry(data.as_bytes()).unwrap();
+    let symbols: Vec<_> = (0..5)
+        .map(|i| entry.element(0, i).unwrap().symbol())
+        .collect();
+    assert_eq!(symbols, vec!["Br", "Ni", "D", "C", "H"]);
+    let written = Writer::write_to_string(&entry);
+    assert!(written.contains(
+        "HETATM    1 BR   BR  A 101      11.104   6.134  -6.504  1.00  0.00          BR"
+    ));
+    assert!(written.contains(
+        "ATOM      4  CA  ALA A   1      11.104   6.134  -6.504  1.00  0.00           C"
+    ));
+}